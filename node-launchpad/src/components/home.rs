@@ -23,7 +23,8 @@ use ratatui::{prelude::*, widgets::*};
 use sn_node_manager::{config::get_node_registry_path, VerbosityLevel};
 use sn_peers_acquisition::{get_bootstrap_peers_from_url, PeersArgs};
 use sn_service_management::{
-    control::ServiceController, NodeRegistry, NodeServiceData, ServiceStatus,
+    control::ServiceController, BandwidthLimits, NodeRegistry, NodeServiceData, ResourceLimits,
+    ServiceStatus,
 };
 use std::{
     path::PathBuf,
@@ -501,8 +502,20 @@ impl Component for Home {
                 let peer_id = peer_id.map(|p| p.to_string()).unwrap_or("-".to_string());
                 let status = format!("{:?}", n.status);
                 let version = format!("v{}", n.version);
-
-                let row = vec![n.service_name.clone(), peer_id, version, status];
+                let latest_event = self
+                    .node_stats
+                    .latest_events
+                    .get(&n.service_name)
+                    .cloned()
+                    .unwrap_or("-".to_string());
+
+                let row = vec![
+                    n.service_name.clone(),
+                    peer_id,
+                    version,
+                    status,
+                    latest_event,
+                ];
                 let row_style = if n.status == ServiceStatus::Running {
                     Style::default().fg(EUCALYPTUS)
                 } else {
@@ -532,6 +545,7 @@ impl Component for Home {
                 Constraint::Min(30),
                 Constraint::Max(20),
                 Constraint::Max(10),
+                Constraint::Max(30),
             ];
             let table = Table::new(node_rows, node_widths)
                 .column_spacing(2)
@@ -657,6 +671,7 @@ fn maintain_n_running_nodes(
         if let Err(err) = sn_node_manager::cmd::node::maintain_n_running_nodes(
             false,
             true,
+            BandwidthLimits::default(),
             count,
             None,
             true,
@@ -669,6 +684,7 @@ fn maintain_n_running_nodes(
             None,
             owner,
             peers_args,
+            ResourceLimits::default(),
             None,
             None,
             safenode_path,
@@ -9,8 +9,12 @@
 use color_eyre::Result;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use sn_service_management::{NodeServiceData, ServiceStatus};
-use std::{path::PathBuf, time::Instant};
+use sn_service_management::{rpc::NodeEventEntry, NodeServiceData, ServiceStatus};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{Instant, SystemTime},
+};
 use tokio::sync::mpsc::UnboundedSender;
 
 use crate::action::{Action, HomeActions};
@@ -19,6 +23,8 @@ use crate::action::{Action, HomeActions};
 pub struct NodeStats {
     pub forwarded_rewards: u64,
     pub memory_usage_mb: usize,
+    /// The latest notable event of each node, e.g. a reward or a bad peer, keyed by service name
+    pub latest_events: BTreeMap<String, String>,
 }
 
 impl NodeStats {
@@ -28,26 +34,28 @@ impl NodeStats {
     }
 
     pub fn fetch_all_node_stats(nodes: &[NodeServiceData], action_sender: UnboundedSender<Action>) {
+        // The stats are only fetched from the running nodes, but the latest event of every node is
+        // shown, e.g. why a node got terminated.
         let node_details = nodes
             .iter()
-            .filter_map(|node| {
-                if node.status == ServiceStatus::Running {
-                    if let Some(metrics_port) = node.metrics_port {
-                        Some((
-                            node.service_name.clone(),
-                            metrics_port,
-                            node.data_dir_path.clone(),
-                        ))
-                    } else {
+            .map(|node| {
+                let metrics_port = if node.status == ServiceStatus::Running {
+                    if node.metrics_port.is_none() {
                         error!(
                             "No metrics port found for {:?}. Skipping stat fetch.",
                             node.service_name
                         );
-                        None
                     }
+                    node.metrics_port
                 } else {
                     None
-                }
+                };
+                (
+                    node.service_name.clone(),
+                    metrics_port,
+                    node.data_dir_path.clone(),
+                    node.clone(),
+                )
             })
             .collect::<Vec<_>>();
         if !node_details.is_empty() {
@@ -56,18 +64,28 @@ impl NodeStats {
                 Self::fetch_all_node_stats_inner(node_details, action_sender).await;
             });
         } else {
-            debug!("No running nodes to fetch stats from.");
+            debug!("No nodes to fetch stats from.");
         }
     }
 
     async fn fetch_all_node_stats_inner(
-        node_details: Vec<(String, u16, PathBuf)>,
+        node_details: Vec<(String, Option<u16>, PathBuf, NodeServiceData)>,
         action_sender: UnboundedSender<Action>,
     ) {
         let mut stream = futures::stream::iter(node_details)
-            .map(|(service_name, metrics_port, data_dir)| async move {
+            .map(|(service_name, metrics_port, data_dir, node)| async move {
+                let result = match metrics_port {
+                    Some(metrics_port) => {
+                        Some(Self::fetch_stat_per_node(metrics_port, data_dir).await)
+                    }
+                    None => None,
+                };
                 (
-                    Self::fetch_stat_per_node(metrics_port, data_dir).await,
+                    result,
+                    sn_node_manager::get_recent_notable_events(&node)
+                        .await
+                        .into_iter()
+                        .next(),
                     service_name,
                 )
             })
@@ -75,14 +93,20 @@ impl NodeStats {
 
         let mut all_node_stats = NodeStats::default();
 
-        while let Some((result, service_name)) = stream.next().await {
+        while let Some((result, latest_event, service_name)) = stream.next().await {
+            if let Some(event) = latest_event {
+                all_node_stats
+                    .latest_events
+                    .insert(service_name.clone(), format_event(&event));
+            }
             match result {
-                Ok(stats) => {
+                Some(Ok(stats)) => {
                     all_node_stats.merge(&stats);
                 }
-                Err(err) => {
+                Some(Err(err)) => {
                     error!("Error while fetching stats from {service_name:?}: {err:?}");
                 }
+                None => {}
             }
         }
 
@@ -106,6 +130,7 @@ impl NodeStats {
         let mut stats = NodeStats {
             memory_usage_mb: 0,
             forwarded_rewards: 0,
+            latest_events: BTreeMap::new(),
         };
         for sample in all_metrics.samples.iter() {
             if sample.metric == "sn_networking_process_memory_used_mb" {
@@ -135,3 +160,17 @@ impl NodeStats {
         Ok(stats)
     }
 }
+
+// e.g. "RewardReceived 5m ago"
+fn format_event(event: &NodeEventEntry) -> String {
+    let age = SystemTime::now()
+        .duration_since(event.timestamp)
+        .unwrap_or_default()
+        .as_secs();
+    let age = match age {
+        0..=59 => format!("{age}s"),
+        60..=3599 => format!("{}m", age / 60),
+        _ => format!("{}h", age / 3600),
+    };
+    format!("{} {age} ago", event.kind)
+}
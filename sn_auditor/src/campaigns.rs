@@ -0,0 +1,409 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bls::SecretKey;
+use color_eyre::eyre::{bail, eyre, Result};
+use serde::{Deserialize, Serialize};
+use sn_client::transfers::{Hash, NanoTokens, SignedSpend, SpendAddress, SpendReason};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// How the participants' identifiers are normalised before being hashed
+/// The scheme must match the one used by the participants when forwarding their rewards
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierScheme {
+    /// Identifiers are used as they are, like Discord usernames
+    #[default]
+    Raw,
+    /// Identifiers are trimmed and lowercased, like email addresses
+    Lowercase,
+    /// Identifiers are prefixed with the given string, to avoid collisions between campaigns
+    Prefixed(String),
+}
+
+impl IdentifierScheme {
+    fn normalise(&self, participant: &str) -> String {
+        match self {
+            IdentifierScheme::Raw => participant.to_string(),
+            IdentifierScheme::Lowercase => participant.trim().to_lowercase(),
+            IdentifierScheme::Prefixed(prefix) => format!("{prefix}{participant}"),
+        }
+    }
+}
+
+/// Configuration of a reward tracking campaign
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CampaignConfig {
+    /// Unique name of the campaign
+    pub name: String,
+    /// Hex encoded secret key used to decrypt the participants' identifiers in the forwarded payments
+    pub encryption_key: String,
+    /// How participants' identifiers are normalised before being hashed
+    #[serde(default)]
+    pub identifier_scheme: IdentifierScheme,
+    /// Payments made before this time (in seconds since UNIX epoch) are ignored
+    #[serde(default)]
+    pub start: Option<u64>,
+    /// Payments made after this time (in seconds since UNIX epoch) are ignored
+    #[serde(default)]
+    pub end: Option<u64>,
+    /// Participants' identifiers
+    #[serde(default)]
+    pub participants: Vec<String>,
+}
+
+/// A payment forwarded to a campaign, with the time its spend was added to the DAG
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TrackedPayment {
+    amount: NanoTokens,
+    #[serde(alias = "first_seen")]
+    time: u64,
+}
+
+/// Payments of a campaign by participant
+type CampaignPayments = BTreeMap<String, BTreeMap<SpendAddress, TrackedPayment>>;
+
+/// State of a campaign saved to disk
+#[derive(Serialize, Deserialize)]
+struct CampaignState {
+    participants: Vec<String>,
+    payments: CampaignPayments,
+}
+
+/// Totals of a participant in a campaign
+#[derive(Clone, Debug, Serialize)]
+pub struct ParticipantTotal {
+    pub payments: usize,
+    pub amount: u64,
+}
+
+/// Summary of a campaign
+#[derive(Clone, Debug, Serialize)]
+pub struct CampaignSummary {
+    pub name: String,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    pub active: bool,
+    pub participants: usize,
+    pub payments: usize,
+    pub amount: u64,
+}
+
+/// A reward tracking campaign
+/// Participants forward a share of their rewards with their identifier encrypted to the campaign's key
+pub struct Campaign {
+    config: CampaignConfig,
+    sk: SecretKey,
+    participants: BTreeMap<Hash, String>,
+    payments: CampaignPayments,
+}
+
+impl Campaign {
+    pub fn new(config: CampaignConfig) -> Result<Self> {
+        // the name is used in file names
+        let is_valid_name = !config.name.is_empty()
+            && config
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !is_valid_name {
+            bail!(
+                "Invalid campaign name {:?}, only letters, digits, '_' and '-' are allowed",
+                config.name
+            );
+        }
+        let sk = SecretKey::from_hex(&config.encryption_key).map_err(|e| {
+            eyre!(
+                "Failed to parse encryption key of campaign {}: {e:?}",
+                config.name
+            )
+        })?;
+        if let (Some(start), Some(end)) = (config.start, config.end) {
+            if start > end {
+                bail!("Campaign {} ends before it starts", config.name);
+            }
+        }
+
+        let mut campaign = Self {
+            sk,
+            participants: BTreeMap::new(),
+            payments: BTreeMap::new(),
+            config,
+        };
+        for p in campaign.config.participants.clone() {
+            campaign.track_participant(&p);
+        }
+        Ok(campaign)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Check if the campaign is running at the given time (in seconds since UNIX epoch)
+    pub fn is_active(&self, now: u64) -> bool {
+        let started = match self.config.start {
+            Some(start) => now >= start,
+            None => true,
+        };
+        let ended = match self.config.end {
+            Some(end) => now > end,
+            None => false,
+        };
+        started && !ended
+    }
+
+    #[cfg(test)]
+    fn is_participant_tracked(&self, participant: &str) -> bool {
+        let hash = self.participant_hash(participant);
+        self.participants.contains_key(&hash)
+    }
+
+    /// Track a new participant, claiming the payments previously recorded for an unknown participant
+    pub fn track_participant(&mut self, participant: &str) {
+        let hash = self.participant_hash(participant);
+        if self
+            .participants
+            .insert(hash, participant.to_string())
+            .is_some()
+        {
+            return;
+        }
+        if !self.config.participants.iter().any(|p| p == participant) {
+            self.config.participants.push(participant.to_string());
+        }
+
+        let unknown = unknown_participant(&hash);
+        if let Some(payments) = self.payments.remove(&unknown) {
+            self.payments
+                .entry(participant.to_string())
+                .or_default()
+                .extend(payments);
+        }
+    }
+
+    /// Record the spend if it is a payment forwarded to this campaign
+    /// `time` is when the spend was added to the DAG, see `SpendDagDb`
+    /// Returns true if the spend was recorded
+    pub fn process_spend(&mut self, spend: &SignedSpend, time: u64) -> bool {
+        let cipher = match spend.reason() {
+            SpendReason::BetaRewardTracking(cipher) => cipher,
+            _ => return false,
+        };
+        // payments forwarded to other campaigns can't be decrypted with our key
+        let hash = match cipher.decrypt_to_username_hash(&self.sk) {
+            Ok(hash) => hash,
+            Err(_) => return false,
+        };
+
+        let addr = spend.address();
+        let participant = match self.participants.get(&hash) {
+            Some(p) => p.clone(),
+            None => {
+                warn!(
+                    "Campaign {}: payment at {addr:?} for an unknown participant: {hash:?}",
+                    self.name()
+                );
+                unknown_participant(&hash)
+            }
+        };
+
+        if !self.is_active(time) {
+            debug!(
+                "Campaign {}: ignoring payment at {addr:?} made outside of the campaign period",
+                self.name()
+            );
+            return false;
+        }
+
+        let amount = spend.spend.amount;
+        trace!(
+            "Campaign {}: got forwarded payment {amount} from {participant} at {addr:?}",
+            self.name()
+        );
+        self.payments
+            .entry(participant)
+            .or_default()
+            .insert(addr, TrackedPayment { amount, time });
+        true
+    }
+
+    /// Totals of each participant
+    pub fn totals(&self) -> BTreeMap<String, ParticipantTotal> {
+        self.payments
+            .iter()
+            .map(|(participant, payments)| {
+                let total = ParticipantTotal {
+                    payments: payments.len(),
+                    amount: payments.values().map(|p| p.amount.as_nano()).sum(),
+                };
+                (participant.clone(), total)
+            })
+            .collect()
+    }
+
+    pub fn summary(&self, now: u64) -> CampaignSummary {
+        let totals = self.totals();
+        CampaignSummary {
+            name: self.config.name.clone(),
+            start: self.config.start,
+            end: self.config.end,
+            active: self.is_active(now),
+            participants: self.participants.len(),
+            payments: totals.values().map(|t| t.payments).sum(),
+            amount: totals.values().map(|t| t.amount).sum(),
+        }
+    }
+
+    /// Totals of each participant in CSV format
+    pub fn totals_csv(&self) -> String {
+        let mut content = "participant,payments,amount\n".to_string();
+        for (participant, total) in self.totals() {
+            let _ = writeln!(
+                content,
+                "\"{}\",{},{}",
+                participant.replace('"', "\"\""),
+                total.payments,
+                total.amount
+            );
+        }
+        content
+    }
+
+    /// Save the participants and recorded payments to disk, so they survive restarts
+    pub fn save_state(&self, dir: &Path) -> Result<()> {
+        let state = CampaignState {
+            participants: self.participants.values().cloned().collect(),
+            payments: self.payments.clone(),
+        };
+        let json = serde_json::to_string_pretty(&state)?;
+        std::fs::write(self.state_file(dir), json)?;
+        Ok(())
+    }
+
+    /// Restore the participants and recorded payments from disk if any
+    pub fn load_state(&mut self, dir: &Path) -> Result<()> {
+        let state_file = self.state_file(dir);
+        if !state_file.exists() {
+            return Ok(());
+        }
+        let json = std::fs::read_to_string(&state_file)?;
+        let state: CampaignState = serde_json::from_str(&json)
+            .map_err(|e| eyre!("Failed to parse campaign state {state_file:?}: {e}"))?;
+        self.payments = state.payments;
+
+        // participants from the config and the ones added since can claim their unknown payments
+        let mut participants = self.config.participants.clone();
+        participants.extend(state.participants);
+        for p in participants {
+            self.participants.remove(&self.participant_hash(&p));
+            self.track_participant(&p);
+        }
+        Ok(())
+    }
+
+    fn state_file(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("campaign_{}.json", self.config.name))
+    }
+
+    fn participant_hash(&self, participant: &str) -> Hash {
+        let id = self.config.identifier_scheme.normalise(participant);
+        Hash::hash(id.as_bytes())
+    }
+}
+
+fn unknown_participant(hash: &Hash) -> String {
+    format!("unknown participant: {hash:?}")
+}
+
+/// Load the campaigns configuration from a JSON file
+pub fn load_campaigns(campaigns_file: &Path) -> Result<Vec<Campaign>> {
+    let json = std::fs::read_to_string(campaigns_file)
+        .map_err(|e| eyre!("Failed to read campaigns file {campaigns_file:?}: {e}"))?;
+    let configs: Vec<CampaignConfig> = serde_json::from_str(&json)
+        .map_err(|e| eyre!("Failed to parse campaigns file {campaigns_file:?}: {e}"))?;
+
+    let mut campaigns: Vec<Campaign> = vec![];
+    for config in configs {
+        if campaigns.iter().any(|c| c.name() == config.name) {
+            bail!(
+                "Duplicate campaign name in {campaigns_file:?}: {}",
+                config.name
+            );
+        }
+        campaigns.push(Campaign::new(config)?);
+    }
+    Ok(campaigns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_client::transfers::DiscordNameCipher;
+
+    fn campaign(scheme: IdentifierScheme, start: Option<u64>, end: Option<u64>) -> Campaign {
+        let sk = SecretKey::random();
+        Campaign::new(CampaignConfig {
+            name: "test".to_string(),
+            encryption_key: sk.to_hex(),
+            identifier_scheme: scheme,
+            start,
+            end,
+            participants: vec!["Alice".to_string()],
+        })
+        .expect("campaign creation to succeed")
+    }
+
+    #[test]
+    fn test_identifier_schemes() {
+        let c = campaign(IdentifierScheme::Lowercase, None, None);
+        assert!(c.is_participant_tracked("alice"));
+        assert!(c.is_participant_tracked(" ALICE "));
+
+        let c = campaign(IdentifierScheme::Raw, None, None);
+        assert!(c.is_participant_tracked("Alice"));
+        assert!(!c.is_participant_tracked("alice"));
+
+        let c = campaign(IdentifierScheme::Prefixed("x:".to_string()), None, None);
+        let cipher = DiscordNameCipher::create("x:Alice", c.sk.public_key())
+            .expect("cipher creation to succeed");
+        let hash = cipher
+            .decrypt_to_username_hash(&c.sk)
+            .expect("decryption to succeed");
+        assert_eq!(c.participants.get(&hash), Some(&"Alice".to_string()));
+    }
+
+    #[test]
+    fn test_campaign_period() {
+        let c = campaign(IdentifierScheme::Raw, Some(100), Some(200));
+        assert!(!c.is_active(99));
+        assert!(c.is_active(100));
+        assert!(c.is_active(200));
+        assert!(!c.is_active(201));
+
+        let c = campaign(IdentifierScheme::Raw, None, Some(200));
+        assert!(c.is_active(0));
+    }
+
+    #[test]
+    fn test_campaign_names_are_safe_file_names() {
+        for name in ["", "../escape", "a/b", "with space"] {
+            let config = CampaignConfig {
+                name: name.to_string(),
+                encryption_key: SecretKey::random().to_hex(),
+                identifier_scheme: IdentifierScheme::Raw,
+                start: None,
+                end: None,
+                participants: vec![],
+            };
+            assert!(Campaign::new(config).is_err(), "{name:?} was accepted");
+        }
+    }
+}
@@ -18,9 +18,11 @@ use sn_client::transfers::{
     Hash, NanoTokens, SignedSpend, SpendAddress, DEFAULT_PAYMENT_FORWARD_SK,
 };
 use sn_client::{Client, SpendDag, SpendDagGet};
+
+use crate::campaigns::{Campaign, CampaignSummary, ParticipantTotal};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc::Sender;
@@ -31,6 +33,7 @@ pub const SPEND_DAG_FILENAME: &str = "spend_dag";
 pub const SPEND_DAG_SVG_FILENAME: &str = "spend_dag.svg";
 /// Store a locally copy to restore on restart
 pub const BETA_PARTICIPANTS_FILENAME: &str = "beta_participants.txt";
+pub const SPEND_TIMES_FILENAME: &str = "spend_times.json";
 
 lazy_static! {
     /// time in seconds UTXOs are refetched in DAG crawl
@@ -62,8 +65,15 @@ pub struct SpendDagDb {
     beta_tracking: Arc<RwLock<BetaTracking>>,
     beta_participants: Arc<RwLock<BTreeMap<Hash, String>>>,
     encryption_sk: Option<SecretKey>,
+    campaigns: Arc<RwLock<Vec<Campaign>>>,
+    spend_times: Arc<RwLock<SpendTimes>>,
 }
 
+/// Time (in seconds since UNIX epoch) each spend was first added to the local DAG
+/// Spends don't carry a timestamp, so this is the closest we have to the time a payment was made.
+/// It is kept with the DAG so that re-crawls and campaigns added later see the same times.
+type SpendTimes = BTreeMap<SpendAddress, u64>;
+
 #[derive(Clone, Default)]
 struct BetaTracking {
     forwarded_payments: ForwardedPayments,
@@ -75,6 +85,13 @@ struct BetaTracking {
 /// Map of Discord usernames to their tracked forwarded payments
 type ForwardedPayments = BTreeMap<String, BTreeSet<(SpendAddress, NanoTokens)>>;
 
+/// Formats the DAG can be exported to for external graph tools
+#[derive(Clone, Copy, Debug)]
+pub enum DagExportFormat {
+    GraphMl,
+    Csv,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct SpendJsonResponse {
     address: String,
@@ -104,6 +121,7 @@ impl SpendDagDb {
                 client.new_dag_with_genesis_only().await?
             }
         };
+        let spend_times = load_spend_times(&path, &dag);
 
         Ok(Self {
             client: Some(client),
@@ -112,6 +130,8 @@ impl SpendDagDb {
             beta_tracking: Arc::new(RwLock::new(Default::default())),
             beta_participants: Arc::new(RwLock::new(BTreeMap::new())),
             encryption_sk,
+            campaigns: Arc::new(RwLock::new(Vec::new())),
+            spend_times: Arc::new(RwLock::new(spend_times)),
         })
     }
 
@@ -127,6 +147,7 @@ impl SpendDagDb {
             .ok_or_else(|| eyre!("Failed to get parent path"))?
             .to_path_buf();
         let dag = SpendDag::load_from_file(&dag_path)?;
+        let spend_times = load_spend_times(&path, &dag);
         Ok(Self {
             client: None,
             path,
@@ -134,6 +155,8 @@ impl SpendDagDb {
             beta_tracking: Arc::new(RwLock::new(Default::default())),
             beta_participants: Arc::new(RwLock::new(BTreeMap::new())),
             encryption_sk,
+            campaigns: Arc::new(RwLock::new(Vec::new())),
+            spend_times: Arc::new(RwLock::new(spend_times)),
         })
     }
 
@@ -170,6 +193,30 @@ impl SpendDagDb {
         Ok(json)
     }
 
+    /// Export the DAG, or the sub DAG within `hops` of an address, in the given format
+    pub async fn export(
+        &self,
+        format: DagExportFormat,
+        around: Option<(SpendAddress, usize)>,
+    ) -> Result<String> {
+        let dag_ref = Arc::clone(&self.dag);
+        let r_handle = dag_ref.read().await;
+        let sub_dag;
+        let dag = match around {
+            Some((addr, hops)) => {
+                sub_dag = r_handle.sub_dag_around(&addr, hops);
+                &sub_dag
+            }
+            None => &*r_handle,
+        };
+
+        let content = match format {
+            DagExportFormat::GraphMl => dag.dump_graphml_format(),
+            DagExportFormat::Csv => dag.dump_csv_edge_list(),
+        };
+        Ok(content)
+    }
+
     /// Dump DAG to disk
     pub async fn dump(&self) -> Result<()> {
         std::fs::create_dir_all(&self.path)?;
@@ -177,9 +224,37 @@ impl SpendDagDb {
         let dag_ref = Arc::clone(&self.dag);
         let r_handle = dag_ref.read().await;
         r_handle.dump_to_file(dag_path)?;
+        std::mem::drop(r_handle);
+        self.dump_spend_times().await
+    }
+
+    /// Save the time each spend was first added to the DAG, so they survive restarts
+    async fn dump_spend_times(&self) -> Result<()> {
+        let by_hex: BTreeMap<String, u64> = self
+            .spend_times
+            .read()
+            .await
+            .iter()
+            .map(|(addr, time)| (addr.to_hex(), *time))
+            .collect();
+        let json = serde_json::to_string(&by_hex)?;
+        let path = self.path.join(SPEND_TIMES_FILENAME);
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(tmp_path, path)?;
         Ok(())
     }
 
+    /// Time the spend was first added to the DAG, recording the current time if it is new
+    async fn spend_time(&self, addr: SpendAddress) -> u64 {
+        *self
+            .spend_times
+            .write()
+            .await
+            .entry(addr)
+            .or_insert_with(now_secs)
+    }
+
     /// Load current DAG svg from disk
     #[cfg(feature = "svg-dag")]
     pub fn load_svg(&self) -> Result<Vec<u8>> {
@@ -219,20 +294,26 @@ impl SpendDagDb {
             .map(|a| (a, Instant::now()))
             .collect();
 
-        // beta rewards processing
+        // beta rewards and campaigns processing
         let self_clone = self.clone();
-        let spend_processing = if let Some(sk) = self.encryption_sk.clone() {
+        let beta_sk = self.encryption_sk.clone();
+        if beta_sk.is_none() {
+            eprintln!("Foundation secret key not set! Beta rewards will not be processed.");
+        }
+        let spend_processing = if self.spend_processing_needed().await {
             let (tx, mut rx) = tokio::sync::mpsc::channel(SPENDS_PROCESSING_BUFFER_SIZE);
             tokio::spawn(async move {
                 while let Some((spend, utxos_for_further_track)) = rx.recv().await {
-                    self_clone
-                        .beta_background_process_spend(spend, &sk, utxos_for_further_track)
-                        .await;
+                    self_clone.campaigns_process_spend(&spend).await;
+                    if let Some(sk) = &beta_sk {
+                        self_clone
+                            .beta_background_process_spend(spend, sk, utxos_for_further_track)
+                            .await;
+                    }
                 }
             });
             Some(tx)
         } else {
-            eprintln!("Foundation secret key not set! Beta rewards will not be processed.");
             None
         };
 
@@ -303,6 +384,12 @@ impl SpendDagDb {
             .await;
         let new_utxos = dag.get_utxos();
 
+        // record the time of the newly found spends
+        {
+            let mut spend_times = self.spend_times.write().await;
+            record_spend_times(&mut spend_times, &dag, now_secs());
+        }
+
         // write updates to local DAG and save to disk
         let mut dag_w_handle = self.dag.write().await;
         *dag_w_handle = dag;
@@ -384,6 +471,117 @@ impl SpendDagDb {
         }
     }
 
+    /// Whether the crawled spends have to be processed for the beta rewards or the campaigns
+    /// The campaigns have to be tracked before the background update starts, which decides it once
+    async fn spend_processing_needed(&self) -> bool {
+        self.encryption_sk.is_some() || !self.campaigns.read().await.is_empty()
+    }
+
+    /// Start tracking the given reward campaigns
+    /// Restores their recorded payments from disk and processes the spends already in the local DAG
+    pub(crate) async fn track_campaigns(&self, mut campaigns: Vec<Campaign>) -> Result<()> {
+        let all_spends: Vec<(SignedSpend, u64)> = {
+            let dag = self.dag.read().await;
+            let mut spend_times = self.spend_times.write().await;
+            let now = now_secs();
+            dag.all_spends()
+                .into_iter()
+                .map(|spend| {
+                    let time = *spend_times.entry(spend.address()).or_insert(now);
+                    (spend.clone(), time)
+                })
+                .collect()
+        };
+        for campaign in campaigns.iter_mut() {
+            campaign.load_state(&self.path)?;
+            let recorded = all_spends
+                .iter()
+                .filter(|(spend, time)| campaign.process_spend(spend, *time))
+                .count();
+            println!(
+                "Tracking campaign {}, found {recorded} payments in the local DAG",
+                campaign.name()
+            );
+        }
+        *self.campaigns.write().await = campaigns;
+        Ok(())
+    }
+
+    /// Process each spend and update the campaigns it is a payment for
+    async fn campaigns_process_spend(&self, spend: &SignedSpend) {
+        let time = self.spend_time(spend.address()).await;
+        let mut campaigns = self.campaigns.write().await;
+        for campaign in campaigns.iter_mut() {
+            campaign.process_spend(spend, time);
+        }
+    }
+
+    /// Summaries of all the tracked campaigns
+    pub(crate) async fn campaigns_summary(&self) -> Vec<CampaignSummary> {
+        let now = now_secs();
+        let campaigns = self.campaigns.read().await;
+        campaigns.iter().map(|c| c.summary(now)).collect()
+    }
+
+    /// Totals of each participant of a campaign, None if there is no such campaign
+    pub(crate) async fn campaign_totals(
+        &self,
+        name: &str,
+    ) -> Option<BTreeMap<String, ParticipantTotal>> {
+        let campaigns = self.campaigns.read().await;
+        campaigns
+            .iter()
+            .find(|c| c.name() == name)
+            .map(|c| c.totals())
+    }
+
+    /// Totals of each participant of a campaign in CSV format, None if there is no such campaign
+    pub(crate) async fn campaign_totals_csv(&self, name: &str) -> Option<String> {
+        let campaigns = self.campaigns.read().await;
+        campaigns
+            .iter()
+            .find(|c| c.name() == name)
+            .map(|c| c.totals_csv())
+    }
+
+    /// Track a new participant in a campaign
+    pub(crate) async fn track_campaign_participant(
+        &self,
+        name: &str,
+        participant: &str,
+    ) -> Result<()> {
+        let mut campaigns = self.campaigns.write().await;
+        match campaigns.iter_mut().find(|c| c.name() == name) {
+            Some(c) => {
+                c.track_participant(participant);
+                Ok(())
+            }
+            None => bail!("No campaign named {name}"),
+        }
+    }
+
+    /// Save the campaigns recorded payments to disk
+    /// and backup their totals to timestamped json files
+    pub(crate) async fn backup_campaigns(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.path)?;
+        let timestamp = now_secs();
+        // the campaigns' payments are judged against these times
+        self.dump_spend_times().await?;
+        let campaigns = self.campaigns.read().await;
+        for campaign in campaigns.iter() {
+            campaign.save_state(&self.path)?;
+            let json = serde_json::to_string_pretty(&campaign.totals())?;
+            let backup_file = self.path.join(format!(
+                "campaign_{}_totals_{timestamp}.json",
+                campaign.name()
+            ));
+            info!("Writing campaign totals backup to {backup_file:?}");
+            std::fs::write(backup_file, json)
+                .map_err(|e| eyre!("Could not write campaign totals backup to disk: {e}"))?;
+        }
+        Ok(())
+    }
+
     /// Merge a SpendDag into the current DAG
     /// This can be used to enrich our DAG with a DAG from another node to avoid costly computations
     /// Make sure to verify the other DAG is trustworthy before calling this function to merge it in
@@ -498,6 +696,46 @@ impl SpendDagDb {
     }
 }
 
+/// Load the spend times saved with the DAG
+/// The spends of a DAG saved before their times were recorded get the time the DAG was last saved,
+/// which is the latest they could have been made.
+fn load_spend_times(path: &Path, dag: &SpendDag) -> SpendTimes {
+    let mut spend_times = SpendTimes::new();
+    let times_path = path.join(SPEND_TIMES_FILENAME);
+    if let Ok(json) = std::fs::read_to_string(&times_path) {
+        match serde_json::from_str::<BTreeMap<String, u64>>(&json) {
+            Ok(by_hex) => spend_times.extend(by_hex.into_iter().filter_map(|(hex, time)| {
+                SpendAddress::from_hex(&hex).ok().map(|addr| (addr, time))
+            })),
+            Err(e) => warn!("Failed to parse spend times {times_path:?}: {e}"),
+        }
+    }
+
+    let dag_saved_time = std::fs::metadata(path.join(SPEND_DAG_FILENAME))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|time| time.as_secs())
+        .unwrap_or_else(now_secs);
+    record_spend_times(&mut spend_times, dag, dag_saved_time);
+    spend_times
+}
+
+/// Record the given time for the spends of the DAG that don't have one yet
+fn record_spend_times(spend_times: &mut SpendTimes, dag: &SpendDag, time: u64) {
+    for spend in dag.all_spends() {
+        let _ = spend_times.entry(spend.address()).or_insert(time);
+    }
+}
+
+/// Current time in seconds since UNIX epoch
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|t| t.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(feature = "svg-dag")]
 fn dag_to_svg(dag: &SpendDag) -> Result<Vec<u8>> {
     let dot = dag.dump_dot_format();
@@ -554,3 +792,35 @@ fn quick_edit_svg(svg: Vec<u8>, dag: &SpendDag) -> Result<Vec<u8>> {
 
     Ok(str.into_bytes())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::campaigns::{CampaignConfig, IdentifierScheme};
+    use sn_client::transfers::UniquePubkey;
+
+    #[tokio::test]
+    async fn campaigns_tracked_at_startup_should_have_their_spends_processed() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dag_path = dir.path().join(SPEND_DAG_FILENAME);
+        let source =
+            SpendAddress::from_unique_pubkey(&UniquePubkey::new(SecretKey::random().public_key()));
+        SpendDag::new(source).dump_to_file(&dag_path)?;
+
+        let dag = SpendDagDb::offline(dag_path, None)?;
+        assert!(!dag.spend_processing_needed().await);
+
+        let campaign = Campaign::new(CampaignConfig {
+            name: "startup".to_string(),
+            encryption_key: SecretKey::random().to_hex(),
+            identifier_scheme: IdentifierScheme::Raw,
+            start: None,
+            end: None,
+            participants: vec!["Alice".to_string()],
+        })?;
+        dag.track_campaigns(vec![campaign]).await?;
+        assert!(dag.spend_processing_needed().await);
+        assert_eq!(dag.campaigns_summary().await.len(), 1);
+        Ok(())
+    }
+}
@@ -9,13 +9,16 @@
 #[macro_use]
 extern crate tracing;
 
+mod campaigns;
 mod dag_db;
 mod routes;
+mod verification;
 
 use bls::SecretKey;
+use campaigns::Campaign;
 use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
-use dag_db::SpendDagDb;
+use dag_db::{DagExportFormat, SpendDagDb};
 use sn_client::Client;
 use sn_logging::{Level, LogBuilder, LogFormat, LogOutputDest};
 use sn_peers_acquisition::PeersArgs;
@@ -28,6 +31,7 @@ const BETA_REWARDS_BACKUP_INTERVAL_SECS: u64 = 20 * 60;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[clap(group(clap::ArgGroup::new("offline_report").args(["verify_offline", "diff"]).multiple(false)))]
 struct Opt {
     #[command(flatten)]
     peers: PeersArgs,
@@ -40,6 +44,25 @@ struct Opt {
     /// Visualize a local DAG file offline, does not connect to the Network
     #[clap(short, long, value_name = "dag_file")]
     offline_viewer: Option<PathBuf>,
+    /// Verify a local DAG file offline and exit, does not connect to the Network
+    ///
+    /// Every spend signature and parent linkage is verified from scratch,
+    /// faults are recomputed and compared to the ones recorded in the file.
+    ///
+    /// Exits with 0 if they match, 2 if they differ and 3 if the DAG is invalid.
+    #[clap(long, value_name = "dag_file", conflicts_with = "offline_viewer")]
+    verify_offline: Option<PathBuf>,
+    /// Compare two local DAG files offline and exit, does not connect to the Network
+    ///
+    /// Lists the spends found in one DAG only, the double spend variants seen by one side only,
+    /// the differing faults and the differences in the UTXO frontier.
+    ///
+    /// Exits with 0 if both DAGs are the same and 2 if they differ.
+    #[clap(long, num_args = 2, value_names = ["our_dag_file", "their_dag_file"], conflicts_with = "offline_viewer")]
+    diff: Option<Vec<PathBuf>>,
+    /// Output the offline verification report or the DAG comparison in JSON format
+    #[clap(long, requires = "offline_report")]
+    json: bool,
 
     /// Specify the logging output destination.
     ///
@@ -71,13 +94,53 @@ struct Opt {
     /// discord usernames of the beta participants
     #[clap(short = 'k', long, value_name = "hex_secret_key")]
     beta_encryption_key: Option<String>,
+
+    /// Reward tracking campaigns to track
+    ///
+    /// Provide a JSON file with a list of campaigns, each with a unique name,
+    /// a hex encoded encryption key, an optional identifier scheme ("raw", "lowercase" or {"prefixed": "<prefix>"}),
+    /// optional start and end times in seconds since UNIX epoch and a list of participants
+    #[clap(long, value_name = "campaigns_file")]
+    campaigns: Option<PathBuf>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let opt = Opt::parse();
     let log_builder = logging_init(opt.log_output_dest, opt.log_format)?;
-    let _log_handles = log_builder.initialize()?;
+    let log_handles = log_builder.initialize()?;
+
+    if let Some(dag_file) = opt.verify_offline {
+        let report = verification::verify_dag_file(&dag_file)?;
+        if opt.json {
+            println!("{}", report.to_json()?);
+        } else {
+            println!("{report}");
+        }
+        // `exit` skips the destructors, the log guards have to be dropped first to flush the logs
+        drop(log_handles);
+        std::process::exit(report.exit_code());
+    }
+
+    if let Some(dag_files) = opt.diff {
+        let (ours, theirs) = match dag_files.as_slice() {
+            [ours, theirs] => (ours, theirs),
+            _ => return Err(eyre!("Two DAG files are required to compare them")),
+        };
+        let diff = verification::diff_dag_files(ours, theirs)?;
+        if opt.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            println!("{diff}");
+        }
+        let exit_code = if diff.is_empty() {
+            verification::EXIT_CODE_MATCH
+        } else {
+            verification::EXIT_CODE_MISMATCH
+        };
+        drop(log_handles);
+        std::process::exit(exit_code);
+    }
 
     let beta_participants = load_and_update_beta_participants(opt.beta_participants)?;
 
@@ -91,10 +154,19 @@ async fn main() -> Result<()> {
     };
     let beta_rewards_on = maybe_sk.is_some();
 
+    let campaigns = match &opt.campaigns {
+        Some(campaigns_file) => campaigns::load_campaigns(campaigns_file)?,
+        None => vec![],
+    };
+    let campaigns_on = !campaigns.is_empty();
+
     if let Some(dag_to_view) = opt.offline_viewer {
         let dag = SpendDagDb::offline(dag_to_view, maybe_sk)?;
         #[cfg(feature = "svg-dag")]
         dag.dump_dag_svg().await?;
+        if campaigns_on {
+            dag.track_campaigns(campaigns).await?;
+        }
 
         start_server(dag).await?;
         return Ok(());
@@ -107,11 +179,12 @@ async fn main() -> Result<()> {
         opt.clean,
         beta_participants,
         maybe_sk,
+        campaigns,
     )
     .await?;
 
-    if beta_rewards_on {
-        initialize_background_rewards_backup(dag.clone());
+    if beta_rewards_on || campaigns_on {
+        initialize_background_rewards_backup(dag.clone(), beta_rewards_on, campaigns_on);
     }
 
     start_server(dag).await
@@ -158,7 +231,7 @@ async fn connect_to_network(peers_args: PeersArgs) -> Result<Client> {
 }
 
 /// Regularly backup the rewards in a timestamped json file
-fn initialize_background_rewards_backup(dag: SpendDagDb) {
+fn initialize_background_rewards_backup(dag: SpendDagDb, beta_rewards: bool, campaigns: bool) {
     tokio::spawn(async move {
         loop {
             trace!(
@@ -168,10 +241,18 @@ fn initialize_background_rewards_backup(dag: SpendDagDb) {
                 BETA_REWARDS_BACKUP_INTERVAL_SECS,
             ))
             .await;
-            println!("Backing up beta rewards...");
+            if beta_rewards {
+                println!("Backing up beta rewards...");
+                if let Err(e) = dag.backup_rewards().await {
+                    eprintln!("Failed to backup beta rewards: {e}");
+                }
+            }
 
-            if let Err(e) = dag.backup_rewards().await {
-                eprintln!("Failed to backup beta rewards: {e}");
+            if campaigns {
+                println!("Backing up campaigns...");
+                if let Err(e) = dag.backup_campaigns().await {
+                    eprintln!("Failed to backup campaigns: {e}");
+                }
             }
         }
     });
@@ -186,6 +267,7 @@ async fn initialize_background_spend_dag_collection(
     clean: bool,
     beta_participants: BTreeSet<String>,
     foundation_sk: Option<SecretKey>,
+    campaigns: Vec<Campaign>,
 ) -> Result<SpendDagDb> {
     println!("Initialize spend dag...");
     let path = get_auditor_data_dir_path()?;
@@ -240,6 +322,11 @@ async fn initialize_background_spend_dag_collection(
         }
     }
 
+    // the background update only processes the spends for the campaigns tracked when it starts
+    if !campaigns.is_empty() {
+        dag.track_campaigns(campaigns).await?;
+    }
+
     // background thread to update DAG
     println!("Starting background DAG collection thread...");
     let d = dag.clone();
@@ -267,10 +354,18 @@ async fn start_server(dag: SpendDagDb) -> Result<()> {
         let response = match request.url() {
             "/" => routes::spend_dag_svg(&dag),
             s if s.starts_with("/spend/") => routes::spend(&dag, &request).await,
+            s if s == "/graphml" || s.starts_with("/graphml/") => {
+                routes::export(&dag, &request, DagExportFormat::GraphMl).await
+            }
+            s if s == "/csv" || s.starts_with("/csv/") => {
+                routes::export(&dag, &request, DagExportFormat::Csv).await
+            }
             s if s.starts_with("/add-participant/") => {
                 routes::add_participant(&dag, &request).await
             }
             "/beta-rewards" => routes::beta_rewards(&dag).await,
+            "/campaigns" => routes::campaigns(&dag).await,
+            s if s.starts_with("/campaigns/") => routes::campaign(&dag, &request).await,
             _ => routes::not_found(),
         };
 
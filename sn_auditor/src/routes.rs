@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::dag_db::{self, SpendDagDb};
+use crate::dag_db::{self, DagExportFormat, SpendDagDb};
 use color_eyre::eyre::{eyre, Result};
 use sn_client::transfers::SpendAddress;
 use std::{
@@ -64,6 +64,58 @@ pub(crate) async fn spend(
     Ok(response)
 }
 
+/// Export the DAG in the given format
+/// Accepts `/<format>` for the whole DAG or `/<format>/<spend_address>/<hops>` for the sub DAG around an address
+pub(crate) async fn export(
+    dag: &SpendDagDb,
+    request: &Request,
+    format: DagExportFormat,
+) -> Result<Response<Cursor<Vec<u8>>>> {
+    let params: Vec<_> = request
+        .url()
+        .split('/')
+        .skip(2)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let around = match params.as_slice() {
+        [] => None,
+        [addr, hops] => {
+            let spend_addr = match SpendAddress::from_str(addr) {
+                Ok(addr) => addr,
+                Err(e) => {
+                    return Ok(Response::from_string(format!(
+                        "Failed to parse address: {e}. Should be /[format]/[spend_address]/[hops]"
+                    ))
+                    .with_status_code(400))
+                }
+            };
+            let hops = match hops.parse::<usize>() {
+                Ok(hops) => hops,
+                Err(e) => {
+                    return Ok(Response::from_string(format!(
+                        "Failed to parse hops: {e}. Should be /[format]/[spend_address]/[hops]"
+                    ))
+                    .with_status_code(400))
+                }
+            };
+            Some((spend_addr, hops))
+        }
+        _ => {
+            return Ok(Response::from_string(
+                "Invalid parameters. Should be /[format] or /[format]/[spend_address]/[hops]",
+            )
+            .with_status_code(400))
+        }
+    };
+
+    let content = dag
+        .export(format, around)
+        .await
+        .map_err(|e| eyre!("Failed to export DAG: {e}"))?;
+    let response = Response::from_data(content);
+    Ok(response)
+}
+
 pub(crate) fn not_found() -> Result<Response<Cursor<Vec<u8>>>> {
     let response = Response::from_string("404: Try /").with_status_code(404);
     Ok(response)
@@ -78,6 +130,70 @@ pub(crate) async fn beta_rewards(dag: &SpendDagDb) -> Result<Response<Cursor<Vec
     Ok(response)
 }
 
+pub(crate) async fn campaigns(dag: &SpendDagDb) -> Result<Response<Cursor<Vec<u8>>>> {
+    let summary = dag.campaigns_summary().await;
+    let json = serde_json::to_string_pretty(&summary)?;
+    let response = Response::from_data(json);
+    Ok(response)
+}
+
+/// Campaign routes:
+/// - `/campaigns/<name>` for the totals of each participant in JSON format
+/// - `/campaigns/<name>/csv` for the totals of each participant in CSV format
+/// - `/campaigns/<name>/add-participant/<participant>` to track a new participant
+pub(crate) async fn campaign(
+    dag: &SpendDagDb,
+    request: &Request,
+) -> Result<Response<Cursor<Vec<u8>>>> {
+    let params: Vec<_> = request
+        .url()
+        .split('/')
+        .skip(2)
+        .filter(|p| !p.is_empty())
+        .collect();
+    match params.as_slice() {
+        [name] => {
+            let name = urlencoding::decode(name)?;
+            match dag.campaign_totals(&name).await {
+                Some(totals) => {
+                    let json = serde_json::to_string_pretty(&totals)?;
+                    Ok(Response::from_data(json))
+                }
+                None => Ok(Response::from_string(format!("No campaign named {name}"))
+                    .with_status_code(404)),
+            }
+        }
+        [name, "csv"] => {
+            let name = urlencoding::decode(name)?;
+            match dag.campaign_totals_csv(&name).await {
+                Some(csv) => Ok(Response::from_data(csv)),
+                None => Ok(Response::from_string(format!("No campaign named {name}"))
+                    .with_status_code(404)),
+            }
+        }
+        [name, "add-participant", participant] => {
+            let name = urlencoding::decode(name)?;
+            let participant = urlencoding::decode(participant)?;
+            if participant.trim().is_empty() {
+                return Ok(
+                    Response::from_string("participant cannot be empty").with_status_code(400)
+                );
+            }
+            if let Err(err) = dag.track_campaign_participant(&name, &participant).await {
+                return Ok(Response::from_string(format!(
+                    "Failed to track new participant: {err}"
+                ))
+                .with_status_code(400));
+            }
+            Ok(Response::from_string("Successfully added participant "))
+        }
+        _ => Ok(Response::from_string(
+            "Invalid campaign route. Should be /campaigns/[name], /campaigns/[name]/csv or /campaigns/[name]/add-participant/[participant]",
+        )
+        .with_status_code(400)),
+    }
+}
+
 pub(crate) async fn add_participant(
     dag: &SpendDagDb,
     request: &Request,
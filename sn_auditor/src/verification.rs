@@ -0,0 +1,152 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+use sn_client::{SpendDag, SpendDagDiff, SpendFault};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Exit code when the recorded faults match the recomputed ones, or when two DAGs are the same
+pub const EXIT_CODE_MATCH: i32 = 0;
+/// Exit code when the recorded faults differ from the recomputed ones, or when two DAGs differ
+pub const EXIT_CODE_MISMATCH: i32 = 2;
+/// Exit code when the DAG itself is invalid and faults could not be recomputed
+pub const EXIT_CODE_INVALID_DAG: i32 = 3;
+
+/// Report of the offline verification of a DAG file
+/// All collections are sorted so the same DAG file always produces the same report
+#[derive(Debug, Serialize)]
+pub struct VerificationReport {
+    dag_file: PathBuf,
+    source: String,
+    spends: usize,
+    utxos: usize,
+    recorded_faults: usize,
+    recomputed_faults: usize,
+    /// Faults found when re-verifying that were not recorded in the DAG file
+    missing_faults: BTreeSet<SpendFault>,
+    /// Faults recorded in the DAG file that are not found when re-verifying
+    stale_faults: BTreeSet<SpendFault>,
+    /// Error making the whole DAG invalid, if any
+    dag_error: Option<String>,
+}
+
+impl VerificationReport {
+    /// The exit code matching the outcome of the verification
+    pub fn exit_code(&self) -> i32 {
+        if self.dag_error.is_some() {
+            EXIT_CODE_INVALID_DAG
+        } else if !self.missing_faults.is_empty() || !self.stale_faults.is_empty() {
+            EXIT_CODE_MISMATCH
+        } else {
+            EXIT_CODE_MATCH
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DAG file: {:?}", self.dag_file)?;
+        writeln!(f, "Source: {}", self.source)?;
+        writeln!(f, "Spends: {}", self.spends)?;
+        writeln!(f, "UTXOs: {}", self.utxos)?;
+        writeln!(f, "Recorded faults: {}", self.recorded_faults)?;
+        writeln!(f, "Recomputed faults: {}", self.recomputed_faults)?;
+        if let Some(err) = &self.dag_error {
+            writeln!(f, "DAG is invalid: {err}")?;
+        }
+        writeln!(
+            f,
+            "Faults found but not recorded: {}",
+            self.missing_faults.len()
+        )?;
+        for fault in self.missing_faults.iter() {
+            writeln!(f, "  + {fault}")?;
+        }
+        writeln!(
+            f,
+            "Faults recorded but not found: {}",
+            self.stale_faults.len()
+        )?;
+        for fault in self.stale_faults.iter() {
+            writeln!(f, "  - {fault}")?;
+        }
+        let verdict = match self.exit_code() {
+            EXIT_CODE_MATCH => "OK: recorded faults match the verification",
+            EXIT_CODE_MISMATCH => "MISMATCH: recorded faults differ from the verification",
+            _ => "INVALID: the DAG could not be verified",
+        };
+        write!(f, "{verdict}")
+    }
+}
+
+/// Load a DAG file and verify it from scratch without any network access:
+/// every spend signature and parent linkage is verified again, the faults are recomputed
+/// and compared to the faults recorded in the file
+pub fn verify_dag_file(dag_file: &Path) -> Result<VerificationReport> {
+    let dag = SpendDag::load_from_file(dag_file)
+        .map_err(|e| eyre!("Failed to load DAG file {dag_file:?}: {e}"))?;
+    info!("Verifying DAG file {dag_file:?} offline...");
+
+    let recorded: BTreeSet<SpendFault> = dag.faults().values().flatten().cloned().collect();
+    let (recomputed, dag_error) = match dag.rebuild_and_verify() {
+        Ok(rebuilt) => {
+            let faults: BTreeSet<SpendFault> =
+                rebuilt.faults().values().flatten().cloned().collect();
+            (faults, None)
+        }
+        Err(e) => {
+            warn!("DAG file {dag_file:?} is invalid: {e}");
+            (BTreeSet::new(), Some(e.to_string()))
+        }
+    };
+
+    let report = VerificationReport {
+        dag_file: dag_file.to_path_buf(),
+        source: dag.source().to_hex(),
+        spends: dag.all_spends().len(),
+        utxos: dag.get_utxos().len(),
+        recorded_faults: recorded.len(),
+        recomputed_faults: recomputed.len(),
+        missing_faults: recomputed.difference(&recorded).cloned().collect(),
+        stale_faults: if dag_error.is_some() {
+            BTreeSet::new()
+        } else {
+            recorded.difference(&recomputed).cloned().collect()
+        },
+        dag_error,
+    };
+    info!("Offline verification of {dag_file:?} done: {report:?}");
+    Ok(report)
+}
+
+/// Load two DAG files and compare them without any network access
+pub fn diff_dag_files(ours: &Path, theirs: &Path) -> Result<SpendDagDiff> {
+    let our_dag = SpendDag::load_from_file(ours)
+        .map_err(|e| eyre!("Failed to load DAG file {ours:?}: {e}"))?;
+    let their_dag = SpendDag::load_from_file(theirs)
+        .map_err(|e| eyre!("Failed to load DAG file {theirs:?}: {e}"))?;
+    if our_dag.source() != their_dag.source() {
+        warn!(
+            "Comparing DAGs with different sources: {:?} and {:?}",
+            our_dag.source(),
+            their_dag.source()
+        );
+    }
+
+    info!("Comparing DAG files {ours:?} and {theirs:?} offline...");
+    let diff = our_dag.diff(&their_dag);
+    info!("Comparison of {ours:?} and {theirs:?} done: {diff:?}");
+    Ok(diff)
+}
@@ -934,7 +934,7 @@ mod tests {
         registers::{EntryHash, RegisterAddress},
         test_utils::{get_funded_wallet, get_new_client, random_file_chunk},
         transfers::MainSecretKey,
-        FolderEntry, UploadCfg, BATCH_SIZE,
+        FolderEntry, PaymentPolicy, UploadCfg, BATCH_SIZE,
     };
 
     use bls::SecretKey;
@@ -956,6 +956,8 @@ mod tests {
             show_holders: false,
             max_repayments_for_failed_data: 1,
             collect_registers: false,
+            payment_policy: PaymentPolicy::new(),
+            check_existing_chunks: true,
         };
         let make_data_public = false;
         (cfg, make_data_public)
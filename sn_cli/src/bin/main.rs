@@ -14,6 +14,7 @@ mod subcommands;
 use subcommands::{
     files::files_cmds,
     folders::folders_cmds,
+    network::network_cmds,
     register::register_cmds,
     wallet::{
         hot_wallet::{wallet_cmds, wallet_cmds_without_client, WalletCmds},
@@ -159,6 +160,7 @@ async fn main() -> Result<()> {
         SubCmd::Register(cmds) => {
             register_cmds(cmds, &client, &client_data_dir_path, should_verify_store).await
         }
+        SubCmd::Network(cmds) => network_cmds(cmds, &client).await,
     };
     println!("Completed with {result:?} of execute {cmd_str:?}");
 
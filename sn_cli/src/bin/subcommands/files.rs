@@ -17,12 +17,14 @@ use color_eyre::{
 };
 use sn_client::{
     protocol::storage::{Chunk, ChunkAddress, RetryStrategy},
-    UploadCfg,
+    transfers::NanoTokens,
+    PaymentPolicy, QuoteSelection, UploadCfg,
 };
 use sn_client::{Client, FilesApi, BATCH_SIZE};
 use std::{
     ffi::OsString,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use walkdir::WalkDir;
 use xor_name::XorName;
@@ -36,6 +38,14 @@ pub enum FilesCmds {
         /// Should the file be made accessible to all. (This is irreversible)
         #[clap(long, name = "make_public", default_value = "false", short = 'p')]
         make_data_public: bool,
+        /// Export the itemised estimate as JSON to this file.
+        #[clap(long, value_name = "FILE")]
+        export: Option<PathBuf>,
+        /// Which of the quotes of the close group would be paid.
+        ///
+        /// Valid values are "cheapest" or "median".
+        #[clap(long, default_value_t = QuoteSelection::Cheapest, value_parser = QuoteSelection::from_str)]
+        quote_selection: QuoteSelection,
     },
     Upload {
         /// The location of the file(s) to upload.
@@ -56,6 +66,29 @@ pub enum FilesCmds {
         /// to 'persistent' (most effort).
         #[clap(long, default_value_t = RetryStrategy::Balanced, short = 'r', help = "Sets the retry strategy on upload failure. Options: 'quick' for minimal effort, 'balanced' for moderate effort, or 'persistent' for maximum effort.")]
         retry_strategy: RetryStrategy,
+        /// The maximum price to pay for storing a single chunk, in tokens.
+        ///
+        /// The upload is aborted if a chunk is quoted above this price.
+        #[clap(long, value_parser = NanoTokens::from_str)]
+        max_price: Option<NanoTokens>,
+        /// The maximum amount to pay for storing the whole upload, in tokens, royalties excluded.
+        ///
+        /// The upload is aborted before paying for the chunk that would exceed it.
+        #[clap(long, value_parser = NanoTokens::from_str)]
+        max_budget: Option<NanoTokens>,
+        /// Which of the quotes of the close group to pay.
+        ///
+        /// Valid values are "cheapest" or "median".
+        #[clap(long, default_value_t = QuoteSelection::Cheapest, value_parser = QuoteSelection::from_str)]
+        quote_selection: QuoteSelection,
+        /// Accept the quotes priced out of line with the metrics the nodes report.
+        #[clap(long)]
+        accept_anomalous_quotes: bool,
+        /// Do not check which chunks are already stored in the network before paying for them.
+        ///
+        /// This saves a request per chunk when uploading data known to be new.
+        #[clap(long)]
+        skip_existence_check: bool,
     },
     Download {
         /// The name to apply to the downloaded file.
@@ -98,11 +131,21 @@ pub(crate) async fn files_cmds(
         FilesCmds::Estimate {
             path,
             make_data_public,
+            export,
+            quote_selection,
         } => {
             let files_api = FilesApi::build(client.clone(), root_dir.to_path_buf())?;
             let chunk_manager = ChunkManager::new(root_dir);
+            let upload_cfg = UploadCfg {
+                payment_policy: PaymentPolicy {
+                    quote_selection,
+                    ..Default::default()
+                },
+                check_existing_chunks: true,
+                ..Default::default()
+            };
             Estimator::new(chunk_manager, files_api)
-                .estimate_cost(path, make_data_public, root_dir)
+                .estimate_cost(path, make_data_public, root_dir, upload_cfg, export)
                 .await?
         }
         FilesCmds::Upload {
@@ -110,6 +153,11 @@ pub(crate) async fn files_cmds(
             batch_size,
             retry_strategy,
             make_data_public,
+            max_price,
+            max_budget,
+            quote_selection,
+            accept_anomalous_quotes,
+            skip_existence_check,
         } => {
             let files_count = count_files_in_path_recursively(&file_path);
 
@@ -127,6 +175,13 @@ pub(crate) async fn files_cmds(
                 batch_size,
                 verify_store,
                 retry_strategy,
+                payment_policy: PaymentPolicy {
+                    max_price_per_record: max_price,
+                    max_total_budget: max_budget,
+                    quote_selection,
+                    reject_anomalous_quotes: !accept_anomalous_quotes,
+                },
+                check_existing_chunks: !skip_existence_check,
                 ..Default::default()
             };
             let files_uploader = FilesUploader::new(client.clone(), root_dir.to_path_buf())
@@ -137,6 +137,7 @@ pub(crate) async fn folders_cmds(
                 verify_store,
                 batch_size,
                 retry_strategy,
+                check_existing_chunks: true,
                 ..Default::default()
             };
             acc_packet.sync(options, make_data_public).await?;
@@ -8,6 +8,7 @@
 
 pub(crate) mod files;
 pub(crate) mod folders;
+pub(crate) mod network;
 pub(crate) mod register;
 pub(crate) mod wallet;
 
@@ -83,4 +84,7 @@ pub(super) enum SubCmd {
     #[clap(name = "register", subcommand)]
     /// Commands for register management
     Register(register::RegisterCmds),
+    #[clap(name = "network", subcommand)]
+    /// Commands for inspecting the network
+    Network(network::NetworkCmds),
 }
@@ -0,0 +1,34 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use clap::Subcommand;
+use color_eyre::Result;
+use sn_client::Client;
+
+#[derive(Subcommand, Debug)]
+pub enum NetworkCmds {
+    /// Estimate the number of nodes, the store cost and the storage capacity of the network.
+    ///
+    /// Random addresses are sampled, the more samples the tighter the confidence bounds.
+    Estimate {
+        /// The number of random addresses to sample.
+        #[clap(long, short = 's', default_value_t = 20)]
+        samples: usize,
+    },
+}
+
+pub(crate) async fn network_cmds(cmds: NetworkCmds, client: &Client) -> Result<()> {
+    match cmds {
+        NetworkCmds::Estimate { samples } => {
+            println!("Sampling {samples} random addresses to estimate the network...");
+            let estimate = client.estimate_network(samples).await?;
+            println!("{estimate}");
+        }
+    }
+    Ok(())
+}
@@ -123,6 +123,59 @@ pub async fn audit(
     Ok(())
 }
 
+/// Audit the ancestry of the wallet's available cash notes all the way back to Genesis
+/// This only gathers the spends our cash notes descend from, instead of the entire DAG
+pub async fn audit_wallet_ancestry(client: &Client, root_dir: &Path) -> Result<()> {
+    let mut wallet = load_account_wallet_or_create_with_mnemonic(root_dir, None)?;
+    let (available_cash_notes, exclusive_access) = wallet.available_cash_notes()?;
+    // we only read the cash notes, no need to hold the wallet lock while crawling
+    drop(exclusive_access);
+
+    let cash_notes: Vec<_> = available_cash_notes.into_iter().map(|(cn, _)| cn).collect();
+    if cash_notes.is_empty() {
+        println!("No cash notes found in the wallet, nothing to audit.");
+        return Ok(());
+    }
+
+    println!(
+        "Auditing the ancestry of {} cash notes back to Genesis, note that this might take a while...",
+        cash_notes.len()
+    );
+    let start_time = std::time::Instant::now();
+    let dag = client.spend_dag_build_ancestry_of(&cash_notes).await?;
+    println!(
+        "Gathered {} ancestor spends in {:?}",
+        dag.all_spends().len(),
+        start_time.elapsed()
+    );
+
+    let mut tainted = 0;
+    for cn in cash_notes.iter() {
+        let addr = SpendAddress::from_unique_pubkey(&cn.unique_pubkey());
+        let value = cn.value()?;
+        let faults = dag.get_ancestry_faults(&addr);
+        if faults.is_empty() {
+            println!("Cash note at {addr:?} ({value}) is valid and comes from Genesis");
+        } else {
+            tainted += 1;
+            println!(
+                "Cash note at {addr:?} ({value}) has {} faults in its ancestry:",
+                faults.len()
+            );
+            println!("{faults:#?}");
+        }
+    }
+
+    if tainted > 0 {
+        bail!(
+            "{tainted} of the {} cash notes in the wallet have faults in their ancestry",
+            cash_notes.len()
+        );
+    }
+    println!("Audit completed successfully, all cash notes in the wallet are valid.");
+    Ok(())
+}
+
 /// Redeem royalties from the Network and deposit them into the wallet
 /// Only works if the wallet has the private key for the royalties
 async fn redeem_royalties(
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    audit::{audit, verify_spend_at},
+    audit::{audit, audit_wallet_ancestry, verify_spend_at},
     helpers::{get_faucet, receive},
     WalletApiHelper,
 };
@@ -127,6 +127,12 @@ pub enum WalletCmds {
         /// Providing this key allow displaying rewards statistics gathered from the DAG.
         #[clap(long, name = "sk_str")]
         sk_str: Option<String>,
+        /// Only audit the ancestry of this wallet's available cash notes
+        ///
+        /// Crawls back from the cash notes to Genesis instead of gathering the entire DAG,
+        /// reporting any fault tainting the wallet's funds
+        #[clap(long, default_value = "false", conflicts_with_all = ["dot", "royalties", "sk_str"])]
+        wallet_ancestry: bool,
     },
     Status,
 }
@@ -219,10 +225,15 @@ pub(crate) async fn wallet_cmds(
             maid_address,
             signature,
         } => get_faucet(root_dir, client, url.clone(), maid_address, signature).await,
+        WalletCmds::Audit {
+            wallet_ancestry: true,
+            ..
+        } => audit_wallet_ancestry(client, root_dir).await,
         WalletCmds::Audit {
             dot,
             royalties,
             sk_str,
+            ..
         } => {
             let sk_key = if let Some(s) = sk_str {
                 match SecretKey::from_hex(&s) {
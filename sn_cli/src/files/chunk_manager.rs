@@ -331,6 +331,11 @@ impl ChunkManager {
             .collect::<Vec<(XorName, PathBuf)>>()
     }
 
+    /// Get the files that have been chunked, along with their chunks
+    pub fn chunked_files(&self) -> impl Iterator<Item = &ChunkedFile> {
+        self.chunks.values()
+    }
+
     pub fn is_chunks_empty(&self) -> bool {
         self.chunks
             .values()
@@ -22,9 +22,9 @@ use xor_name::XorName;
 
 use sn_client::{
     protocol::storage::{Chunk, ChunkAddress, RetryStrategy},
-    FilesApi, FilesDownload, FilesDownloadEvent,
+    FilesApi, FilesDownload, FilesDownloadEvent, ProgressTracker,
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// The default folder to download files to.
 const DOWNLOAD_FOLDER: &str = "safe_files";
@@ -110,12 +110,15 @@ pub async fn download_file(
 
     let progress_handler = tokio::spawn(async move {
         let mut progress_bar: Option<ProgressBar> = None;
+        let progress_tracker = ProgressTracker::new();
         // The loop is guaranteed to end, as the channel will be closed when the download completes or errors out.
         while let Some(event) = download_events_rx.recv().await {
+            progress_tracker.on_download_event(&event);
             match event {
                 FilesDownloadEvent::Downloaded(_) => {
                     if let Some(progress_bar) = &progress_bar {
                         progress_bar.inc(1);
+                        progress_bar.set_message(progress_tracker.snapshot().bytes_summary());
                     }
                 }
                 FilesDownloadEvent::ChunksCount(count) => {
@@ -148,6 +151,10 @@ pub async fn download_file(
         if let Some(progress_bar) = progress_bar {
             progress_bar.finish_and_clear();
         }
+        match serde_json::to_string(&progress_tracker.snapshot()) {
+            Ok(snapshot) => info!("Download progress: {snapshot}"),
+            Err(err) => warn!("Failed to serialize the download progress: {err:?}"),
+        }
     });
 
     let download_result = files_download
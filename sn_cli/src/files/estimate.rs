@@ -10,13 +10,35 @@ use super::ChunkManager;
 
 use std::path::{Path, PathBuf};
 
-use color_eyre::Result;
+use chrono::{DateTime, Utc};
+use color_eyre::{eyre::eyre, Result};
+use serde::Serialize;
+use sn_client::{transfers::NanoTokens, FilesApi, UploadCfg, UploadEstimate, Uploader};
 
-use sn_client::{
-    protocol::{storage::ChunkAddress, NetworkAddress},
-    transfers::NanoTokens,
-    FilesApi,
-};
+/// The estimated cost of a single file. The amounts are in nanos.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEstimate {
+    pub path: PathBuf,
+    pub chunks: usize,
+    pub already_stored_chunks: usize,
+    pub storage_cost: NanoTokens,
+    pub royalty_fees: NanoTokens,
+}
+
+/// The itemised estimate of an upload. The amounts are in nanos.
+///
+/// A chunk shared by several files is counted in each of them, but paid only once in the totals.
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimateReport {
+    pub files: Vec<FileEstimate>,
+    pub chunks: usize,
+    pub already_stored_chunks: usize,
+    pub storage_cost: NanoTokens,
+    pub royalty_fees: NanoTokens,
+    pub total_cost: NanoTokens,
+    /// The earliest expiry of the quotes, in RFC 3339, after which the estimate might not hold
+    pub quote_expiry: Option<String>,
+}
 
 pub struct Estimator {
     chunk_manager: ChunkManager,
@@ -31,53 +53,117 @@ impl Estimator {
         }
     }
 
-    /// Estimate the upload cost of a chosen file
+    /// Estimate the upload cost of a chosen file, from the real quotes of the network, without paying.
+    ///
+    /// The report is printed, and exported as JSON to `export_path` if provided.
     pub async fn estimate_cost(
         mut self,
         path: PathBuf,
         make_data_public: bool,
         root_dir: &Path,
+        upload_cfg: UploadCfg,
+        export_path: Option<PathBuf>,
     ) -> Result<()> {
         self.chunk_manager
             .chunk_path(&path, false, make_data_public)?;
 
-        let mut estimate: u64 = 0;
-
         let balance = FilesApi::new(self.files_api.client().clone(), root_dir.to_path_buf())
             .wallet()?
-            .balance()
-            .as_nano();
-
-        for (chunk_address, _location) in self.chunk_manager.get_chunks() {
-            let c = self.files_api.clone();
-
-            tokio::spawn(async move {
-                let (_peer, _cost, quote) = c
-                    .wallet()
-                    .expect("estimate_cost: Wallet error.")
-                    .get_store_cost_at_address(NetworkAddress::from_chunk_address(
-                        ChunkAddress::new(chunk_address),
-                    ))
-                    .await
-                    .expect("estimate_cost: Error with file.");
-                quote.cost.as_nano()
-            })
-            .await
-            .map(|nanos| estimate += nanos)
-            .expect("estimate_cost: Concurrency error.");
+            .balance();
+
+        println!("Collecting the quotes of the network, nothing will be paid...");
+        let mut uploader = Uploader::new(self.files_api.client().clone(), root_dir.to_path_buf());
+        uploader.set_upload_cfg(upload_cfg);
+        uploader.insert_chunk_paths(self.chunk_manager.get_chunks());
+        let estimate = uploader.estimate().await?;
+
+        let report = self.build_report(&estimate)?;
+        print_report(&report, balance);
+
+        if let Some(export_path) = export_path {
+            let json = serde_json::to_string_pretty(&report)?;
+            std::fs::write(&export_path, json)?;
+            println!("Estimate exported to {export_path:?}");
         }
 
-        let total = balance.saturating_sub(estimate);
+        Ok(())
+    }
 
-        println!("**************************************");
-        println!("Your current balance: {}", NanoTokens::from(balance));
-        println!("Transfer cost estimate: {}", NanoTokens::from(estimate));
+    fn build_report(&self, estimate: &UploadEstimate) -> Result<EstimateReport> {
+        let mut files = vec![];
+        for chunked_file in self.chunk_manager.chunked_files() {
+            let mut file = FileEstimate {
+                path: chunked_file.file_path.clone(),
+                chunks: chunked_file.chunks.len(),
+                already_stored_chunks: 0,
+                storage_cost: NanoTokens::zero(),
+                royalty_fees: NanoTokens::zero(),
+            };
+            for (xorname, _path) in chunked_file.chunks.iter() {
+                let item = estimate
+                    .items
+                    .get(xorname)
+                    .ok_or_else(|| eyre!("No quote was collected for the chunk {xorname:?}"))?;
+                if item.already_stored {
+                    file.already_stored_chunks += 1;
+                }
+                file.storage_cost = file
+                    .storage_cost
+                    .checked_add(item.storage_cost)
+                    .ok_or_else(|| eyre!("Storage cost overflow"))?;
+                file.royalty_fees = file
+                    .royalty_fees
+                    .checked_add(item.royalty_fee)
+                    .ok_or_else(|| eyre!("Royalty fees overflow"))?;
+            }
+            files.push(file);
+        }
+
+        Ok(EstimateReport {
+            files,
+            chunks: estimate.items.len(),
+            already_stored_chunks: estimate.already_stored_count,
+            storage_cost: estimate.storage_cost,
+            royalty_fees: estimate.royalty_fees,
+            total_cost: estimate.total_cost()?,
+            quote_expiry: estimate
+                .quote_expiry
+                .map(|expiry| DateTime::<Utc>::from(expiry).to_rfc3339()),
+        })
+    }
+}
+
+fn print_report(report: &EstimateReport, balance: NanoTokens) {
+    println!("**************************************");
+    for file in report.files.iter() {
         println!(
-            "Your balance estimate after transfer: {}",
-            NanoTokens::from(total)
+            "{:?}: {} chunks ({} already stored), storage cost {}, royalties {}",
+            file.path,
+            file.chunks,
+            file.already_stored_chunks,
+            file.storage_cost,
+            file.royalty_fees
         );
-        println!("**************************************");
-
-        Ok(())
     }
+    println!("**************************************");
+    println!(
+        "Chunks: {} ({} already stored)",
+        report.chunks, report.already_stored_chunks
+    );
+    println!("Storage cost estimate: {}", report.storage_cost);
+    println!("Royalties estimate: {}", report.royalty_fees);
+    println!("Transfer cost estimate: {}", report.total_cost);
+    println!("Your current balance: {balance}");
+    println!(
+        "Your balance estimate after transfer: {}",
+        NanoTokens::from(
+            balance
+                .as_nano()
+                .saturating_sub(report.total_cost.as_nano())
+        )
+    );
+    if let Some(quote_expiry) = &report.quote_expiry {
+        println!("The quotes expire at {quote_expiry}");
+    }
+    println!("**************************************");
 }
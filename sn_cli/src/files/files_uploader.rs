@@ -15,10 +15,12 @@ use rand::prelude::SliceRandom;
 use rand::thread_rng;
 use sn_client::{
     transfers::{TransferError, WalletError},
-    Client, Error as ClientError, UploadCfg, UploadEvent, UploadSummary, Uploader,
+    Client, Error as ClientError, ProgressSnapshot, ProgressTracker, UploadCfg, UploadEvent,
+    UploadSummary, Uploader,
 };
 use sn_protocol::storage::{Chunk, ChunkAddress};
 use std::{
+    collections::BTreeSet,
     ffi::OsString,
     path::{Path, PathBuf},
     time::{Duration, Instant},
@@ -36,6 +38,8 @@ pub struct FilesUploadSummary {
     pub completed_files: Vec<(PathBuf, OsString, ChunkAddress)>,
     /// The list of incomplete files (FilePath, FileName, HeadChunkAddress)
     pub incomplete_files: Vec<(PathBuf, OsString, ChunkAddress)>,
+    /// The progress of the upload once it has finished.
+    pub progress: ProgressSnapshot,
 }
 
 /// A trait designed to customize the standard output behavior for file upload processes.
@@ -144,6 +148,7 @@ impl FilesUploader {
             );
         }
 
+        let progress_tracker = Self::progress_tracker(&chunk_manager, &chunks_to_upload);
         let now = Instant::now();
         let mut uploader = Uploader::new(self.client, self.root_dir);
         uploader.set_upload_cfg(self.upload_cfg);
@@ -153,6 +158,7 @@ impl FilesUploader {
             chunk_manager,
             self.make_data_public,
             chunks_to_upload_len,
+            progress_tracker.clone(),
             uploader.get_event_receiver(),
             self.status_notifier.take(),
         )?;
@@ -188,6 +194,7 @@ impl FilesUploader {
                     (path.clone(), file_name.clone(), *head_address)
                 })
                 .collect(),
+            progress: progress_tracker.snapshot(),
         };
         Ok(summary)
     }
@@ -276,11 +283,37 @@ impl FilesUploader {
         Ok(failed_chunks)
     }
 
+    /// Track the progress of the files having chunks left to upload
+    fn progress_tracker(
+        chunk_manager: &ChunkManager,
+        chunks_to_upload: &[(XorName, PathBuf)],
+    ) -> ProgressTracker {
+        let chunk_size = |path: &PathBuf| std::fs::metadata(path).map_or(0, |m| m.len());
+        let to_upload: BTreeSet<_> = chunks_to_upload
+            .iter()
+            .map(|(xorname, _)| xorname)
+            .collect();
+        let progress_tracker = ProgressTracker::new();
+        for chunked_file in chunk_manager.chunked_files() {
+            let chunks: Vec<_> = chunked_file
+                .chunks
+                .iter()
+                .filter(|(xorname, _)| to_upload.contains(xorname))
+                .map(|(xorname, path)| (*xorname, chunk_size(path)))
+                .collect();
+            if !chunks.is_empty() {
+                progress_tracker.add_file(chunked_file.file_name.to_string_lossy(), chunks);
+            }
+        }
+        progress_tracker
+    }
+
     #[allow(clippy::type_complexity)]
     fn spawn_upload_events_handler(
         mut chunk_manager: ChunkManager,
         make_data_public: bool,
         chunks_to_upload_len: usize,
+        progress_tracker: ProgressTracker,
         mut upload_event_rx: Receiver<UploadEvent>,
         status_notifier: Option<Box<dyn FilesUploadStatusNotifier>>,
     ) -> Result<JoinHandle<Result<(ChunkManager, Option<Box<dyn FilesUploadStatusNotifier>>)>>>
@@ -291,6 +324,8 @@ impl FilesUploader {
             // The loop is guaranteed to end, as the channel will be
             // closed when the upload completes or errors out.
             while let Some(event) = upload_event_rx.recv().await {
+                progress_tracker.on_upload_event(&event);
+                progress_bar.set_message(progress_tracker.snapshot().bytes_summary());
                 match event {
                     UploadEvent::ChunkUploaded(addr)
                     | UploadEvent::ChunkAlreadyExistsInNetwork(addr) => {
@@ -306,10 +341,15 @@ impl FilesUploader {
                     }
                     UploadEvent::RegisterUploaded { .. }
                     | UploadEvent::RegisterUpdated { .. }
+                    | UploadEvent::UploadRetried(_)
                     | UploadEvent::PaymentMade { .. } => {}
                 }
             }
             progress_bar.finish_and_clear();
+            match serde_json::to_string(&progress_tracker.snapshot()) {
+                Ok(snapshot) => info!("Upload progress: {snapshot}"),
+                Err(err) => warn!("Failed to serialize the upload progress: {err:?}"),
+            }
 
             // this check is to make sure that we don't partially write to the uploaded_files file if the upload process
             // terminates with an error. This race condition can happen as we bail on `upload_result` before we await the
@@ -9,9 +9,10 @@
 use super::{
     error::{Error, Result},
     Client, ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver, ClientRegister,
-    WalletClient,
+    RecordCache, WalletClient,
 };
 use bls::{PublicKey, SecretKey, Signature};
+use bytes::Bytes;
 use libp2p::{
     identity::Keypair,
     kad::{Quorum, Record},
@@ -24,7 +25,7 @@ use sn_networking::{
     get_signed_spend_from_record, multiaddr_is_global,
     target_arch::{interval, spawn, timeout, Instant},
     GetRecordCfg, GetRecordError, NetworkBuilder, NetworkError, NetworkEvent, PutRecordCfg,
-    VerificationKind, CLOSE_GROUP_SIZE,
+    TransportMode, VerificationKind, CLOSE_GROUP_SIZE,
 };
 use sn_protocol::{
     error::Error as ProtocolError,
@@ -106,11 +107,9 @@ impl Client {
         let root_dir = std::env::temp_dir();
         trace!("Starting Kad swarm in client mode..{root_dir:?}.");
 
-        #[cfg(not(feature = "open-metrics"))]
-        let network_builder = NetworkBuilder::new(Keypair::generate_ed25519(), local, root_dir);
-
-        #[cfg(feature = "open-metrics")]
         let mut network_builder = NetworkBuilder::new(Keypair::generate_ed25519(), local, root_dir);
+        // Prefer QUIC, but fall back to TCP for the networks blocking UDP
+        network_builder.transport_mode(TransportMode::QuicAndTcp);
         #[cfg(feature = "open-metrics")]
         network_builder.metrics_registry(Some(Registry::default()));
 
@@ -125,6 +124,7 @@ impl Client {
             network: network.clone(),
             events_broadcaster,
             signer: Arc::new(signer),
+            record_cache: None,
         };
 
         // subscribe to our events channel first, so we don't have intermittent
@@ -307,6 +307,73 @@ impl Client {
         self.events_broadcaster.subscribe()
     }
 
+    /// Set a cache of the records fetched from the network.
+    ///
+    /// The Chunks, Registers and Spends got afterwards are served from the cache while fresh,
+    /// including through the `FilesDownload`, `FoldersApi` and `ClientRegister` created from this
+    /// client. The cache is shared by all the clones of this client.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use sn_client::{Client, Error, RecordCache, RecordCacheConfig};
+    /// use bls::SecretKey;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(),Error>{
+    /// let mut client = Client::new(SecretKey::random(), None, None, None).await?;
+    /// client.set_record_cache(RecordCache::new(RecordCacheConfig::default()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_record_cache(&mut self, record_cache: RecordCache) {
+        self.record_cache = Some(record_cache);
+    }
+
+    /// The cache of the records fetched from the network, if any.
+    /// Its `stats()` provide the hit rate.
+    pub fn record_cache(&self) -> Option<&RecordCache> {
+        self.record_cache.as_ref()
+    }
+
+    /// Gets the record from the cache if it is fresh there, otherwise from the network.
+    /// The records got from the network are cached when `cache_fetched` is true.
+    async fn get_record_with_cache(
+        &self,
+        address: &NetworkAddress,
+        get_cfg: &GetRecordCfg,
+        use_cached: bool,
+        cache_fetched: bool,
+    ) -> std::result::Result<Record, NetworkError> {
+        let key = address.to_record_key();
+        if use_cached {
+            if let Some(value) = self.record_cache.as_ref().and_then(|c| c.get(address)) {
+                trace!(
+                    "Got record {:?} from the cache",
+                    PrettyPrintRecordKey::from(&key)
+                );
+                return Ok(Record::new(key, value.to_vec()));
+            }
+        }
+
+        let record = self.network.get_record_from_network(key, get_cfg).await?;
+        if cache_fetched {
+            self.cache_record(address, &record);
+        }
+        Ok(record)
+    }
+
+    fn cache_record(&self, address: &NetworkAddress, record: &Record) {
+        if let Some(cache) = &self.record_cache {
+            cache.put(address.clone(), Bytes::from(record.value.clone()));
+        }
+    }
+
+    /// Drops the record of that address from the cache, e.g. once we updated it
+    pub(crate) fn invalidate_cached_record(&self, address: &NetworkAddress) {
+        if let Some(cache) = &self.record_cache {
+            cache.invalidate(address);
+        }
+    }
+
     /// Sign the given data.
     ///
     /// # Arguments
@@ -448,7 +515,7 @@ impl Client {
         address: RegisterAddress,
         is_verifying: bool,
     ) -> Result<SignedRegister> {
-        let key = NetworkAddress::from_register_address(address).to_record_key();
+        let network_address = NetworkAddress::from_register_address(address);
         let get_quorum = if is_verifying {
             Quorum::N(NonZeroUsize::new(2).ok_or(Error::NonZeroUsizeWasInitialisedAsZero)?)
         } else {
@@ -466,7 +533,10 @@ impl Client {
             expected_holders: Default::default(),
         };
 
-        let maybe_record = self.network.get_record_from_network(key, &get_cfg).await;
+        // A verifying read always goes to the network, but refreshes the cache
+        let maybe_record = self
+            .get_record_with_cache(&network_address, &get_cfg, !is_verifying, true)
+            .await;
         let record = match &maybe_record {
             Ok(r) => r,
             Err(NetworkError::GetRecordError(GetRecordError::SplitRecord { result_map })) => {
@@ -722,7 +792,27 @@ impl Client {
         retry_strategy: Option<RetryStrategy>,
     ) -> Result<Chunk> {
         info!("Getting chunk: {address:?}");
-        let key = NetworkAddress::from_chunk_address(address).to_record_key();
+        let network_address = NetworkAddress::from_chunk_address(address);
+
+        // Chunks are content addressed, hence the cached ones can be trusted once verified
+        if !show_holders {
+            if let Some(value) = self
+                .record_cache
+                .as_ref()
+                .and_then(|c| c.get(&network_address))
+            {
+                let record = Record::new(network_address.to_record_key(), value.to_vec());
+                match try_deserialize_record::<Chunk>(&record) {
+                    Ok(chunk) if XorName::from_content(chunk.value()) == *address.xorname() => {
+                        return Ok(chunk);
+                    }
+                    _ => {
+                        warn!("Invalid cached chunk at {address:?}, fetching it again");
+                        self.invalidate_cached_record(&network_address);
+                    }
+                }
+            }
+        }
 
         let expected_holders = if show_holders {
             let result: HashSet<_> = self
@@ -743,10 +833,13 @@ impl Client {
             target_record: None,
             expected_holders,
         };
-        let record = self.network.get_record_from_network(key, &get_cfg).await?;
+        let record = self
+            .get_record_with_cache(&network_address, &get_cfg, false, false)
+            .await?;
         let header = RecordHeader::from_record(&record)?;
         if let RecordKind::Chunk = header.kind {
             let chunk: Chunk = try_deserialize_record(&record)?;
+            self.cache_record(&network_address, &record);
             Ok(chunk)
         } else {
             Err(NetworkError::RecordKindMismatch(RecordKind::Chunk).into())
@@ -758,25 +851,45 @@ impl Client {
     pub async fn verify_chunk_stored(&self, chunk: &Chunk) -> Result<()> {
         let address = chunk.network_address();
         info!("Verifying chunk: {address:?}");
+        if let Err(err) = self.prove_chunk_existence(chunk).await {
+            error!("Failed to verify the existence of chunk {address:?} with err {err:?}");
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Check if a `Chunk` is already stored by the expected nodes on the network, without retries.
+    /// Used to skip paying again for the chunks of an upload that are already stored.
+    ///
+    /// Failing to prove the existence, for whatever reason, reports the chunk as not stored.
+    pub async fn is_chunk_stored(&self, chunk: &Chunk) -> bool {
+        match self.prove_chunk_existence(chunk).await {
+            Ok(()) => true,
+            Err(err) => {
+                debug!(
+                    "Chunk {:?} is not known to be stored: {err:?}",
+                    chunk.network_address()
+                );
+                false
+            }
+        }
+    }
+
+    async fn prove_chunk_existence(&self, chunk: &Chunk) -> Result<()> {
         let random_nonce = thread_rng().gen::<u64>();
         let record_value = try_serialize_record(&chunk, RecordKind::Chunk)?;
         let expected_proof = ChunkProof::new(record_value.as_ref(), random_nonce);
 
-        if let Err(err) = self
-            .network
+        self.network
             .verify_chunk_existence(
-                address.clone(),
+                chunk.network_address(),
                 random_nonce,
                 expected_proof,
                 Quorum::N(NonZeroUsize::new(2).ok_or(Error::NonZeroUsizeWasInitialisedAsZero)?),
                 None,
             )
-            .await
-        {
-            error!("Failed to verify the existence of chunk {address:?} with err {err:?}");
-            return Err(err.into());
-        }
-
+            .await?;
         Ok(())
     }
 
@@ -988,16 +1101,27 @@ impl Client {
         address: SpendAddress,
         get_cfg: GetRecordCfg,
     ) -> Result<SignedSpend> {
-        let key = NetworkAddress::from_spend_address(address).to_record_key();
+        let network_address = NetworkAddress::from_spend_address(address);
 
         info!(
             "Getting spend at {address:?} with record_key {:?}",
-            PrettyPrintRecordKey::from(&key)
+            PrettyPrintRecordKey::from(&network_address.to_record_key())
         );
-        let record = self
-            .network
-            .get_record_from_network(key.clone(), &get_cfg)
-            .await?;
+        // Only the verified spends fetched from the network get cached, below.
+        // Caching them again when served from the cache would extend their TTL.
+        let cached = self
+            .record_cache
+            .as_ref()
+            .and_then(|cache| cache.get(&network_address));
+        let is_cached = cached.is_some();
+        let record = match cached {
+            Some(value) => Record::new(network_address.to_record_key(), value.to_vec()),
+            None => {
+                self.network
+                    .get_record_from_network(network_address.to_record_key(), &get_cfg)
+                    .await?
+            }
+        };
         info!(
             "For spend at {address:?} got record from the network, {:?}",
             PrettyPrintRecordKey::from(&record.key)
@@ -1019,6 +1143,9 @@ impl Client {
         match signed_spend.verify(signed_spend.spent_tx_hash()) {
             Ok(()) => {
                 trace!("Verified signed spend got from network for {address:?}");
+                if !is_cached {
+                    self.cache_record(&network_address, &record);
+                }
                 Ok(signed_spend.clone())
             }
             Err(err) => {
@@ -7,11 +7,13 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod dag_crawling;
+mod dag_diff;
 mod dag_error;
 mod spend_dag;
 
 #[cfg(test)]
 mod tests;
 
+pub use dag_diff::{FaultsDiff, SpendDagDiff, SpendVariantsDiff};
 pub use dag_error::{DagError, SpendFault};
 pub use spend_dag::{SpendDag, SpendDagGet};
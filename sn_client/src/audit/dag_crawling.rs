@@ -6,12 +6,12 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{Client, Error, SpendDag};
+use crate::{Client, Error, SpendDag, SpendDagGet, SpendFault};
 
 use futures::{future::join_all, StreamExt};
 use sn_networking::{GetRecordError, NetworkError};
 use sn_transfers::{
-    SignedSpend, SpendAddress, SpendReason, WalletError, WalletResult,
+    is_genesis_spend, CashNote, SignedSpend, SpendAddress, SpendReason, WalletError, WalletResult,
     DEFAULT_NETWORK_ROYALTIES_PK, GENESIS_SPEND_UNIQUE_KEY, NETWORK_ROYALTIES_PK,
 };
 use std::{
@@ -440,6 +440,145 @@ impl Client {
         Ok(())
     }
 
+    /// Builds a SpendDag from the ancestry of the given CashNotes, following their parent_tx
+    /// backwards all the way to Genesis.
+    /// Unlike building the DAG from Genesis, this only gathers the spends these CashNotes descend from,
+    /// so the crawl is bounded by the CashNotes' history instead of the size of the whole Network.
+    /// Once gathered, the DAG faults are recorded and each ancestor is verified against its own parents.
+    /// Use `SpendDag::get_ancestry_faults` on the CashNotes' addresses to get the faults tainting them.
+    ///
+    /// ```text
+    ///                                   -> Spend3 ---> CashNote1
+    ///                                 /
+    /// Genesis -> Spend1 -----> Spend2 ---> Spend4 ---> CashNote2
+    ///
+    /// ```
+    pub async fn spend_dag_build_ancestry_of(
+        &self,
+        cash_notes: &[CashNote],
+    ) -> WalletResult<SpendDag> {
+        let genesis_addr = SpendAddress::from_unique_pubkey(&GENESIS_SPEND_UNIQUE_KEY);
+        let mut dag = SpendDag::new(genesis_addr);
+
+        // start from the spends that created our CashNotes
+        let mut addrs_to_get: BTreeSet<SpendAddress> = cash_notes
+            .iter()
+            .flat_map(|cn| cn.parent_tx.inputs.iter())
+            .map(|input| SpendAddress::from_unique_pubkey(&input.unique_pubkey))
+            .collect();
+
+        // use iteration instead of recursion to avoid stack overflow
+        let mut known_addrs = BTreeSet::new();
+        let mut depth: u32 = 0;
+        let start = std::time::Instant::now();
+
+        while !addrs_to_get.is_empty() {
+            info!(
+                "Depth {depth} - Getting {} ancestor spends",
+                addrs_to_get.len()
+            );
+            known_addrs.extend(addrs_to_get.iter().cloned());
+
+            // get all ancestor spends in parallel
+            let mut stream = futures::stream::iter(addrs_to_get)
+                .map(|a| async move { (self.crawl_spend(a).await, a) })
+                .buffer_unordered(crate::MAX_CONCURRENT_TASKS);
+
+            let mut next_gen_addrs = BTreeSet::new();
+            while let Some((get_spend, addr)) = stream.next().await {
+                let spends = match get_spend {
+                    InternalGetNetworkSpend::Spend(spend) => vec![*spend],
+                    InternalGetNetworkSpend::DoubleSpend(spends) => {
+                        info!("Depth {depth} - Fetched double spend(s) of len {} at {addr:?}, following all of them.", spends.len());
+                        spends
+                    }
+                    InternalGetNetworkSpend::NotFound => {
+                        // its descendants will get a MissingAncestry fault when verifying the DAG
+                        warn!(
+                            "Depth {depth} - Ancestor spend at {addr:?} not found on the Network"
+                        );
+                        continue;
+                    }
+                    InternalGetNetworkSpend::Error(err) => {
+                        warn!("Depth {depth} - Failed to get ancestor spend at {addr:?}: {err}");
+                        continue;
+                    }
+                };
+
+                for spend in spends {
+                    // Genesis has no ancestors to follow
+                    if !is_genesis_spend(&spend) {
+                        next_gen_addrs.extend(
+                            spend.spend.parent_tx.inputs.iter().map(|input| {
+                                SpendAddress::from_unique_pubkey(&input.unique_pubkey)
+                            }),
+                        );
+                    }
+                    dag.insert(addr, spend);
+                }
+            }
+
+            // only get ancestors we haven't already gathered
+            addrs_to_get = next_gen_addrs
+                .into_iter()
+                .filter(|a| !known_addrs.contains(a))
+                .collect();
+            depth += 1;
+        }
+
+        let elapsed = start.elapsed();
+        let n = known_addrs.len();
+        info!("Collected the ancestry of {} CashNotes through {depth} generations, getting {n} spends in {elapsed:?}", cash_notes.len());
+
+        // verify the DAG
+        info!("Now verifying the ancestry SpendDAG and recording errors...");
+        if let Err(e) = dag.record_faults(&dag.source()) {
+            let s = format!("Collected ancestry DAG is invalid, this is probably a bug: {e}");
+            error!("{s}");
+            return Err(WalletError::Dag(s));
+        }
+
+        // verify each ancestor against its own parents
+        let mut faults = BTreeSet::new();
+        for spend in dag.all_spends() {
+            if is_genesis_spend(spend) {
+                continue;
+            }
+            let addr = spend.address();
+            if let Err(e) = spend.verify(spend.spent_tx_hash()) {
+                warn!("Invalid signature for ancestor spend at {addr:?}: {e}");
+                faults.insert(SpendFault::InvalidTransaction(addr, format!("{e}")));
+                continue;
+            }
+
+            // missing parents are already recorded as MissingAncestry
+            let parent_spends = match dag_parent_spends(&dag, spend) {
+                Some(parents) => parents,
+                None => continue,
+            };
+            if let Err(e) = spend.verify_parent_spends(parent_spends.iter()) {
+                warn!("Failed to verify parent spends of ancestor spend at {addr:?}: {e}");
+                faults.insert(SpendFault::InvalidTransaction(addr, format!("{e}")));
+            }
+        }
+
+        // CashNotes whose parent spends could not be gathered are not in the DAG
+        for cn in cash_notes {
+            let addr = SpendAddress::from_unique_pubkey(&cn.unique_pubkey());
+            for input in cn.parent_tx.inputs.iter() {
+                let ancestor = SpendAddress::from_unique_pubkey(&input.unique_pubkey);
+                if matches!(dag.get_spend(&ancestor), SpendDagGet::SpendNotFound) {
+                    faults.insert(SpendFault::MissingAncestry { addr, ancestor });
+                }
+            }
+        }
+
+        for fault in faults {
+            dag.insert_fault(fault);
+        }
+        Ok(dag)
+    }
+
     /// Extends an existing SpendDag starting from the given utxos
     /// If verify is true, records faults in the DAG
     pub async fn spend_dag_continue_from(
@@ -518,6 +657,30 @@ impl Client {
     }
 }
 
+/// Helper function to get the parent spends of a spend from the DAG.
+/// Double spent parents are narrowed down to the ones spent in our parent_tx.
+/// Returns None if any of the parents is missing from the DAG.
+fn dag_parent_spends(dag: &SpendDag, spend: &SignedSpend) -> Option<BTreeSet<SignedSpend>> {
+    let mut parent_spends = BTreeSet::new();
+    for input in spend.spend.parent_tx.inputs.iter() {
+        let parent_addr = SpendAddress::from_unique_pubkey(&input.unique_pubkey);
+        match dag.get_spend(&parent_addr) {
+            SpendDagGet::Spend(s) => {
+                parent_spends.insert(*s);
+            }
+            SpendDagGet::DoubleSpend(spends) => {
+                parent_spends.extend(
+                    spends
+                        .into_iter()
+                        .filter(|s| s.spent_tx_hash() == spend.parent_tx_hash()),
+                );
+            }
+            SpendDagGet::Utxo | SpendDagGet::SpendNotFound => return None,
+        }
+    }
+    Some(parent_spends)
+}
+
 /// Helper function to analyze spend for beta_tracking optimization.
 /// returns the new_utxos that needs to be further tracked.
 fn beta_track_analyze_spend(spend: &SignedSpend) -> BTreeSet<SpendAddress> {
@@ -0,0 +1,201 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use sn_transfers::SpendAddress;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use super::{SpendDag, SpendDagGet, SpendFault};
+
+/// The differences between two SpendDags, ours and theirs
+/// Useful to understand why two auditors disagree
+/// All the lists are sorted by spend address
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendDagDiff {
+    /// Spends gathered in our DAG only
+    pub spends_only_in_ours: BTreeSet<SpendAddress>,
+    /// Spends gathered in their DAG only
+    pub spends_only_in_theirs: BTreeSet<SpendAddress>,
+    /// Spends gathered in both DAGs with variants seen by one side only (double spends)
+    pub differing_spends: Vec<SpendVariantsDiff>,
+    /// Spends with different faults recorded in each DAG
+    pub differing_faults: Vec<FaultsDiff>,
+    /// UTXOs at the frontier of our DAG only
+    pub utxos_only_in_ours: BTreeSet<SpendAddress>,
+    /// UTXOs at the frontier of their DAG only
+    pub utxos_only_in_theirs: BTreeSet<SpendAddress>,
+}
+
+/// The spend variants at an address seen by one side only, identified by their hex encoded hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendVariantsDiff {
+    pub address: SpendAddress,
+    pub only_in_ours: BTreeSet<String>,
+    pub only_in_theirs: BTreeSet<String>,
+}
+
+/// The faults at an address recorded by one side only
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaultsDiff {
+    pub address: SpendAddress,
+    pub only_in_ours: BTreeSet<SpendFault>,
+    pub only_in_theirs: BTreeSet<SpendFault>,
+}
+
+impl SpendDagDiff {
+    /// Returns true if both DAGs are the same
+    pub fn is_empty(&self) -> bool {
+        self.spends_only_in_ours.is_empty()
+            && self.spends_only_in_theirs.is_empty()
+            && self.differing_spends.is_empty()
+            && self.differing_faults.is_empty()
+            && self.utxos_only_in_ours.is_empty()
+            && self.utxos_only_in_theirs.is_empty()
+    }
+}
+
+impl SpendDag {
+    /// Compare our DAG with theirs
+    /// Lists the spends gathered on one side only, the spends with variants seen by one side only,
+    /// the spends with differing faults and the differences in the UTXO frontier
+    pub fn diff(&self, theirs: &SpendDag) -> SpendDagDiff {
+        let our_variants = spend_variants(self);
+        let their_variants = spend_variants(theirs);
+        let mut diff = SpendDagDiff::default();
+
+        for (addr, ours) in our_variants.iter() {
+            match their_variants.get(addr) {
+                None => {
+                    diff.spends_only_in_ours.insert(*addr);
+                }
+                Some(theirs) if theirs != ours => {
+                    let variants_diff = SpendVariantsDiff {
+                        address: *addr,
+                        only_in_ours: ours.difference(theirs).cloned().collect(),
+                        only_in_theirs: theirs.difference(ours).cloned().collect(),
+                    };
+                    diff.differing_spends.push(variants_diff);
+                }
+                Some(_) => {}
+            }
+        }
+        diff.spends_only_in_theirs = their_variants
+            .keys()
+            .filter(|addr| !our_variants.contains_key(addr))
+            .cloned()
+            .collect();
+
+        let faulty_addrs: BTreeSet<_> = self
+            .faults()
+            .keys()
+            .chain(theirs.faults().keys())
+            .cloned()
+            .collect();
+        for addr in faulty_addrs {
+            let ours = self.get_spend_faults(&addr);
+            let theirs = theirs.get_spend_faults(&addr);
+            if ours != theirs {
+                let faults_diff = FaultsDiff {
+                    address: addr,
+                    only_in_ours: ours.difference(&theirs).cloned().collect(),
+                    only_in_theirs: theirs.difference(&ours).cloned().collect(),
+                };
+                diff.differing_faults.push(faults_diff);
+            }
+        }
+
+        let our_utxos = self.get_utxos();
+        let their_utxos = theirs.get_utxos();
+        diff.utxos_only_in_ours = our_utxos.difference(&their_utxos).cloned().collect();
+        diff.utxos_only_in_theirs = their_utxos.difference(&our_utxos).cloned().collect();
+
+        diff
+    }
+}
+
+/// Helper that returns the hashes of the spend variants at each gathered address of the DAG
+fn spend_variants(dag: &SpendDag) -> BTreeMap<SpendAddress, BTreeSet<String>> {
+    let mut variants: BTreeMap<SpendAddress, BTreeSet<String>> = BTreeMap::new();
+    for spend in dag.all_spends() {
+        let addr = spend.address();
+        let hashes: Vec<String> = match dag.get_spend(&addr) {
+            SpendDagGet::Spend(s) => vec![s.spend.hash().to_hex()],
+            SpendDagGet::DoubleSpend(spends) => {
+                spends.iter().map(|s| s.spend.hash().to_hex()).collect()
+            }
+            SpendDagGet::Utxo | SpendDagGet::SpendNotFound => vec![],
+        };
+        variants.entry(addr).or_default().extend(hashes);
+    }
+    variants
+}
+
+impl fmt::Display for SpendDagDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "Both DAGs are the same");
+        }
+
+        writeln!(f, "Spends only in ours: {}", self.spends_only_in_ours.len())?;
+        for addr in self.spends_only_in_ours.iter() {
+            writeln!(f, "  < {addr:?}")?;
+        }
+        writeln!(
+            f,
+            "Spends only in theirs: {}",
+            self.spends_only_in_theirs.len()
+        )?;
+        for addr in self.spends_only_in_theirs.iter() {
+            writeln!(f, "  > {addr:?}")?;
+        }
+        writeln!(
+            f,
+            "Spends with differing variants: {}",
+            self.differing_spends.len()
+        )?;
+        for variants in self.differing_spends.iter() {
+            writeln!(f, "  {:?}", variants.address)?;
+            for v in variants.only_in_ours.iter() {
+                writeln!(f, "    < {v}")?;
+            }
+            for v in variants.only_in_theirs.iter() {
+                writeln!(f, "    > {v}")?;
+            }
+        }
+        writeln!(
+            f,
+            "Spends with differing faults: {}",
+            self.differing_faults.len()
+        )?;
+        for faults in self.differing_faults.iter() {
+            writeln!(f, "  {:?}", faults.address)?;
+            for fault in faults.only_in_ours.iter() {
+                writeln!(f, "    < {fault}")?;
+            }
+            for fault in faults.only_in_theirs.iter() {
+                writeln!(f, "    > {fault}")?;
+            }
+        }
+        writeln!(f, "UTXOs only in ours: {}", self.utxos_only_in_ours.len())?;
+        for addr in self.utxos_only_in_ours.iter() {
+            writeln!(f, "  < {addr:?}")?;
+        }
+        write!(
+            f,
+            "UTXOs only in theirs: {}",
+            self.utxos_only_in_theirs.len()
+        )?;
+        for addr in self.utxos_only_in_theirs.iter() {
+            write!(f, "\n  > {addr:?}")?;
+        }
+        Ok(())
+    }
+}
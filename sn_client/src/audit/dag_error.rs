@@ -62,6 +62,18 @@ impl DagError {
 }
 
 impl SpendFault {
+    /// Short name of the fault, without the details
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SpendFault::DoubleSpend(_) => "DoubleSpend",
+            SpendFault::MissingAncestry { .. } => "MissingAncestry",
+            SpendFault::DoubleSpentAncestor { .. } => "DoubleSpentAncestor",
+            SpendFault::InvalidTransaction(_, _) => "InvalidTransaction",
+            SpendFault::PoisonedAncestry(_, _) => "PoisonedAncestry",
+            SpendFault::OrphanSpend { .. } => "OrphanSpend",
+        }
+    }
+
     pub fn spend_address(&self) -> SpendAddress {
         match self {
             SpendFault::DoubleSpend(addr)
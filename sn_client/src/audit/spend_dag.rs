@@ -16,6 +16,7 @@ use sn_transfers::{
 };
 use std::{
     collections::{BTreeMap, BTreeSet},
+    fmt::Write,
     path::Path,
 };
 
@@ -281,6 +282,196 @@ impl SpendDag {
         format!("{:?}", Dot::with_config(&self.dag, &[]))
     }
 
+    /// Dump the DAG in GraphML format, for loading into external graph tools
+    /// Unlike the dot format, this remains usable for DAGs with a large number of spends
+    /// Nodes carry the spend address, status, amount, reason hash and recorded faults,
+    /// edges carry the amount given by the parent spend
+    pub fn dump_graphml_format(&self) -> String {
+        let nodes = self.spends_by_index();
+        let mut content = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="address" for="node" attr.name="address" attr.type="string"/>
+  <key id="status" for="node" attr.name="status" attr.type="string"/>
+  <key id="amount" for="node" attr.name="amount" attr.type="long"/>
+  <key id="reason_hash" for="node" attr.name="reason_hash" attr.type="string"/>
+  <key id="faults" for="node" attr.name="faults" attr.type="string"/>
+  <key id="given" for="edge" attr.name="amount" attr.type="long"/>
+  <graph id="spend_dag" edgedefault="directed">
+"#,
+        );
+
+        for node_idx in self.dag.node_indices() {
+            let addr = self.dag[node_idx];
+            let spend = nodes.get(&node_idx.index()).copied().flatten();
+            let amount = spend
+                .map(|s| s.spend.amount.as_nano().to_string())
+                .unwrap_or_default();
+            let reason_hash = spend
+                .map(|s| s.spend.reason.hash().to_hex())
+                .unwrap_or_default();
+            let _ = writeln!(content, "    <node id=\"n{}\">", node_idx.index());
+            let _ = writeln!(
+                content,
+                "      <data key=\"address\">{}</data>",
+                addr.to_hex()
+            );
+            let _ = writeln!(
+                content,
+                "      <data key=\"status\">{}</data>",
+                self.spend_status(&addr)
+            );
+            let _ = writeln!(content, "      <data key=\"amount\">{amount}</data>");
+            let _ = writeln!(
+                content,
+                "      <data key=\"reason_hash\">{reason_hash}</data>"
+            );
+            let _ = writeln!(
+                content,
+                "      <data key=\"faults\">{}</data>",
+                self.fault_flags(&addr)
+            );
+            let _ = writeln!(content, "    </node>");
+        }
+
+        for edge in self.dag.edge_references() {
+            let _ = writeln!(
+                content,
+                "    <edge source=\"n{}\" target=\"n{}\">",
+                edge.source().index(),
+                edge.target().index()
+            );
+            let _ = writeln!(
+                content,
+                "      <data key=\"given\">{}</data>",
+                edge.weight().as_nano()
+            );
+            let _ = writeln!(content, "    </edge>");
+        }
+
+        content.push_str("  </graph>\n</graphml>\n");
+        content
+    }
+
+    /// Dump the DAG as a flat CSV edge list, one line per spend and parent pair
+    /// Columns are: spend address, parent address, amount, reason hash, double spend flag and fault flags
+    /// Spends without a known parent (such as the DAG source) have an empty parent address
+    pub fn dump_csv_edge_list(&self) -> String {
+        let nodes = self.spends_by_index();
+        let mut content =
+            "spend_address,parent_address,amount,reason_hash,double_spend,faults\n".to_string();
+
+        for node_idx in self.dag.node_indices() {
+            let addr = self.dag[node_idx];
+            let spend = nodes.get(&node_idx.index()).copied().flatten();
+            let reason_hash = spend
+                .map(|s| s.spend.reason.hash().to_hex())
+                .unwrap_or_default();
+            let is_double_spend = matches!(self.spends.get(&addr), Some(DagEntry::DoubleSpend(_)));
+            let faults = self.fault_flags(&addr);
+
+            let parents: Vec<_> = self
+                .dag
+                .edges_directed(node_idx, petgraph::Direction::Incoming)
+                .map(|e| (Some(self.dag[e.source()]), *e.weight()))
+                .collect();
+            let parents = if parents.is_empty() {
+                let amount = spend.map(|s| s.spend.amount).unwrap_or(NanoTokens::zero());
+                vec![(None, amount)]
+            } else {
+                parents
+            };
+
+            for (parent, amount) in parents {
+                let parent = parent.map(|p| p.to_hex()).unwrap_or_default();
+                let _ = writeln!(
+                    content,
+                    "{},{parent},{},{reason_hash},{is_double_spend},{faults}",
+                    addr.to_hex(),
+                    amount.as_nano(),
+                );
+            }
+        }
+        content
+    }
+
+    /// Get the sub DAG made of the spends within `hops` generations of the given address,
+    /// following both ancestors and descendants
+    /// The sub DAG uses the given address as its source and keeps the faults recorded in this DAG
+    /// Spends beyond the hop limit are only referred to, like spends that were not gathered yet
+    pub fn sub_dag_around(&self, addr: &SpendAddress, hops: usize) -> SpendDag {
+        let mut in_range = BTreeSet::from_iter([*addr]);
+        let mut frontier = BTreeSet::from_iter([*addr]);
+        for _ in 0..hops {
+            let mut next_frontier = BTreeSet::new();
+            for a in frontier.iter() {
+                let neighbors = self
+                    .get_spend_indexes(a)
+                    .into_iter()
+                    .flat_map(|idx| self.dag.neighbors_undirected(NodeIndex::new(idx)))
+                    .map(|i| self.dag[i]);
+                next_frontier.extend(neighbors.filter(|n| !in_range.contains(n)));
+            }
+            in_range.extend(next_frontier.iter().cloned());
+            frontier = next_frontier;
+        }
+
+        let mut sub_dag = SpendDag::new(*addr);
+        for a in in_range.iter() {
+            for spend in self.spends.get(a).map(|e| e.spends()).unwrap_or_default() {
+                sub_dag.insert(*a, spend.clone());
+            }
+        }
+        for a in in_range.iter() {
+            if let Some(faults) = self.faults.get(a) {
+                sub_dag.faults.insert(*a, faults.clone());
+            }
+        }
+        sub_dag
+    }
+
+    /// Helper that maps the DAG node indexes to their spends, None for spends not gathered yet
+    fn spends_by_index(&self) -> BTreeMap<DagIndex, Option<&SignedSpend>> {
+        let mut nodes = BTreeMap::new();
+        for entry in self.spends.values() {
+            match entry {
+                DagEntry::NotGatheredYet(idx) => {
+                    nodes.insert(*idx, None);
+                }
+                DagEntry::Spend(spend, idx) => {
+                    nodes.insert(*idx, Some(&**spend));
+                }
+                DagEntry::DoubleSpend(spends) => {
+                    for (spend, idx) in spends {
+                        nodes.insert(*idx, Some(spend));
+                    }
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Helper that returns a short status for the spend at the given address
+    fn spend_status(&self, addr: &SpendAddress) -> &'static str {
+        match self.spends.get(addr) {
+            None => "not_found",
+            Some(DagEntry::NotGatheredYet(_)) => "utxo",
+            Some(DagEntry::DoubleSpend(_)) => "double_spend",
+            Some(DagEntry::Spend(_, _)) => "spend",
+        }
+    }
+
+    /// Helper that returns the kinds of faults recorded at the given address separated by `;`
+    fn fault_flags(&self, addr: &SpendAddress) -> String {
+        self.get_spend_faults(addr)
+            .iter()
+            .map(|f| f.kind())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
     pub fn dump_payment_forward_statistics(&self, sk: &SecretKey) -> String {
         let mut statistics: BTreeMap<String, Vec<NanoTokens>> = Default::default();
 
@@ -376,6 +567,42 @@ impl SpendDag {
         self.faults.get(addr).cloned().unwrap_or_default()
     }
 
+    /// Get the recorded faults for a given spend address and for all of its ancestors in the DAG
+    /// These are all the faults that taint the spend (or the UTXO) at that address
+    pub fn get_ancestry_faults(&self, addr: &SpendAddress) -> BTreeSet<SpendFault> {
+        let mut faults = BTreeSet::new();
+        let mut visited = BTreeSet::new();
+        let mut to_traverse = BTreeSet::from_iter([*addr]);
+        while let Some(current_addr) = to_traverse.pop_first() {
+            if !visited.insert(current_addr) {
+                continue;
+            }
+            faults.extend(self.get_spend_faults(&current_addr));
+
+            // continue traversal through the parents of this spend
+            let parents = self
+                .get_spend_indexes(&current_addr)
+                .into_iter()
+                .flat_map(|idx| {
+                    self.dag
+                        .neighbors_directed(NodeIndex::new(idx), petgraph::Direction::Incoming)
+                })
+                .map(|parent_idx| self.dag[parent_idx])
+                .filter(|parent_addr| !visited.contains(parent_addr));
+            to_traverse.extend(parents);
+        }
+        faults
+    }
+
+    /// Record a fault found outside of the DAG verification process
+    /// Note that it will be cleared by the next call to `record_faults`
+    pub(super) fn insert_fault(&mut self, fault: SpendFault) {
+        self.faults
+            .entry(fault.spend_address())
+            .or_default()
+            .insert(fault);
+    }
+
     /// Helper to get underlying index of spend entry in the DAG
     /// This unstable API is used to access the underlying graph for testing purposes
     /// An empty vec is returned if the spend is not in the DAG
@@ -605,6 +832,44 @@ impl SpendDag {
         false
     }
 
+    /// Rebuild the DAG from scratch out of its spends and recompute its faults
+    /// Every spend is inserted in a new DAG with the same source, re-linking it to its parents
+    /// and descendants, and every spend signature is verified again
+    /// Spends with an invalid signature or stored at the wrong address are recorded as InvalidTransaction
+    /// The faults recorded in this DAG are ignored, compare them with the returned DAG's faults
+    pub fn rebuild_and_verify(&self) -> Result<SpendDag, DagError> {
+        let mut dag = SpendDag::new(self.source);
+        for (addr, entry) in self.spends.iter() {
+            for spend in entry.spends() {
+                dag.insert(*addr, spend.clone());
+            }
+        }
+        dag.record_faults(&self.source)?;
+
+        for (addr, entry) in self.spends.iter() {
+            for spend in entry.spends() {
+                if spend.address() != *addr {
+                    warn!(
+                        "Spend at {:?} is stored at the wrong address: {addr:?}",
+                        spend.address()
+                    );
+                    dag.insert_fault(SpendFault::InvalidTransaction(
+                        *addr,
+                        format!("spend at {:?} stored at the wrong address", spend.address()),
+                    ));
+                }
+                if let Err(e) = spend.verify(spend.spent_tx_hash()) {
+                    warn!("Invalid signature for spend at {addr:?}: {e}");
+                    dag.insert_fault(SpendFault::InvalidTransaction(
+                        *addr,
+                        format!("invalid spend signature: {e}"),
+                    ));
+                }
+            }
+        }
+        Ok(dag)
+    }
+
     /// Verify the DAG and record faults in the DAG
     /// If the DAG is invalid, return an error immediately, without mutating the DAG
     pub fn record_faults(&mut self, source: &SpendAddress) -> Result<(), DagError> {
@@ -15,7 +15,7 @@ use setup::MockNetwork;
 use eyre::Result;
 use sn_transfers::SpendAddress;
 
-use crate::{SpendDag, SpendFault};
+use crate::{SpendDag, SpendDagGet, SpendFault};
 
 #[test]
 fn test_spend_dag_verify_valid_simple() -> Result<()> {
@@ -473,3 +473,229 @@ fn test_spend_dag_orphans() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_spend_dag_ancestry_faults() -> Result<()> {
+    let mut net = MockNetwork::genesis()?;
+    let genesis = net.genesis_spend;
+
+    let owner1 = net.new_pk_with_balance(100)?;
+    let owner2 = net.new_pk_with_balance(0)?;
+    let owner3 = net.new_pk_with_balance(0)?;
+    let owner_clean = net.new_pk_with_balance(100)?;
+
+    let spend_missing = net
+        .send(&owner1, &owner2, 100)?
+        .first()
+        .expect("spend_missing should have 1 element")
+        .to_owned();
+    let spent_after = net
+        .send(&owner2, &owner3, 100)?
+        .first()
+        .expect("spent_after should have 1 element")
+        .to_owned();
+    let tainted_utxo = net
+        .wallets
+        .get(&owner3)
+        .expect("owner3 wallet to exist")
+        .cn
+        .first()
+        .expect("owner3 wallet to have 1 cashnote")
+        .unique_pubkey();
+    let tainted_utxo_addr = SpendAddress::from_unique_pubkey(&tainted_utxo);
+    let clean_utxo = net
+        .wallets
+        .get(&owner_clean)
+        .expect("owner_clean wallet to exist")
+        .cn
+        .first()
+        .expect("owner_clean wallet to have 1 cashnote")
+        .unique_pubkey();
+    let clean_utxo_addr = SpendAddress::from_unique_pubkey(&clean_utxo);
+
+    // create dag with one missing spend on the tainted branch
+    let net_spends = net
+        .spends
+        .into_iter()
+        .filter(|s| spend_missing != s.address());
+    let mut dag = SpendDag::new(genesis);
+    for spend in net_spends {
+        dag.insert(spend.address(), spend.clone());
+    }
+    dag.record_faults(&genesis)?;
+
+    // make sure the faults of the ancestors are reported for the tainted utxo
+    let got = dag.get_ancestry_faults(&tainted_utxo_addr);
+    let expected = SpendFault::MissingAncestry {
+        addr: spent_after,
+        ancestor: spend_missing,
+    };
+    assert!(
+        got.contains(&expected),
+        "Utxo should be tainted by the missing ancestry of its parent"
+    );
+
+    // make sure the other branch is not tainted
+    assert_eq!(
+        dag.get_ancestry_faults(&clean_utxo_addr),
+        BTreeSet::new(),
+        "Utxo on a valid branch should not have faults in its ancestry"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_spend_dag_sub_dag_export() -> Result<()> {
+    let mut net = MockNetwork::genesis()?;
+    let genesis = net.genesis_spend;
+
+    let owner1 = net.new_pk_with_balance(100)?;
+    let owner2 = net.new_pk_with_balance(0)?;
+    let owner3 = net.new_pk_with_balance(0)?;
+    let owner4 = net.new_pk_with_balance(0)?;
+    let owner5 = net.new_pk_with_balance(0)?;
+
+    let spend1 = net.send(&owner1, &owner2, 100)?;
+    let spend2 = net.send(&owner2, &owner3, 100)?;
+    let spend3 = net.send(&owner3, &owner4, 100)?;
+    let spend4 = net.send(&owner4, &owner5, 100)?;
+    let [spend1, spend2, spend3, spend4] = [spend1, spend2, spend3, spend4]
+        .map(|s| s.first().expect("each send should have 1 spend").to_owned());
+
+    let mut dag = SpendDag::new(genesis);
+    for spend in net.spends {
+        dag.insert(spend.address(), spend.clone());
+    }
+    dag.record_faults(&genesis)?;
+
+    // only the spends within one hop are gathered in the sub DAG
+    let sub_dag = dag.sub_dag_around(&spend2, 1);
+    assert_eq!(sub_dag.source(), spend2);
+    assert!(matches!(sub_dag.get_spend(&spend1), SpendDagGet::Spend(_)));
+    assert!(matches!(sub_dag.get_spend(&spend2), SpendDagGet::Spend(_)));
+    assert!(matches!(sub_dag.get_spend(&spend3), SpendDagGet::Spend(_)));
+    assert!(matches!(sub_dag.get_spend(&spend4), SpendDagGet::Utxo));
+    assert_eq!(sub_dag.all_spends().len(), 3);
+
+    // the edge list links each spend to its parent
+    let csv = sub_dag.dump_csv_edge_list();
+    let expected_line = format!("{},{},100,", spend3.to_hex(), spend2.to_hex());
+    assert!(
+        csv.lines().any(|l| l.starts_with(&expected_line)),
+        "CSV edge list should contain the edge from spend2 to spend3"
+    );
+
+    let graphml = sub_dag.dump_graphml_format();
+    assert!(graphml.contains(&spend3.to_hex()));
+    // 3 gathered spends and the 2 spends they refer to at the hop limit
+    assert_eq!(graphml.matches("<node ").count(), 5);
+    Ok(())
+}
+
+#[test]
+fn test_spend_dag_rebuild_and_verify() -> Result<()> {
+    let mut net = MockNetwork::genesis()?;
+    let genesis = net.genesis_spend;
+
+    let owner1 = net.new_pk_with_balance(100)?;
+    let owner2 = net.new_pk_with_balance(0)?;
+    let owner3 = net.new_pk_with_balance(0)?;
+    let owner4 = net.new_pk_with_balance(0)?;
+
+    net.send(&owner1, &owner2, 100)?;
+    let spend_missing = net
+        .send(&owner2, &owner3, 100)?
+        .first()
+        .expect("spend_missing should have 1 element")
+        .to_owned();
+    net.send(&owner3, &owner4, 100)?;
+
+    // create dag with one missing spend so it has faults to recompute
+    let net_spends = net
+        .spends
+        .into_iter()
+        .filter(|s| spend_missing != s.address());
+    let mut dag = SpendDag::new(genesis);
+    for spend in net_spends {
+        dag.insert(spend.address(), spend.clone());
+    }
+    dag.record_faults(&genesis)?;
+    assert!(!dag.faults().is_empty());
+
+    // rebuilding from scratch should find the same faults
+    let rebuilt = dag.rebuild_and_verify()?;
+    assert_eq!(rebuilt.faults(), dag.faults());
+    assert_eq!(rebuilt.all_spends().len(), dag.all_spends().len());
+    assert_eq!(rebuilt.get_utxos(), dag.get_utxos());
+    Ok(())
+}
+
+#[test]
+fn test_spend_dag_diff() -> Result<()> {
+    let mut net = MockNetwork::genesis()?;
+    let genesis = net.genesis_spend;
+
+    let owner1 = net.new_pk_with_balance(100)?;
+    let owner2 = net.new_pk_with_balance(0)?;
+    let owner3 = net.new_pk_with_balance(0)?;
+    let owner4 = net.new_pk_with_balance(0)?;
+
+    net.send(&owner1, &owner2, 100)?;
+    let spend_missing = net
+        .send(&owner2, &owner3, 100)?
+        .first()
+        .expect("spend_missing should have 1 element")
+        .to_owned();
+    let last_spend = net
+        .send(&owner3, &owner4, 100)?
+        .first()
+        .expect("last_spend should have 1 element")
+        .to_owned();
+
+    // our dag has all the spends, theirs misses one in the middle, the latest one misses the last one
+    let mut ours = SpendDag::new(genesis);
+    let mut theirs = SpendDag::new(genesis);
+    let mut latest = SpendDag::new(genesis);
+    for spend in net.spends {
+        ours.insert(spend.address(), spend.clone());
+        if spend.address() != spend_missing {
+            theirs.insert(spend.address(), spend.clone());
+        }
+        if spend.address() != last_spend {
+            latest.insert(spend.address(), spend.clone());
+        }
+    }
+    ours.record_faults(&genesis)?;
+    theirs.record_faults(&genesis)?;
+    latest.record_faults(&genesis)?;
+
+    assert!(ours.diff(&ours).is_empty());
+
+    let diff = ours.diff(&theirs);
+    assert!(!diff.is_empty());
+    assert_eq!(
+        diff.spends_only_in_ours,
+        BTreeSet::from_iter([spend_missing])
+    );
+    assert!(diff.spends_only_in_theirs.is_empty());
+    assert!(diff.differing_spends.is_empty());
+    // the missing spend is still linked to its child in theirs, hence is not a UTXO there
+    assert!(diff.utxos_only_in_ours.is_empty());
+    assert!(diff.utxos_only_in_theirs.is_empty());
+    assert!(!diff.differing_faults.is_empty());
+
+    // the diff is symmetric
+    let reverse = theirs.diff(&ours);
+    assert_eq!(reverse.spends_only_in_theirs, diff.spends_only_in_ours);
+    assert_eq!(reverse.differing_faults.len(), diff.differing_faults.len());
+
+    // without the last spend, its address is a UTXO instead of the ones it created
+    let diff = ours.diff(&latest);
+    assert_eq!(diff.spends_only_in_ours, BTreeSet::from_iter([last_spend]));
+    assert_eq!(diff.utxos_only_in_theirs, BTreeSet::from_iter([last_spend]));
+    assert!(!diff.utxos_only_in_ours.is_empty());
+    assert!(diff.differing_faults.is_empty());
+    let reverse = latest.diff(&ours);
+    assert_eq!(reverse.utxos_only_in_ours, diff.utxos_only_in_theirs);
+    Ok(())
+}
@@ -13,6 +13,7 @@ use crate::UploadSummary;
 use super::ClientEvent;
 use sn_protocol::NetworkAddress;
 use sn_registers::{Entry, EntryHash};
+use sn_transfers::NanoTokens;
 use std::collections::BTreeSet;
 use thiserror::Error;
 use tokio::time::Duration;
@@ -114,6 +115,9 @@ pub enum Error {
     #[error("Could not find register after batch sync: {0:?}")]
     RegisterNotFoundAfterUpload(XorName),
 
+    #[error("None of the {0} random addresses could be sampled to estimate the network")]
+    NotEnoughNetworkSamples(usize),
+
     #[error("Could not connect due to incompatible network protocols. Our protocol: {0} Network protocol: {1}")]
     UnsupportedProtocol(String, String),
 
@@ -148,6 +152,24 @@ pub enum Error {
         summary: UploadSummary,
     },
 
+    #[error("The quote of {cost} exceeds the max price of {max_price} per record")]
+    QuoteAboveMaxPrice {
+        cost: NanoTokens,
+        max_price: NanoTokens,
+    },
+
+    #[error("Paying {required} would exceed the upload budget of {budget}")]
+    PaymentBudgetExceeded {
+        budget: NanoTokens,
+        required: NanoTokens,
+    },
+
+    #[error("None of the {sampled} quotes sampled is acceptable")]
+    NoAcceptableQuote { sampled: usize },
+
+    #[error("Invalid quote selection {0:?}, expected \"cheapest\" or \"median\"")]
+    InvalidQuoteSelection(String),
+
     #[error("Error occurred when access wallet file")]
     FailedToAccessWallet,
 
@@ -248,6 +248,13 @@ impl FilesDownload {
             .download_entire_file_inner(address, data_map_chunk, downloaded_file_path)
             .await;
 
+        if let Some(record_cache) = self.api.client.record_cache() {
+            info!(
+                "Record cache stats after downloading {address:?}: {}",
+                record_cache.stats()
+            );
+        }
+
         // send an event indicating that the download process completed with an error
         if result.is_err() {
             self.send_event(FilesDownloadEvent::Error).await?;
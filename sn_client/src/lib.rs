@@ -18,6 +18,10 @@ mod event;
 mod faucet;
 mod files;
 mod folders;
+mod network_estimate;
+mod payment_policy;
+mod progress;
+mod record_cache;
 mod register;
 mod uploader;
 mod wallet;
@@ -37,7 +41,9 @@ pub use sn_transfers as transfers;
 const MAX_CONCURRENT_TASKS: usize = 4096;
 
 pub use self::{
-    audit::{DagError, SpendDag, SpendDagGet, SpendFault},
+    audit::{
+        DagError, FaultsDiff, SpendDag, SpendDagDiff, SpendDagGet, SpendFault, SpendVariantsDiff,
+    },
     error::Error,
     event::{ClientEvent, ClientEventsBroadcaster, ClientEventsReceiver},
     faucet::fund_faucet_from_genesis_wallet,
@@ -46,8 +52,12 @@ pub use self::{
         FilesApi, BATCH_SIZE,
     },
     folders::{FolderEntry, FoldersApi, Metadata},
+    network_estimate::{Estimate, NetworkEstimate},
+    payment_policy::{PaymentPolicy, QuoteSelection},
+    progress::{FileProgress, FileStatus, ProgressSnapshot, ProgressTracker},
+    record_cache::{RecordCache, RecordCacheConfig, RecordCacheStats},
     register::ClientRegister,
-    uploader::{UploadCfg, UploadEvent, UploadSummary, Uploader},
+    uploader::{ItemEstimate, UploadCfg, UploadEstimate, UploadEvent, UploadSummary, Uploader},
     wallet::{broadcast_signed_spends, send, StoragePaymentResult, WalletClient},
 };
 pub(crate) use error::Result;
@@ -125,4 +135,5 @@ pub struct Client {
     network: Network,
     events_broadcaster: ClientEventsBroadcaster,
     signer: Arc<bls::SecretKey>,
+    record_cache: Option<RecordCache>,
 }
@@ -0,0 +1,278 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{error::Result, Client, Error};
+use futures::future::join_all;
+use libp2p::PeerId;
+use sn_networking::CLOSE_GROUP_SIZE;
+use sn_protocol::NetworkAddress;
+use std::fmt;
+
+/// Number of peers in a full kbucket
+const K_VALUE: usize = 20;
+/// z-score of the 95% two-sided confidence interval
+const Z_95: f64 = 1.96;
+/// Number of nodes holding a copy of each record, i.e. its close group.
+/// This is an approximation: during churn a record can briefly be held by a few more nodes.
+const REPLICATION_FACTOR: f64 = CLOSE_GROUP_SIZE as f64;
+
+/// A value estimated from a set of samples, with its 95% confidence bounds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub samples: usize,
+}
+
+impl Estimate {
+    /// Estimate the mean of the samples, using the normal approximation for the bounds.
+    /// The lower bound is floored at zero, all the estimated quantities being positive.
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+        let margin = if samples.len() > 1 {
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (count - 1.0);
+            Z_95 * (variance / count).sqrt()
+        } else {
+            // A single sample tells nothing about the spread
+            mean.abs()
+        };
+
+        Some(Self {
+            mean,
+            lower: (mean - margin).max(0.0),
+            upper: mean + margin,
+            samples: samples.len(),
+        })
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} (95% CI {:.0} - {:.0}, {} samples)",
+            self.mean, self.lower, self.upper, self.samples
+        )
+    }
+}
+
+/// Network-wide size, price and capacity estimated from the random addresses sampled
+#[derive(Debug, Clone)]
+pub struct NetworkEstimate {
+    /// Number of nodes, from the density of the closest peers around the sampled addresses
+    pub node_count: Estimate,
+    /// Number of nodes, from the density of our own kbuckets
+    pub routing_table_node_count: usize,
+    /// Store cost quoted at the sampled addresses, in nanos
+    pub store_cost: Estimate,
+    /// Distinct records stored across the network, each one being held by its whole close group
+    pub used_records: Estimate,
+    /// Distinct records that can still be stored across the network
+    pub free_records: Estimate,
+    /// Ratio of the used records over the max records of the quoting nodes
+    pub utilisation: Estimate,
+}
+
+impl fmt::Display for NetworkEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Nodes: {}", self.node_count)?;
+        writeln!(
+            f,
+            "Nodes (routing table): {}",
+            self.routing_table_node_count
+        )?;
+        writeln!(f, "Store cost (nanos): {}", self.store_cost)?;
+        writeln!(f, "Used records: {}", self.used_records)?;
+        writeln!(f, "Free records: {}", self.free_records)?;
+        write!(
+            f,
+            "Utilisation: {:.2}% (95% CI {:.2}% - {:.2}%)",
+            self.utilisation.mean * 100.0,
+            self.utilisation.lower * 100.0,
+            self.utilisation.upper * 100.0
+        )
+    }
+}
+
+/// What was learnt from a single random address
+struct AddressSample {
+    node_count: f64,
+    store_cost: f64,
+    records_stored: f64,
+    max_records: f64,
+}
+
+impl Client {
+    /// Estimate the size, the store cost and the capacity of the network by sampling `samples` random addresses.
+    ///
+    /// For each address the closest peers are looked up, their spread giving the density of the nodes,
+    /// and a store cost is requested, the `QuotingMetrics` of the quote giving the records held by the node.
+    /// Addresses failing to be sampled are skipped, an error is returned only if none could be sampled.
+    pub async fn estimate_network(&self, samples: usize) -> Result<NetworkEstimate> {
+        let routing_table_node_count = self.estimate_node_count_from_kbuckets().await?;
+
+        let tasks = (0..samples).map(|_| {
+            let address = NetworkAddress::from_peer(PeerId::random());
+            self.sample_address(address)
+        });
+        let address_samples: Vec<_> = join_all(tasks).await.into_iter().flatten().collect();
+        info!(
+            "Sampled {} addresses out of {samples} to estimate the network",
+            address_samples.len()
+        );
+
+        let estimate = |values: Vec<f64>| {
+            Estimate::from_samples(&values).ok_or(Error::NotEnoughNetworkSamples(samples))
+        };
+        let node_count = estimate(address_samples.iter().map(|s| s.node_count).collect())?;
+        let store_cost = estimate(address_samples.iter().map(|s| s.store_cost).collect())?;
+        // Every record is held by the nodes of its close group, hence the copies held by all the nodes
+        // are divided by the replication factor to count the distinct records.
+        let used_records = estimate(
+            address_samples
+                .iter()
+                .map(|s| s.records_stored * s.node_count / REPLICATION_FACTOR)
+                .collect(),
+        )?;
+        let free_records = estimate(
+            address_samples
+                .iter()
+                .map(|s| {
+                    (s.max_records - s.records_stored).max(0.0) * s.node_count / REPLICATION_FACTOR
+                })
+                .collect(),
+        )?;
+        let utilisation = estimate(
+            address_samples
+                .iter()
+                .filter(|s| s.max_records > 0.0)
+                .map(|s| s.records_stored / s.max_records)
+                .collect(),
+        )?;
+
+        Ok(NetworkEstimate {
+            node_count,
+            routing_table_node_count,
+            store_cost,
+            used_records,
+            free_records,
+            utilisation,
+        })
+    }
+
+    /// Estimate the number of nodes the same way the nodes do from their routing table:
+    /// each full bucket halves the part of the address space the remaining peers are found in.
+    async fn estimate_node_count_from_kbuckets(&self) -> Result<usize> {
+        let kbuckets = self.network.get_kbuckets().await?;
+        let (full_buckets, peers_in_non_full_buckets) =
+            kbuckets
+                .values()
+                .fold((0_u32, 0_usize), |(full, non_full), peers| {
+                    if peers.len() >= K_VALUE {
+                        (full + 1, non_full)
+                    } else {
+                        (full, non_full + peers.len())
+                    }
+                });
+        Ok((peers_in_non_full_buckets + 1).saturating_mul(2_usize.saturating_pow(full_buckets)))
+    }
+
+    async fn sample_address(&self, address: NetworkAddress) -> Option<AddressSample> {
+        let closest_peers = match self.network.client_get_closest_peers(&address).await {
+            Ok(peers) => peers,
+            Err(err) => {
+                warn!("Failed to get the closest peers of {address:?} to estimate the network: {err:?}");
+                return None;
+            }
+        };
+        let node_count = node_count_from_closest_peers(&address, &closest_peers)?;
+
+        let (_, _, quote) = match self
+            .network
+            .get_store_costs_from_network(address.clone(), vec![])
+            .await
+        {
+            Ok(quote) => quote,
+            Err(err) => {
+                warn!(
+                    "Failed to get the store cost at {address:?} to estimate the network: {err:?}"
+                );
+                return None;
+            }
+        };
+
+        Some(AddressSample {
+            node_count,
+            store_cost: quote.cost.as_nano() as f64,
+            records_stored: quote.quoting_metrics.close_records_stored as f64,
+            max_records: quote.quoting_metrics.max_records as f64,
+        })
+    }
+}
+
+/// With the nodes evenly spread, the k-th closest peer of an address is expected
+/// at `k / node_count` of the address space, giving the number of nodes back.
+fn node_count_from_closest_peers(address: &NetworkAddress, peers: &[PeerId]) -> Option<f64> {
+    let farthest = peers
+        .iter()
+        .map(|peer| distance_fraction(address, &NetworkAddress::from_peer(*peer)))
+        .fold(0.0_f64, f64::max);
+    (farthest > 0.0).then(|| peers.len() as f64 / farthest)
+}
+
+/// The XOR distance between two addresses, as a fraction of the whole address space
+fn distance_fraction(a: &NetworkAddress, b: &NetworkAddress) -> f64 {
+    let a = a.as_kbucket_key();
+    let b = b.as_kbucket_key();
+    // The 64 most significant bits are precise enough for a fraction
+    let mut leading = [0_u8; 8];
+    for (byte, (a, b)) in leading
+        .iter_mut()
+        .zip(a.hashed_bytes().iter().zip(b.hashed_bytes()))
+    {
+        *byte = a ^ b;
+    }
+    u64::from_be_bytes(leading) as f64 / 2_f64.powi(64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_bounds_contain_the_mean() {
+        let estimate = Estimate::from_samples(&[90.0, 110.0, 100.0, 95.0, 105.0]);
+        assert!(matches!(estimate, Some(e) if e.mean == 100.0 && e.samples == 5));
+        assert!(matches!(estimate, Some(e) if e.lower > 90.0 && e.lower < 100.0));
+        assert!(matches!(estimate, Some(e) if e.upper > 100.0 && e.upper < 110.0));
+
+        assert_eq!(Estimate::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn estimate_lower_bound_is_never_negative() {
+        let estimate = Estimate::from_samples(&[1.0, 1000.0]);
+        assert!(matches!(estimate, Some(e) if e.lower == 0.0));
+    }
+
+    #[test]
+    fn distance_to_self_is_zero() {
+        let address = NetworkAddress::from_peer(PeerId::random());
+        assert_eq!(distance_fraction(&address, &address), 0.0);
+
+        let other = NetworkAddress::from_peer(PeerId::random());
+        let fraction = distance_fraction(&address, &other);
+        assert!(fraction > 0.0 && fraction < 1.0);
+        assert!(node_count_from_closest_peers(&address, &[]).is_none());
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{error::Result, Error};
+use sn_networking::{calculate_cost_for_records, PayeeQuote};
+use sn_transfers::{NanoTokens, PaymentQuote};
+use std::{fmt, str::FromStr};
+
+/// A quote costing more than this factor of what its own `QuotingMetrics` justify,
+/// or of the median of the quotes sampled along with it, is considered anomalous.
+const ANOMALOUS_PRICE_FACTOR: u64 = 10;
+
+/// How to pick the payee among the quotes of the close group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteSelection {
+    /// The cheapest valid quote
+    #[default]
+    Cheapest,
+    /// The median of the valid quotes, paying a fair price rather than the lowest bidder
+    Median,
+}
+
+impl FromStr for QuoteSelection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cheapest" => Ok(Self::Cheapest),
+            "median" => Ok(Self::Median),
+            _ => Err(Error::InvalidQuoteSelection(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for QuoteSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cheapest => write!(f, "cheapest"),
+            Self::Median => write!(f, "median"),
+        }
+    }
+}
+
+/// The rules a client applies before paying for the storage of its records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaymentPolicy {
+    /// Refuse to pay more than this for a single record
+    pub max_price_per_record: Option<NanoTokens>,
+    /// Refuse to pay more than this for all the records of an upload, royalties excluded
+    pub max_total_budget: Option<NanoTokens>,
+    /// How to pick the payee among the quotes
+    pub quote_selection: QuoteSelection,
+    /// Discard the quotes priced out of line with the `QuotingMetrics` sampled along with them
+    pub reject_anomalous_quotes: bool,
+}
+
+impl Default for PaymentPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaymentPolicy {
+    /// Pay the cheapest non anomalous quote, without any price limit
+    pub const fn new() -> Self {
+        Self {
+            max_price_per_record: None,
+            max_total_budget: None,
+            quote_selection: QuoteSelection::Cheapest,
+            reject_anomalous_quotes: true,
+        }
+    }
+
+    /// Select the quote to pay among the quotes of the close group.
+    ///
+    /// A quote of zero means the record already exists and is always selected.
+    /// The price limit is not checked here, see `check_price`.
+    pub fn select_quote(&self, mut quotes: Vec<PayeeQuote>) -> Result<PayeeQuote> {
+        quotes.sort_by_key(|(_, _, quote)| quote.cost);
+        if let Some(existing) = quotes
+            .iter()
+            .position(|(_, _, quote)| quote.cost == NanoTokens::zero())
+        {
+            return Ok(quotes.swap_remove(existing));
+        }
+
+        let sampled = quotes.len();
+        if self.reject_anomalous_quotes {
+            let median_cost = median(&quotes).map(|(_, _, quote)| quote.cost);
+            quotes.retain(|(peer_id, _, quote)| {
+                let anomalous = is_anomalous(quote, median_cost);
+                if anomalous {
+                    warn!("Discarding the anomalous quote of {peer_id:?}: {quote:?}");
+                }
+                !anomalous
+            });
+        }
+
+        let selected = match self.quote_selection {
+            QuoteSelection::Cheapest => quotes.first(),
+            QuoteSelection::Median => median(&quotes),
+        };
+        selected
+            .cloned()
+            .ok_or(Error::NoAcceptableQuote { sampled })
+    }
+
+    /// Check the price of a single record against `max_price_per_record`
+    pub fn check_price(&self, cost: NanoTokens) -> Result<()> {
+        match self.max_price_per_record {
+            Some(max_price) if cost > max_price => {
+                Err(Error::QuoteAboveMaxPrice { cost, max_price })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the total cost of the records paid so far plus `cost` against `max_total_budget`
+    pub fn check_budget(&self, committed: NanoTokens, cost: NanoTokens) -> Result<NanoTokens> {
+        let total = committed
+            .checked_add(cost)
+            .ok_or(Error::TotalPriceTooHigh)?;
+        match self.max_total_budget {
+            Some(budget) if total > budget => Err(Error::PaymentBudgetExceeded {
+                budget,
+                required: total,
+            }),
+            _ => Ok(total),
+        }
+    }
+}
+
+/// The lower median of quotes sorted by cost
+fn median(quotes: &[PayeeQuote]) -> Option<&PayeeQuote> {
+    quotes.get(quotes.len().saturating_sub(1) / 2)
+}
+
+/// A quote is anomalous when its metrics are inconsistent, or when it is priced far above
+/// what its metrics justify or what the other nodes of the close group ask for.
+fn is_anomalous(quote: &PaymentQuote, median_cost: Option<NanoTokens>) -> bool {
+    let metrics = &quote.quoting_metrics;
+    if metrics.close_records_stored > metrics.max_records {
+        return true;
+    }
+
+    let cost = quote.cost.as_nano();
+    let expected = calculate_cost_for_records(metrics).max(1);
+    if cost > expected.saturating_mul(ANOMALOUS_PRICE_FACTOR) {
+        return true;
+    }
+
+    matches!(median_cost, Some(median) if cost > median.as_nano().max(1).saturating_mul(ANOMALOUS_PRICE_FACTOR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::PeerId;
+    use sn_transfers::{MainPubkey, QuotingMetrics};
+
+    fn quote(records_stored: usize) -> PayeeQuote {
+        let quoting_metrics = QuotingMetrics {
+            close_records_stored: records_stored,
+            max_records: 2048,
+            received_payment_count: records_stored,
+            live_time: 0,
+        };
+        let mut quote = PaymentQuote::test_dummy(Default::default(), NanoTokens::zero());
+        quote.cost = NanoTokens::from(calculate_cost_for_records(&quoting_metrics));
+        quote.quoting_metrics = quoting_metrics;
+        (
+            PeerId::random(),
+            MainPubkey::new(bls::SecretKey::random().public_key()),
+            quote,
+        )
+    }
+
+    #[test]
+    fn cheapest_or_median_quote_is_selected() -> Result<()> {
+        let quotes = vec![quote(300), quote(100), quote(200)];
+        let cheapest = PaymentPolicy::default().select_quote(quotes.clone())?;
+        assert_eq!(cheapest.2, quotes[1].2);
+
+        let median = PaymentPolicy {
+            quote_selection: QuoteSelection::Median,
+            ..Default::default()
+        }
+        .select_quote(quotes.clone())?;
+        assert_eq!(median.2, quotes[2].2);
+        Ok(())
+    }
+
+    #[test]
+    fn anomalous_quotes_are_discarded() -> Result<()> {
+        let mut overpriced = quote(100);
+        overpriced.2.cost = NanoTokens::from(overpriced.2.cost.as_nano() * 100);
+        let mut inconsistent = quote(100);
+        inconsistent.2.quoting_metrics.close_records_stored = 4096;
+        let fair = quote(150);
+
+        let selected = PaymentPolicy::default().select_quote(vec![
+            overpriced.clone(),
+            inconsistent.clone(),
+            fair.clone(),
+        ])?;
+        assert_eq!(selected.2, fair.2);
+
+        let result = PaymentPolicy::default().select_quote(vec![overpriced, inconsistent]);
+        assert!(matches!(
+            result,
+            Err(Error::NoAcceptableQuote { sampled: 2 })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn price_and_budget_limits_are_enforced() -> Result<()> {
+        let policy = PaymentPolicy {
+            max_price_per_record: Some(NanoTokens::from(10)),
+            max_total_budget: Some(NanoTokens::from(25)),
+            ..Default::default()
+        };
+        policy.check_price(NanoTokens::from(10))?;
+        assert!(policy.check_price(NanoTokens::from(11)).is_err());
+
+        let committed = policy.check_budget(NanoTokens::zero(), NanoTokens::from(10))?;
+        let committed = policy.check_budget(committed, NanoTokens::from(10))?;
+        assert!(matches!(
+            policy.check_budget(committed, NanoTokens::from(10)),
+            Err(Error::PaymentBudgetExceeded { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{FilesDownloadEvent, UploadEvent};
+use serde::Serialize;
+use sn_networking::target_arch::Instant;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tokio::time::Duration;
+use xor_name::XorName;
+
+/// The state of a single file being transferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FileStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// The progress of a single file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileProgress {
+    pub name: String,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+    pub status: FileStatus,
+}
+
+/// A point in time view of the progress of an upload or a download.
+///
+/// Chunks already present in the network count as done, but not towards the throughput.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressSnapshot {
+    pub elapsed_secs: f64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub chunks_done: usize,
+    pub chunks_total: usize,
+    pub chunks_skipped: usize,
+    pub retries: usize,
+    pub bytes_per_sec: f64,
+    pub chunks_per_sec: f64,
+    /// Estimated seconds left, `None` until the throughput is known
+    pub eta_secs: Option<u64>,
+    pub failed: bool,
+    pub files: Vec<FileProgress>,
+}
+
+impl ProgressSnapshot {
+    /// The bytes, throughput, ETA and retries, leaving out the chunks for the progress bars already showing them
+    pub fn bytes_summary(&self) -> String {
+        let mut summary = format!(
+            "{}/{}, {}/s",
+            format_bytes(self.bytes_done),
+            format_bytes(self.bytes_total),
+            format_bytes(self.bytes_per_sec as u64),
+        );
+        if let Some(eta_secs) = self.eta_secs {
+            summary.push_str(&format!(", ETA {}m{:02}s", eta_secs / 60, eta_secs % 60));
+        }
+        if self.retries > 0 {
+            summary.push_str(&format!(", {} retries", self.retries));
+        }
+        if self.failed {
+            summary.push_str(", failed");
+        }
+        summary
+    }
+}
+
+impl fmt::Display for ProgressSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} chunks ({} skipped), {}",
+            self.chunks_done,
+            self.chunks_total,
+            self.chunks_skipped,
+            self.bytes_summary()
+        )
+    }
+}
+
+/// Tracks the progress of an upload or a download from its `UploadEvent`s or `FilesDownloadEvent`s,
+/// so that every consumer reports the same numbers.
+///
+/// The chunks, and the files they belong to, should be added before the transfer starts. Cloning the tracker
+/// gives another handle to the same progress, allowing it to be fed from the events loop and read elsewhere.
+#[derive(Debug, Clone)]
+pub struct ProgressTracker {
+    state: Arc<Mutex<ProgressState>>,
+}
+
+#[derive(Debug)]
+struct ProgressState {
+    started_at: Instant,
+    chunks: HashMap<XorName, TrackedChunk>,
+    files: Vec<TrackedFile>,
+    bytes_done: u64,
+    bytes_transferred: u64,
+    bytes_total: u64,
+    chunks_done: usize,
+    chunks_skipped: usize,
+    /// Chunks announced by the download events without being added beforehand
+    untracked_chunks_total: usize,
+    untracked_chunks_done: usize,
+    retries: usize,
+    failed: bool,
+}
+
+#[derive(Debug)]
+struct TrackedChunk {
+    size: u64,
+    done: bool,
+    /// Indexes of the files this chunk is part of
+    files: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct TrackedFile {
+    name: String,
+    chunks_done: usize,
+    chunks_total: usize,
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ProgressState {
+                started_at: Instant::now(),
+                chunks: Default::default(),
+                files: Default::default(),
+                bytes_done: 0,
+                bytes_transferred: 0,
+                bytes_total: 0,
+                chunks_done: 0,
+                chunks_skipped: 0,
+                untracked_chunks_total: 0,
+                untracked_chunks_done: 0,
+                retries: 0,
+                failed: false,
+            })),
+        }
+    }
+
+    /// Add a file along with its chunks and their size in bytes.
+    /// A chunk shared by several files is transferred, and counted in the totals, only once.
+    pub fn add_file(
+        &self,
+        name: impl Into<String>,
+        chunks: impl IntoIterator<Item = (XorName, u64)>,
+    ) {
+        let mut state = self.lock();
+        let file_index = state.files.len();
+        let mut chunks_total = 0;
+        for (xorname, size) in chunks {
+            chunks_total += 1;
+            state.add_chunk(xorname, size).files.push(file_index);
+        }
+        state.files.push(TrackedFile {
+            name: name.into(),
+            chunks_done: 0,
+            chunks_total,
+        });
+    }
+
+    /// Add records that are not part of any file, e.g. Registers, along with their size in bytes.
+    pub fn add_chunks(&self, chunks: impl IntoIterator<Item = (XorName, u64)>) {
+        let mut state = self.lock();
+        for (xorname, size) in chunks {
+            let _ = state.add_chunk(xorname, size);
+        }
+    }
+
+    pub fn on_upload_event(&self, event: &UploadEvent) {
+        let mut state = self.lock();
+        match event {
+            UploadEvent::ChunkUploaded(address) => state.mark_done(*address.xorname(), false),
+            UploadEvent::ChunkAlreadyExistsInNetwork(address) => {
+                state.mark_done(*address.xorname(), true)
+            }
+            UploadEvent::RegisterUploaded(register) => {
+                state.mark_done(register.address().xorname(), false)
+            }
+            UploadEvent::RegisterUpdated(register) => {
+                state.mark_done(register.address().xorname(), true)
+            }
+            UploadEvent::UploadRetried(_) => state.retries += 1,
+            UploadEvent::Error => state.failed = true,
+            UploadEvent::PaymentMade { .. } => {}
+        }
+    }
+
+    pub fn on_download_event(&self, event: &FilesDownloadEvent) {
+        let mut state = self.lock();
+        match event {
+            FilesDownloadEvent::Downloaded(address) => {
+                let xorname = *address.xorname();
+                if state.chunks.contains_key(&xorname) {
+                    state.mark_done(xorname, false);
+                } else {
+                    state.untracked_chunks_done += 1;
+                }
+            }
+            FilesDownloadEvent::ChunksCount(count) | FilesDownloadEvent::DatamapCount(count) => {
+                state.untracked_chunks_total += count;
+            }
+            FilesDownloadEvent::Error => state.failed = true,
+        }
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let state = self.lock();
+        state.snapshot(state.started_at.elapsed())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl ProgressState {
+    fn add_chunk(&mut self, xorname: XorName, size: u64) -> &mut TrackedChunk {
+        let bytes_total = &mut self.bytes_total;
+        self.chunks.entry(xorname).or_insert_with(|| {
+            *bytes_total += size;
+            TrackedChunk {
+                size,
+                done: false,
+                files: vec![],
+            }
+        })
+    }
+
+    fn mark_done(&mut self, xorname: XorName, skipped: bool) {
+        let Some(chunk) = self.chunks.get_mut(&xorname) else {
+            trace!("Progress of the untracked record {xorname:?} is ignored");
+            return;
+        };
+        if chunk.done {
+            return;
+        }
+        chunk.done = true;
+
+        self.chunks_done += 1;
+        self.bytes_done += chunk.size;
+        if skipped {
+            self.chunks_skipped += 1;
+        } else {
+            self.bytes_transferred += chunk.size;
+        }
+        for file_index in chunk.files.iter() {
+            if let Some(file) = self.files.get_mut(*file_index) {
+                file.chunks_done += 1;
+            }
+        }
+    }
+
+    fn snapshot(&self, elapsed: Duration) -> ProgressSnapshot {
+        let chunks_done = self.chunks_done + self.untracked_chunks_done;
+        let chunks_total = self.chunks.len() + self.untracked_chunks_total;
+        let chunks_transferred = chunks_done - self.chunks_skipped;
+
+        let elapsed_secs = elapsed.as_secs_f64();
+        let (bytes_per_sec, chunks_per_sec) = if elapsed_secs > 0.0 {
+            (
+                self.bytes_transferred as f64 / elapsed_secs,
+                chunks_transferred as f64 / elapsed_secs,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        // Prefer the bytes when they are known, the chunks not all being of the same size
+        let bytes_left = self.bytes_total.saturating_sub(self.bytes_done);
+        let chunks_left = chunks_total.saturating_sub(chunks_done);
+        let eta_secs = if chunks_left == 0 {
+            Some(0)
+        } else if bytes_left > 0 && bytes_per_sec > 0.0 {
+            Some((bytes_left as f64 / bytes_per_sec).ceil() as u64)
+        } else if chunks_per_sec > 0.0 {
+            Some((chunks_left as f64 / chunks_per_sec).ceil() as u64)
+        } else {
+            None
+        };
+
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let status = if file.chunks_done == file.chunks_total {
+                    FileStatus::Completed
+                } else if self.failed {
+                    FileStatus::Failed
+                } else if file.chunks_done == 0 {
+                    FileStatus::Pending
+                } else {
+                    FileStatus::InProgress
+                };
+                FileProgress {
+                    name: file.name.clone(),
+                    chunks_done: file.chunks_done,
+                    chunks_total: file.chunks_total,
+                    status,
+                }
+            })
+            .collect();
+
+        ProgressSnapshot {
+            elapsed_secs,
+            bytes_done: self.bytes_done,
+            bytes_total: self.bytes_total,
+            chunks_done,
+            chunks_total,
+            chunks_skipped: self.chunks_skipped,
+            retries: self.retries,
+            bytes_per_sec,
+            chunks_per_sec,
+            eta_secs,
+            failed: self.failed,
+            files,
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_protocol::storage::ChunkAddress;
+
+    #[test]
+    fn shared_chunks_are_counted_once_and_files_report_their_status() {
+        let tracker = ProgressTracker::new();
+        let shared = XorName::random(&mut rand::thread_rng());
+        let first = XorName::random(&mut rand::thread_rng());
+        let second = XorName::random(&mut rand::thread_rng());
+        tracker.add_file("a", [(shared, 100), (first, 100)]);
+        tracker.add_file("b", [(shared, 100), (second, 300)]);
+
+        tracker.on_upload_event(&UploadEvent::ChunkAlreadyExistsInNetwork(
+            ChunkAddress::new(shared),
+        ));
+        tracker.on_upload_event(&UploadEvent::UploadRetried(first));
+        tracker.on_upload_event(&UploadEvent::ChunkUploaded(ChunkAddress::new(first)));
+
+        let state = tracker.lock();
+        let snapshot = state.snapshot(Duration::from_secs(2));
+        assert_eq!(snapshot.chunks_total, 3);
+        assert_eq!(snapshot.chunks_done, 2);
+        assert_eq!(snapshot.chunks_skipped, 1);
+        assert_eq!(snapshot.bytes_total, 500);
+        assert_eq!(snapshot.bytes_done, 200);
+        assert_eq!(snapshot.retries, 1);
+        // only the uploaded chunk counts towards the throughput
+        assert_eq!(snapshot.bytes_per_sec, 50.0);
+        assert_eq!(snapshot.eta_secs, Some(6));
+        assert_eq!(snapshot.files[0].status, FileStatus::Completed);
+        assert_eq!(snapshot.files[1].status, FileStatus::InProgress);
+    }
+
+    #[test]
+    fn download_progress_uses_the_announced_chunks_count() {
+        let tracker = ProgressTracker::new();
+        tracker.on_download_event(&FilesDownloadEvent::ChunksCount(4));
+        tracker.on_download_event(&FilesDownloadEvent::Downloaded(ChunkAddress::new(
+            XorName::random(&mut rand::thread_rng()),
+        )));
+
+        let snapshot = tracker.lock().snapshot(Duration::from_secs(1));
+        assert_eq!(snapshot.chunks_total, 4);
+        assert_eq!(snapshot.chunks_done, 1);
+        assert_eq!(snapshot.eta_secs, Some(3));
+
+        tracker.on_download_event(&FilesDownloadEvent::Error);
+        assert!(tracker.snapshot().failed);
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bytes::Bytes;
+use sn_networking::target_arch::Instant;
+use sn_protocol::NetworkAddress;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::time::Duration;
+
+/// Default max size of the records kept in memory
+const DEFAULT_MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// Default time a Register is served from the cache before being fetched again
+const DEFAULT_REGISTER_TTL: Duration = Duration::from_secs(30);
+/// Default time a Spend is served from the cache before being fetched again.
+/// Kept short, as a double spend only shows up when fetching the spend from the network.
+const DEFAULT_SPEND_TTL: Duration = Duration::from_secs(5);
+
+/// Configuration of the client side `RecordCache`
+#[derive(Debug, Clone)]
+pub struct RecordCacheConfig {
+    /// Max size of the records kept in memory, the oldest ones being evicted first
+    pub max_memory_bytes: usize,
+    /// Time a Register is served from the cache. `Duration::ZERO` disables caching the Registers.
+    pub register_ttl: Duration,
+    /// Time a Spend is served from the cache. `Duration::ZERO` disables caching the Spends.
+    pub spend_ttl: Duration,
+    /// Set to also persist the records on disk, under that directory
+    pub disk_dir: Option<PathBuf>,
+}
+
+impl Default for RecordCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            register_ttl: DEFAULT_REGISTER_TTL,
+            spend_ttl: DEFAULT_SPEND_TTL,
+            disk_dir: None,
+        }
+    }
+}
+
+/// The hits and misses of a `RecordCache`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of records held in memory
+    pub entries: usize,
+    /// Size of the records held in memory
+    pub memory_bytes: usize,
+}
+
+impl RecordCacheStats {
+    /// Share of the lookups served by the cache, from 0.0 to 1.0
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for RecordCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} records using {} bytes",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.entries,
+            self.memory_bytes
+        )
+    }
+}
+
+#[derive(Debug)]
+struct CachedRecord {
+    value: Bytes,
+    inserted_at: Instant,
+    /// Position in the eviction order
+    seq: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    records: HashMap<NetworkAddress, CachedRecord>,
+    /// The cached addresses, oldest first
+    insertion_order: BTreeMap<u64, NetworkAddress>,
+    next_seq: u64,
+    memory_bytes: usize,
+}
+
+impl CacheEntries {
+    fn remove(&mut self, address: &NetworkAddress) {
+        if let Some(record) = self.records.remove(address) {
+            let _ = self.insertion_order.remove(&record.seq);
+            self.memory_bytes -= record.value.len();
+        }
+    }
+}
+
+/// An optional client side cache of the records fetched from the network, keyed by their address.
+///
+/// Chunks are content addressed, hence are cached indefinitely and verified by the callers
+/// when served. Registers and Spends can change, hence are only served for a configurable TTL,
+/// a short one for the Spends so double spends are still detected.
+/// The cache is shared by all the clones of the `Client` it is set on.
+#[derive(Debug, Clone)]
+pub struct RecordCache {
+    config: Arc<RecordCacheConfig>,
+    entries: Arc<Mutex<CacheEntries>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl RecordCache {
+    pub fn new(config: RecordCacheConfig) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = &config.disk_dir {
+            if let Err(err) = std::fs::create_dir_all(dir) {
+                warn!("Failed to create the record cache dir {dir:?}, caching in memory only: {err:?}");
+            }
+        }
+        Self {
+            config: Arc::new(config),
+            entries: Default::default(),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    /// Returns how long the record at the address can be served from the cache.
+    /// `None` means forever, `Some(Duration::ZERO)` that it shall not be cached.
+    fn ttl(&self, address: &NetworkAddress) -> Option<Duration> {
+        match address {
+            NetworkAddress::ChunkAddress(_) => None,
+            NetworkAddress::RegisterAddress(_) => Some(self.config.register_ttl),
+            NetworkAddress::SpendAddress(_) => Some(self.config.spend_ttl),
+            _ => Some(Duration::ZERO),
+        }
+    }
+
+    fn is_cacheable(&self, address: &NetworkAddress) -> bool {
+        self.ttl(address) != Some(Duration::ZERO)
+    }
+
+    /// Returns the fresh record value cached for that address, if any
+    pub(crate) fn get(&self, address: &NetworkAddress) -> Option<Bytes> {
+        if !self.is_cacheable(address) {
+            return None;
+        }
+
+        let found = self
+            .get_from_memory(address)
+            .or_else(|| self.get_from_disk(address));
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        let _ = counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    fn get_from_memory(&self, address: &NetworkAddress) -> Option<Bytes> {
+        let ttl = self.ttl(address);
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let is_fresh = entries
+            .records
+            .get(address)
+            .map(|record| !matches!(ttl, Some(ttl) if record.inserted_at.elapsed() >= ttl))?;
+        if !is_fresh {
+            entries.remove(address);
+            return None;
+        }
+        entries
+            .records
+            .get(address)
+            .map(|record| record.value.clone())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn get_from_disk(&self, address: &NetworkAddress) -> Option<Bytes> {
+        let path = self.disk_path(address)?;
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = modified.elapsed().unwrap_or_default();
+        if let Some(ttl) = self.ttl(address) {
+            if age >= ttl {
+                let _ = std::fs::remove_file(&path);
+                return None;
+            }
+        }
+
+        let value = Bytes::from(std::fs::read(&path).ok()?);
+        // Keep it in memory for the next time, without extending its TTL
+        let inserted_at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        self.insert_in_memory(address.clone(), value.clone(), inserted_at);
+        Some(value)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn get_from_disk(&self, _address: &NetworkAddress) -> Option<Bytes> {
+        None
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn disk_path(&self, address: &NetworkAddress) -> Option<PathBuf> {
+        let dir = self.config.disk_dir.as_ref()?;
+        Some(dir.join(hex::encode(address.to_record_key().as_ref())))
+    }
+
+    /// Caches the record value fetched from the network
+    pub(crate) fn put(&self, address: NetworkAddress, value: Bytes) {
+        if !self.is_cacheable(&address) {
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(dir), Some(path)) = (&self.config.disk_dir, self.disk_path(&address)) {
+            // Written aside then renamed, so a concurrent reader or a crash never sees a partial record
+            let written = tempfile::NamedTempFile::new_in(dir).and_then(|mut file| {
+                file.write_all(&value)?;
+                let _ = file.persist(&path).map_err(|err| err.error)?;
+                Ok(())
+            });
+            if let Err(err) = written {
+                warn!("Failed to write the cached record {address:?} to {path:?}: {err:?}");
+            }
+        }
+        self.insert_in_memory(address, value, Instant::now());
+    }
+
+    fn insert_in_memory(&self, address: NetworkAddress, value: Bytes, inserted_at: Instant) {
+        if value.len() > self.config.max_memory_bytes {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        entries.remove(&address);
+        while entries.memory_bytes + value.len() > self.config.max_memory_bytes {
+            let Some((_, oldest)) = entries.insertion_order.pop_first() else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+
+        let seq = entries.next_seq;
+        entries.next_seq += 1;
+        entries.memory_bytes += value.len();
+        let _ = entries.insertion_order.insert(seq, address.clone());
+        let _ = entries.records.insert(
+            address,
+            CachedRecord {
+                value,
+                inserted_at,
+                seq,
+            },
+        );
+    }
+
+    /// Drops the record cached for that address, e.g. after it got updated or failed verification
+    pub fn invalidate(&self, address: &NetworkAddress) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(address);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.disk_path(address) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// The hits and misses since the cache got created
+    pub fn stats(&self) -> RecordCacheStats {
+        let entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        RecordCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: entries.records.len(),
+            memory_bytes: entries.memory_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_protocol::storage::{ChunkAddress, RegisterAddress, SpendAddress};
+    use xor_name::XorName;
+
+    fn chunk_address() -> NetworkAddress {
+        NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(
+            &mut rand::thread_rng(),
+        )))
+    }
+
+    fn register_address() -> NetworkAddress {
+        NetworkAddress::from_register_address(RegisterAddress::new(
+            XorName::random(&mut rand::thread_rng()),
+            bls::SecretKey::random().public_key(),
+        ))
+    }
+
+    #[test]
+    fn chunks_are_cached_and_registers_expire() {
+        let cache = RecordCache::new(RecordCacheConfig {
+            register_ttl: Duration::from_millis(50),
+            ..Default::default()
+        });
+        let chunk = chunk_address();
+        let register = register_address();
+
+        assert_eq!(cache.get(&chunk), None);
+        cache.put(chunk.clone(), Bytes::from_static(b"chunk"));
+        cache.put(register.clone(), Bytes::from_static(b"register"));
+        assert_eq!(cache.get(&chunk), Some(Bytes::from_static(b"chunk")));
+        assert_eq!(cache.get(&register), Some(Bytes::from_static(b"register")));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&register), None);
+        assert_eq!(cache.get(&chunk), Some(Bytes::from_static(b"chunk")));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.entries, 1);
+        assert!((stats.hit_rate() - 0.6).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn oldest_records_are_evicted_first() {
+        let cache = RecordCache::new(RecordCacheConfig {
+            max_memory_bytes: 10,
+            register_ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        let first = chunk_address();
+        let second = chunk_address();
+        let third = chunk_address();
+        cache.put(first.clone(), Bytes::from_static(b"12345"));
+        cache.put(second.clone(), Bytes::from_static(b"12345"));
+        cache.put(third.clone(), Bytes::from_static(b"12345"));
+
+        assert_eq!(cache.get(&first), None);
+        assert!(cache.get(&second).is_some());
+        assert!(cache.get(&third).is_some());
+        assert_eq!(cache.stats().memory_bytes, 10);
+
+        // Registers caching is disabled by the zero TTL
+        let register = register_address();
+        cache.put(register.clone(), Bytes::from_static(b"1"));
+        assert_eq!(cache.get(&register), None);
+    }
+
+    #[test]
+    fn spends_expire_after_their_ttl() {
+        let cache = RecordCache::new(RecordCacheConfig {
+            spend_ttl: Duration::from_millis(50),
+            ..Default::default()
+        });
+        let spend = NetworkAddress::from_spend_address(SpendAddress::new(XorName::random(
+            &mut rand::thread_rng(),
+        )));
+        cache.put(spend.clone(), Bytes::from_static(b"spend"));
+        assert_eq!(cache.get(&spend), Some(Bytes::from_static(b"spend")));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(cache.get(&spend), None);
+
+        // Spends caching is disabled by the zero TTL
+        let cache = RecordCache::new(RecordCacheConfig {
+            spend_ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        cache.put(spend.clone(), Bytes::from_static(b"spend"));
+        assert_eq!(cache.get(&spend), None);
+    }
+
+    #[test]
+    fn records_are_persisted_on_disk() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = RecordCacheConfig {
+            disk_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let chunk = chunk_address();
+        RecordCache::new(config.clone()).put(chunk.clone(), Bytes::from_static(b"chunk"));
+
+        let cache = RecordCache::new(config);
+        assert_eq!(cache.get(&chunk), Some(Bytes::from_static(b"chunk")));
+
+        cache.invalidate(&chunk);
+        assert_eq!(cache.get(&chunk), None);
+        Ok(())
+    }
+}
@@ -855,7 +855,10 @@ impl ClientRegister {
         };
 
         // Register edits might exist, so we cannot be sure that just because we get a record back that this should fail
-        Ok(client.network.put_record(record, &put_cfg).await?)
+        let result = client.network.put_record(record, &put_cfg).await;
+        // The cached copy, if any, is now outdated
+        client.invalidate_cached_record(&network_address);
+        Ok(result?)
     }
 
     /// Retrieve a `Register` from the Network.
@@ -0,0 +1,71 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{upload::InnerUploader, ItemEstimate, UploadEstimate, UploadItem};
+use crate::{Error, Result};
+use futures::{StreamExt, TryStreamExt};
+use sn_transfers::{calculate_royalties_fee, NanoTokens, QUOTE_EXPIRATION_SECS};
+use std::time::Duration;
+
+impl InnerUploader {
+    /// Collect the quotes of all the items without paying nor uploading anything.
+    ///
+    /// The quotes are selected with the payment policy, as they would be during the upload.
+    /// As during the upload, the chunks already stored are found by proving their existence
+    /// when `check_existing_chunks` is set. Those are reported but cost nothing.
+    pub(super) async fn estimate(self) -> Result<UploadEstimate> {
+        let payment_policy = self.cfg.payment_policy;
+        let check_existing_chunks = self.cfg.check_existing_chunks;
+        let tasks = self.all_upload_items.into_iter().map(|(xorname, item)| {
+            let client = self.client.clone();
+            let address = item.address();
+            async move {
+                if check_existing_chunks
+                    && matches!(item, UploadItem::Chunk { .. })
+                    && InnerUploader::check_chunk_existence(client.clone(), item).await
+                {
+                    trace!("The chunk {xorname:?} is already stored, it costs nothing");
+                    let item_estimate = ItemEstimate {
+                        address,
+                        storage_cost: NanoTokens::zero(),
+                        royalty_fee: NanoTokens::zero(),
+                        already_stored: true,
+                        quote_expiry: None,
+                    };
+                    return Ok((xorname, item_estimate));
+                }
+
+                let quotes = client
+                    .network
+                    .get_store_quotes_from_network(address.clone(), vec![])
+                    .await?;
+                let (_peer_id, _main_pubkey, quote) = payment_policy.select_quote(quotes)?;
+                trace!(
+                    "Estimated the store cost of {xorname:?} as {:?}",
+                    quote.cost
+                );
+                let item_estimate = ItemEstimate {
+                    address,
+                    storage_cost: quote.cost,
+                    royalty_fee: calculate_royalties_fee(quote.cost),
+                    already_stored: false,
+                    quote_expiry: Some(
+                        quote.timestamp + Duration::from_secs(QUOTE_EXPIRATION_SECS),
+                    ),
+                };
+                Ok::<_, Error>((xorname, item_estimate))
+            }
+        });
+        let items = futures::stream::iter(tasks)
+            .buffer_unordered(self.cfg.batch_size)
+            .try_collect()
+            .await?;
+
+        UploadEstimate::from_items(items)
+    }
+}
@@ -6,12 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod estimate;
 #[cfg(test)]
 mod tests;
 mod upload;
 
 use self::upload::{start_upload, InnerUploader, MAX_REPAYMENTS_PER_FAILED_ITEM};
-use crate::{Client, ClientRegister, Error, Result, BATCH_SIZE};
+use crate::{Client, ClientRegister, Error, PaymentPolicy, Result, BATCH_SIZE};
 use itertools::Either;
 use sn_networking::PayeeQuote;
 use sn_protocol::{
@@ -24,6 +25,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fmt::Debug,
     path::PathBuf,
+    time::SystemTime,
 };
 use tokio::sync::mpsc;
 use xor_name::XorName;
@@ -37,6 +39,10 @@ pub struct UploadCfg {
     pub retry_strategy: RetryStrategy,
     pub max_repayments_for_failed_data: usize, // we want people to specify an explicit limit here.
     pub collect_registers: bool,
+    pub payment_policy: PaymentPolicy,
+    /// Check which chunks are already stored before paying for them. This costs a chunk proof
+    /// request per chunk, so it is off by default and left to the callers that want it.
+    pub check_existing_chunks: bool,
 }
 
 impl Default for UploadCfg {
@@ -48,6 +54,8 @@ impl Default for UploadCfg {
             retry_strategy: RetryStrategy::Balanced,
             max_repayments_for_failed_data: MAX_REPAYMENTS_PER_FAILED_ITEM,
             collect_registers: false,
+            payment_policy: PaymentPolicy::default(),
+            check_existing_chunks: false,
         }
     }
 }
@@ -92,6 +100,59 @@ impl UploadSummary {
     }
 }
 
+/// The estimated cost of storing a single item, from the quote selected for it.
+#[derive(Debug, Clone)]
+pub struct ItemEstimate {
+    pub address: NetworkAddress,
+    pub storage_cost: NanoTokens,
+    pub royalty_fee: NanoTokens,
+    /// The record is already stored in the network, nothing is to be paid for it.
+    pub already_stored: bool,
+    /// The time the quote stops being accepted by the node, if any was needed.
+    pub quote_expiry: Option<SystemTime>,
+}
+
+/// The result of an estimate-only run of the `Uploader`.
+#[derive(Debug, Clone)]
+pub struct UploadEstimate {
+    pub items: BTreeMap<XorName, ItemEstimate>,
+    pub storage_cost: NanoTokens,
+    pub royalty_fees: NanoTokens,
+    pub already_stored_count: usize,
+    /// The earliest expiry of the quotes. The estimate might not hold past it.
+    pub quote_expiry: Option<SystemTime>,
+}
+
+impl UploadEstimate {
+    fn from_items(items: BTreeMap<XorName, ItemEstimate>) -> Result<Self> {
+        let mut storage_cost = NanoTokens::zero();
+        let mut royalty_fees = NanoTokens::zero();
+        for item in items.values() {
+            storage_cost = storage_cost
+                .checked_add(item.storage_cost)
+                .ok_or(Error::NumericOverflow)?;
+            royalty_fees = royalty_fees
+                .checked_add(item.royalty_fee)
+                .ok_or(Error::NumericOverflow)?;
+        }
+
+        Ok(Self {
+            already_stored_count: items.values().filter(|item| item.already_stored).count(),
+            quote_expiry: items.values().filter_map(|item| item.quote_expiry).min(),
+            items,
+            storage_cost,
+            royalty_fees,
+        })
+    }
+
+    /// The storage cost plus the royalties.
+    pub fn total_cost(&self) -> Result<NanoTokens> {
+        self.storage_cost
+            .checked_add(self.royalty_fees)
+            .ok_or(Error::NumericOverflow)
+    }
+}
+
 #[derive(Debug, Clone)]
 /// The events emitted from the upload process.
 pub enum UploadEvent {
@@ -107,6 +168,8 @@ pub enum UploadEvent {
     /// No payments were made.
     /// The returned register contains the remote replica merged with the passed in register.
     RegisterUpdated(ClientRegister),
+    /// Uploading the item failed and it has been queued again, either to the same payee or to a new one.
+    UploadRetried(XorName),
     /// Payment for a batch of records has been made.
     PaymentMade {
         storage_cost: NanoTokens,
@@ -155,6 +218,18 @@ impl Uploader {
         }
     }
 
+    /// Collect the real quotes of all the inserted items without paying nor uploading anything.
+    ///
+    /// The items already stored in the network are reported with a zero cost.
+    /// The quotes are selected with the payment policy of the `UploadCfg`, as during an upload.
+    pub async fn estimate(mut self) -> Result<UploadEstimate> {
+        self.inner
+            .take()
+            .expect("Uploader::new makes sure inner is present")
+            .estimate()
+            .await
+    }
+
     /// Update all the configurations by passing the `UploadCfg` struct
     pub fn set_upload_cfg(&mut self, cfg: UploadCfg) {
         // Self can only be constructed with new(), which will set inner to InnerUploader always.
@@ -232,6 +307,28 @@ impl Uploader {
             .set_collect_registers(collect_registers);
     }
 
+    /// Sets the policy applied to the store cost quotes before paying for the data: price limits,
+    /// the choice of the quote among the close group and the rejection of the anomalous quotes.
+    ///
+    /// By default, the cheapest non anomalous quote is paid, without any price limit.
+    pub fn set_payment_policy(&mut self, payment_policy: PaymentPolicy) {
+        self.inner
+            .as_mut()
+            .expect("Uploader::new makes sure inner is present")
+            .set_payment_policy(payment_policy);
+    }
+
+    /// Sets the option to check which chunks are already stored in the network before getting their store cost.
+    /// The chunks found are skipped without paying for them, at the cost of an extra request per chunk.
+    ///
+    /// By default, this option is set to false.
+    pub fn set_check_existing_chunks(&mut self, check_existing_chunks: bool) {
+        self.inner
+            .as_mut()
+            .expect("Uploader::new makes sure inner is present")
+            .set_check_existing_chunks(check_existing_chunks);
+    }
+
     /// Returns a receiver for UploadEvent.
     /// This method is optional and the upload process can be performed without it.
     pub fn get_event_receiver(&mut self) -> mpsc::Receiver<UploadEvent> {
@@ -287,6 +384,13 @@ trait UploaderInterface: Send + Sync {
         task_result_sender: mpsc::Sender<TaskResult>,
     );
 
+    fn submit_check_chunk_existence_task(
+        &mut self,
+        client: Client,
+        upload_item: UploadItem,
+        task_result_sender: mpsc::Sender<TaskResult>,
+    );
+
     #[allow(clippy::too_many_arguments)]
     fn submit_get_store_cost_task(
         &mut self,
@@ -296,6 +400,7 @@ trait UploaderInterface: Send + Sync {
         address: NetworkAddress,
         get_store_cost_strategy: GetStoreCostStrategy,
         max_repayments_for_failed_data: usize,
+        payment_policy: PaymentPolicy,
         task_result_sender: mpsc::Sender<TaskResult>,
     );
 
@@ -346,6 +451,14 @@ impl InnerUploader {
         self.cfg.collect_registers = collect_registers;
     }
 
+    pub(super) fn set_payment_policy(&mut self, payment_policy: PaymentPolicy) {
+        self.cfg.payment_policy = payment_policy;
+    }
+
+    pub(super) fn set_check_existing_chunks(&mut self, check_existing_chunks: bool) {
+        self.cfg.check_existing_chunks = check_existing_chunks;
+    }
+
     pub(super) fn get_event_receiver(&mut self) -> mpsc::Receiver<UploadEvent> {
         let (tx, rx) = mpsc::channel(100);
         self.event_sender = Some(tx);
@@ -427,6 +540,10 @@ enum TaskResult {
         updated_register: ClientRegister,
     },
     PushRegisterErr(XorName),
+    CheckChunkExistenceOk {
+        xorname: XorName,
+        exists: bool,
+    },
     GetStoreCostOk {
         xorname: XorName,
         quote: Box<PayeeQuote>,
@@ -13,13 +13,20 @@ use crate::{
         get_dummy_chunk_paths, get_dummy_registers, get_inner_uploader, start_uploading_with_steps,
         TestSteps,
     },
+    uploader::{ItemEstimate, UploadEstimate},
     Error as ClientError, UploadEvent,
 };
 use assert_matches::assert_matches;
 use eyre::Result;
 use sn_logging::LogBuilder;
-use std::collections::VecDeque;
+use sn_protocol::{storage::ChunkAddress, NetworkAddress};
+use sn_transfers::{calculate_royalties_fee, NanoTokens, QUOTE_EXPIRATION_SECS};
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
 use tempfile::tempdir;
+use xor_name::XorName;
 
 // ===== HAPPY PATH =======
 
@@ -144,6 +151,34 @@ async fn register_should_be_paid_and_uploaded_if_it_does_not_exists() -> Result<
     Ok(())
 }
 
+/// 5. Chunk: if the existence check finds it, then skip it without getting its store cost.
+#[tokio::test]
+async fn chunk_found_by_the_existence_check_should_be_skipped_without_payment() -> Result<()> {
+    let _log_guards = LogBuilder::init_single_threaded_tokio_test("uploader", true);
+    let temp_dir = tempdir()?;
+    let (mut inner_uploader, task_result_rx) = get_inner_uploader(temp_dir.path().to_path_buf())?;
+
+    // cfg
+    inner_uploader.set_batch_size(1);
+    inner_uploader.set_check_existing_chunks(true);
+    inner_uploader.insert_chunk_paths(get_dummy_chunk_paths(1, temp_dir.path().to_path_buf()));
+
+    // the path to test
+    let steps = vec![TestSteps::CheckChunkExistenceOk { exists: true }];
+
+    let (upload_handle, events_handle) =
+        start_uploading_with_steps(inner_uploader, VecDeque::from(steps), task_result_rx);
+
+    let stats = upload_handle.await??;
+    let events = events_handle.await?;
+
+    assert_eq!(stats.skipped_count, 1);
+    assert_eq!(stats.storage_cost, NanoTokens::zero());
+    assert_eq!(events.len(), 1);
+    assert_matches!(events[0], UploadEvent::ChunkAlreadyExistsInNetwork(_));
+    Ok(())
+}
+
 // ===== REPAYMENTS ======
 
 /// 1. Chunks: if upload task fails > threshold, then get store cost should be triggered with SelectDifferentStrategy
@@ -181,10 +216,12 @@ async fn chunks_should_perform_repayment_if_the_upload_fails_multiple_times() ->
     let _stats = upload_handle.await??;
     let events = events_handle.await?;
 
-    assert_eq!(events.len(), 3);
+    assert_eq!(events.len(), 5);
     assert_matches!(events[0], UploadEvent::PaymentMade { .. });
-    assert_matches!(events[1], UploadEvent::PaymentMade { .. });
-    assert_matches!(events[2], UploadEvent::ChunkUploaded(..));
+    assert_matches!(events[1], UploadEvent::UploadRetried(..));
+    assert_matches!(events[2], UploadEvent::UploadRetried(..));
+    assert_matches!(events[3], UploadEvent::PaymentMade { .. });
+    assert_matches!(events[4], UploadEvent::ChunkUploaded(..));
     Ok(())
 }
 
@@ -224,10 +261,12 @@ async fn registers_should_perform_repayment_if_the_upload_fails_multiple_times()
     let _stats = upload_handle.await??;
     let events = events_handle.await?;
 
-    assert_eq!(events.len(), 3);
+    assert_eq!(events.len(), 5);
     assert_matches!(events[0], UploadEvent::PaymentMade { .. });
-    assert_matches!(events[1], UploadEvent::PaymentMade { .. });
-    assert_matches!(events[2], UploadEvent::RegisterUploaded(..));
+    assert_matches!(events[1], UploadEvent::UploadRetried(..));
+    assert_matches!(events[2], UploadEvent::UploadRetried(..));
+    assert_matches!(events[3], UploadEvent::PaymentMade { .. });
+    assert_matches!(events[4], UploadEvent::RegisterUploaded(..));
     Ok(())
 }
 
@@ -452,8 +491,49 @@ async fn maximum_repayment_error_should_be_triggered_during_get_store_cost() ->
     );
     let events = events_handle.await?;
 
-    assert_eq!(events.len(), 2);
+    assert_eq!(events.len(), 6);
     assert_matches!(events[0], UploadEvent::PaymentMade { .. });
-    assert_matches!(events[1], UploadEvent::PaymentMade { .. });
+    assert_matches!(events[1], UploadEvent::UploadRetried(..));
+    assert_matches!(events[2], UploadEvent::UploadRetried(..));
+    assert_matches!(events[3], UploadEvent::PaymentMade { .. });
+    assert_matches!(events[4], UploadEvent::UploadRetried(..));
+    assert_matches!(events[5], UploadEvent::UploadRetried(..));
+    Ok(())
+}
+
+// ===== ESTIMATE =======
+
+#[test]
+fn estimate_should_sum_the_quotes_and_skip_the_stored_items() -> Result<()> {
+    let now = SystemTime::now();
+    let expiry = now + Duration::from_secs(QUOTE_EXPIRATION_SECS);
+    let item = |cost: u64, quote_expiry: Option<SystemTime>| ItemEstimate {
+        address: NetworkAddress::from_chunk_address(ChunkAddress::new(XorName::random(
+            &mut rand::thread_rng(),
+        ))),
+        storage_cost: NanoTokens::from(cost),
+        royalty_fee: calculate_royalties_fee(NanoTokens::from(cost)),
+        already_stored: cost == 0,
+        quote_expiry,
+    };
+    let items = [
+        item(100, Some(expiry)),
+        item(0, None),
+        item(300, Some(expiry + Duration::from_secs(10))),
+    ]
+    .into_iter()
+    .map(|item| (XorName::random(&mut rand::thread_rng()), item))
+    .collect();
+
+    let estimate = UploadEstimate::from_items(items)?;
+    assert_eq!(estimate.storage_cost, NanoTokens::from(400));
+    assert_eq!(
+        estimate.royalty_fees,
+        calculate_royalties_fee(NanoTokens::from(100))
+            .checked_add(calculate_royalties_fee(NanoTokens::from(300)))
+            .ok_or(ClientError::NumericOverflow)?
+    );
+    assert_eq!(estimate.already_stored_count, 1);
+    assert_eq!(estimate.quote_expiry, Some(expiry));
     Ok(())
 }
@@ -13,7 +13,7 @@ use crate::{
     },
     ClientRegister, UploadEvent,
 };
-use crate::{Client, Result as ClientResult, UploadSummary};
+use crate::{Client, PaymentPolicy, Result as ClientResult, UploadSummary};
 use assert_matches::assert_matches;
 use bls::SecretKey;
 use eyre::Result;
@@ -133,6 +133,35 @@ impl UploaderInterface for TestUploader {
         }
     }
 
+    // The existence check is an optional step: unless the test expects it, the chunk is reported as not stored.
+    fn submit_check_chunk_existence_task(
+        &mut self,
+        _client: Client,
+        upload_item: UploadItem,
+        _task_result_sender: mpsc::Sender<TaskResult>,
+    ) {
+        let xorname = upload_item.xorname();
+        let exists = match self.test_steps.front() {
+            Some(TestSteps::CheckChunkExistenceOk { exists }) => {
+                let exists = *exists;
+                let _ = self.test_steps.pop_front();
+                exists
+            }
+            _ => false,
+        };
+        let handle = Handle::current();
+        let task_result_sender = self.task_result_sender.clone();
+
+        println!("spawn_check_chunk_existence called for: {xorname:?}. Exists: {exists:?}");
+        info!("TEST: spawn_check_chunk_existence called for: {xorname:?}. Exists: {exists:?}");
+        handle.spawn(async move {
+            task_result_sender
+                .send(TaskResult::CheckChunkExistenceOk { xorname, exists })
+                .await
+                .expect("Failed to send task result");
+        });
+    }
+
     fn submit_get_store_cost_task(
         &mut self,
         _client: Client,
@@ -141,6 +170,7 @@ impl UploaderInterface for TestUploader {
         _address: NetworkAddress,
         get_store_cost_strategy: GetStoreCostStrategy,
         max_repayments_for_failed_data: usize,
+        _payment_policy: PaymentPolicy,
         _task_result_sender: mpsc::Sender<TaskResult>,
     ) {
         let step = self
@@ -363,6 +393,9 @@ pub enum TestSteps {
     GetRegisterErr,
     PushRegisterOk,
     PushRegisterErr,
+    CheckChunkExistenceOk {
+        exists: bool,
+    },
     GetStoreCostOk {
         trigger_zero_cost: bool,
         assert_select_different_payee: bool,
@@ -431,6 +464,7 @@ pub fn build_unconnected_client(root_dir: PathBuf) -> Result<Client> {
         network,
         events_broadcaster: Default::default(),
         signer: Arc::new(SecretKey::random()),
+        record_cache: None,
     };
     Ok(client)
 }
@@ -13,7 +13,7 @@ use super::{
 use crate::{
     acc_packet::load_account_wallet_or_create_with_mnemonic,
     transfers::{TransferError, WalletError},
-    Client, ClientRegister, Error as ClientError, Result, Uploader, WalletClient,
+    Client, ClientRegister, Error as ClientError, PaymentPolicy, Result, Uploader, WalletClient,
 };
 use bytes::Bytes;
 use itertools::Either;
@@ -74,8 +74,8 @@ pub(super) async fn start_upload(
         if let Some(channels) = uploader.testing_task_channels.take() {
             channels
         } else {
-            // 6 because of the 6 pipelines, 1 for redundancy.
-            mpsc::channel(uploader.cfg.batch_size * 6 + 1)
+            // 7 because of the 7 pipelines, 1 for redundancy.
+            mpsc::channel(uploader.cfg.batch_size * 7 + 1)
         };
     let (make_payment_sender, make_payment_receiver) = mpsc::channel(uploader.cfg.batch_size);
 
@@ -85,18 +85,26 @@ pub(super) async fn start_upload(
         uploader.cfg.batch_size,
     )?;
 
-    // chunks can be pushed to pending_get_store_cost directly
-    uploader.pending_to_get_store_cost = uploader
+    // chunks can be pushed to pending_get_store_cost directly, unless we first check if they're already stored.
+    let chunks = uploader
         .all_upload_items
         .iter()
         .filter_map(|(xorname, item)| {
             if let UploadItem::Chunk { .. } = item {
-                Some((*xorname, GetStoreCostStrategy::Cheapest))
+                Some(*xorname)
             } else {
                 None
             }
         })
-        .collect();
+        .collect::<Vec<_>>();
+    if uploader.cfg.check_existing_chunks {
+        uploader.pending_to_check_existence = chunks;
+    } else {
+        uploader.pending_to_get_store_cost = chunks
+            .into_iter()
+            .map(|xorname| (xorname, GetStoreCostStrategy::Cheapest))
+            .collect();
+    }
 
     // registers have to be verified + merged with remote replica, so we have to fetch it first.
     uploader.pending_to_get_register = uploader
@@ -183,6 +191,26 @@ pub(super) async fn start_upload(
             );
         }
 
+        // try to check if a chunk is already stored if we have enough buffer.
+        // The results are used to fill up `pending_to_get_store_cost`.
+        while !uploader.pending_to_check_existence.is_empty()
+            && uploader.on_going_existence_checks.len() < uploader.cfg.batch_size
+        {
+            let upload_item = uploader.pop_item_for_existence_check()?;
+            trace!(
+                "Conditions met for checking the existence of {:?}",
+                upload_item.xorname()
+            );
+            let _ = uploader
+                .on_going_existence_checks
+                .insert(upload_item.xorname());
+            interface.submit_check_chunk_existence_task(
+                uploader.client.clone(),
+                upload_item,
+                task_result_sender.clone(),
+            );
+        }
+
         // try to get store cost for an item if pending_to_pay needs items & if we have enough buffer.
         while !uploader.pending_to_get_store_cost.is_empty()
             && uploader.on_going_get_cost.len() < uploader.cfg.batch_size
@@ -200,6 +228,7 @@ pub(super) async fn start_upload(
                 address,
                 get_store_cost_strategy,
                 uploader.cfg.max_repayments_for_failed_data,
+                uploader.cfg.payment_policy,
                 task_result_sender.clone(),
             );
         }
@@ -242,7 +271,9 @@ pub(super) async fn start_upload(
 
         // Fire None to trigger a forced round of making leftover payments, if there are not enough store cost tasks
         // to fill up the buffer.
-        if uploader.pending_to_get_store_cost.is_empty()
+        if uploader.pending_to_check_existence.is_empty()
+            && uploader.on_going_existence_checks.is_empty()
+            && uploader.pending_to_get_store_cost.is_empty()
             && uploader.on_going_get_cost.is_empty()
             && !uploader.on_going_payments.is_empty()
             && uploader.on_going_payments.len() < uploader.cfg.batch_size
@@ -322,6 +353,28 @@ pub(super) async fn start_upload(
                     return Err(ClientError::SequentialNetworkErrors);
                 }
             }
+            TaskResult::CheckChunkExistenceOk { xorname, exists } => {
+                let _ = uploader.on_going_existence_checks.remove(&xorname);
+                trace!("CheckChunkExistenceOk for {xorname:?}, exists: {exists:?}");
+
+                if exists {
+                    // skip the chunk, never getting a quote nor paying for it.
+                    let removed_item = uploader
+                        .all_upload_items
+                        .remove(&xorname)
+                        .ok_or(ClientError::UploadableItemNotFound(xorname))?;
+                    let _ = uploader.uploaded_addresses.insert(removed_item.address());
+                    uploader.skipped_count += 1;
+                    if let UploadItem::Chunk { address, .. } = removed_item {
+                        uploader
+                            .emit_upload_event(UploadEvent::ChunkAlreadyExistsInNetwork(address));
+                    }
+                } else {
+                    uploader
+                        .pending_to_get_store_cost
+                        .push((xorname, GetStoreCostStrategy::Cheapest));
+                }
+            }
             TaskResult::GetStoreCostOk { xorname, quote } => {
                 let _ = uploader.on_going_get_cost.remove(&xorname);
                 uploader.get_store_cost_errors = 0; // reset error if Ok. We only throw error after 'n' sequential errors
@@ -329,6 +382,11 @@ pub(super) async fn start_upload(
                 trace!("GetStoreCostOk for {xorname:?}'s store_cost {:?}", quote.2);
 
                 if quote.2.cost != NanoTokens::zero() {
+                    // Refusing the price is not recoverable, retrying would get the same quotes.
+                    let payment_policy = uploader.cfg.payment_policy;
+                    payment_policy.check_price(quote.2.cost)?;
+                    uploader.quoted_storage_cost =
+                        payment_policy.check_budget(uploader.quoted_storage_cost, quote.2.cost)?;
                     uploader.pending_to_pay.push((xorname, quote));
                 }
                 // if cost is 0, then it already in the network.
@@ -493,6 +551,7 @@ pub(super) async fn start_upload(
                 } else {
                     uploader.pending_to_upload.push(xorname);
                 }
+                uploader.emit_upload_event(UploadEvent::UploadRetried(xorname));
             }
         }
     }
@@ -505,6 +564,22 @@ impl UploaderInterface for Uploader {
             .expect("Uploader::new makes sure inner is present")
     }
 
+    fn submit_check_chunk_existence_task(
+        &mut self,
+        client: Client,
+        upload_item: UploadItem,
+        task_result_sender: mpsc::Sender<TaskResult>,
+    ) {
+        let xorname = upload_item.xorname();
+        trace!("Spawning check_chunk_existence for {xorname:?}");
+        let _handle = tokio::spawn(async move {
+            let exists = InnerUploader::check_chunk_existence(client, upload_item).await;
+            let _ = task_result_sender
+                .send(TaskResult::CheckChunkExistenceOk { xorname, exists })
+                .await;
+        });
+    }
+
     fn submit_get_store_cost_task(
         &mut self,
         client: Client,
@@ -513,6 +588,7 @@ impl UploaderInterface for Uploader {
         address: NetworkAddress,
         get_store_cost_strategy: GetStoreCostStrategy,
         max_repayments_for_failed_data: usize,
+        payment_policy: PaymentPolicy,
         task_result_sender: mpsc::Sender<TaskResult>,
     ) {
         trace!("Spawning get_store_cost for {xorname:?}");
@@ -524,6 +600,7 @@ impl UploaderInterface for Uploader {
                 address,
                 get_store_cost_strategy.clone(),
                 max_repayments_for_failed_data,
+                payment_policy,
             )
             .await
             {
@@ -667,6 +744,7 @@ pub(super) struct InnerUploader {
     pub(super) all_upload_items: HashMap<XorName, UploadItem>,
     pub(super) pending_to_get_register: Vec<RegisterAddress>,
     pub(super) pending_to_push_register: Vec<XorName>,
+    pub(super) pending_to_check_existence: Vec<XorName>,
     pub(super) pending_to_get_store_cost: Vec<(XorName, GetStoreCostStrategy)>,
     pub(super) pending_to_pay: Vec<(XorName, Box<PayeeQuote>)>,
     pub(super) pending_to_upload: Vec<XorName>,
@@ -674,9 +752,11 @@ pub(super) struct InnerUploader {
     // trackers
     pub(super) on_going_get_register: BTreeSet<XorName>,
     pub(super) on_going_push_register: BTreeSet<XorName>,
+    pub(super) on_going_existence_checks: BTreeSet<XorName>,
     pub(super) on_going_get_cost: BTreeSet<XorName>,
     pub(super) on_going_payments: BTreeSet<XorName>,
     pub(super) on_going_uploads: BTreeSet<XorName>,
+    pub(super) quoted_storage_cost: NanoTokens, // all the accepted quotes, checked against the budget
 
     // error trackers
     pub(super) n_errors_during_uploads: BTreeMap<XorName, usize>,
@@ -716,15 +796,18 @@ impl InnerUploader {
             all_upload_items: Default::default(),
             pending_to_get_register: Default::default(),
             pending_to_push_register: Default::default(),
+            pending_to_check_existence: Default::default(),
             pending_to_get_store_cost: Default::default(),
             pending_to_pay: Default::default(),
             pending_to_upload: Default::default(),
 
             on_going_get_register: Default::default(),
             on_going_push_register: Default::default(),
+            on_going_existence_checks: Default::default(),
             on_going_get_cost: Default::default(),
             on_going_payments: Default::default(),
             on_going_uploads: Default::default(),
+            quoted_storage_cost: NanoTokens::zero(),
 
             n_errors_during_uploads: Default::default(),
             push_register_errors: Default::default(),
@@ -762,6 +845,19 @@ impl InnerUploader {
         }
     }
 
+    fn pop_item_for_existence_check(&mut self) -> Result<UploadItem> {
+        let xorname = self
+            .pending_to_check_existence
+            .pop()
+            .ok_or(ClientError::UploadStateTrackerIsEmpty)?;
+        let upload_item = self
+            .all_upload_items
+            .get(&xorname)
+            .cloned()
+            .ok_or(ClientError::UploadableItemNotFound(xorname))?;
+        Ok(upload_item)
+    }
+
     fn pop_item_for_get_store_cost(
         &mut self,
     ) -> Result<(XorName, NetworkAddress, GetStoreCostStrategy)> {
@@ -939,6 +1035,26 @@ impl InnerUploader {
         Ok(reg)
     }
 
+    pub(super) async fn check_chunk_existence(client: Client, upload_item: UploadItem) -> bool {
+        let chunk = match upload_item {
+            UploadItem::Chunk { chunk, .. } => {
+                match chunk {
+                    Either::Left(chunk) => chunk,
+                    Either::Right(path) => match std::fs::read(&path) {
+                        Ok(bytes) => Chunk::new(Bytes::from(bytes)),
+                        Err(err) => {
+                            // the upload will fail later on, we just don't skip it here.
+                            warn!("Failed to read the chunk at {path:?} to check its existence: {err:?}");
+                            return false;
+                        }
+                    },
+                }
+            }
+            UploadItem::Register { .. } => return false,
+        };
+        client.is_chunk_stored(&chunk).await
+    }
+
     async fn get_store_cost(
         client: Client,
         wallet_api: WalletApi,
@@ -946,6 +1062,7 @@ impl InnerUploader {
         address: NetworkAddress,
         get_store_cost_strategy: GetStoreCostStrategy,
         max_repayments_for_failed_data: usize,
+        payment_policy: PaymentPolicy,
     ) -> Result<PayeeQuote> {
         let filter_list = match get_store_cost_strategy {
             GetStoreCostStrategy::Cheapest => vec![],
@@ -974,11 +1091,11 @@ impl InnerUploader {
                 filter_list
             }
         };
-        let quote = client
+        let quotes = client
             .network
-            .get_store_costs_from_network(address, filter_list)
+            .get_store_quotes_from_network(address, filter_list)
             .await?;
-        Ok(quote)
+        payment_policy.select_quote(quotes)
     }
 
     async fn upload_item(
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::Error;
+use crate::{Error, PaymentPolicy};
 
 use super::{error::Result, Client};
 use backoff::{backoff::Backoff, ExponentialBackoff};
@@ -35,6 +35,7 @@ const MAX_RESEND_PENDING_TX_ATTEMPTS: usize = 10;
 pub struct WalletClient {
     client: Client,
     wallet: HotWallet,
+    payment_policy: PaymentPolicy,
 }
 
 /// The result of the payment made for a set of Content Addresses
@@ -67,7 +68,18 @@ impl WalletClient {
     /// # }
     /// ```
     pub fn new(client: Client, wallet: HotWallet) -> Self {
-        Self { client, wallet }
+        Self {
+            client,
+            wallet,
+            payment_policy: PaymentPolicy::default(),
+        }
+    }
+
+    /// Sets the policy applied to the store cost quotes by `pay_for_storage`.
+    ///
+    /// By default, the cheapest non anomalous quote is paid, without any price limit.
+    pub fn set_payment_policy(&mut self, payment_policy: PaymentPolicy) {
+        self.payment_policy = payment_policy;
     }
 
     /// Stores the wallet to the local wallet directory.
@@ -499,12 +511,18 @@ impl WalletClient {
         let mut tasks = JoinSet::new();
         for content_addr in content_addrs {
             let client = self.client.clone();
+            let payment_policy = self.payment_policy;
             tasks.spawn(async move {
-                let cost = client
+                let cost = match client
                     .network
-                    .get_store_costs_from_network(content_addr.clone(), vec![])
+                    .get_store_quotes_from_network(content_addr.clone(), vec![])
                     .await
-                    .map_err(|error| WalletError::CouldNotSendMoney(error.to_string()));
+                {
+                    Ok(quotes) => payment_policy
+                        .select_quote(quotes)
+                        .map_err(|error| WalletError::CouldNotSendMoney(error.to_string())),
+                    Err(error) => Err(WalletError::CouldNotSendMoney(error.to_string())),
+                };
 
                 debug!("Storecosts retrieved for {content_addr:?} {cost:?}");
                 (content_addr, cost)
@@ -515,6 +533,7 @@ impl WalletClient {
         // collect store costs
         let mut cost_map = BTreeMap::default();
         let mut skipped_chunks = vec![];
+        let mut total_cost = NanoTokens::zero();
         #[allow(clippy::mutable_key_type)]
         while let Some(res) = tasks.join_next().await {
             match res {
@@ -524,6 +543,13 @@ impl WalletClient {
                             skipped_chunks.push(xorname);
                             debug!("Skipped existing chunk {content_addr:?}");
                         } else {
+                            // Refusing the price is not worth retrying, the quotes would be the same.
+                            let checked =
+                                self.payment_policy.check_price(cost.2.cost).and_then(|_| {
+                                    self.payment_policy.check_budget(total_cost, cost.2.cost)
+                                });
+                            total_cost = checked
+                                .map_err(|error| WalletError::PaymentRefused(error.to_string()))?;
                             debug!("Storecost inserted into payment map for {content_addr:?}");
                             let _ = cost_map.insert(xorname, (cost.1, cost.2, cost.0.to_bytes()));
                         }
@@ -217,12 +217,12 @@ async fn upload_downloaded_book(
     };
 
     info!(
-        "File {file_path:?} uploaded completed with summary {:?}",
-        summary.upload_summary
+        "File {file_path:?} uploaded completed with summary {:?}, progress {}",
+        summary.upload_summary, summary.progress
     );
     println!(
-        "File {file_path:?} uploaded completed with summary {:?}",
-        summary.upload_summary
+        "File {file_path:?} uploaded completed with summary {:?}, progress {}",
+        summary.upload_summary, summary.progress
     );
 
     let mut head_addresses = vec![];
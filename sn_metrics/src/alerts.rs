@@ -0,0 +1,105 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::BTreeMap;
+
+/// Mirrors `MAX_RECORDS_COUNT` in `sn_networking::record_store`.
+const MAX_RECORDS_COUNT: usize = 4096;
+/// The fraction of the record store that is considered nearly full.
+const RECORD_STORE_FULL_RATIO: f64 = 0.9;
+/// The fraction of the keys to fetch during replication that can fail before alerting.
+const REPLICATION_FAILURE_RATIO: f64 = 0.2;
+/// The number of peers that can be newly flagged as bad within an hour before alerting.
+const BAD_NODES_INCREASE: usize = 5;
+
+#[derive(serde::Serialize)]
+pub(crate) struct AlertRules {
+    groups: Vec<RuleGroup>,
+}
+
+#[derive(serde::Serialize)]
+struct RuleGroup {
+    name: String,
+    rules: Vec<AlertRule>,
+}
+
+#[derive(serde::Serialize)]
+struct AlertRule {
+    alert: String,
+    expr: String,
+    #[serde(rename = "for")]
+    pending_for: String,
+    labels: BTreeMap<String, String>,
+    annotations: BTreeMap<String, String>,
+}
+
+impl AlertRule {
+    fn new(alert: &str, expr: String, pending_for: &str, severity: &str, summary: &str) -> Self {
+        Self {
+            alert: alert.to_string(),
+            expr,
+            pending_for: pending_for.to_string(),
+            labels: BTreeMap::from([("severity".to_string(), severity.to_string())]),
+            annotations: BTreeMap::from([("summary".to_string(), summary.to_string())]),
+        }
+    }
+}
+
+// build the alerting rules for the nodes scraped by the given job
+pub(crate) fn build_alert_rules(job_name: &str) -> AlertRules {
+    let rules = vec![
+        AlertRule::new(
+            "NodeDown",
+            format!("up{{job=\"{job_name}\"}} == 0"),
+            "1m",
+            "critical",
+            "Node {{ $labels.node_id }} is not responding to metrics scrapes",
+        ),
+        AlertRule::new(
+            "NodeHasNoConnectedPeers",
+            "sn_networking_connected_peers == 0".to_string(),
+            "5m",
+            "critical",
+            "Node {{ $labels.node_id }} has no connected peers",
+        ),
+        AlertRule::new(
+            "RecordStoreNearlyFull",
+            format!(
+                "sn_networking_records_stored / {MAX_RECORDS_COUNT} > {RECORD_STORE_FULL_RATIO}"
+            ),
+            "10m",
+            "warning",
+            "The record store of node {{ $labels.node_id }} is nearly full",
+        ),
+        AlertRule::new(
+            "HighReplicationFailureRate",
+            format!(
+                "rate(sn_networking_replication_fetch_failures_total[10m]) \
+                 / rate(sn_networking_replication_fetches_total[10m]) \
+                 > {REPLICATION_FAILURE_RATIO}"
+            ),
+            "15m",
+            "warning",
+            "Replication is failing for node {{ $labels.node_id }}",
+        ),
+        AlertRule::new(
+            "BadNodesRising",
+            format!("delta(sn_networking_bad_nodes[1h]) >= {BAD_NODES_INCREASE}"),
+            "0m",
+            "warning",
+            "Node {{ $labels.node_id }} has flagged {{ $value }} more peers as bad in the last hour",
+        ),
+    ];
+
+    AlertRules {
+        groups: vec![RuleGroup {
+            name: "safe_network_nodes".to_string(),
+            rules,
+        }],
+    }
+}
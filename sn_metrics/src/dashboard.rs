@@ -0,0 +1,275 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde_json::{json, Value};
+
+const DASHBOARD_UID: &str = "node_metrics";
+const DATASOURCE_UID: &str = "prometheusdatasourceuuid";
+const NODE_FILTER: &str = "node_id=~\"$var_node_list\"";
+const PANEL_WIDTH: u32 = 12;
+const PANEL_HEIGHT: u32 = 8;
+
+/// A time series panel over one of the metrics exported by `sn_networking` or `sn_node`.
+/// The `{filter}` placeholder in the expression is replaced with the node selection.
+struct PanelSpec {
+    title: &'static str,
+    expr: &'static str,
+    legend: &'static str,
+    unit: &'static str,
+}
+
+const PANELS: &[PanelSpec] = &[
+    PanelSpec {
+        title: "Connected Peers",
+        expr: "sn_networking_connected_peers{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Peers in Routing Table",
+        expr: "sn_networking_peers_in_routing_table{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Open Connections",
+        expr: "sn_networking_open_connections{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Estimated Network Size",
+        expr: "sn_networking_estimated_network_size{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Records Stored",
+        expr: "sn_networking_records_stored{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Store Cost",
+        expr: "sn_networking_store_cost{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "PUTs per second",
+        expr: "sum by (node_id, record_type) (rate(sn_node_put_record_ok_total{filter}[$__rate_interval]))",
+        legend: "{{node_id}} {{record_type}}",
+        unit: "ops",
+    },
+    PanelSpec {
+        title: "PUT Errors per second",
+        expr: "rate(sn_node_put_record_err_total{filter}[$__rate_interval])",
+        legend: "{{node_id}}",
+        unit: "ops",
+    },
+    PanelSpec {
+        title: "Replications Triggered per second",
+        expr: "rate(sn_node_replication_triggered_total{filter}[$__rate_interval])",
+        legend: "{{node_id}}",
+        unit: "ops",
+    },
+    PanelSpec {
+        title: "Replication Failures per second",
+        expr: "rate(sn_networking_node_issues_total{filter, issue=\"ReplicationFailure\"}[$__rate_interval])",
+        legend: "{{node_id}}",
+        unit: "ops",
+    },
+    PanelSpec {
+        title: "Bad Nodes",
+        expr: "sn_networking_bad_nodes{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Issues Reported against Peers per second",
+        expr: "sum by (node_id, issue) (rate(sn_networking_node_issues_total{filter}[$__rate_interval]))",
+        legend: "{{node_id}} {{issue}}",
+        unit: "ops",
+    },
+    PanelSpec {
+        title: "Upload Bandwidth",
+        expr: "rate(sn_networking_uploaded_bytes_total{filter}[$__rate_interval])",
+        legend: "{{node_id}}",
+        unit: "Bps",
+    },
+    PanelSpec {
+        title: "Download Bandwidth",
+        expr: "rate(sn_networking_downloaded_bytes_total{filter}[$__rate_interval])",
+        legend: "{{node_id}}",
+        unit: "Bps",
+    },
+    PanelSpec {
+        title: "Reward Balance",
+        expr: "sn_node_current_reward_wallet_balance{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Total Forwarded Rewards",
+        expr: "sn_node_total_forwarded_rewards{filter}",
+        legend: "{{node_id}}",
+        unit: "none",
+    },
+    PanelSpec {
+        title: "Uptime",
+        expr: "sn_node_uptime{filter}",
+        legend: "{{node_id}}",
+        unit: "s",
+    },
+    PanelSpec {
+        title: "Process Memory Usage",
+        expr: "sn_networking_process_memory_used_mb{filter}",
+        legend: "{{node_id}}",
+        unit: "decmbytes",
+    },
+    PanelSpec {
+        title: "Process CPU Usage",
+        expr: "sn_networking_process_cpu_usage_percentage{filter}",
+        legend: "{{node_id}}",
+        unit: "percent",
+    },
+];
+
+// build the Grafana dashboard for the given number of nodes
+pub(crate) fn build_dashboard(node_count: usize) -> Value {
+    let mut panels = vec![nodes_up_panel(node_count)];
+    for (index, spec) in PANELS.iter().enumerate() {
+        let index = index as u32;
+        let grid_pos = json!({
+            "h": PANEL_HEIGHT,
+            "w": PANEL_WIDTH,
+            "x": (index % 2) * PANEL_WIDTH,
+            // the first row is taken by the 'Nodes Up' panel
+            "y": PANEL_HEIGHT / 2 + (index / 2) * PANEL_HEIGHT,
+        });
+        panels.push(timeseries_panel(index + 2, spec, grid_pos));
+    }
+
+    json!({
+        "annotations": {
+            "list": [{
+                "builtIn": 1,
+                "datasource": { "type": "grafana", "uid": "-- Grafana --" },
+                "enable": true,
+                "hide": true,
+                "iconColor": "rgba(0, 211, 255, 1)",
+                "name": "Annotations & Alerts",
+                "type": "dashboard"
+            }]
+        },
+        "editable": true,
+        "graphTooltip": 1,
+        "links": [],
+        "panels": panels,
+        "refresh": "5s",
+        "schemaVersion": 38,
+        "tags": [],
+        "templating": { "list": [node_list_variable()] },
+        "time": { "from": "now-30m", "to": "now" },
+        "timepicker": {},
+        "timezone": "",
+        "title": "Node Metrics",
+        "uid": DASHBOARD_UID,
+        "version": 1
+    })
+}
+
+// A stat panel turning red when fewer nodes than the ones discovered are up
+fn nodes_up_panel(node_count: usize) -> Value {
+    json!({
+        "datasource": datasource(),
+        "fieldConfig": {
+            "defaults": {
+                "color": { "mode": "thresholds" },
+                "thresholds": {
+                    "mode": "absolute",
+                    "steps": [
+                        { "color": "red", "value": null },
+                        { "color": "green", "value": node_count }
+                    ]
+                },
+                "unit": "none"
+            },
+            "overrides": []
+        },
+        "gridPos": { "h": PANEL_HEIGHT / 2, "w": 2 * PANEL_WIDTH, "x": 0, "y": 0 },
+        "id": 1,
+        "options": {
+            "colorMode": "background",
+            "graphMode": "none",
+            "reduceOptions": { "calcs": ["lastNotNull"], "fields": "", "values": false },
+            "textMode": "value_and_name"
+        },
+        "targets": [{
+            "datasource": datasource(),
+            "expr": format!("sum(up{{{NODE_FILTER}}})"),
+            "legendFormat": format!("Nodes Up (of {node_count})"),
+            "refId": "A"
+        }],
+        "title": "Nodes Up",
+        "type": "stat"
+    })
+}
+
+fn timeseries_panel(id: u32, spec: &PanelSpec, grid_pos: Value) -> Value {
+    json!({
+        "datasource": datasource(),
+        "fieldConfig": {
+            "defaults": { "unit": spec.unit },
+            "overrides": []
+        },
+        "gridPos": grid_pos,
+        "id": id,
+        "options": {
+            "legend": { "displayMode": "list", "placement": "bottom", "showLegend": true },
+            "tooltip": { "mode": "multi", "sort": "desc" }
+        },
+        "targets": [{
+            "datasource": datasource(),
+            "expr": spec.expr.replace("{filter", &format!("{{{NODE_FILTER}")),
+            "legendFormat": spec.legend,
+            "refId": "A"
+        }],
+        "title": spec.title,
+        "type": "timeseries"
+    })
+}
+
+fn node_list_variable() -> Value {
+    json!({
+        "current": { "selected": true, "text": ["All"], "value": ["$__all"] },
+        "datasource": datasource(),
+        "definition": "label_values(node_id)",
+        "description": "The list of nodes that we are tracking",
+        "hide": 0,
+        "includeAll": true,
+        "label": "Node List",
+        "multi": true,
+        "name": "var_node_list",
+        "options": [],
+        "query": {
+            "query": "label_values(node_id)",
+            "refId": "PrometheusVariableQueryEditor-VariableQuery"
+        },
+        // refresh the node list whenever the time range changes, to pick up added nodes
+        "refresh": 2,
+        "regex": "",
+        "skipUrlSync": false,
+        "sort": 1,
+        "type": "query"
+    })
+}
+
+fn datasource() -> Value {
+    json!({ "type": "prometheus", "uid": DATASOURCE_UID })
+}
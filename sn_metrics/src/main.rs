@@ -6,6 +6,9 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod alerts;
+mod dashboard;
+
 use clap::{command, Arg, ArgAction};
 use color_eyre::{eyre::eyre, Result};
 use regex::Regex;
@@ -15,15 +18,22 @@ use std::{
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
     process::Command,
+    thread,
+    time::Duration,
 };
 use walkdir::WalkDir;
 
 const LOG_FILENAME_PREFIX: &str = "safenode.log";
+const JOB_NAME: &str = "safe_network_testnet";
+const ALERT_RULES_FILENAME: &str = "alert_rules.yml";
+const TARGETS_FILENAME: &str = "targets.json";
+const WATCH_INTERVAL: Duration = Duration::from_secs(30);
 type NodeId = String;
 
 #[derive(serde::Serialize)]
 struct PrometheusConfig {
     global: Global,
+    rule_files: Vec<String>,
     scrape_configs: Vec<ScrapeConfigs>,
 }
 
@@ -38,7 +48,15 @@ struct ScrapeConfigs {
     job_name: String,
     // Override the global default
     scrape_interval: String,
-    static_configs: Vec<StaticConfig>,
+    // The targets are read from a file, so that Prometheus picks up added or removed nodes
+    // without a restart
+    file_sd_configs: Vec<FileSdConfig>,
+}
+
+#[derive(serde::Serialize)]
+struct FileSdConfig {
+    files: Vec<String>,
+    refresh_interval: String,
 }
 
 #[derive(serde::Serialize)]
@@ -66,6 +84,13 @@ fn main() -> Result<()> {
                 .help("Runs the docker containers for you")
                 .action(ArgAction::SetTrue)
             )
+        .arg(
+            Arg::new("watch")
+                .short('w')
+                .long("watch")
+                .help("Keeps scanning the log directories, updating the scrape targets and the dashboard as nodes are added or removed")
+                .action(ArgAction::SetTrue)
+            )
         .arg(
             Arg::new("log_dirs")
                 .help("Provide one or more log directories to get the metrics server from.\nAll the files inside a provided dir are scanned.")
@@ -76,27 +101,28 @@ fn main() -> Result<()> {
         .get_matches();
 
     let should_run_containers = matches.get_flag("run");
-    let mut metrics_server_list = BTreeMap::<NodeId, url::Url>::new();
-    for log_dir in matches.get_many::<PathBuf>("log_dirs").unwrap() {
-        metrics_server_list.extend(get_metric_servers(log_dir)?);
-    }
+    let should_watch = matches.get_flag("watch");
+    let log_dirs: Vec<PathBuf> = matches
+        .get_many::<PathBuf>("log_dirs")
+        .ok_or_else(|| eyre!("No log directory was provided"))?
+        .cloned()
+        .collect();
 
-    if metrics_server_list.is_empty() {
+    let metrics_server_list = get_all_metric_servers(&log_dirs)?;
+    // nodes can still be started while watching
+    if metrics_server_list.is_empty() && !should_watch {
         return Err(eyre!("Could not find any metrics server. Aborting!"));
     }
 
-    println!(
-        "Collecting metrics from {} nodes",
-        metrics_server_list.len()
-    );
-    let prometheus_config = build_prometheus_config(metrics_server_list);
-    let prometheus_config = serde_yaml::to_string(&prometheus_config)?;
-
     let working_dir = get_working_dir()?;
-    // write prometheus config
+    // write prometheus config and alerting rules
     let prometheus_dir = working_dir.join("prometheus");
     fs::create_dir_all(&prometheus_dir)?;
+    let prometheus_config = serde_yaml::to_string(&build_prometheus_config())?;
     fs::write(prometheus_dir.join("prometheus.yml"), prometheus_config)?;
+    let alert_rules = serde_yaml::to_string(&alerts::build_alert_rules(JOB_NAME))?;
+    fs::write(prometheus_dir.join(ALERT_RULES_FILENAME), alert_rules)?;
+    write_targets_and_dashboard(&working_dir, &metrics_server_list)?;
 
     if should_run_containers {
         // stop the containers if running already
@@ -131,6 +157,65 @@ fn main() -> Result<()> {
         println!("The Prometheus config file has been updated with the metrics server URLs. The containers are not yet started\nRead the docs to start/stop the containers.");
     }
 
+    if should_watch {
+        watch(&log_dirs, &working_dir, metrics_server_list)?;
+    }
+
+    Ok(())
+}
+
+// Rescan the log directories periodically and rewrite the targets and dashboard when the set of
+// nodes changes. Prometheus and Grafana reload them by themselves.
+fn watch(
+    log_dirs: &[PathBuf],
+    working_dir: &Path,
+    mut metrics_server_list: BTreeMap<NodeId, url::Url>,
+) -> Result<()> {
+    println!(
+        "Watching for added or removed nodes every {}s. Press Ctrl-C to stop.",
+        WATCH_INTERVAL.as_secs()
+    );
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let latest = get_all_metric_servers(log_dirs)?;
+        if latest != metrics_server_list {
+            write_targets_and_dashboard(working_dir, &latest)?;
+            metrics_server_list = latest;
+        }
+    }
+}
+
+fn get_all_metric_servers(log_dirs: &[PathBuf]) -> Result<BTreeMap<NodeId, url::Url>> {
+    let mut metrics_server_list = BTreeMap::<NodeId, url::Url>::new();
+    for log_dir in log_dirs {
+        metrics_server_list.extend(get_metric_servers(log_dir)?);
+    }
+    Ok(metrics_server_list)
+}
+
+// Write the scrape targets for Prometheus and the Grafana dashboard for the given nodes
+fn write_targets_and_dashboard(
+    working_dir: &Path,
+    metrics_server_list: &BTreeMap<NodeId, url::Url>,
+) -> Result<()> {
+    println!(
+        "Collecting metrics from {} nodes",
+        metrics_server_list.len()
+    );
+    let targets = serde_json::to_string_pretty(&build_targets(metrics_server_list)?)?;
+    fs::write(
+        working_dir.join("prometheus").join(TARGETS_FILENAME),
+        targets,
+    )?;
+
+    let dashboards_dir = working_dir
+        .join("grafana")
+        .join("provisioning")
+        .join("dashboards");
+    fs::create_dir_all(&dashboards_dir)?;
+    let dashboard =
+        serde_json::to_string_pretty(&dashboard::build_dashboard(metrics_server_list.len()))?;
+    fs::write(dashboards_dir.join("safe-network.json"), dashboard)?;
     Ok(())
 }
 
@@ -193,30 +278,39 @@ fn get_metric_servers(path: &Path) -> Result<BTreeMap<NodeId, url::Url>> {
     Ok(urls)
 }
 
-// build the prometheus config given the NodeId and the metrics server url
-fn build_prometheus_config(metrics_server_list: BTreeMap<NodeId, url::Url>) -> PrometheusConfig {
-    let static_configs = metrics_server_list
-        .into_iter()
-        .map(|(node_id, url)| StaticConfig {
-            targets: vec![format!(
-                "host.docker.internal:{}",
-                url.port()
-                    .expect("Port should be present for the metrics server")
-            )],
-            labels: Labels {
-                node_id: last_n_chars(&node_id, 4),
-            },
+// build the scrape targets given the NodeId and the metrics server url
+fn build_targets(metrics_server_list: &BTreeMap<NodeId, url::Url>) -> Result<Vec<StaticConfig>> {
+    metrics_server_list
+        .iter()
+        .map(|(node_id, url)| {
+            let port = url
+                .port()
+                .ok_or_else(|| eyre!("Port should be present for the metrics server {url}"))?;
+            Ok(StaticConfig {
+                targets: vec![format!("host.docker.internal:{port}")],
+                labels: Labels {
+                    node_id: last_n_chars(node_id, 4),
+                },
+            })
         })
-        .collect();
+        .collect()
+}
+
+// build the prometheus config, which reads the scrape targets and alerting rules from files
+fn build_prometheus_config() -> PrometheusConfig {
     PrometheusConfig {
         global: Global {
             scrape_interval: "15s".to_string(),
             evaluation_interval: "15s".to_string(),
         },
+        rule_files: vec![ALERT_RULES_FILENAME.to_string()],
         scrape_configs: vec![ScrapeConfigs {
-            job_name: "safe_network_testnet".to_string(),
+            job_name: JOB_NAME.to_string(),
             scrape_interval: "5s".to_string(),
-            static_configs,
+            file_sd_configs: vec![FileSdConfig {
+                files: vec![TARGETS_FILENAME.to_string()],
+                refresh_interval: "30s".to_string(),
+            }],
         }],
     }
 }
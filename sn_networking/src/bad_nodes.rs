@@ -0,0 +1,500 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{cmd::NodeIssue, target_arch::Instant};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// File under the node's root dir where the reputation of the peers is persisted
+const BAD_NODES_FILENAME: &str = "bad_nodes";
+
+/// Max number of evidences we keep per peer, to avoid mem leaks
+const MAX_EVIDENCES_PER_PEER: usize = 10;
+
+/// To avoid being too sensitive, an issue is only counted
+/// when after certain while since the last one of that peer
+const MIN_INTERVAL_BETWEEN_ISSUES: Duration = Duration::from_secs(10);
+
+/// A peer is considered as bad once the score of any kind of issue reaches this value,
+/// i.e. roughly three issues of the same kind within a short period
+const BAD_SCORE: f64 = 2.5;
+
+/// A bad peer is only considered as recovered once the scores of all its issues decayed below this value
+const RECOVERED_SCORE: f64 = 1.0;
+
+/// Peers with all their scores below this value and not being bad are forgotten
+const FORGOTTEN_SCORE: f64 = 0.01;
+
+impl NodeIssue {
+    /// The time it takes for the score of this kind of issue to decay by half.
+    /// Transient issues decay fast so honest nodes having an outage can recover,
+    /// misbehaviours decay slowly so bad nodes stay shunned.
+    fn half_life(&self) -> Duration {
+        match self {
+            NodeIssue::ConnectionIssue => Duration::from_secs(5 * 60),
+            NodeIssue::ReplicationFailure => Duration::from_secs(15 * 60),
+            NodeIssue::CloseNodesShunning => Duration::from_secs(60 * 60),
+            // A bad quote can be an honest node with a skewed clock or a stale view of the
+            // network, so a peer that just turned bad for it recovers within an hour.
+            NodeIssue::BadQuoting => Duration::from_secs(30 * 60),
+            NodeIssue::FailedChunkProofCheck => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Score of one kind of issue, decaying over time since it was last updated
+#[derive(Debug, Clone, Copy)]
+struct IssueScore {
+    score: f64,
+    updated: Instant,
+}
+
+impl IssueScore {
+    fn decayed(&self, issue: &NodeIssue, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let half_lives = elapsed.as_secs_f64() / issue.half_life().as_secs_f64();
+        self.score * 0.5f64.powf(half_lives)
+    }
+}
+
+/// An issue reported against a peer, along with the details backing it
+#[derive(Debug, Clone)]
+pub struct IssueEvidence {
+    pub issue: NodeIssue,
+    /// e.g. the nonce of a failed chunk proof or the offending quote
+    pub details: String,
+    /// How long ago the issue was reported
+    pub age: Duration,
+}
+
+/// Snapshot of the reputation of a peer, to be inspected over the node RPC
+#[derive(Debug, Clone)]
+pub struct PeerReputation {
+    pub peer_id: PeerId,
+    pub is_bad: bool,
+    /// Current decayed score per kind of issue
+    pub scores: BTreeMap<NodeIssue, f64>,
+    /// Most recent evidences, the oldest first
+    pub evidences: Vec<IssueEvidence>,
+}
+
+#[derive(Debug, Default)]
+struct PeerRecord {
+    scores: BTreeMap<NodeIssue, IssueScore>,
+    evidences: VecDeque<(NodeIssue, String, Instant)>,
+    last_issue: Option<Instant>,
+    is_bad: bool,
+}
+
+impl PeerRecord {
+    fn max_score(&self, now: Instant) -> f64 {
+        self.scores
+            .iter()
+            .map(|(issue, score)| score.decayed(issue, now))
+            .fold(0.0, f64::max)
+    }
+
+    /// A bad peer stays bad until all its scores decayed below `RECOVERED_SCORE`
+    fn is_bad(&self, now: Instant) -> bool {
+        self.is_bad && self.max_score(now) >= RECOVERED_SCORE
+    }
+}
+
+/// The reputation of the peers, scoring each kind of `NodeIssue` with time decay.
+/// For nodes, it is persisted under the root dir so bad nodes stay shunned across restarts.
+#[derive(Debug, Default)]
+pub(crate) struct BadNodes {
+    peers: BTreeMap<PeerId, PeerRecord>,
+    /// Where to persist the reputations, `None` for clients
+    file_path: Option<PathBuf>,
+    /// Whether the reputations changed since they were last persisted
+    changed: bool,
+    /// Set while a write is ongoing, so the writes never race with each other
+    flushing: Arc<AtomicBool>,
+    /// Set when the last write failed, so it is retried on the next flush
+    flush_failed: Arc<AtomicBool>,
+}
+
+impl BadNodes {
+    /// Create the reputations persisted under the provided dir, restoring the existing ones if any
+    pub(crate) fn new_persisted(root_dir: PathBuf) -> Self {
+        let file_path = root_dir.join(BAD_NODES_FILENAME);
+        let peers = Self::restore(&file_path).unwrap_or_default();
+        info!(
+            "Restored the reputation of {} peers from {file_path:?}",
+            peers.len()
+        );
+        Self {
+            peers,
+            file_path: Some(file_path),
+            changed: false,
+            flushing: Default::default(),
+            flush_failed: Default::default(),
+        }
+    }
+
+    /// Returns true if the peer is currently considered as bad
+    pub(crate) fn is_bad(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .is_some_and(|record| record.is_bad(Instant::now()))
+    }
+
+    /// The number of peers currently considered as bad
+    #[cfg(feature = "open-metrics")]
+    pub(crate) fn bad_count(&self) -> usize {
+        let now = Instant::now();
+        self.peers
+            .values()
+            .filter(|record| record.is_bad(now))
+            .count()
+    }
+
+    /// Record an issue against a peer along with its evidence.
+    /// Returns the kind of issue the peer is newly considered as bad for, if any.
+    /// The changes are persisted on the next `flush`.
+    pub(crate) fn record_issue(
+        &mut self,
+        peer_id: PeerId,
+        issue: NodeIssue,
+        details: Option<String>,
+    ) -> Option<NodeIssue> {
+        self.record_issue_at(peer_id, issue, details, Instant::now())
+    }
+
+    fn record_issue_at(
+        &mut self,
+        peer_id: PeerId,
+        issue: NodeIssue,
+        details: Option<String>,
+        now: Instant,
+    ) -> Option<NodeIssue> {
+        let record = self.peers.entry(peer_id).or_default();
+
+        if let Some(details) = details {
+            if record.evidences.len() >= MAX_EVIDENCES_PER_PEER {
+                let _ = record.evidences.pop_front();
+            }
+            record.evidences.push_back((issue, details, now));
+            self.changed = true;
+        }
+
+        let is_new_issue = match record.last_issue {
+            Some(last) => now.saturating_duration_since(last) > MIN_INTERVAL_BETWEEN_ISSUES,
+            None => true,
+        };
+        if !is_new_issue {
+            return None;
+        }
+        record.last_issue = Some(now);
+        self.changed = true;
+
+        // a previously bad peer that had recovered gets a clean slate
+        let was_bad = record.is_bad(now);
+        record.is_bad = was_bad;
+
+        let current = record
+            .scores
+            .get(&issue)
+            .map(|score| score.decayed(&issue, now))
+            .unwrap_or(0.0);
+        let score = current + 1.0;
+        let _ = record.scores.insert(
+            issue,
+            IssueScore {
+                score,
+                updated: now,
+            },
+        );
+
+        if !was_bad && score >= BAD_SCORE {
+            record.is_bad = true;
+            info!("Peer {peer_id:?} accumulated a score of {score:.2} for issue {issue:?}. Consider it as a bad node now.");
+            return Some(issue);
+        }
+        None
+    }
+
+    /// Current reputation of all the tracked peers
+    pub(crate) fn reputations(&self) -> Vec<PeerReputation> {
+        let now = Instant::now();
+        self.peers
+            .iter()
+            .map(|(peer_id, record)| PeerReputation {
+                peer_id: *peer_id,
+                is_bad: record.is_bad(now),
+                scores: record
+                    .scores
+                    .iter()
+                    .map(|(issue, score)| (*issue, score.decayed(issue, now)))
+                    .collect(),
+                evidences: record
+                    .evidences
+                    .iter()
+                    .map(|(issue, details, reported)| IssueEvidence {
+                        issue: *issue,
+                        details: details.clone(),
+                        age: now.saturating_duration_since(*reported),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Forget the peers whose issues have all decayed away
+    fn prune(&mut self, now: Instant) {
+        self.peers
+            .retain(|_, record| record.is_bad(now) || record.max_score(now) >= FORGOTTEN_SCORE);
+    }
+
+    /// Persist the reputations if they changed since the last time, and if we are a node.
+    /// The file is replaced atomically, so a crash mid-write never loses the previous reputations.
+    pub(crate) fn flush(&mut self) {
+        let Some(file_path) = self.file_path.clone() else {
+            return;
+        };
+        // try again on the next flush, once the ongoing write is done
+        if self.flushing.load(Ordering::Acquire) {
+            return;
+        }
+        if self.flush_failed.swap(false, Ordering::AcqRel) {
+            self.changed = true;
+        }
+        if !self.changed {
+            return;
+        }
+        self.flushing.store(true, Ordering::Release);
+        self.changed = false;
+        let now = Instant::now();
+        self.prune(now);
+
+        let wall_now = SystemTime::now();
+        let to_wall_time = |instant: Instant| wall_now - now.saturating_duration_since(instant);
+        let persisted: Vec<PersistedPeer> = self
+            .peers
+            .iter()
+            .map(|(peer_id, record)| PersistedPeer {
+                peer_id: peer_id.to_string(),
+                is_bad: record.is_bad(now),
+                scores: record
+                    .scores
+                    .iter()
+                    .map(|(issue, score)| (*issue, score.score, to_wall_time(score.updated)))
+                    .collect(),
+                evidences: record
+                    .evidences
+                    .iter()
+                    .map(|(issue, details, reported)| {
+                        (*issue, details.clone(), to_wall_time(*reported))
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let flushing = Arc::clone(&self.flushing);
+        let flush_failed = Arc::clone(&self.flush_failed);
+        // the writes are blocking, keep them off the runtime driving the swarm
+        let _handle = tokio::task::spawn_blocking(move || {
+            match rmp_serde::to_vec(&persisted) {
+                Ok(bytes) => {
+                    let tmp_path = file_path.with_extension("tmp");
+                    if let Err(err) =
+                        fs::write(&tmp_path, bytes).and_then(|_| fs::rename(&tmp_path, &file_path))
+                    {
+                        warn!("Failed to persist the bad nodes to {file_path:?}, retrying on the next flush: {err:?}");
+                        flush_failed.store(true, Ordering::Release);
+                    }
+                }
+                Err(err) => warn!("Failed to serialize the bad nodes: {err:?}"),
+            }
+            flushing.store(false, Ordering::Release);
+        });
+    }
+
+    fn restore(file_path: &PathBuf) -> Option<BTreeMap<PeerId, PeerRecord>> {
+        let bytes = fs::read(file_path).ok()?;
+        let persisted: Vec<PersistedPeer> = match rmp_serde::from_slice(&bytes) {
+            Ok(persisted) => persisted,
+            Err(err) => {
+                warn!("Failed to deserialize the bad nodes from {file_path:?}: {err:?}");
+                return None;
+            }
+        };
+
+        let now = Instant::now();
+        let wall_now = SystemTime::now();
+        let age = |time: SystemTime| wall_now.duration_since(time).unwrap_or_default();
+        let to_instant = |time: SystemTime| now.checked_sub(age(time)).unwrap_or(now);
+
+        let mut peers = BTreeMap::new();
+        for peer in persisted {
+            let Ok(peer_id) = PeerId::from_str(&peer.peer_id) else {
+                warn!(
+                    "Skipping invalid peer id {:?} in the bad nodes",
+                    peer.peer_id
+                );
+                continue;
+            };
+            // The decay since the last update is applied right away,
+            // as `Instant`s prior to the process start are not always representable.
+            let scores = peer
+                .scores
+                .into_iter()
+                .map(|(issue, score, updated)| {
+                    let half_lives = age(updated).as_secs_f64() / issue.half_life().as_secs_f64();
+                    let decayed = IssueScore {
+                        score: score * 0.5f64.powf(half_lives),
+                        updated: now,
+                    };
+                    (issue, decayed)
+                })
+                .collect();
+            let evidences = peer
+                .evidences
+                .into_iter()
+                .map(|(issue, details, reported)| (issue, details, to_instant(reported)))
+                .collect();
+            let record = PeerRecord {
+                scores,
+                evidences,
+                last_issue: None,
+                is_bad: peer.is_bad,
+            };
+            let _ = peers.insert(peer_id, record);
+        }
+        Some(peers)
+    }
+}
+
+/// On disk format of the reputation of a peer, using wall clock times to survive restarts
+#[derive(Serialize, Deserialize)]
+struct PersistedPeer {
+    peer_id: String,
+    is_bad: bool,
+    scores: Vec<(NodeIssue, f64, SystemTime)>,
+    evidences: Vec<(NodeIssue, String, SystemTime)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue_at(bad_nodes: &mut BadNodes, peer_id: PeerId, issue: NodeIssue, now: Instant) {
+        let _ = bad_nodes.record_issue_at(peer_id, issue, None, now);
+    }
+
+    #[test]
+    fn peer_becomes_bad_after_repeated_issues() {
+        let mut bad_nodes = BadNodes::default();
+        let peer_id = PeerId::random();
+        let start = Instant::now();
+
+        issue_at(&mut bad_nodes, peer_id, NodeIssue::BadQuoting, start);
+        // issues too close to the previous one are not counted
+        issue_at(&mut bad_nodes, peer_id, NodeIssue::BadQuoting, start);
+        issue_at(
+            &mut bad_nodes,
+            peer_id,
+            NodeIssue::BadQuoting,
+            start + Duration::from_secs(20),
+        );
+        assert!(!bad_nodes.is_bad(&peer_id));
+
+        let newly_bad = bad_nodes.record_issue_at(
+            peer_id,
+            NodeIssue::BadQuoting,
+            Some("quote".to_string()),
+            start + Duration::from_secs(40),
+        );
+        assert_eq!(newly_bad, Some(NodeIssue::BadQuoting));
+        assert!(bad_nodes.is_bad(&peer_id));
+        assert_eq!(bad_nodes.reputations()[0].evidences.len(), 1);
+    }
+
+    #[test]
+    fn only_counted_issues_need_flushing() {
+        let mut bad_nodes = BadNodes::default();
+        let peer_id = PeerId::random();
+        let start = Instant::now();
+
+        issue_at(&mut bad_nodes, peer_id, NodeIssue::ConnectionIssue, start);
+        assert!(bad_nodes.changed);
+        bad_nodes.changed = false;
+
+        // dropped for being too close to the previous one
+        issue_at(&mut bad_nodes, peer_id, NodeIssue::ConnectionIssue, start);
+        assert!(!bad_nodes.changed);
+    }
+
+    #[test]
+    fn connection_issues_decay_and_peer_recovers() {
+        let mut bad_nodes = BadNodes::default();
+        let peer_id = PeerId::random();
+        let start = Instant::now();
+
+        for i in 0..3 {
+            issue_at(
+                &mut bad_nodes,
+                peer_id,
+                NodeIssue::ConnectionIssue,
+                start + Duration::from_secs(20 * i),
+            );
+        }
+        let record = bad_nodes.peers.get(&peer_id).expect("peer to be tracked");
+        assert!(record.is_bad(start + Duration::from_secs(60)));
+        // after a few half lives the transient issues are forgiven
+        assert!(!record.is_bad(start + Duration::from_secs(30 * 60)));
+
+        // while misbehaviours are still remembered
+        for i in 0..3 {
+            issue_at(
+                &mut bad_nodes,
+                peer_id,
+                NodeIssue::FailedChunkProofCheck,
+                start + Duration::from_secs(3600 + 20 * i),
+            );
+        }
+        let record = bad_nodes.peers.get(&peer_id).expect("peer to be tracked");
+        assert!(record.is_bad(start + Duration::from_secs(2 * 3600)));
+    }
+
+    #[tokio::test]
+    async fn failed_flush_is_retried() {
+        let root_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        // the root dir does not exist yet, so the first write fails
+        let mut bad_nodes = BadNodes::new_persisted(root_dir.clone());
+        let _ = bad_nodes.record_issue(PeerId::random(), NodeIssue::ConnectionIssue, None);
+
+        bad_nodes.flush();
+        while bad_nodes.flushing.load(Ordering::Acquire) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!root_dir.join(BAD_NODES_FILENAME).exists());
+
+        fs::create_dir_all(&root_dir).expect("Failed to create directory");
+        bad_nodes.flush();
+        while bad_nodes.flushing.load(Ordering::Acquire) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let restored = BadNodes::new_persisted(root_dir.clone());
+        assert_eq!(restored.peers.len(), 1);
+
+        let _ = fs::remove_dir_all(&root_dir);
+    }
+}
@@ -0,0 +1,476 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Bandwidth limiting of the connections, applied to every substream of the muxed connections,
+//! hence covering Kademlia, req/resp and all the other protocols alike.
+
+use crate::target_arch::Instant;
+use futures::{ready, AsyncRead, AsyncWrite, Future};
+use libp2p::{
+    core::{
+        muxing::{StreamMuxer, StreamMuxerBox, StreamMuxerEvent, SubstreamBox},
+        transport::Boxed,
+    },
+    PeerId, Transport,
+};
+#[cfg(feature = "open-metrics")]
+use prometheus_client::metrics::counter::Counter;
+use std::{
+    collections::HashMap,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll},
+};
+use tokio::time::{sleep, Duration, Sleep};
+
+/// Below this share of the download budget left, the replication fetches are slowed down
+/// to leave room for the client GETs.
+const REPLICATION_HEADROOM_THRESHOLD: f64 = 0.5;
+
+/// Bandwidth limits of a node, in bytes per second. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BandwidthLimits {
+    /// Limit of the total upload rate
+    pub upload: Option<u64>,
+    /// Limit of the total download rate
+    pub download: Option<u64>,
+    /// Limit of the upload and of the download rates with each peer
+    pub per_peer: Option<u64>,
+}
+
+impl BandwidthLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.upload.is_none() && self.download.is_none() && self.per_peer.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Upload,
+    Download,
+}
+
+/// A classic token bucket, allowing bursts of up to one second worth of traffic.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    /// The bytes that can be transferred right now
+    fn available(&mut self) -> usize {
+        self.refill();
+        self.tokens.max(0.0) as usize
+    }
+
+    /// The delay until some bytes can be transferred again
+    fn time_to_refill(&self) -> Duration {
+        // Wait for a few KBs at once rather than being woken up for every byte.
+        let wanted = (self.rate / 100.0).max(1.0) - self.tokens;
+        Duration::from_secs_f64(wanted.max(0.0) / self.rate)
+    }
+
+    /// Tokens may go below zero when several substreams race for the same bucket,
+    /// which is then paid back by waiting longer.
+    fn consume(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+
+    /// Share of the burst budget left
+    fn headroom(&mut self) -> f64 {
+        self.refill();
+        (self.tokens / self.rate).clamp(0.0, 1.0)
+    }
+}
+
+/// The upload and download buckets of one peer, shared by all the connections with that peer
+#[derive(Debug)]
+pub(crate) struct PeerQuota {
+    upload: Option<Mutex<TokenBucket>>,
+    download: Option<Mutex<TokenBucket>>,
+}
+
+impl PeerQuota {
+    fn bucket(&self, direction: Direction) -> Option<&Mutex<TokenBucket>> {
+        match direction {
+            Direction::Upload => self.upload.as_ref(),
+            Direction::Download => self.download.as_ref(),
+        }
+    }
+}
+
+/// Enforces the `BandwidthLimits` and keeps track of the bandwidth used.
+#[derive(Debug)]
+pub(crate) struct BandwidthLimiter {
+    limits: BandwidthLimits,
+    upload: Option<Mutex<TokenBucket>>,
+    download: Option<Mutex<TokenBucket>>,
+    peers: Mutex<HashMap<PeerId, Weak<PeerQuota>>>,
+    #[cfg(feature = "open-metrics")]
+    usage_metrics: Option<BandwidthUsageMetrics>,
+}
+
+#[cfg(feature = "open-metrics")]
+#[derive(Debug, Clone)]
+pub(crate) struct BandwidthUsageMetrics {
+    pub(crate) uploaded_bytes: Counter,
+    pub(crate) downloaded_bytes: Counter,
+    pub(crate) throttled: Counter,
+}
+
+impl BandwidthLimiter {
+    pub(crate) fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            limits,
+            upload: limits.upload.map(|rate| Mutex::new(TokenBucket::new(rate))),
+            download: limits
+                .download
+                .map(|rate| Mutex::new(TokenBucket::new(rate))),
+            peers: Default::default(),
+            #[cfg(feature = "open-metrics")]
+            usage_metrics: None,
+        }
+    }
+
+    /// Whether the connections have to be wrapped, to enforce the limits or to record the bandwidth used
+    pub(crate) fn is_needed(&self) -> bool {
+        #[cfg(feature = "open-metrics")]
+        if self.usage_metrics.is_some() {
+            return true;
+        }
+        !self.limits.is_unlimited()
+    }
+
+    #[cfg(feature = "open-metrics")]
+    /// Set the metrics recording the bandwidth used
+    pub(crate) fn set_usage_metrics(mut self, usage_metrics: BandwidthUsageMetrics) -> Self {
+        self.usage_metrics = Some(usage_metrics);
+        self
+    }
+
+    /// The quota shared by all the connections with the peer
+    fn quota_of(&self, peer_id: PeerId) -> Arc<PeerQuota> {
+        let mut peers = self.peers.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(quota) = peers.get(&peer_id).and_then(Weak::upgrade) {
+            return quota;
+        }
+        // Forget about the peers we are no longer connected to.
+        peers.retain(|_, quota| quota.strong_count() > 0);
+
+        let quota = Arc::new(PeerQuota {
+            upload: self
+                .limits
+                .per_peer
+                .map(|rate| Mutex::new(TokenBucket::new(rate))),
+            download: self
+                .limits
+                .per_peer
+                .map(|rate| Mutex::new(TokenBucket::new(rate))),
+        });
+        let _ = peers.insert(peer_id, Arc::downgrade(&quota));
+        quota
+    }
+
+    /// Share of the download budget currently left, from 0.0 to 1.0. Always 1.0 when unlimited.
+    pub(crate) fn download_headroom(&self) -> f64 {
+        self.download.as_ref().map_or(1.0, |bucket| {
+            bucket
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .headroom()
+        })
+    }
+
+    /// Returns whether the replication shall be slowed down to leave room for the client GETs
+    pub(crate) fn is_download_congested(&self) -> bool {
+        self.download_headroom() < REPLICATION_HEADROOM_THRESHOLD
+    }
+
+    fn global_bucket(&self, direction: Direction) -> Option<&Mutex<TokenBucket>> {
+        match direction {
+            Direction::Upload => self.upload.as_ref(),
+            Direction::Download => self.download.as_ref(),
+        }
+    }
+
+    /// The bytes that can be transferred right now, or the delay before trying again
+    fn allowance(&self, quota: &PeerQuota, direction: Direction, wanted: usize) -> Allowance {
+        let mut allowed = wanted;
+        let mut wait = Duration::ZERO;
+        for bucket in [self.global_bucket(direction), quota.bucket(direction)]
+            .into_iter()
+            .flatten()
+        {
+            let mut bucket = bucket.lock().unwrap_or_else(|err| err.into_inner());
+            let available = bucket.available();
+            if available == 0 {
+                wait = wait.max(bucket.time_to_refill());
+            }
+            allowed = allowed.min(available);
+        }
+
+        if allowed == 0 && wanted > 0 {
+            #[cfg(feature = "open-metrics")]
+            if let Some(metrics) = &self.usage_metrics {
+                let _ = metrics.throttled.inc();
+            }
+            Allowance::Wait(wait)
+        } else {
+            Allowance::Bytes(allowed)
+        }
+    }
+
+    fn record(&self, quota: &PeerQuota, direction: Direction, bytes: usize) {
+        for bucket in [self.global_bucket(direction), quota.bucket(direction)]
+            .into_iter()
+            .flatten()
+        {
+            bucket
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .consume(bytes);
+        }
+
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.usage_metrics {
+            let counter = match direction {
+                Direction::Upload => &metrics.uploaded_bytes,
+                Direction::Download => &metrics.downloaded_bytes,
+            };
+            let _ = counter.inc_by(bytes as u64);
+        }
+    }
+}
+
+enum Allowance {
+    Bytes(usize),
+    Wait(Duration),
+}
+
+/// Wraps the muxer of every connection established by the transport to enforce the limits.
+pub(crate) fn throttle_transport(
+    transport: Boxed<(PeerId, StreamMuxerBox)>,
+    limiter: Arc<BandwidthLimiter>,
+) -> Boxed<(PeerId, StreamMuxerBox)> {
+    transport
+        .map(move |(peer_id, muxer), _| {
+            let quota = limiter.quota_of(peer_id);
+            let muxer = ThrottledMuxer {
+                inner: muxer,
+                limiter: Arc::clone(&limiter),
+                quota,
+            };
+            (peer_id, StreamMuxerBox::new(muxer))
+        })
+        .boxed()
+}
+
+struct ThrottledMuxer {
+    inner: StreamMuxerBox,
+    limiter: Arc<BandwidthLimiter>,
+    quota: Arc<PeerQuota>,
+}
+
+impl ThrottledMuxer {
+    fn throttle(&self, substream: SubstreamBox) -> ThrottledSubstream {
+        ThrottledSubstream {
+            inner: substream,
+            limiter: Arc::clone(&self.limiter),
+            quota: Arc::clone(&self.quota),
+            read_delay: None,
+            write_delay: None,
+        }
+    }
+}
+
+impl StreamMuxer for ThrottledMuxer {
+    type Substream = ThrottledSubstream;
+    type Error = io::Error;
+
+    fn poll_inbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let this = self.get_mut();
+        let substream = ready!(Pin::new(&mut this.inner).poll_inbound(cx))?;
+        Poll::Ready(Ok(this.throttle(substream)))
+    }
+
+    fn poll_outbound(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let this = self.get_mut();
+        let substream = ready!(Pin::new(&mut this.inner).poll_outbound(cx))?;
+        Poll::Ready(Ok(this.throttle(substream)))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+
+    fn poll(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        Pin::new(&mut self.get_mut().inner).poll(cx)
+    }
+}
+
+struct ThrottledSubstream {
+    inner: SubstreamBox,
+    limiter: Arc<BandwidthLimiter>,
+    quota: Arc<PeerQuota>,
+    read_delay: Option<Pin<Box<Sleep>>>,
+    write_delay: Option<Pin<Box<Sleep>>>,
+}
+
+/// Waits for the allowance of the direction, returning the bytes that can be transferred
+fn poll_allowance(
+    cx: &mut Context<'_>,
+    delay: &mut Option<Pin<Box<Sleep>>>,
+    limiter: &BandwidthLimiter,
+    quota: &PeerQuota,
+    direction: Direction,
+    wanted: usize,
+) -> Poll<usize> {
+    loop {
+        if let Some(sleeping) = delay.as_mut() {
+            ready!(sleeping.as_mut().poll(cx));
+            *delay = None;
+        }
+        match limiter.allowance(quota, direction, wanted) {
+            Allowance::Bytes(allowed) => return Poll::Ready(allowed),
+            Allowance::Wait(wait) => *delay = Some(Box::pin(sleep(wait))),
+        }
+    }
+}
+
+impl AsyncRead for ThrottledSubstream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let allowed = ready!(poll_allowance(
+            cx,
+            &mut this.read_delay,
+            &this.limiter,
+            &this.quota,
+            Direction::Download,
+            buf.len(),
+        ));
+        let read = ready!(Pin::new(&mut this.inner).poll_read(cx, &mut buf[..allowed]))?;
+        this.limiter.record(&this.quota, Direction::Download, read);
+        Poll::Ready(Ok(read))
+    }
+}
+
+impl AsyncWrite for ThrottledSubstream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let allowed = ready!(poll_allowance(
+            cx,
+            &mut this.write_delay,
+            &this.limiter,
+            &this.quota,
+            Direction::Upload,
+            buf.len(),
+        ));
+        let written = ready!(Pin::new(&mut this.inner).poll_write(cx, &buf[..allowed]))?;
+        this.limiter.record(&this.quota, Direction::Upload, written);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_limits_the_rate() {
+        let mut bucket = TokenBucket::new(1000);
+        assert_eq!(bucket.available(), 1000);
+
+        bucket.consume(1500);
+        assert_eq!(bucket.available(), 0);
+        assert!(bucket.headroom() < 0.01);
+        // waiting for the debt to be paid back, plus a 10ms worth of traffic
+        assert!(bucket.time_to_refill() >= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn allowance_is_the_lowest_of_global_and_peer_limits() {
+        let limiter = BandwidthLimiter::new(BandwidthLimits {
+            upload: Some(10_000),
+            download: None,
+            per_peer: Some(1_000),
+        });
+        let peer = PeerId::random();
+        let quota = limiter.quota_of(peer);
+
+        assert!(matches!(
+            limiter.allowance(&quota, Direction::Upload, 5_000),
+            Allowance::Bytes(1_000)
+        ));
+        limiter.record(&quota, Direction::Upload, 1_000);
+        assert!(matches!(
+            limiter.allowance(&quota, Direction::Upload, 5_000),
+            Allowance::Wait(_)
+        ));
+
+        // Another peer still has its own quota, within the global limit
+        let other_quota = limiter.quota_of(PeerId::random());
+        assert!(matches!(
+            limiter.allowance(&other_quota, Direction::Upload, 500),
+            Allowance::Bytes(500)
+        ));
+
+        // The connections to the same peer share the quota
+        assert!(Arc::ptr_eq(&quota, &limiter.quota_of(peer)));
+
+        // Downloads are not limited globally, only per peer
+        assert!(!limiter.is_download_congested());
+        assert!(matches!(
+            limiter.allowance(&quota, Direction::Download, 5_000),
+            Allowance::Bytes(1_000)
+        ));
+    }
+}
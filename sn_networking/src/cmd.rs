@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::{
+    bad_nodes::PeerReputation,
     driver::{PendingGetClosestType, SwarmDriver},
     error::{NetworkError, Result},
     event::TerminateNodeReason,
@@ -21,6 +22,7 @@ use libp2p::{
     swarm::dial_opts::DialOpts,
     Multiaddr, PeerId,
 };
+use serde::{Deserialize, Serialize};
 use sn_protocol::{
     messages::{Cmd, Request, Response},
     storage::{RecordHeader, RecordKind, RecordType},
@@ -42,7 +44,7 @@ const MAX_CONTINUOUS_HDD_WRITE_ERROR: usize = 5;
 // Shall be synced with `sn_node::PERIODIC_REPLICATION_INTERVAL_MAX_S`
 const REPLICATION_TIMEOUT: Duration = Duration::from_secs(45);
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum NodeIssue {
     /// Connection issues observed
     ConnectionIssue,
@@ -171,6 +173,11 @@ pub enum SwarmCmd {
     RecordNodeIssue {
         peer_id: PeerId,
         issue: NodeIssue,
+        evidence: Option<String>,
+    },
+    // Returns the reputation of the peers we recorded issues against
+    GetPeerReputations {
+        sender: oneshot::Sender<Vec<PeerReputation>>,
     },
     // Whether peer is considered as `in trouble` by self
     IsPeerShunned {
@@ -288,12 +295,19 @@ impl Debug for SwarmCmd {
             SwarmCmd::SendRequest { req, peer, .. } => {
                 write!(f, "SwarmCmd::SendRequest req: {req:?}, peer: {peer:?}")
             }
-            SwarmCmd::RecordNodeIssue { peer_id, issue } => {
+            SwarmCmd::RecordNodeIssue {
+                peer_id,
+                issue,
+                evidence,
+            } => {
                 write!(
                     f,
-                    "SwarmCmd::SendNodeStatus peer {peer_id:?}, issue: {issue:?}"
+                    "SwarmCmd::SendNodeStatus peer {peer_id:?}, issue: {issue:?}, evidence: {evidence:?}"
                 )
             }
+            SwarmCmd::GetPeerReputations { .. } => {
+                write!(f, "SwarmCmd::GetPeerReputations")
+            }
             SwarmCmd::IsPeerShunned { target, .. } => {
                 write!(f, "SwarmCmd::IsPeerInTrouble target: {target:?}")
             }
@@ -732,19 +746,23 @@ impl SwarmDriver {
                     .map_err(|_| NetworkError::InternalMsgChannelDropped)?;
             }
 
-            SwarmCmd::RecordNodeIssue { peer_id, issue } => {
+            SwarmCmd::RecordNodeIssue {
+                peer_id,
+                issue,
+                evidence,
+            } => {
                 cmd_string = "RecordNodeIssues";
                 let _ = self.bad_nodes_ongoing_verifications.remove(&peer_id);
-                self.record_node_issue(peer_id, issue);
+                self.record_node_issue(peer_id, issue, evidence);
+            }
+            SwarmCmd::GetPeerReputations { sender } => {
+                cmd_string = "GetPeerReputations";
+                let _ = sender.send(self.bad_nodes.reputations());
             }
             SwarmCmd::IsPeerShunned { target, sender } => {
                 cmd_string = "IsPeerInTrouble";
                 let is_bad = if let Some(peer_id) = target.as_peer_id() {
-                    if let Some((_issues, is_bad)) = self.bad_nodes.get(&peer_id) {
-                        *is_bad
-                    } else {
-                        false
-                    }
+                    self.bad_nodes.is_bad(&peer_id)
                 } else {
                     false
                 };
@@ -754,10 +772,8 @@ impl SwarmDriver {
                 cmd_string = "QuoteVerification";
                 for (peer_id, quote) in quotes {
                     // Do nothing if already being bad
-                    if let Some((_issues, is_bad)) = self.bad_nodes.get(&peer_id) {
-                        if *is_bad {
-                            continue;
-                        }
+                    if self.bad_nodes.is_bad(&peer_id) {
+                        continue;
                     }
                     self.verify_peer_quote(peer_id, quote);
                 }
@@ -780,76 +796,46 @@ impl SwarmDriver {
         Ok(())
     }
 
-    fn record_node_issue(&mut self, peer_id: PeerId, issue: NodeIssue) {
-        info!("Peer {peer_id:?} is reported as having issue {issue:?}");
-        let (issue_vec, is_bad) = self.bad_nodes.entry(peer_id).or_default();
-
-        let mut is_new_bad = false;
-        let mut bad_behaviour: String = "".to_string();
-
-        // If being considered as bad already, skip certain operations
-        if !(*is_bad) {
-            // Remove outdated entries
-            issue_vec.retain(|(_, timestamp)| timestamp.elapsed().as_secs() < 300);
-
-            // check if vec is already 10 long, if so, remove the oldest issue
-            // we only track 10 issues to avoid mem leaks
-            if issue_vec.len() == 10 {
-                issue_vec.remove(0);
-            }
-
-            // To avoid being too sensitive, only consider as a new issue
-            // when after certain while since the last one
-            let is_new_issue = if let Some((_issue, timestamp)) = issue_vec.last() {
-                timestamp.elapsed().as_secs() > 10
-            } else {
-                true
-            };
-
-            if is_new_issue {
-                issue_vec.push((issue, Instant::now()));
-            }
-
-            // Only consider candidate as a bad node when:
-            //   accumulated THREE same kind issues within certain period
-            for (issue, _timestamp) in issue_vec.iter() {
-                let issue_counts = issue_vec
-                    .iter()
-                    .filter(|(i, _timestamp)| *issue == *i)
-                    .count();
-                if issue_counts >= 3 {
-                    *is_bad = true;
-                    is_new_bad = true;
-                    bad_behaviour = format!("{issue:?}");
-                    info!("Peer {peer_id:?} accumulated {issue_counts} times of issue {issue:?}. Consider it as a bad node now.");
-                    // Once a bad behaviour detected, no point to continue
-                    break;
-                }
-            }
+    fn record_node_issue(&mut self, peer_id: PeerId, issue: NodeIssue, evidence: Option<String>) {
+        info!("Peer {peer_id:?} is reported as having issue {issue:?}, evidence: {evidence:?}");
+        let newly_bad = self.bad_nodes.record_issue(peer_id, issue, evidence);
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.network_metrics {
+            let _ = metrics.node_issues.get_or_create(&issue.into()).inc();
         }
+        self.record_bad_nodes_metric();
 
-        if *is_bad {
+        if self.bad_nodes.is_bad(&peer_id) {
             warn!("Cleaning out bad_peer {peer_id:?}");
             if let Some(dead_peer) = self.swarm.behaviour_mut().kademlia.remove_peer(&peer_id) {
                 self.update_on_peer_removal(*dead_peer.node.key.preimage());
                 let _ = self.check_for_change_in_our_close_group();
             }
 
-            if is_new_bad {
+            if let Some(issue) = newly_bad {
                 self.send_event(NetworkEvent::PeerConsideredAsBad {
                     detected_by: self.self_peer_id,
                     bad_peer: peer_id,
-                    bad_behaviour,
+                    bad_behaviour: format!("{issue:?}"),
                 });
             }
         }
     }
 
+    /// Update the gauge of the peers currently considered as bad
+    pub(crate) fn record_bad_nodes_metric(&self) {
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.network_metrics {
+            let _ = metrics.bad_nodes.set(self.bad_nodes.bad_count() as i64);
+        }
+    }
+
     fn verify_peer_quote(&mut self, peer_id: PeerId, quote: PaymentQuote) {
         if let Some(history_quote) = self.quotes_history.get(&peer_id) {
             if !history_quote.historical_verify(&quote) {
                 info!("From {peer_id:?}, detected a bad quote {quote:?} against history_quote {history_quote:?}");
-                self.record_node_issue(peer_id, NodeIssue::BadQuoting);
+                let evidence = format!("quote {quote:?} against history_quote {history_quote:?}");
+                self.record_node_issue(peer_id, NodeIssue::BadQuoting, Some(evidence));
                 return;
             }
 
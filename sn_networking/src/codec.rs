@@ -0,0 +1,186 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::version::wire_version_of_protocol;
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use libp2p::{request_response, StreamProtocol};
+use serde::{de::DeserializeOwned, Serialize};
+use sn_protocol::{
+    messages::{Request, Response},
+    version::WIRE_PROTOCOL_VERSION,
+};
+use std::io;
+
+/// Max size of a request, same as the libp2p cbor codec we used before the wire versions
+const REQUEST_SIZE_MAXIMUM: u64 = 1024 * 1024;
+/// Max size of a response, same as the libp2p cbor codec we used before the wire versions
+const RESPONSE_SIZE_MAXIMUM: u64 = 10 * 1024 * 1024;
+
+/// Codec of the `Request`/`Response` messages, aware of the wire version negotiated for the stream.
+///
+/// Messages are cbor encoded and framed by closing the stream, as the libp2p cbor codec does,
+/// so the legacy wire version 0 stays compatible with the peers not negotiating yet.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WireCodec;
+
+#[async_trait]
+impl request_response::Codec for WireCodec {
+    type Protocol = StreamProtocol;
+    type Request = Request;
+    type Response = Response;
+
+    async fn read_request<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+    ) -> io::Result<Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let layout = negotiated_layout(protocol)?;
+        let bytes = read_to_end(io, REQUEST_SIZE_MAXIMUM).await?;
+        match layout {
+            WireLayout::Legacy => decode_legacy(&bytes),
+            WireLayout::Current => decode(&bytes),
+        }
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+    ) -> io::Result<Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let layout = negotiated_layout(protocol)?;
+        let bytes = read_to_end(io, RESPONSE_SIZE_MAXIMUM).await?;
+        match layout {
+            WireLayout::Legacy => decode_legacy(&bytes),
+            WireLayout::Current => decode(&bytes),
+        }
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+        req: Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let bytes = match negotiated_layout(protocol)? {
+            WireLayout::Legacy => encode_legacy(&req)?,
+            WireLayout::Current => encode(&req)?,
+        };
+        io.write_all(&bytes).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+        resp: Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let bytes = match negotiated_layout(protocol)? {
+            WireLayout::Legacy => encode_legacy(&resp)?,
+            WireLayout::Current => encode(&resp)?,
+        };
+        io.write_all(&bytes).await
+    }
+}
+
+/// The layouts of the messages we understand, one per supported wire version
+enum WireLayout {
+    /// Wire version 0, the layout of the libp2p cbor codec used before the versions got negotiated
+    Legacy,
+    /// The layout of `WIRE_PROTOCOL_VERSION`
+    Current,
+}
+
+fn negotiated_layout(protocol: &StreamProtocol) -> io::Result<WireLayout> {
+    match wire_version_of_protocol(protocol.as_ref()) {
+        Some(0) => Ok(WireLayout::Legacy),
+        Some(WIRE_PROTOCOL_VERSION) => Ok(WireLayout::Current),
+        Some(version) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("No message layout for the wire version {version}"),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown req/response protocol {protocol:?}"),
+        )),
+    }
+}
+
+async fn read_to_end<T>(io: &mut T, limit: u64) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut bytes = Vec::new();
+    let _ = io.take(limit).read_to_end(&mut bytes).await?;
+    Ok(bytes)
+}
+
+/// The legacy messages are the same types as the current ones, cbor encoded the way the libp2p cbor codec does.
+/// Once the layout of the messages changes, the legacy types shall be decoded here and converted.
+fn decode_legacy<M: DeserializeOwned>(bytes: &[u8]) -> io::Result<M> {
+    cbor4ii::serde::from_slice(bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
+
+fn encode_legacy<M: Serialize>(msg: &M) -> io::Result<Vec<u8>> {
+    cbor4ii::serde::to_vec(Vec::new(), msg)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
+
+fn decode<M: DeserializeOwned>(bytes: &[u8]) -> io::Result<M> {
+    cbor4ii::serde::from_slice(bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
+
+fn encode<M: Serialize>(msg: &M) -> io::Result<Vec<u8>> {
+    cbor4ii::serde::to_vec(Vec::new(), msg)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::req_response_protocol_str;
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec;
+    use sn_protocol::{messages::Query, NetworkAddress};
+
+    #[tokio::test]
+    async fn requests_of_the_legacy_layout_are_decoded() -> eyre::Result<()> {
+        let legacy = StreamProtocol::try_from_owned(req_response_protocol_str(0))?;
+        let request = Request::Query(Query::GetStoreCost(NetworkAddress::from_peer(
+            libp2p::PeerId::random(),
+        )));
+        // as written by the libp2p cbor codec of the peers not negotiating the wire version yet
+        let bytes = cbor4ii::serde::to_vec(Vec::new(), &request)?;
+
+        let decoded = WireCodec
+            .read_request(&legacy, &mut Cursor::new(bytes.clone()))
+            .await?;
+        assert_eq!(format!("{decoded:?}"), format!("{request:?}"));
+
+        let mut written = Cursor::new(Vec::new());
+        WireCodec
+            .write_request(&legacy, &mut written, request)
+            .await?;
+        assert_eq!(written.into_inner(), bytes);
+        Ok(())
+    }
+}
@@ -6,14 +6,19 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bandwidth::{self, BandwidthLimiter, BandwidthLimits};
 #[cfg(feature = "open-metrics")]
 use crate::metrics::NetworkMetrics;
 #[cfg(feature = "open-metrics")]
 use crate::metrics_service::run_metrics_server;
+use crate::transport::{self, TransportMode};
 use crate::{
+    bad_nodes::BadNodes,
     bootstrap::{ContinuousBootstrap, BOOTSTRAP_INTERVAL},
     circular_vec::CircularVec,
     cmd::SwarmCmd,
+    codec::WireCodec,
     error::{NetworkError, Result},
     event::{NetworkEvent, NodeEvent},
     multiaddr_pop_p2p,
@@ -24,12 +29,11 @@ use crate::{
     replication_fetcher::ReplicationFetcher,
     target_arch::{interval, spawn, Instant},
     version::{
-        IDENTIFY_CLIENT_VERSION_STR, IDENTIFY_NODE_VERSION_STR, IDENTIFY_PROTOCOL_STR,
-        REQ_RESPONSE_VERSION_STR,
+        req_response_protocols, IDENTIFY_CLIENT_VERSION_STR, IDENTIFY_NODE_VERSION_STR,
+        IDENTIFY_PROTOCOL_STR,
     },
     GetRecordError, Network, CLOSE_GROUP_SIZE,
 };
-use crate::{transport, NodeIssue};
 use futures::future::Either;
 use futures::StreamExt;
 #[cfg(feature = "local-discovery")]
@@ -50,16 +54,18 @@ use libp2p::{
 #[cfg(feature = "open-metrics")]
 use prometheus_client::registry::Registry;
 use sn_protocol::{
-    messages::{ChunkProof, Nonce, Request, Response},
+    messages::{ChunkProof, Nonce, Response},
     storage::RetryStrategy,
     NetworkAddress, PrettyPrintKBucketKey, PrettyPrintRecordKey,
 };
 use sn_transfers::PaymentQuote;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
     fmt::Debug,
     net::SocketAddr,
-    num::NonZeroUsize,
+    num::{NonZeroU8, NonZeroUsize},
     path::PathBuf,
 };
 use tokio::sync::{mpsc, oneshot};
@@ -74,6 +80,9 @@ pub(crate) const CLOSET_RECORD_CHECK_INTERVAL: Duration = Duration::from_secs(15
 /// Interval over which we query relay manager to check if we can make any more reservations.
 pub(crate) const RELAY_MANAGER_RESERVATION_INTERVAL: Duration = Duration::from_secs(30);
 
+/// Interval over which the changes to the reputation of the peers are persisted.
+pub(crate) const BAD_NODES_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
 /// The ways in which the Get Closest queries are used.
 pub(crate) enum PendingGetClosestType {
     /// The network discovery method is present at the networking layer
@@ -95,10 +104,6 @@ pub(crate) type PendingGetRecord = HashMap<
     ),
 >;
 
-/// 10 is the max number of issues per node we track to avoid mem leaks
-/// The boolean flag to indicate whether the node is considered as bad or not
-pub(crate) type BadNodes = BTreeMap<PeerId, (Vec<(NodeIssue, Instant)>, bool)>;
-
 /// What is the largest packet to send over the network.
 /// Records larger than this will be rejected.
 // TODO: revisit once cashnote_redemption is in
@@ -190,7 +195,7 @@ pub enum VerificationKind {
 pub(super) struct NodeBehaviour {
     #[cfg(feature = "upnp")]
     pub(super) upnp: libp2p::swarm::behaviour::toggle::Toggle<libp2p::upnp::tokio::Behaviour>,
-    pub(super) request_response: request_response::cbor::Behaviour<Request, Response>,
+    pub(super) request_response: request_response::Behaviour<WireCodec>,
     pub(super) kademlia: kad::Behaviour<UnifiedRecordStore>,
     #[cfg(feature = "local-discovery")]
     pub(super) mdns: mdns::tokio::Behaviour,
@@ -210,6 +215,9 @@ pub struct NetworkBuilder {
     request_timeout: Option<Duration>,
     concurrency_limit: Option<usize>,
     initial_peers: Vec<Multiaddr>,
+    transport_mode: TransportMode,
+    #[cfg(not(target_arch = "wasm32"))]
+    bandwidth_limits: BandwidthLimits,
     #[cfg(feature = "open-metrics")]
     metrics_registry: Option<Registry>,
     #[cfg(feature = "open-metrics")]
@@ -230,6 +238,9 @@ impl NetworkBuilder {
             request_timeout: None,
             concurrency_limit: None,
             initial_peers: Default::default(),
+            transport_mode: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            bandwidth_limits: Default::default(),
             #[cfg(feature = "open-metrics")]
             metrics_registry: None,
             #[cfg(feature = "open-metrics")]
@@ -259,6 +270,18 @@ impl NetworkBuilder {
         self.initial_peers = initial_peers;
     }
 
+    /// Set the transports used to connect to the other peers, QUIC only by default.
+    /// With both transports, the QUIC address of a peer is dialed first, then the TCP one as a fallback.
+    pub fn transport_mode(&mut self, transport_mode: TransportMode) {
+        self.transport_mode = transport_mode;
+    }
+
+    /// Set the global and per peer bandwidth limits, unlimited by default
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bandwidth_limits(&mut self, bandwidth_limits: BandwidthLimits) {
+        self.bandwidth_limits = bandwidth_limits;
+    }
+
     #[cfg(feature = "open-metrics")]
     pub fn metrics_registry(&mut self, metrics_registry: Option<Registry>) {
         self.metrics_registry = metrics_registry;
@@ -334,6 +357,7 @@ impl NetworkBuilder {
         };
 
         let listen_addr = self.listen_addr;
+        let transport_mode = self.transport_mode;
         #[cfg(feature = "upnp")]
         let upnp = self.upnp;
 
@@ -351,12 +375,29 @@ impl NetworkBuilder {
         let listen_socket_addr = listen_addr.ok_or(NetworkError::ListenAddressNotProvided)?;
 
         // Listen on QUIC
-        let addr_quic = Multiaddr::from(listen_socket_addr.ip())
-            .with(Protocol::Udp(listen_socket_addr.port()))
-            .with(Protocol::QuicV1);
-        swarm_driver
-            .listen_on(addr_quic)
-            .expect("Multiaddr should be supported by our configured transports");
+        if transport_mode.uses_quic() {
+            let addr_quic = Multiaddr::from(listen_socket_addr.ip())
+                .with(Protocol::Udp(listen_socket_addr.port()))
+                .with(Protocol::QuicV1);
+            swarm_driver
+                .listen_on(addr_quic)
+                .expect("Multiaddr should be supported by our configured transports");
+        }
+
+        // Listen on TCP, on the same port number as QUIC.
+        // With the `websockets` feature, that TCP port is already taken by the WebSocket listener.
+        #[cfg(not(feature = "websockets"))]
+        if transport_mode.uses_tcp() {
+            let addr_tcp = Multiaddr::from(listen_socket_addr.ip())
+                .with(Protocol::Tcp(listen_socket_addr.port()));
+            swarm_driver
+                .listen_on(addr_tcp)
+                .expect("Multiaddr should be supported by our configured transports");
+        }
+        #[cfg(feature = "websockets")]
+        if transport_mode.uses_tcp() {
+            warn!("Not listening on plain TCP, the port is used by the WebSocket listener");
+        }
 
         // Listen on WebSocket
         #[cfg(any(feature = "websockets", target_arch = "wasm32"))]
@@ -440,17 +481,18 @@ impl NetworkBuilder {
             let cfg = RequestResponseConfig::default()
                 .with_request_timeout(self.request_timeout.unwrap_or(REQUEST_TIMEOUT_DEFAULT_S));
 
-            info!(
-                "Building request response with {:?}",
-                REQ_RESPONSE_VERSION_STR.as_str()
-            );
-            request_response::cbor::Behaviour::new(
-                [(
-                    StreamProtocol::new(&REQ_RESPONSE_VERSION_STR),
-                    req_res_protocol,
-                )],
-                cfg,
-            )
+            // Listed with the highest wire version first,
+            // hence the highest version supported by both peers gets negotiated per stream.
+            let protocols = req_response_protocols()
+                .into_iter()
+                .map(|protocol| {
+                    let protocol = StreamProtocol::try_from_owned(protocol)
+                        .map_err(|err| NetworkError::InvalidProtocol(err.to_string()))?;
+                    Ok((protocol, req_res_protocol.clone()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            info!("Building request response with {protocols:?}");
+            request_response::Behaviour::new(protocols, cfg)
         };
 
         let (network_event_sender, network_event_receiver) = mpsc::channel(NETWORKING_CHANNEL_SIZE);
@@ -511,7 +553,8 @@ impl NetworkBuilder {
             libp2p::identify::Behaviour::new(cfg)
         };
 
-        let main_transport = transport::build_transport(&self.keypair);
+        info!("Building the transport with {:?}", self.transport_mode);
+        let main_transport = transport::build_transport(&self.keypair, self.transport_mode);
 
         let transport = if !self.local {
             debug!("Preventing non-global dials");
@@ -548,6 +591,29 @@ impl NetworkBuilder {
             })
             .boxed();
 
+        // Wrapping every connection, including the relayed ones, to enforce the limits
+        // and to record the bandwidth used. Skipped when there is nothing to enforce nor record.
+        #[cfg(not(target_arch = "wasm32"))]
+        let bandwidth_limiter = {
+            if !self.bandwidth_limits.is_unlimited() {
+                info!("Limiting the bandwidth to {:?}", self.bandwidth_limits);
+            }
+            let limiter = BandwidthLimiter::new(self.bandwidth_limits);
+            #[cfg(feature = "open-metrics")]
+            let limiter = if let Some(metrics) = &network_metrics {
+                limiter.set_usage_metrics(metrics.bandwidth_usage.clone())
+            } else {
+                limiter
+            };
+            Arc::new(limiter)
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let transport = if bandwidth_limiter.is_needed() {
+            bandwidth::throttle_transport(transport, Arc::clone(&bandwidth_limiter))
+        } else {
+            transport
+        };
+
         let relay_server = {
             let relay_server_cfg = relay::Config::default();
             libp2p::relay::Behaviour::new(peer_id, relay_server_cfg)
@@ -577,11 +643,27 @@ impl NetworkBuilder {
 
         let bootstrap = ContinuousBootstrap::new();
         let replication_fetcher = ReplicationFetcher::new(peer_id, network_event_sender.clone());
-        let mut relay_manager = RelayManager::new(self.initial_peers, peer_id);
+        // The replication yields to the client GETs when the download budget runs low
+        #[cfg(not(target_arch = "wasm32"))]
+        let replication_fetcher = replication_fetcher.set_bandwidth_limiter(bandwidth_limiter);
+        #[cfg(feature = "open-metrics")]
+        let replication_fetcher = if let Some(metrics) = &network_metrics {
+            replication_fetcher.set_fetch_metrics(metrics.replication_fetches.clone())
+        } else {
+            replication_fetcher
+        };
+        let mut relay_manager = RelayManager::new(self.initial_peers, peer_id, self.transport_mode);
         if !is_client {
             relay_manager.enable_hole_punching(self.is_behind_home_network);
         }
 
+        // Only nodes persist the reputation of their peers
+        let bad_nodes = if is_client {
+            BadNodes::default()
+        } else {
+            BadNodes::new_persisted(self.root_dir.clone())
+        };
+
         let swarm_driver = SwarmDriver {
             swarm,
             self_peer_id: peer_id,
@@ -592,6 +674,7 @@ impl NetworkBuilder {
             peers_in_rt: 0,
             bootstrap,
             relay_manager,
+            transport_mode: self.transport_mode,
             close_group: Default::default(),
             replication_fetcher,
             #[cfg(feature = "open-metrics")]
@@ -610,7 +693,7 @@ impl NetworkBuilder {
             handling_statistics: Default::default(),
             handled_times: 0,
             hard_disk_write_error: 0,
-            bad_nodes: Default::default(),
+            bad_nodes,
             bad_nodes_ongoing_verifications: Default::default(),
             quotes_history: Default::default(),
             replication_targets: Default::default(),
@@ -634,6 +717,8 @@ pub struct SwarmDriver {
     pub(crate) peers_in_rt: usize,
     pub(crate) bootstrap: ContinuousBootstrap,
     pub(crate) relay_manager: RelayManager,
+    /// The transports used to dial the peers
+    transport_mode: TransportMode,
     /// The peers that are closer to our PeerId. Includes self.
     pub(crate) close_group: Vec<PeerId>,
     pub(crate) replication_fetcher: ReplicationFetcher,
@@ -679,6 +764,7 @@ impl SwarmDriver {
         let mut bootstrap_interval = interval(BOOTSTRAP_INTERVAL);
         let mut set_farthest_record_interval = interval(CLOSET_RECORD_CHECK_INTERVAL);
         let mut relay_manager_reservation_interval = interval(RELAY_MANAGER_RESERVATION_INTERVAL);
+        let mut bad_nodes_flush_interval = interval(BAD_NODES_FLUSH_INTERVAL);
 
         loop {
             tokio::select! {
@@ -721,6 +807,11 @@ impl SwarmDriver {
                     }
                 }
                 _ = relay_manager_reservation_interval.tick() => self.relay_manager.try_connecting_to_relay(&mut self.swarm, &self.bad_nodes),
+                _ = bad_nodes_flush_interval.tick() => {
+                    self.bad_nodes.flush();
+                    // peers recover once their issues expire, which no event reports
+                    self.record_bad_nodes_metric();
+                }
             }
         }
     }
@@ -814,13 +905,17 @@ impl SwarmDriver {
         trace!(%addr, "Dialing manually");
 
         let peer_id = multiaddr_pop_p2p(&mut addr);
+        let mut addrs = self.transport_mode.dial_addrs(addr);
         let opts = match peer_id {
             Some(peer_id) => DialOpts::peer_id(peer_id)
                 // If we have a peer ID, we can prevent simultaneous dials.
                 .condition(PeerCondition::NotDialing)
-                .addresses(vec![addr])
+                .addresses(addrs)
+                // One address at a time, so the fallback ones are only dialed if the preferred one fails
+                .override_dial_concurrency_factor(NonZeroU8::MIN)
                 .build(),
-            None => DialOpts::unknown_peer_id().address(addr).build(),
+            // Without a peer ID, only a single address can be dialed
+            None => DialOpts::unknown_peer_id().address(addrs.remove(0)).build(),
         };
 
         self.swarm.dial(opts)
@@ -161,6 +161,9 @@ pub enum NetworkError {
     #[error("Node Listen Address was not provided during construction")]
     ListenAddressNotProvided,
 
+    #[error("Invalid req/response protocol: {0}")]
+    InvalidProtocol(String),
+
     #[cfg(feature = "open-metrics")]
     #[error("Network Metric error")]
     NetworkMetricError,
@@ -12,7 +12,7 @@ use crate::{
     multiaddr_is_global, multiaddr_strip_p2p,
     relay_manager::is_a_relayed_peer,
     target_arch::Instant,
-    version::{IDENTIFY_NODE_VERSION_STR, IDENTIFY_PROTOCOL_STR},
+    version::{is_node_agent_version, wire_version_range_of_peer, IDENTIFY_PROTOCOL_STR},
     NetworkEvent, Result, SwarmDriver,
 };
 use itertools::Itertools;
@@ -29,7 +29,7 @@ use libp2p::{
     },
     Multiaddr, PeerId, TransportError,
 };
-use sn_protocol::get_port_from_multiaddr;
+use sn_protocol::{get_port_from_multiaddr, version::WireVersionRange};
 use std::collections::HashSet;
 use tokio::time::Duration;
 
@@ -130,19 +130,31 @@ impl SwarmDriver {
                     libp2p::identify::Event::Received { peer_id, info } => {
                         trace!(%peer_id, ?info, "identify: received info");
 
-                        if info.protocol_version != IDENTIFY_PROTOCOL_STR.to_string() {
-                            warn!(?info.protocol_version, "identify: {peer_id:?} does not have the same protocol. Our IDENTIFY_PROTOCOL_STR: {:?}", IDENTIFY_PROTOCOL_STR.as_str());
+                        // Peers are compatible as long as they share a wire version,
+                        // the highest common one is then negotiated per stream.
+                        let their_wire_versions =
+                            wire_version_range_of_peer(&info.protocol_version, &info.protocols);
+                        let common_wire_version = their_wire_versions.and_then(|theirs| {
+                            WireVersionRange::supported().highest_common(&theirs)
+                        });
+                        match common_wire_version {
+                            Some(version) => {
+                                trace!("identify: {peer_id:?} supports the wire versions {their_wire_versions:?}, {version} being the highest common one with us");
+                            }
+                            None => {
+                                warn!(?info.protocol_version, "identify: {peer_id:?} does not share a wire version with us. Our IDENTIFY_PROTOCOL_STR: {:?}", IDENTIFY_PROTOCOL_STR.as_str());
 
-                            self.send_event(NetworkEvent::PeerWithUnsupportedProtocol {
-                                our_protocol: IDENTIFY_PROTOCOL_STR.to_string(),
-                                their_protocol: info.protocol_version,
-                            });
+                                self.send_event(NetworkEvent::PeerWithUnsupportedProtocol {
+                                    our_protocol: IDENTIFY_PROTOCOL_STR.to_string(),
+                                    their_protocol: info.protocol_version,
+                                });
 
-                            return Ok(());
+                                return Ok(());
+                            }
                         }
 
                         // if client, return.
-                        if info.agent_version != IDENTIFY_NODE_VERSION_STR.to_string() {
+                        if !is_node_agent_version(&info.agent_version) {
                             return Ok(());
                         }
 
@@ -250,7 +262,7 @@ impl SwarmDriver {
                             // during the connection establish process, only check cached black_list
                             // The periodical check, which involves network queries shall filter
                             // out bad_nodes eventually.
-                            if let Some((_issues, true)) = self.bad_nodes.get(&peer_id) {
+                            if self.bad_nodes.is_bad(&peer_id) {
                                 info!("Peer {peer_id:?} is considered as bad, blocking it.");
                             } else {
                                 self.remove_bootstrap_from_full(peer_id);
@@ -537,6 +549,7 @@ impl SwarmDriver {
                         self.handle_cmd(SwarmCmd::RecordNodeIssue {
                             peer_id: failed_peer_id,
                             issue: crate::NodeIssue::ConnectionIssue,
+                            evidence: None,
                         })?;
 
                         let _ = self.check_for_change_in_our_close_group();
@@ -576,6 +589,7 @@ impl SwarmDriver {
                     // are not actually reachable. This event returns addresses with ports that were not set by the user,
                     // so we must not add those ports as they will not be forwarded.
                     // Setting this will also switch kad to server mode if it's not already in it.
+                    // QUIC and TCP listen on the same port, so this holds for the candidates of both transports.
                     if let Some(our_port) = self.listen_port {
                         if let Some(port) = get_port_from_multiaddr(&address) {
                             if port == our_port {
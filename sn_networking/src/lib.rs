@@ -9,9 +9,13 @@
 #[macro_use]
 extern crate tracing;
 
+mod bad_nodes;
+#[cfg(not(target_arch = "wasm32"))]
+mod bandwidth;
 mod bootstrap;
 mod circular_vec;
 mod cmd;
+mod codec;
 mod driver;
 mod error;
 mod event;
@@ -34,7 +38,11 @@ pub mod version;
 // re-export arch dependent deps for use in the crate, or above
 pub use target_arch::{interval, sleep, spawn, Instant, Interval};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::bandwidth::BandwidthLimits;
+
 pub use self::{
+    bad_nodes::{IssueEvidence, PeerReputation},
     cmd::{NodeIssue, SwarmLocalState},
     driver::{
         GetRecordCfg, NetworkBuilder, PutRecordCfg, SwarmDriver, VerificationKind, MAX_PACKET_SIZE,
@@ -44,6 +52,7 @@ pub use self::{
     record_store::{calculate_cost_for_records, NodeRecordStore},
     transfers::{get_raw_signed_spends_from_record, get_signed_spend_from_record},
 };
+pub use sn_protocol::transport::TransportMode;
 
 use self::{cmd::SwarmCmd, error::Result};
 use backoff::{Error as BackoffError, ExponentialBackoff};
@@ -402,6 +411,27 @@ impl Network {
         record_address: NetworkAddress,
         ignore_peers: Vec<PeerId>,
     ) -> Result<PayeeQuote> {
+        // get the lowest cost
+        let payee = self
+            .get_store_quotes_from_network(record_address, ignore_peers)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(NetworkError::NoStoreCostResponses)?;
+        info!("Final fees calculated as: {payee:?}");
+        Ok(payee)
+    }
+
+    /// Get all the store cost quotes of the majority of the closest peers to the provided RecordKey,
+    /// the cheapest first. This allows the caller to apply its own choice of the payee.
+    /// Record already exists will have a cost of zero to be returned.
+    ///
+    /// Ignore the quote from any peers from `ignore_peers`.
+    pub async fn get_store_quotes_from_network(
+        &self,
+        record_address: NetworkAddress,
+        ignore_peers: Vec<PeerId>,
+    ) -> Result<Vec<PayeeQuote>> {
         // The requirement of having at least CLOSE_GROUP_SIZE
         // close nodes will be checked internally automatically.
         let close_nodes = self.get_closest_peers(&record_address, true).await?;
@@ -473,7 +503,7 @@ impl Network {
             .take(close_group_majority())
             .collect();
 
-        get_fees_from_store_cost_responses(all_costs)
+        get_quotes_from_store_cost_responses(all_costs)
     }
 
     /// Get a record from the network
@@ -811,7 +841,35 @@ impl Network {
     }
 
     pub fn record_node_issues(&self, peer_id: PeerId, issue: NodeIssue) {
-        self.send_swarm_cmd(SwarmCmd::RecordNodeIssue { peer_id, issue });
+        self.send_swarm_cmd(SwarmCmd::RecordNodeIssue {
+            peer_id,
+            issue,
+            evidence: None,
+        });
+    }
+
+    /// Same as `record_node_issues` but also keeps the evidence of the issue,
+    /// e.g. the nonce of a failed chunk proof
+    pub fn record_node_issue_with_evidence(
+        &self,
+        peer_id: PeerId,
+        issue: NodeIssue,
+        evidence: String,
+    ) {
+        self.send_swarm_cmd(SwarmCmd::RecordNodeIssue {
+            peer_id,
+            issue,
+            evidence: Some(evidence),
+        });
+    }
+
+    /// Returns the reputation of the peers we have recorded issues against
+    pub async fn get_peer_reputations(&self) -> Result<Vec<PeerReputation>> {
+        let (sender, receiver) = oneshot::channel();
+        self.send_swarm_cmd(SwarmCmd::GetPeerReputations { sender });
+        receiver
+            .await
+            .map_err(|_e| NetworkError::InternalMsgChannelDropped)
     }
 
     pub fn historical_verify_quotes(&self, quotes: Vec<(PeerId, PaymentQuote)>) {
@@ -904,11 +962,10 @@ impl Network {
     }
 }
 
-/// Given `all_costs` it will return the closest / lowest cost
-/// Closest requiring it to be within CLOSE_GROUP nodes
-fn get_fees_from_store_cost_responses(
+/// Given `all_costs` it will return the quotes sorted by fee, lowest to highest
+fn get_quotes_from_store_cost_responses(
     mut all_costs: Vec<(NetworkAddress, MainPubkey, PaymentQuote)>,
-) -> Result<PayeeQuote> {
+) -> Result<Vec<PayeeQuote>> {
     // sort all costs by fee, lowest to highest
     // if there's a tie in cost, sort by pubkey
     all_costs.sort_by(
@@ -921,21 +978,22 @@ fn get_fees_from_store_cost_responses(
         },
     );
 
-    // get the lowest cost
     trace!("Got all costs: {all_costs:?}");
-    let payee = all_costs
-        .into_iter()
-        .next()
-        .ok_or(NetworkError::NoStoreCostResponses)?;
-    info!("Final fees calculated as: {payee:?}");
-    // we dont need to have the address outside of here for now
-    let payee_id = if let Some(peer_id) = payee.0.as_peer_id() {
-        peer_id
-    } else {
-        error!("Can't get PeerId from payee {:?}", payee.0);
+    if all_costs.is_empty() {
         return Err(NetworkError::NoStoreCostResponses);
-    };
-    Ok((payee_id, payee.1, payee.2))
+    }
+    // we dont need to have the address outside of here for now
+    all_costs
+        .into_iter()
+        .map(|(address, main_pubkey, quote)| {
+            if let Some(peer_id) = address.as_peer_id() {
+                Ok((peer_id, main_pubkey, quote))
+            } else {
+                error!("Can't get PeerId from payee {address:?}");
+                Err(NetworkError::NoStoreCostResponses)
+            }
+        })
+        .collect()
 }
 
 /// Get the value of the provided Quorum
@@ -1044,7 +1102,11 @@ mod tests {
             ));
         }
         let expected_price = costs[0].2.cost.as_nano();
-        let (_peer_id, _key, price) = get_fees_from_store_cost_responses(costs)?;
+        let quotes = get_quotes_from_store_cost_responses(costs)?;
+        let (_peer_id, _key, price) = quotes
+            .into_iter()
+            .next()
+            .ok_or(NetworkError::NoStoreCostResponses)?;
 
         assert_eq!(
             price.cost.as_nano(),
@@ -1075,9 +1137,9 @@ mod tests {
         // this should be the lowest price
         let expected_price = costs[0].2.cost.as_nano();
 
-        let (_peer_id, _key, price) = match get_fees_from_store_cost_responses(costs) {
-            Err(_) => bail!("Should not have errored as we have enough responses"),
-            Ok(cost) => cost,
+        let (_peer_id, _key, price) = match get_quotes_from_store_cost_responses(costs) {
+            Ok(quotes) if !quotes.is_empty() => quotes[0].clone(),
+            _ => bail!("Should not have errored as we have enough responses"),
         };
 
         assert_eq!(
@@ -6,11 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::target_arch::sleep;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bandwidth::BandwidthUsageMetrics;
+use crate::{cmd::NodeIssue, replication_fetcher::ReplicationFetchMetrics, target_arch::sleep};
 use libp2p::metrics::{Metrics as Libp2pMetrics, Recorder};
-#[cfg(feature = "upnp")]
-use prometheus_client::metrics::{counter::Counter, family::Family};
-use prometheus_client::{metrics::gauge::Gauge, registry::Registry};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
+    registry::Registry,
+};
 use sysinfo::{Pid, ProcessRefreshKind, System};
 use tokio::time::Duration;
 
@@ -34,6 +38,11 @@ pub(crate) struct NetworkMetrics {
     pub(crate) peers_in_routing_table: Gauge,
     pub(crate) records_stored: Gauge,
     pub(crate) store_cost: Gauge,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) bandwidth_usage: BandwidthUsageMetrics,
+    pub(crate) bad_nodes: Gauge,
+    pub(crate) node_issues: Family<NodeIssueLabels, Counter>,
+    pub(crate) replication_fetches: ReplicationFetchMetrics,
     #[cfg(feature = "upnp")]
     pub(crate) upnp_events: Family<upnp::UpnpEventLabels, Counter>,
 
@@ -42,6 +51,19 @@ pub(crate) struct NetworkMetrics {
     process_cpu_usage_percentage: Gauge,
 }
 
+#[derive(Debug, Clone, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub(crate) struct NodeIssueLabels {
+    issue: String,
+}
+
+impl From<NodeIssue> for NodeIssueLabels {
+    fn from(issue: NodeIssue) -> Self {
+        Self {
+            issue: format!("{issue:?}"),
+        }
+    }
+}
+
 impl NetworkMetrics {
     pub fn new(registry: &mut Registry) -> Self {
         let libp2p_metrics = Libp2pMetrics::new(registry);
@@ -86,6 +108,58 @@ impl NetworkMetrics {
             store_cost.clone(),
         );
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let bandwidth_usage = BandwidthUsageMetrics {
+            uploaded_bytes: Counter::default(),
+            downloaded_bytes: Counter::default(),
+            throttled: Counter::default(),
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            sub_registry.register(
+                "uploaded_bytes",
+                "The bytes sent to the other peers, over all the protocols",
+                bandwidth_usage.uploaded_bytes.clone(),
+            );
+            sub_registry.register(
+                "downloaded_bytes",
+                "The bytes received from the other peers, over all the protocols",
+                bandwidth_usage.downloaded_bytes.clone(),
+            );
+            sub_registry.register(
+                "bandwidth_throttled",
+                "The number of times a transfer got delayed by the bandwidth limits",
+                bandwidth_usage.throttled.clone(),
+            );
+        }
+
+        let bad_nodes = Gauge::default();
+        sub_registry.register(
+            "bad_nodes",
+            "The number of peers currently considered as bad",
+            bad_nodes.clone(),
+        );
+        let node_issues = Family::default();
+        sub_registry.register(
+            "node_issues",
+            "The issues reported against other peers, by kind of issue",
+            node_issues.clone(),
+        );
+        let replication_fetches = ReplicationFetchMetrics {
+            fetches: Counter::default(),
+            failed_fetches: Counter::default(),
+        };
+        sub_registry.register(
+            "replication_fetches",
+            "The keys fetched from other peers during replication",
+            replication_fetches.fetches.clone(),
+        );
+        sub_registry.register(
+            "replication_fetch_failures",
+            "The keys that failed to be fetched from other peers during replication",
+            replication_fetches.failed_fetches.clone(),
+        );
+
         #[cfg(feature = "upnp")]
         let upnp_events = Family::default();
         #[cfg(feature = "upnp")]
@@ -117,6 +191,11 @@ impl NetworkMetrics {
             open_connections,
             peers_in_routing_table,
             store_cost,
+            #[cfg(not(target_arch = "wasm32"))]
+            bandwidth_usage,
+            bad_nodes,
+            node_issues,
+            replication_fetches,
             #[cfg(feature = "upnp")]
             upnp_events,
             process_memory_used_mb,
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::driver::{BadNodes, NodeBehaviour};
+use crate::{bad_nodes::BadNodes, driver::NodeBehaviour, transport::TransportMode};
 use itertools::Itertools;
 use libp2p::{
     core::transport::ListenerId, multiaddr::Protocol, Multiaddr, PeerId, StreamProtocol, Swarm,
@@ -27,6 +27,7 @@ pub(crate) fn is_a_relayed_peer(addrs: &HashSet<Multiaddr>) -> bool {
 #[derive(Debug)]
 pub(crate) struct RelayManager {
     self_peer_id: PeerId,
+    transport_mode: TransportMode,
     // server states
     reserved_by: HashSet<PeerId>,
     // client states
@@ -40,13 +41,18 @@ pub(crate) struct RelayManager {
 }
 
 impl RelayManager {
-    pub(crate) fn new(initial_peers: Vec<Multiaddr>, self_peer_id: PeerId) -> Self {
+    pub(crate) fn new(
+        initial_peers: Vec<Multiaddr>,
+        self_peer_id: PeerId,
+        transport_mode: TransportMode,
+    ) -> Self {
         let candidates = initial_peers
             .into_iter()
             .filter_map(|addr| {
                 for protocol in addr.iter() {
                     if let Protocol::P2p(peer_id) = protocol {
-                        let relay_addr = Self::craft_relay_address(&addr, Some(peer_id))?;
+                        let relay_addr =
+                            Self::craft_relay_address(&addr, Some(peer_id), transport_mode)?;
 
                         return Some((peer_id, relay_addr));
                     }
@@ -56,6 +62,7 @@ impl RelayManager {
             .collect();
         Self {
             self_peer_id,
+            transport_mode,
             reserved_by: Default::default(),
             enable_client: false,
             connected_relays: Default::default(),
@@ -74,11 +81,7 @@ impl RelayManager {
     /// If a peer is considered as a bad node, closing it's connection would remove that server from the listen addr.
     #[allow(clippy::nonminimal_bool)]
     pub(crate) fn keep_alive_peer(&self, peer_id: &PeerId, bad_nodes: &BadNodes) -> bool {
-        let is_not_bad = if let Some((_, is_bad)) = bad_nodes.get(peer_id) {
-            !*is_bad
-        } else {
-            true
-        };
+        let is_not_bad = !bad_nodes.is_bad(peer_id);
 
         // we disconnect from bad server
         (self.connected_relays.contains_key(peer_id) && is_not_bad)
@@ -101,11 +104,9 @@ impl RelayManager {
             return;
         }
 
-        if let Some((_, is_bad)) = bad_nodes.get(peer_id) {
-            if *is_bad {
-                debug!("Not adding peer {peer_id:?} as relay candidate as it is a bad node.");
-                return;
-            }
+        if bad_nodes.is_bad(peer_id) {
+            debug!("Not adding peer {peer_id:?} as relay candidate as it is a bad node.");
+            return;
         }
 
         if Self::does_it_support_relay_server_protocol(stream_protocols) {
@@ -113,7 +114,9 @@ impl RelayManager {
             if let Some(addr) = addrs.iter().next() {
                 // The calling place shall already checked whether the peer is `relayed`.
                 // Hence here can add the addr directly.
-                if let Some(relay_addr) = Self::craft_relay_address(addr, Some(*peer_id)) {
+                if let Some(relay_addr) =
+                    Self::craft_relay_address(addr, Some(*peer_id), self.transport_mode)
+                {
                     debug!("Adding {peer_id:?} with {relay_addr:?} as a potential relay candidate");
                     self.candidates.push_back((*peer_id, relay_addr));
                 }
@@ -158,11 +161,9 @@ impl RelayManager {
 
             if let Some((peer_id, relay_addr)) = self.candidates.remove(index) {
                 // skip if detected as a bad node
-                if let Some((_, is_bad)) = bad_nodes.get(&peer_id) {
-                    if *is_bad {
-                        trace!("Peer {peer_id:?} is considered as a bad node. Skipping it.");
-                        continue;
-                    }
+                if bad_nodes.is_bad(&peer_id) {
+                    trace!("Peer {peer_id:?} is considered as a bad node. Skipping it.");
+                    continue;
                 }
 
                 if self.connected_relays.contains_key(&peer_id)
@@ -268,18 +269,34 @@ impl RelayManager {
     }
 
     /// The listen addr should be something like /ip4/198.51.100.0/tcp/55555/p2p/QmRelay/p2p-circuit/
-    fn craft_relay_address(addr: &Multiaddr, peer_id: Option<PeerId>) -> Option<Multiaddr> {
+    /// The relay is dialed with our transport, on the same port number whichever the transport of the addr.
+    fn craft_relay_address(
+        addr: &Multiaddr,
+        peer_id: Option<PeerId>,
+        transport_mode: TransportMode,
+    ) -> Option<Multiaddr> {
         let mut output_addr = Multiaddr::empty();
 
         let ip = addr
             .iter()
             .find(|protocol| matches!(protocol, Protocol::Ip4(_)))?;
         output_addr.push(ip);
-        let port = addr
-            .iter()
-            .find(|protocol| matches!(protocol, Protocol::Udp(_)))?;
-        output_addr.push(port);
-        output_addr.push(Protocol::QuicV1);
+        let (port, is_udp) = addr.iter().find_map(|protocol| match protocol {
+            Protocol::Udp(port) => Some((port, true)),
+            Protocol::Tcp(port) => Some((port, false)),
+            _ => None,
+        })?;
+        let use_quic = match transport_mode {
+            TransportMode::Quic => true,
+            TransportMode::Tcp => false,
+            TransportMode::QuicAndTcp => is_udp,
+        };
+        if use_quic {
+            output_addr.push(Protocol::Udp(port));
+            output_addr.push(Protocol::QuicV1);
+        } else {
+            output_addr.push(Protocol::Tcp(port));
+        }
 
         let peer_id = {
             if let Some(peer_id) = peer_id {
@@ -7,19 +7,30 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 #![allow(clippy::mutable_key_type)]
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bandwidth::BandwidthLimiter;
 use crate::target_arch::spawn;
 use crate::{event::NetworkEvent, target_arch::Instant};
 use libp2p::{
     kad::{KBucketDistance as Distance, RecordKey, K_VALUE},
     PeerId,
 };
+#[cfg(feature = "open-metrics")]
+use prometheus_client::metrics::counter::Counter;
 use sn_protocol::{storage::RecordType, NetworkAddress, PrettyPrintRecordKey};
 use std::collections::{hash_map::Entry, BTreeSet, HashMap};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
 use tokio::{sync::mpsc, time::Duration};
 
 // Max parallel fetches that can be undertaken at the same time.
 const MAX_PARALLEL_FETCH: usize = K_VALUE.get();
 
+// Max parallel fetches when the download bandwidth is running low,
+// leaving the rest of the bandwidth to the client GETs.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_PARALLEL_FETCH_WHEN_CONGESTED: usize = 1;
+
 // The duration after which a peer will be considered failed to fetch data from,
 // if no response got from that peer.
 // Note this will also cover the period that node self write the fetched copy to disk.
@@ -47,6 +58,20 @@ pub(crate) struct ReplicationFetcher {
     /// used when the node is full, but we still have "close" data coming in
     /// that is _not_ closer than our farthest max record
     farthest_acceptable_distance: Option<Distance>,
+    /// Used to lower the priority of the replication when the download bandwidth is limited
+    #[cfg(not(target_arch = "wasm32"))]
+    bandwidth_limiter: Option<Arc<BandwidthLimiter>>,
+    #[cfg(feature = "open-metrics")]
+    fetch_metrics: Option<ReplicationFetchMetrics>,
+}
+
+/// The keys fetched during replication, and those that failed to be fetched.
+/// Both count keys, so their ratio is the failure rate of the fetches.
+#[cfg(feature = "open-metrics")]
+#[derive(Debug, Clone)]
+pub(crate) struct ReplicationFetchMetrics {
+    pub(crate) fetches: Counter,
+    pub(crate) failed_fetches: Counter,
 }
 
 impl ReplicationFetcher {
@@ -59,9 +84,43 @@ impl ReplicationFetcher {
             event_sender,
             distance_range: None,
             farthest_acceptable_distance: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            bandwidth_limiter: None,
+            #[cfg(feature = "open-metrics")]
+            fetch_metrics: None,
         }
     }
 
+    /// Set the counters of the keys fetched and failed to be fetched
+    #[cfg(feature = "open-metrics")]
+    pub(crate) fn set_fetch_metrics(mut self, fetch_metrics: ReplicationFetchMetrics) -> Self {
+        self.fetch_metrics = Some(fetch_metrics);
+        self
+    }
+
+    /// Set the limiter used to lower the priority of the replication fetches
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn set_bandwidth_limiter(
+        mut self,
+        bandwidth_limiter: Arc<BandwidthLimiter>,
+    ) -> Self {
+        self.bandwidth_limiter = Some(bandwidth_limiter);
+        self
+    }
+
+    /// The number of fetches that can be undertaken at the same time.
+    /// Replication has a lower priority than the client GETs, hence is slowed down
+    /// when the download bandwidth is running low.
+    fn max_parallel_fetch(&self) -> usize {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(limiter) = &self.bandwidth_limiter {
+            if limiter.is_download_congested() {
+                return MAX_PARALLEL_FETCH_WHEN_CONGESTED;
+            }
+        }
+        MAX_PARALLEL_FETCH
+    }
+
     /// Set the distance range.
     pub(crate) fn set_replication_distance_range(&mut self, distance_range: u32) {
         self.distance_range = Some(distance_range);
@@ -148,6 +207,10 @@ impl ReplicationFetcher {
                 .or_insert(Instant::now() + PENDING_TIMEOUT);
         });
 
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.fetch_metrics {
+            let _ = metrics.fetches.inc_by(keys_to_fetch.len() as u64);
+        }
         keys_to_fetch.extend(self.next_keys_to_fetch());
 
         keys_to_fetch
@@ -218,13 +281,14 @@ impl ReplicationFetcher {
 
     // Returns the set of keys that has to be fetched from the peer/network.
     // Target must not be under-fetching
-    // and no more than MAX_PARALLEL_FETCH fetches to be undertaken at the same time.
+    // and no more than `max_parallel_fetch` fetches to be undertaken at the same time.
     pub(crate) fn next_keys_to_fetch(&mut self) -> Vec<(PeerId, RecordKey)> {
         self.prune_expired_keys_and_slow_nodes();
 
         trace!("Next to fetch....");
 
-        if self.on_going_fetches.len() >= MAX_PARALLEL_FETCH {
+        let max_parallel_fetch = self.max_parallel_fetch();
+        if self.on_going_fetches.len() >= max_parallel_fetch {
             warn!("Replication Fetcher doesn't have free fetch capacity. Currently has {} entries in queue.",
                 self.to_be_fetched.len());
             return vec![];
@@ -253,7 +317,7 @@ impl ReplicationFetcher {
             // Already carried out expiration pruning above.
             // Hence here only need to check whether is ongoing fetching.
             // Also avoid fetching same record from different nodes.
-            if self.on_going_fetches.len() < MAX_PARALLEL_FETCH
+            if self.on_going_fetches.len() < max_parallel_fetch
                 && !self
                     .on_going_fetches
                     .contains_key(&(key.clone(), t.clone()))
@@ -266,7 +330,7 @@ impl ReplicationFetcher {
             }
 
             // break out the loop early if we can do no more now
-            if self.on_going_fetches.len() >= MAX_PARALLEL_FETCH {
+            if self.on_going_fetches.len() >= max_parallel_fetch {
                 break;
             }
         }
@@ -283,6 +347,10 @@ impl ReplicationFetcher {
                 pretty_keys
             );
         }
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.fetch_metrics {
+            let _ = metrics.fetches.inc_by(data_to_fetch.len() as u64);
+        }
 
         data_to_fetch
             .iter()
@@ -311,6 +379,11 @@ impl ReplicationFetcher {
                 }
             });
 
+        #[cfg(feature = "open-metrics")]
+        if let Some(metrics) = &self.fetch_metrics {
+            let _ = metrics.failed_fetches.inc_by(failed_fetches.len() as u64);
+        }
+
         let mut failed_holders = BTreeSet::new();
 
         for (record_key, peer_id) in failed_fetches {
@@ -3,3 +3,4 @@
 pub(crate) mod mod_impl;
 
 pub(crate) use mod_impl::build_transport;
+pub(crate) use sn_protocol::transport::TransportMode;
@@ -1,45 +1,80 @@
-#[cfg(feature = "websockets")]
+use super::TransportMode;
 use futures::future::Either;
-#[cfg(feature = "websockets")]
-use libp2p::{core::upgrade, noise, yamux};
 use libp2p::{
-    core::{muxing::StreamMuxerBox, transport},
+    core::{muxing::StreamMuxerBox, transport, upgrade},
     identity::Keypair,
-    PeerId, Transport as _,
+    noise, yamux, PeerId, Transport as _,
 };
 
-pub(crate) fn build_transport(keypair: &Keypair) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
-    let trans = generate_quic_transport(keypair);
+pub(crate) fn build_transport(
+    keypair: &Keypair,
+    mode: TransportMode,
+) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
+    let generate_quic_transport = || {
+        libp2p::quic::tokio::Transport::new(libp2p::quic::Config::new(keypair))
+            .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)))
+    };
 
-    #[cfg(feature = "websockets")]
     // Using a closure here due to the complex return type
-    let generate_ws_transport = || {
-        let tcp = libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default());
-        libp2p::websocket::WsConfig::new(tcp)
-            .upgrade(upgrade::Version::V1)
-            .authenticate(
-                noise::Config::new(keypair)
-                    .expect("Signing libp2p-noise static DH keypair failed."),
-            )
-            .multiplex(yamux::Config::default())
+    let generate_tcp_transport = || {
+        // Reusing the listening port for the outgoing connections, so the addresses observed by
+        // the other peers carry our listening port and can be confirmed as our external address.
+        libp2p::tcp::tokio::Transport::new(
+            libp2p::tcp::Config::default()
+                .nodelay(true)
+                .port_reuse(true),
+        )
+        .upgrade(upgrade::Version::V1)
+        .authenticate(
+            noise::Config::new(keypair).expect("Signing libp2p-noise static DH keypair failed."),
+        )
+        .multiplex(yamux::Config::default())
+        .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)))
     };
 
-    // With the `websockets` feature enabled, we add it as a fallback transport.
-    #[cfg(feature = "websockets")]
-    let trans = trans
-        .or_transport(generate_ws_transport())
-        .map(|either_output, _| match either_output {
-            Either::Left((peer_id, muxer)) => (peer_id, StreamMuxerBox::new(muxer)),
-            Either::Right((peer_id, muxer)) => (peer_id, StreamMuxerBox::new(muxer)),
-        });
-    #[cfg(not(feature = "websockets"))]
-    let trans = trans.map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)));
+    let trans = match mode {
+        TransportMode::Quic => generate_quic_transport().boxed(),
+        TransportMode::Tcp => generate_tcp_transport().boxed(),
+        // All the addresses of a peer are dialed concurrently, the first connection established wins.
+        TransportMode::QuicAndTcp => generate_quic_transport()
+            .or_transport(generate_tcp_transport())
+            .map(|either_output, _| match either_output {
+                Either::Left(output) => output,
+                Either::Right(output) => output,
+            })
+            .boxed(),
+    };
 
-    trans.boxed()
+    with_websockets(trans, keypair)
 }
 
-fn generate_quic_transport(
+/// With the `websockets` feature enabled, we add it as a fallback transport.
+#[cfg(feature = "websockets")]
+fn with_websockets(
+    trans: transport::Boxed<(PeerId, StreamMuxerBox)>,
     keypair: &Keypair,
-) -> libp2p::quic::GenTransport<libp2p::quic::tokio::Provider> {
-    libp2p::quic::tokio::Transport::new(libp2p::quic::Config::new(keypair))
+) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
+    let tcp = libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default());
+    let ws = libp2p::websocket::WsConfig::new(tcp)
+        .upgrade(upgrade::Version::V1)
+        .authenticate(
+            noise::Config::new(keypair).expect("Signing libp2p-noise static DH keypair failed."),
+        )
+        .multiplex(yamux::Config::default());
+
+    trans
+        .or_transport(ws)
+        .map(|either_output, _| match either_output {
+            Either::Left(output) => output,
+            Either::Right((peer_id, muxer)) => (peer_id, StreamMuxerBox::new(muxer)),
+        })
+        .boxed()
+}
+
+#[cfg(not(feature = "websockets"))]
+fn with_websockets(
+    trans: transport::Boxed<(PeerId, StreamMuxerBox)>,
+    _keypair: &Keypair,
+) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
+    trans
 }
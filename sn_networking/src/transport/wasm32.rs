@@ -1,13 +1,17 @@
 // wasm32 environments typically only support WebSockets (and WebRTC or WebTransport), so no plain UDP or TCP.
 
+use super::TransportMode;
 use libp2p::{
     core::{muxing::StreamMuxerBox, transport, upgrade},
     identity::Keypair,
     noise, websocket_websys, yamux, PeerId, Transport as _,
 };
 
-pub(crate) fn build_transport(keypair: &Keypair) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
-    // We build a single transport here, WebSockets.
+pub(crate) fn build_transport(
+    keypair: &Keypair,
+    _mode: TransportMode,
+) -> transport::Boxed<(PeerId, StreamMuxerBox)> {
+    // We build a single transport here, WebSockets, whatever the transport mode.
     websocket_websys::Transport::default()
         .upgrade(upgrade::Version::V1)
         .authenticate(
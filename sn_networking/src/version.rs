@@ -7,42 +7,114 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use lazy_static::lazy_static;
+use libp2p::StreamProtocol;
+use sn_protocol::version::WireVersionRange;
+
+/// The truncated crate version the protocols were identified by before the wire version negotiation.
+/// It is frozen so we keep talking to the peers not negotiating yet, as wire version 0.
+const LEGACY_TRUNCATED_VERSION_STR: &str = "0.16";
 
 lazy_static! {
     /// The node version used during Identify Behaviour.
+    /// Peers not negotiating the wire version yet only consider the peers with this exact agent version
+    /// as nodes, hence it keeps the legacy truncated version during the transition.
     pub static ref IDENTIFY_NODE_VERSION_STR: String =
         format!(
-            "safe{}/node/{}",
+            "safe{}/node/{LEGACY_TRUNCATED_VERSION_STR}",
             write_network_version_with_slash(),
-            get_truncate_version_str()
         );
 
     /// The client version used during Identify Behaviour.
     pub static ref IDENTIFY_CLIENT_VERSION_STR: String =
         format!(
-            "safe{}/client/{}",
+            "safe{}/client/{LEGACY_TRUNCATED_VERSION_STR}",
             write_network_version_with_slash(),
-            get_truncate_version_str()
         );
 
-    /// / first version for the req/response protocol
-    pub static ref REQ_RESPONSE_VERSION_STR: String =
+    /// The identify protocol version.
+    /// Peers not negotiating the wire version yet reject any other identify protocol version,
+    /// hence the legacy one is kept during the transition.
+    /// The supported wire versions are advertised through the req/response protocols listed by Identify,
+    /// and negotiated per stream.
+    pub static ref IDENTIFY_PROTOCOL_STR: String =
         format!(
-            "/safe{}/node/{}",
+            "safe{}/{LEGACY_TRUNCATED_VERSION_STR}",
             write_network_version_with_slash(),
-            get_truncate_version_str()
         );
 
+    /// The prefix of the node agent version, regardless of the crate version
+    static ref IDENTIFY_NODE_AGENT_PREFIX: String =
+        format!("safe{}/node/", write_network_version_with_slash());
+}
 
-    /// The identify protocol version
-    pub static ref IDENTIFY_PROTOCOL_STR: String =
+/// The req/response protocol for a given wire version.
+/// The version 0 is the legacy protocol identified by the truncated crate version.
+pub fn req_response_protocol_str(wire_version: u32) -> String {
+    if wire_version == 0 {
         format!(
-            "safe{}/{}",
-            write_network_version_with_slash(),
-            get_truncate_version_str()
-        );
+            "/safe{}/node/{LEGACY_TRUNCATED_VERSION_STR}",
+            write_network_version_with_slash()
+        )
+    } else {
+        format!(
+            "/safe{}/node/wire/{wire_version}",
+            write_network_version_with_slash()
+        )
+    }
+}
+
+/// The req/response protocols we support, in order of preference, i.e. the highest wire version first.
+/// Identify lists them to the peers, which advertises our range of wire versions,
+/// and the protocol negotiation of each stream then picks the highest version supported by both peers.
+pub fn req_response_protocols() -> Vec<String> {
+    WireVersionRange::supported()
+        .versions()
+        .map(req_response_protocol_str)
+        .collect()
+}
+
+/// The wire version of a negotiated req/response protocol
+pub fn wire_version_of_protocol(protocol: &str) -> Option<u32> {
+    WireVersionRange::supported()
+        .versions()
+        .find(|version| req_response_protocol_str(*version) == protocol)
+}
+
+/// The wire version of a req/response protocol advertised by a peer, including the ones we do not support
+fn advertised_wire_version(protocol: &str) -> Option<u32> {
+    if protocol == req_response_protocol_str(0) {
+        return Some(0);
+    }
+    let prefix = format!("/safe{}/node/wire/", write_network_version_with_slash());
+    protocol.strip_prefix(&prefix)?.parse().ok()
+}
 
+/// The range of wire versions a peer supports, as advertised by the req/response protocols Identify lists.
+/// Clients only open outbound streams and do not list any, then the ones using the legacy identify
+/// protocol version are known to support at least the version 0.
+pub fn wire_version_range_of_peer(
+    identify_protocol: &str,
+    protocols: &[StreamProtocol],
+) -> Option<WireVersionRange> {
+    let versions: Vec<u32> = protocols
+        .iter()
+        .filter_map(|protocol| advertised_wire_version(protocol.as_ref()))
+        .collect();
+    match (versions.iter().min(), versions.iter().max()) {
+        (Some(min), Some(max)) => Some(WireVersionRange {
+            min: *min,
+            max: *max,
+        }),
+        _ if identify_protocol == IDENTIFY_PROTOCOL_STR.as_str() => {
+            Some(WireVersionRange { min: 0, max: 0 })
+        }
+        _ => None,
+    }
+}
 
+/// Returns true if the Identify agent version is the one of a node, of any release
+pub fn is_node_agent_version(agent_version: &str) -> bool {
+    agent_version.starts_with(IDENTIFY_NODE_AGENT_PREFIX.as_str())
 }
 
 /// Get the network version string.
@@ -74,14 +146,55 @@ fn write_network_version_with_slash() -> String {
     }
 }
 
-// Protocol support shall be downward compatible for patch only version update.
-// i.e. versions of `A.B.X` or `A.B.X-alpha.Y` shall be considered as a same protocol of `A.B`
-fn get_truncate_version_str() -> String {
-    let version_str = env!("CARGO_PKG_VERSION");
-    let parts = version_str.split('.').collect::<Vec<_>>();
-    if parts.len() >= 2 {
-        format!("{}.{}", parts[0], parts[1])
-    } else {
-        panic!("Cannot obtain truncated version str for {version_str:?}: {parts:?}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn req_response_protocols_map_back_to_their_wire_version() {
+        let protocols = req_response_protocols();
+        assert_eq!(
+            protocols.len(),
+            WireVersionRange::supported().versions().count()
+        );
+        for (protocol, version) in protocols
+            .iter()
+            .zip(WireVersionRange::supported().versions())
+        {
+            assert_eq!(wire_version_of_protocol(protocol), Some(version));
+        }
+        assert_eq!(wire_version_of_protocol("/safe/node/wire/999"), None);
+    }
+
+    #[test]
+    fn peers_wire_version_range_is_parsed() {
+        let protocols = |versions: &[u32]| -> Vec<StreamProtocol> {
+            versions
+                .iter()
+                .map(|version| {
+                    StreamProtocol::try_from_owned(req_response_protocol_str(*version))
+                        .expect("valid protocol")
+                })
+                .chain([StreamProtocol::new("/ipfs/id/1.0.0")])
+                .collect()
+        };
+        assert_eq!(
+            wire_version_range_of_peer(&IDENTIFY_PROTOCOL_STR, &protocols(&[3, 2, 0])),
+            Some(WireVersionRange { min: 0, max: 3 })
+        );
+        assert_eq!(
+            wire_version_range_of_peer(&IDENTIFY_PROTOCOL_STR, &protocols(&[])),
+            Some(WireVersionRange { min: 0, max: 0 })
+        );
+        assert_eq!(
+            wire_version_range_of_peer("safe/unknown", &protocols(&[2])),
+            Some(WireVersionRange { min: 2, max: 2 })
+        );
+        assert_eq!(
+            wire_version_range_of_peer("safe/unknown", &protocols(&[])),
+            None
+        );
+        assert!(is_node_agent_version(&IDENTIFY_NODE_VERSION_STR));
+        assert!(!is_node_agent_version(&IDENTIFY_CLIENT_VERSION_STR));
     }
 }
@@ -17,6 +17,7 @@ use libp2p::{identity::Keypair, PeerId};
 #[cfg(feature = "metrics")]
 use sn_logging::metrics::init_metrics;
 use sn_logging::{Level, LogFormat, LogOutputDest, ReloadHandle};
+use sn_networking::{BandwidthLimits, TransportMode};
 use sn_node::{Marker, NodeBuilder, NodeEvent, NodeEventsReceiver};
 use sn_peers_acquisition::PeersArgs;
 use sn_protocol::{node::get_safenode_root_dir, node_rpc::NodeCtrl};
@@ -26,6 +27,7 @@ use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     process::Command,
+    str::FromStr,
     time::Duration,
 };
 use tokio::{
@@ -79,6 +81,38 @@ struct Opt {
     #[clap(long, default_value_t = false)]
     upnp: bool,
 
+    /// Specify the transports the node listens on and dials with.
+    ///
+    /// Valid values are "quic", "tcp" or "quic-and-tcp".
+    ///
+    /// `quic` is the default value. Use `tcp` on networks where UDP is blocked.
+    ///
+    /// The peers only known by an address of the other transport are dialed on the same port
+    /// number with the selected transport.
+    #[clap(long, default_value_t = TransportMode::Quic, value_parser = TransportMode::from_str, verbatim_doc_comment)]
+    transport: TransportMode,
+
+    /// Specify the maximum upload bandwidth of the node, in kilobytes per second.
+    ///
+    /// If the argument is not used, the upload bandwidth is unlimited.
+    #[clap(long, verbatim_doc_comment)]
+    max_upload_bandwidth: Option<u64>,
+
+    /// Specify the maximum download bandwidth of the node, in kilobytes per second.
+    ///
+    /// If the argument is not used, the download bandwidth is unlimited.
+    ///
+    /// When the limit is approached, the replication between nodes is slowed down to leave room for
+    /// the GETs of the clients.
+    #[clap(long, verbatim_doc_comment)]
+    max_download_bandwidth: Option<u64>,
+
+    /// Specify the maximum upload and download bandwidth with each peer, in kilobytes per second.
+    ///
+    /// If the argument is not used, the bandwidth with each peer is only limited by the global limits.
+    #[clap(long, verbatim_doc_comment)]
+    max_peer_bandwidth: Option<u64>,
+
     /// Specify the logging output destination.
     ///
     /// Valid values are "stdout", "data-dir", or a custom path.
@@ -190,7 +224,11 @@ fn main() -> Result<()> {
         init_logging(&opt, keypair.public().to_peer_id())?;
 
     let rt = Runtime::new()?;
-    let bootstrap_peers = rt.block_on(opt.peers.get_peers())?;
+    let bootstrap_peers: Vec<_> = rt
+        .block_on(opt.peers.get_peers())?
+        .into_iter()
+        .map(|peer| opt.transport.adapt_addr(peer))
+        .collect();
     let msg = format!(
         "Running {} v{}",
         env!("CARGO_BIN_NAME"),
@@ -222,6 +260,12 @@ fn main() -> Result<()> {
             opt.upnp,
         );
         node_builder.is_behind_home_network = opt.home_network;
+        node_builder.transport_mode(opt.transport);
+        node_builder.bandwidth_limits(BandwidthLimits {
+            upload: opt.max_upload_bandwidth.map(|kbps| kbps * 1000),
+            download: opt.max_download_bandwidth.map(|kbps| kbps * 1000),
+            per_peer: opt.max_peer_bandwidth.map(|kbps| kbps * 1000),
+        });
         #[cfg(feature = "open-metrics")]
         let mut node_builder = node_builder;
         // if enable flag is provided or only if the port is specified then enable the server by setting Some()
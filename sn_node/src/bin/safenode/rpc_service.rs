@@ -11,10 +11,11 @@ use sn_logging::ReloadHandle;
 use sn_node::RunningNode;
 use sn_protocol::node_rpc::NodeCtrl;
 use sn_protocol::safenode_proto::{
-    k_buckets_response,
+    k_buckets_response, node_event_history_response, peer_reputations_response,
     safe_node_server::{SafeNode, SafeNodeServer},
     KBucketsRequest, KBucketsResponse, NetworkInfoRequest, NetworkInfoResponse, NodeEvent,
-    NodeEventsRequest, NodeInfoRequest, NodeInfoResponse, RecordAddressesRequest,
+    NodeEventHistoryRequest, NodeEventHistoryResponse, NodeEventsRequest, NodeInfoRequest,
+    NodeInfoResponse, PeerReputationsRequest, PeerReputationsResponse, RecordAddressesRequest,
     RecordAddressesResponse, RestartRequest, RestartResponse, StopRequest, StopResponse,
     UpdateLogLevelRequest, UpdateLogLevelResponse, UpdateRequest, UpdateResponse,
 };
@@ -23,7 +24,7 @@ use std::{
     env,
     net::SocketAddr,
     process,
-    time::{Duration, Instant},
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 use tokio::sync::mpsc::{self, Sender};
 use tokio_stream::wrappers::ReceiverStream;
@@ -142,6 +143,45 @@ impl SafeNode for SafeNodeRpcService {
         Ok(Response::new(ReceiverStream::new(client_rx)))
     }
 
+    async fn node_event_history(
+        &self,
+        request: Request<NodeEventHistoryRequest>,
+    ) -> Result<Response<NodeEventHistoryResponse>, Status> {
+        debug!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+
+        let since = UNIX_EPOCH + Duration::from_secs(request.get_ref().since_secs);
+        let mut events = vec![];
+        for record in self
+            .running_node
+            .node_events_channel()
+            .history(since, &request.get_ref().kinds)
+        {
+            let event_bytes = match record.event.to_bytes() {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    debug!("Error {err:?} while converting NodeEvent to bytes, ignoring the event");
+                    continue;
+                }
+            };
+            events.push(node_event_history_response::Entry {
+                timestamp_secs: record
+                    .timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                kind: record.event.kind().to_string(),
+                description: format!("{:?}", record.event),
+                event: event_bytes,
+            });
+        }
+
+        Ok(Response::new(NodeEventHistoryResponse { events }))
+    }
+
     async fn record_addresses(
         &self,
         request: Request<RecordAddressesRequest>,
@@ -190,6 +230,50 @@ impl SafeNode for SafeNodeRpcService {
         Ok(Response::new(KBucketsResponse { kbuckets }))
     }
 
+    async fn peer_reputations(
+        &self,
+        request: Request<PeerReputationsRequest>,
+    ) -> Result<Response<PeerReputationsResponse>, Status> {
+        debug!(
+            "RPC request received at {}: {:?}",
+            self.addr,
+            request.get_ref()
+        );
+
+        let reputations = self
+            .running_node
+            .get_peer_reputations()
+            .await
+            .map_err(|err| {
+                Status::new(
+                    Code::Internal,
+                    format!("Failed to get the peer reputations: {err}"),
+                )
+            })?
+            .into_iter()
+            .map(|reputation| peer_reputations_response::Reputation {
+                peer_id: reputation.peer_id.to_bytes(),
+                is_bad: reputation.is_bad,
+                scores: reputation
+                    .scores
+                    .into_iter()
+                    .map(|(issue, score)| (format!("{issue:?}"), score))
+                    .collect(),
+                evidences: reputation
+                    .evidences
+                    .into_iter()
+                    .map(|evidence| peer_reputations_response::Evidence {
+                        issue: format!("{:?}", evidence.issue),
+                        details: evidence.details,
+                        age_secs: evidence.age.as_secs(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Response::new(PeerReputationsResponse { reputations }))
+    }
+
     async fn stop(&self, request: Request<StopRequest>) -> Result<Response<StopResponse>, Status> {
         debug!(
             "RPC request received at {}: {:?}",
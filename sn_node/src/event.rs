@@ -6,39 +6,84 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    event_history::{NodeEventHistory, NodeEventRecord},
+};
 
 use serde::{Deserialize, Serialize};
-use sn_protocol::storage::{ChunkAddress, RegisterAddress};
-use sn_transfers::UniquePubkey;
+use sn_protocol::{
+    storage::{ChunkAddress, RegisterAddress},
+    NetworkAddress,
+};
+use sn_transfers::{NanoTokens, UniquePubkey};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+use strum::IntoStaticStr;
 use tokio::sync::broadcast;
 
 const NODE_EVENT_CHANNEL_SIZE: usize = 500;
 
 /// Channel where users of the public API can listen to events broadcasted by the node.
+/// The events are also kept in a bounded history, so they can be queried after the fact.
 #[derive(Clone)]
-pub struct NodeEventsChannel(broadcast::Sender<NodeEvent>);
+pub struct NodeEventsChannel {
+    sender: broadcast::Sender<NodeEvent>,
+    history: Arc<Mutex<NodeEventHistory>>,
+}
 
 /// Type of channel receiver where events are broadcasted to by the node.
 pub type NodeEventsReceiver = broadcast::Receiver<NodeEvent>;
 
 impl Default for NodeEventsChannel {
     fn default() -> Self {
-        Self(broadcast::channel(NODE_EVENT_CHANNEL_SIZE).0)
+        Self {
+            sender: broadcast::channel(NODE_EVENT_CHANNEL_SIZE).0,
+            history: Arc::new(Mutex::new(NodeEventHistory::default())),
+        }
     }
 }
 
 impl NodeEventsChannel {
+    /// Creates a channel whose history is persisted in the node's root dir, picking up the events
+    /// recorded before a restart.
+    pub(crate) fn with_persisted_history(root_dir: &Path) -> Self {
+        Self {
+            sender: broadcast::channel(NODE_EVENT_CHANNEL_SIZE).0,
+            history: Arc::new(Mutex::new(NodeEventHistory::load(root_dir))),
+        }
+    }
+
     /// Returns a new receiver to listen to the channel.
     /// Multiple receivers can be actively listening.
     pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
-        self.0.subscribe()
+        self.sender.subscribe()
+    }
+
+    /// Returns the events from the history that happened at or after `since`, oldest first.
+    /// When `kinds` is not empty, only the events of these kinds are returned, see `NodeEvent::kind`.
+    pub fn history(&self, since: SystemTime, kinds: &[String]) -> Vec<NodeEventRecord> {
+        match self.history.lock() {
+            Ok(history) => history.query(since, kinds),
+            Err(err) => {
+                error!("The node event history lock is poisoned: {err}");
+                vec![]
+            }
+        }
     }
 
     // Broadcast a new event, meant to be a helper only used by the sn_node's internals.
     pub(crate) fn broadcast(&self, event: NodeEvent) {
+        match self.history.lock() {
+            Ok(mut history) => history.push(event.clone()),
+            Err(err) => error!("The node event history lock is poisoned: {err}"),
+        }
+
         let event_string = format!("{event:?}");
-        if let Err(err) = self.0.send(event) {
+        if let Err(err) = self.sender.send(event) {
             trace!(
                 "Error occurred when trying to broadcast a node event ({event_string:?}): {err}"
             );
@@ -47,12 +92,12 @@ impl NodeEventsChannel {
 
     /// Returns the number of active receivers
     pub fn receiver_count(&self) -> usize {
-        self.0.receiver_count()
+        self.sender.receiver_count()
     }
 }
 
 /// Type of events broadcasted by the node to the public API.
-#[derive(Clone, Serialize, custom_debug::Debug, Deserialize)]
+#[derive(Clone, Serialize, custom_debug::Debug, Deserialize, IntoStaticStr)]
 pub enum NodeEvent {
     /// The node has been connected to the network
     ConnectedToNetwork,
@@ -68,9 +113,23 @@ pub enum NodeEvent {
     ChannelClosed,
     /// Terminates the node
     TerminateNode(String),
+    /// A peer has been considered as bad by this node, due to the detected bad behaviour
+    PeerConsideredAsBad {
+        /// The address of the bad peer
+        bad_peer: NetworkAddress,
+        /// The issue that made the peer bad
+        bad_behaviour: String,
+    },
+    /// A payment to store a record has been received, with the amount deposited to the wallet
+    RewardReceived(NanoTokens, NetworkAddress),
 }
 
 impl NodeEvent {
+    /// The name of the variant, used to filter the events from the history
+    pub fn kind(&self) -> &'static str {
+        self.into()
+    }
+
     /// Convert NodeEvent to bytes
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
         rmp_serde::to_vec(&self).map_err(|_| Error::NodeEventParsingFailed)
@@ -0,0 +1,294 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::event::NodeEvent;
+
+use serde::{Deserialize, Serialize};
+use sn_service_management::{
+    event_history::{node_event_history_path, read_node_event_history, PersistedNodeEvent},
+    rpc::NodeEventEntry,
+};
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+
+/// The maximum number of events retained in the history.
+const NODE_EVENT_HISTORY_SIZE: usize = 1000;
+
+/// A `NodeEvent` along with the time it was broadcasted by the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeEventRecord {
+    /// The time the event was broadcasted
+    pub timestamp: SystemTime,
+    /// The event itself
+    pub event: NodeEvent,
+}
+
+/// A bounded history of the events broadcasted by the node.
+///
+/// When backed by a file, the events are handed over to a writer thread, which appends them to
+/// the file so the history survives restarts and terminations. The writer compacts the file down
+/// to the retained events once it holds twice as many events as the history.
+#[derive(Debug, Default)]
+pub(crate) struct NodeEventHistory {
+    records: VecDeque<NodeEventRecord>,
+    writer: Option<HistoryWriter>,
+}
+
+#[derive(Debug)]
+struct HistoryWriter {
+    sender: mpsc::Sender<PersistedNodeEvent>,
+    handle: JoinHandle<()>,
+}
+
+impl NodeEventHistory {
+    /// Loads the history persisted in the node's root dir, starting an empty one if there is none.
+    pub(crate) fn load(root_dir: &Path) -> Self {
+        let file_path = node_event_history_path(root_dir);
+        let history_file = match read_node_event_history(root_dir) {
+            Ok(history_file) => history_file,
+            Err(err) => {
+                // leave the file untouched, it could still be recovered
+                warn!("Failed to read the node event history at {file_path:?}, it will not be persisted: {err:?}");
+                return Self::default();
+            }
+        };
+        // drop an event partially written before a termination, so the next ones are not
+        // appended to it
+        if let Err(err) = truncate(&file_path, history_file.valid_len) {
+            warn!("Failed to truncate the node event history at {file_path:?}: {err:?}");
+        }
+
+        let events_in_file = history_file.events.len();
+        let retained: VecDeque<_> = history_file
+            .events
+            .into_iter()
+            .skip(events_in_file.saturating_sub(NODE_EVENT_HISTORY_SIZE))
+            .collect();
+        let records = retained
+            .iter()
+            .filter_map(|persisted| match NodeEvent::from_bytes(&persisted.event) {
+                Ok(event) => Some(NodeEventRecord {
+                    timestamp: persisted.entry.timestamp,
+                    event,
+                }),
+                Err(err) => {
+                    debug!("Ignoring the undecodable node event {persisted:?}: {err:?}");
+                    None
+                }
+            })
+            .collect::<VecDeque<_>>();
+        debug!(
+            "Loaded {} node events from the history at {file_path:?}",
+            records.len()
+        );
+
+        Self {
+            records,
+            writer: Some(HistoryWriter::spawn(file_path, retained, events_in_file)),
+        }
+    }
+
+    /// Adds an event to the history, evicting the oldest one if the history is full.
+    pub(crate) fn push(&mut self, event: NodeEvent) {
+        let record = NodeEventRecord {
+            timestamp: SystemTime::now(),
+            event,
+        };
+
+        if let Some(writer) = &self.writer {
+            match record.event.to_bytes() {
+                Ok(event_bytes) => {
+                    let persisted = PersistedNodeEvent {
+                        entry: NodeEventEntry {
+                            timestamp: record.timestamp,
+                            kind: record.event.kind().to_string(),
+                            description: format!("{:?}", record.event),
+                        },
+                        event: event_bytes,
+                    };
+                    if writer.sender.send(persisted).is_err() {
+                        warn!(
+                            "The node event history writer has stopped, the event is not persisted"
+                        );
+                    }
+                }
+                Err(err) => warn!("Failed to encode the node event to persist it: {err:?}"),
+            }
+        }
+
+        if self.records.len() == NODE_EVENT_HISTORY_SIZE {
+            let _ = self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Returns the events that happened at or after `since`, oldest first.
+    /// When `kinds` is not empty, only the events of these kinds are returned.
+    pub(crate) fn query(&self, since: SystemTime, kinds: &[String]) -> Vec<NodeEventRecord> {
+        self.records
+            .iter()
+            .filter(|record| record.timestamp >= since)
+            .filter(|record| kinds.is_empty() || kinds.iter().any(|k| k == record.event.kind()))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Drop for NodeEventHistory {
+    // let the writer persist the pending events before the node stops
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            drop(writer.sender);
+            if writer.handle.join().is_err() {
+                error!("The node event history writer panicked");
+            }
+        }
+    }
+}
+
+impl HistoryWriter {
+    fn spawn(
+        file_path: PathBuf,
+        mut retained: VecDeque<PersistedNodeEvent>,
+        mut events_in_file: usize,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel::<PersistedNodeEvent>();
+        let handle = thread::spawn(move || {
+            // write the events received meanwhile in one go
+            while let Ok(event) = receiver.recv() {
+                let mut batch = vec![event];
+                batch.extend(receiver.try_iter());
+                for event in batch.iter() {
+                    if retained.len() == NODE_EVENT_HISTORY_SIZE {
+                        let _ = retained.pop_front();
+                    }
+                    retained.push_back(event.clone());
+                }
+
+                let result = if events_in_file + batch.len() >= 2 * NODE_EVENT_HISTORY_SIZE {
+                    compact(&file_path, &retained).map(|()| retained.len())
+                } else {
+                    append(&file_path, &batch).map(|()| events_in_file + batch.len())
+                };
+                match result {
+                    Ok(count) => events_in_file = count,
+                    Err(err) => {
+                        warn!("Failed to persist the node event history to {file_path:?}: {err:?}")
+                    }
+                }
+            }
+        });
+        Self { sender, handle }
+    }
+}
+
+fn truncate(file_path: &Path, len: u64) -> std::io::Result<()> {
+    match fs::metadata(file_path) {
+        Ok(metadata) if metadata.len() > len => {
+            OpenOptions::new().write(true).open(file_path)?.set_len(len)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn append(file_path: &Path, events: &[PersistedNodeEvent]) -> std::io::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    write_events(BufWriter::new(file), events.iter())
+}
+
+// The file is replaced atomically, so a crash mid-write never loses the retained events.
+fn compact(file_path: &Path, retained: &VecDeque<PersistedNodeEvent>) -> std::io::Result<()> {
+    let tmp_path = file_path.with_extension("tmp");
+    write_events(BufWriter::new(File::create(&tmp_path)?), retained.iter())?;
+    fs::rename(&tmp_path, file_path)
+}
+
+fn write_events<'a>(
+    mut writer: BufWriter<File>,
+    events: impl Iterator<Item = &'a PersistedNodeEvent>,
+) -> std::io::Result<()> {
+    for event in events {
+        let line = event.to_line().map_err(std::io::Error::other)?;
+        writer.write_all(&line)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn history_should_be_bounded_and_survive_a_reload() {
+        let root_dir = tempfile::tempdir().expect("Failed to create a temp dir");
+
+        let mut history = NodeEventHistory::load(root_dir.path());
+        history.push(NodeEvent::ConnectedToNetwork);
+        for _ in 0..(2 * NODE_EVENT_HISTORY_SIZE) {
+            history.push(NodeEvent::ChannelClosed);
+        }
+        history.push(NodeEvent::TerminateNode("test".to_string()));
+        assert_eq!(history.records.len(), NODE_EVENT_HISTORY_SIZE);
+        // waits for the writer to persist the events
+        drop(history);
+
+        let reloaded = NodeEventHistory::load(root_dir.path());
+        assert_eq!(reloaded.records.len(), NODE_EVENT_HISTORY_SIZE);
+        assert!(reloaded
+            .query(SystemTime::UNIX_EPOCH, &["ConnectedToNetwork".to_string()])
+            .is_empty());
+        let terminations = reloaded.query(SystemTime::UNIX_EPOCH, &["TerminateNode".to_string()]);
+        assert_eq!(terminations.len(), 1);
+        assert!(reloaded
+            .query(SystemTime::now() + Duration::from_secs(60), &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn partially_written_event_should_be_dropped_on_load() {
+        let root_dir = tempfile::tempdir().expect("Failed to create a temp dir");
+
+        let mut history = NodeEventHistory::load(root_dir.path());
+        history.push(NodeEvent::ConnectedToNetwork);
+        drop(history);
+
+        // a termination in the middle of writing an event
+        let file_path = node_event_history_path(root_dir.path());
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&file_path)
+            .expect("Failed to open the history");
+        file.write_all(br#"{"entry":{"timestamp""#)
+            .expect("Failed to write to the history");
+        drop(file);
+
+        let mut history = NodeEventHistory::load(root_dir.path());
+        assert_eq!(history.records.len(), 1);
+        history.push(NodeEvent::TerminateNode("test".to_string()));
+        drop(history);
+
+        let reloaded = NodeEventHistory::load(root_dir.path());
+        let kinds: Vec<_> = reloaded
+            .query(SystemTime::UNIX_EPOCH, &[])
+            .iter()
+            .map(|record| record.event.kind())
+            .collect();
+        assert_eq!(kinds, vec!["ConnectedToNetwork", "TerminateNode"]);
+    }
+}
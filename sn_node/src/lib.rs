@@ -29,6 +29,7 @@ extern crate tracing;
 
 mod error;
 mod event;
+mod event_history;
 mod log_markers;
 #[cfg(feature = "open-metrics")]
 mod metrics;
@@ -39,6 +40,7 @@ mod replication;
 
 pub use self::{
     event::{NodeEvent, NodeEventsChannel, NodeEventsReceiver},
+    event_history::NodeEventRecord,
     log_markers::Marker,
     node::{NodeBuilder, NodeCmd, PERIODIC_REPLICATION_INTERVAL_MAX_S},
 };
@@ -46,7 +48,7 @@ pub use self::{
 use crate::error::{Error, Result};
 
 use libp2p::PeerId;
-use sn_networking::{Network, SwarmLocalState};
+use sn_networking::{Network, PeerReputation, SwarmLocalState};
 use sn_protocol::{get_port_from_multiaddr, NetworkAddress};
 use sn_transfers::{HotWallet, NanoTokens};
 use std::{
@@ -130,4 +132,11 @@ impl RunningNode {
         let kbuckets = self.network.get_kbuckets().await?;
         Ok(kbuckets)
     }
+
+    /// Returns the reputation of the peers the node recorded issues against,
+    /// along with the evidence of these issues.
+    pub async fn get_peer_reputations(&self) -> Result<Vec<PeerReputation>> {
+        let reputations = self.network.get_peer_reputations().await?;
+        Ok(reputations)
+    }
 }
@@ -23,8 +23,8 @@ use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::registry::Registry;
 use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use sn_networking::{
-    close_group_majority, Instant, Network, NetworkBuilder, NetworkError, NetworkEvent, NodeIssue,
-    SwarmDriver, CLOSE_GROUP_SIZE,
+    close_group_majority, BandwidthLimits, Instant, Network, NetworkBuilder, NetworkError,
+    NetworkEvent, NodeIssue, SwarmDriver, TransportMode, CLOSE_GROUP_SIZE,
 };
 use sn_protocol::{
     error::Error as ProtocolError,
@@ -84,6 +84,8 @@ pub struct NodeBuilder {
     /// Enable hole punching for nodes connecting from home networks.
     pub is_behind_home_network: bool,
     owner: Option<String>,
+    transport_mode: TransportMode,
+    bandwidth_limits: BandwidthLimits,
     #[cfg(feature = "upnp")]
     upnp: bool,
 }
@@ -109,11 +111,23 @@ impl NodeBuilder {
             metrics_server_port: None,
             is_behind_home_network: false,
             owner,
+            transport_mode: TransportMode::default(),
+            bandwidth_limits: BandwidthLimits::default(),
             #[cfg(feature = "upnp")]
             upnp,
         }
     }
 
+    /// Set the transports the node listens on and dials with. Defaults to QUIC only
+    pub fn transport_mode(&mut self, transport_mode: TransportMode) {
+        self.transport_mode = transport_mode;
+    }
+
+    /// Set the upload and download bandwidth limits. Unlimited by default
+    pub fn bandwidth_limits(&mut self, bandwidth_limits: BandwidthLimits) {
+        self.bandwidth_limits = bandwidth_limits;
+    }
+
     #[cfg(feature = "open-metrics")]
     /// Set the port for the OpenMetrics server. Defaults to a random port if not set
     pub fn metrics_server_port(&mut self, port: Option<u16>) {
@@ -156,6 +170,7 @@ impl NodeBuilder {
             (None, None)
         };
 
+        let node_events_channel = NodeEventsChannel::with_persisted_history(&self.root_dir);
         let mut network_builder = NetworkBuilder::new(self.keypair, self.local, self.root_dir);
 
         network_builder.listen_addr(self.addr);
@@ -165,12 +180,13 @@ impl NodeBuilder {
         network_builder.metrics_server_port(self.metrics_server_port);
         network_builder.initial_peers(self.initial_peers.clone());
         network_builder.is_behind_home_network(self.is_behind_home_network);
+        network_builder.transport_mode(self.transport_mode);
+        network_builder.bandwidth_limits(self.bandwidth_limits);
 
         #[cfg(feature = "upnp")]
         network_builder.upnp(self.upnp);
 
         let (network, network_event_receiver, swarm_driver) = network_builder.build_node()?;
-        let node_events_channel = NodeEventsChannel::default();
         let (node_cmds, _) = broadcast::channel(10);
 
         let node = NodeInner {
@@ -482,6 +498,11 @@ impl Node {
                 bad_behaviour,
             } => {
                 event_header = "PeerConsideredAsBad";
+                self.events_channel()
+                    .broadcast(NodeEvent::PeerConsideredAsBad {
+                        bad_peer: NetworkAddress::from_peer(bad_peer),
+                        bad_behaviour: bad_behaviour.clone(),
+                    });
                 let request = Request::Cmd(Cmd::PeerConsideredAsBad {
                     detected_by: NetworkAddress::from_peer(detected_by),
                     bad_peer: NetworkAddress::from_peer(bad_peer),
@@ -602,9 +623,11 @@ impl Node {
                     // repeat the verification for couple of times (in case of error).
                     // Only report the node as bad when ALL the verification attempts failed.
                     let mut attempts = 0;
+                    let mut evidence = String::new();
                     while attempts < MAX_CHUNK_PROOF_VERIFY_ATTEMPTS {
-                        if chunk_proof_verify_peer(&network, peer_id, &keys_to_verify).await {
-                            return;
+                        match chunk_proof_verify_peer(&network, peer_id, &keys_to_verify).await {
+                            Ok(()) => return,
+                            Err(failure) => evidence = failure,
                         }
                         // Replication interval is 22s - 45s.
                         // Hence some re-try erquired to allow copies to spread out.
@@ -613,11 +636,15 @@ impl Node {
                     }
                     // Now ALL attempts failed, hence report the issue.
                     // Note this won't immediately trigger the node to be considered as BAD.
-                    // Only the same peer accumulated three same issue
-                    // within 5 mins will be considered as BAD.
+                    // Only the same peer accumulating a high enough score of the same issue
+                    // will be considered as BAD.
                     // As the chunk_proof_check will be triggered every periodical replication,
                     // a low performed or cheaty peer will raise multiple issue alerts during it.
-                    network.record_node_issues(peer_id, NodeIssue::FailedChunkProofCheck);
+                    network.record_node_issue_with_evidence(
+                        peer_id,
+                        NodeIssue::FailedChunkProofCheck,
+                        evidence,
+                    );
                 });
             }
         }
@@ -990,11 +1017,12 @@ fn write_forwarded_balance_value(balance_file_path: &PathBuf, balance: u64) -> R
     Ok(())
 }
 
+/// Returns the failed key and nonce as evidence when the peer fails the verification
 async fn chunk_proof_verify_peer(
     network: &Network,
     peer_id: PeerId,
     keys: &[NetworkAddress],
-) -> bool {
+) -> std::result::Result<(), String> {
     for key in keys.iter() {
         let check_passed = if let Ok(Some(record)) =
             network.get_local_record(&key.to_record_key()).await
@@ -1017,20 +1045,22 @@ async fn chunk_proof_verify_peer(
                 })
                 .count();
 
-            n_verified >= 1
+            if n_verified >= 1 {
+                Ok(())
+            } else {
+                Err(format!("failed chunk proof for {key:?} with nonce {nonce}"))
+            }
         } else {
             error!(
                  "To verify peer {peer_id:?} Could not get ChunkProof for {key:?} as we don't have the record locally."
             );
-            true
+            Ok(())
         };
 
-        if !check_passed {
-            return false;
-        }
+        check_passed?;
     }
 
-    true
+    Ok(())
 }
 
 fn received_valid_chunk_proof(
@@ -519,6 +519,11 @@ impl Node {
             "The new wallet balance is {new_balance}, after earning {}",
             new_balance - old_balance
         );
+        self.events_channel()
+            .broadcast(crate::NodeEvent::RewardReceived(
+                NanoTokens::from(new_balance - old_balance),
+                address.clone(),
+            ));
 
         #[cfg(feature = "open-metrics")]
         if let Some(node_metrics) = self.node_metrics() {
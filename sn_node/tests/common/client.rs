@@ -13,6 +13,7 @@ use sn_client::{
     acc_packet::{create_faucet_account_and_wallet, load_account_wallet_or_create_with_mnemonic},
     send, Client,
 };
+use sn_node_manager::{config::get_daemon_auth_token_path, daemon_auth};
 use sn_peers_acquisition::parse_peer_addr;
 use sn_protocol::safenode_proto::{NodeInfoRequest, RestartRequest};
 use sn_service_management::{
@@ -379,15 +380,16 @@ impl Droplet {
         daemon_endpoint: SocketAddr,
         retain_peer_id: bool,
     ) -> Result<()> {
+        let token = daemon_auth::read_token(&get_daemon_auth_token_path()?)?;
         let mut rpc_client = get_safenode_manager_rpc_client(daemon_endpoint).await?;
 
-        let _response = rpc_client
-            .restart_node_service(Request::new(NodeServiceRestartRequest {
-                peer_id: peer_id.to_bytes(),
-                delay_millis: 0,
-                retain_peer_id,
-            }))
-            .await?;
+        let mut request = Request::new(NodeServiceRestartRequest {
+            peer_id: peer_id.to_bytes(),
+            delay_millis: 0,
+            retain_peer_id,
+        });
+        daemon_auth::authorise_request(&mut request, &token)?;
+        let _response = rpc_client.restart_node_service(request).await?;
 
         println!("Node restart requested to safenodemand {daemon_endpoint}");
         info!("Node restart requested to safenodemand {daemon_endpoint}");
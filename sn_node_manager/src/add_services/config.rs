@@ -10,6 +10,7 @@ use color_eyre::{eyre::eyre, Result};
 use libp2p::Multiaddr;
 use service_manager::{ServiceInstallCtx, ServiceLabel};
 use sn_logging::LogFormat;
+use sn_service_management::{BandwidthLimits, ResourceLimits};
 use std::{
     ffi::OsString,
     net::{Ipv4Addr, SocketAddr},
@@ -17,7 +18,7 @@ use std::{
     str::FromStr,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PortRange {
     Single(u16),
     Range(u16, u16),
@@ -43,6 +44,7 @@ pub fn parse_port_range(s: &str) -> Result<PortRange> {
 #[derive(Debug, PartialEq)]
 pub struct InstallNodeServiceCtxBuilder {
     pub autostart: bool,
+    pub bandwidth_limits: BandwidthLimits,
     pub bootstrap_peers: Vec<Multiaddr>,
     pub data_dir_path: PathBuf,
     pub env_variables: Option<Vec<(String, String)>>,
@@ -55,6 +57,7 @@ pub struct InstallNodeServiceCtxBuilder {
     pub metrics_port: Option<u16>,
     pub node_port: Option<u16>,
     pub owner: Option<String>,
+    pub resource_limits: ResourceLimits,
     pub rpc_socket_addr: SocketAddr,
     pub safenode_path: PathBuf,
     pub service_user: Option<String>,
@@ -101,6 +104,7 @@ impl InstallNodeServiceCtxBuilder {
             args.push(OsString::from("--owner"));
             args.push(OsString::from(owner));
         }
+        args.extend(self.bandwidth_limits.to_args());
 
         if !self.bootstrap_peers.is_empty() {
             let peers_str = self
@@ -113,7 +117,7 @@ impl InstallNodeServiceCtxBuilder {
             args.push(OsString::from(peers_str));
         }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: self.autostart,
             contents: None,
@@ -122,13 +126,20 @@ impl InstallNodeServiceCtxBuilder {
             program: self.safenode_path.to_path_buf(),
             username: self.service_user.clone(),
             working_directory: None,
-        })
+        };
+        // User-mode services are installed without a service user.
+        install_ctx.contents = self
+            .resource_limits
+            .service_definition(&install_ctx, self.service_user.is_none())?;
+        Ok(install_ctx)
     }
 }
 
+#[derive(Default)]
 pub struct AddNodeServiceOptions {
     pub auto_restart: bool,
     pub auto_set_nat_flags: bool,
+    pub bandwidth_limits: BandwidthLimits,
     pub bootstrap_peers: Vec<Multiaddr>,
     pub count: Option<u16>,
     pub delete_safenode_src: bool,
@@ -141,6 +152,7 @@ pub struct AddNodeServiceOptions {
     pub metrics_port: Option<PortRange>,
     pub owner: Option<String>,
     pub node_port: Option<PortRange>,
+    pub resource_limits: ResourceLimits,
     pub rpc_address: Option<Ipv4Addr>,
     pub rpc_port: Option<PortRange>,
     pub safenode_src_path: PathBuf,
@@ -16,6 +16,7 @@ use self::config::{
 };
 use crate::{
     config::{create_owned_dir, get_user_safenode_data_dir},
+    output::{FailedOperations, ServiceOutcome},
     VerbosityLevel, DAEMON_SERVICE_NAME,
 };
 use color_eyre::{
@@ -229,6 +230,7 @@ pub async fn add_node(
 
         let install_ctx = InstallNodeServiceCtxBuilder {
             autostart: options.auto_restart,
+            bandwidth_limits: options.bandwidth_limits,
             bootstrap_peers: options.bootstrap_peers.clone(),
             data_dir_path: service_data_dir_path.clone(),
             env_variables: options.env_variables.clone(),
@@ -241,6 +243,7 @@ pub async fn add_node(
             name: service_name.clone(),
             node_port,
             owner: options.owner.clone(),
+            resource_limits: options.resource_limits,
             rpc_socket_addr,
             safenode_path: service_safenode_path.clone(),
             service_user: options.user.clone(),
@@ -261,6 +264,7 @@ pub async fn add_node(
 
                 node_registry.nodes.push(NodeServiceData {
                     auto_restart: options.auto_restart,
+                    bandwidth_limits: options.bandwidth_limits,
                     connected_peers: None,
                     data_dir_path: service_data_dir_path.clone(),
                     genesis: options.genesis,
@@ -272,6 +276,7 @@ pub async fn add_node(
                     metrics_port: metrics_free_port,
                     node_port,
                     number: node_number,
+                    resource_limits: options.resource_limits,
                     reward_balance: None,
                     rpc_socket_addr,
                     owner: options.owner.clone(),
@@ -331,8 +336,30 @@ pub async fn add_node(
                 println!("{} {}: {}", "✕".red(), failed.0, failed.1);
             }
         }
-        return Err(eyre!("Failed to add one or more services")
-            .suggestion("However, any services that were successfully added will be usable."));
+        let outcomes = added_service_data
+            .iter()
+            .filter_map(|(name, ..)| {
+                node_registry
+                    .nodes
+                    .iter()
+                    .find(|node| node.service_name == *name)
+                    .map(ServiceOutcome::from_node)
+            })
+            .chain(
+                failed_service_data
+                    .into_iter()
+                    .map(|(name, err)| ServiceOutcome {
+                        service_name: name,
+                        error: Some(err),
+                        ..Default::default()
+                    }),
+            )
+            .collect();
+        return Err(FailedOperations {
+            message: "Failed to add one or more services".to_string(),
+            outcomes,
+        })
+        .suggestion("However, any services that were successfully added will be usable.");
     }
 
     let added_services_names = added_service_data
@@ -24,7 +24,9 @@ use mockall::{mock, predicate::*, Sequence};
 use predicates::prelude::*;
 use service_manager::ServiceInstallCtx;
 use sn_service_management::{auditor::AuditorServiceData, control::ServiceControl};
-use sn_service_management::{error::Result as ServiceControlResult, NatDetectionStatus};
+use sn_service_management::{
+    error::Result as ServiceControlResult, BandwidthLimits, NatDetectionStatus, ResourceLimits,
+};
 use sn_service_management::{
     DaemonServiceData, FaucetServiceData, NodeRegistry, NodeServiceData, ServiceStatus,
 };
@@ -112,6 +114,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -124,6 +127,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -166,6 +170,7 @@ async fn add_genesis_node_should_use_latest_version_and_add_one_service() -> Res
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -217,6 +222,7 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: true,
@@ -231,6 +237,7 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
             pid: None,
             peer_id: None,
             owner: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             status: ServiceStatus::Added,
@@ -282,6 +289,7 @@ async fn add_genesis_node_should_return_an_error_if_there_is_already_a_genesis_n
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -350,6 +358,7 @@ async fn add_genesis_node_should_return_an_error_if_count_is_greater_than_1() ->
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -403,6 +412,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -415,6 +425,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -440,6 +451,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         .in_sequence(&mut seq);
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
         env_variables: None,
@@ -452,6 +464,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         name: "safenode2".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8083),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -477,6 +490,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         .in_sequence(&mut seq);
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         data_dir_path: node_data_dir.to_path_buf().join("safenode3"),
         bootstrap_peers: vec![],
         env_variables: None,
@@ -489,6 +503,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
         name: "safenode3".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8085),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -532,6 +547,7 @@ async fn add_node_should_use_latest_version_and_add_three_services() -> Result<(
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -634,6 +650,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: new_peers.clone(),
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -646,6 +663,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -688,6 +706,7 @@ async fn add_node_should_update_the_bootstrap_peers_inside_node_registry() -> Re
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -764,6 +783,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
         .in_sequence(&mut seq);
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: env_variables.clone(),
@@ -776,6 +796,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -818,6 +839,7 @@ async fn add_node_should_update_the_environment_variables_inside_node_registry()
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -868,6 +890,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: true,
@@ -882,6 +905,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -912,6 +936,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
         .in_sequence(&mut seq);
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode2"),
         env_variables: None,
@@ -923,6 +948,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
         metrics_port: None,
         name: "safenode2".to_string(),
         node_port: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8083),
         owner: None,
         safenode_path: node_data_dir
@@ -967,6 +993,7 @@ async fn add_new_node_should_add_another_service() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -1034,6 +1061,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
         .in_sequence(&mut seq);
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -1046,6 +1074,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
         name: "safenode1".to_string(),
         node_port: Some(custom_port),
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -1089,6 +1118,7 @@ async fn add_node_should_use_custom_ports_for_one_service() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -1307,6 +1337,7 @@ async fn add_node_should_use_a_custom_port_range() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -1337,6 +1368,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> R
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1351,6 +1383,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> R
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1400,6 +1433,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_is_used() -> R
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -1428,6 +1462,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_us
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1442,6 +1477,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_us
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1491,6 +1527,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_port_in_range_is_us
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -1557,6 +1594,7 @@ async fn add_node_should_return_an_error_if_port_and_node_count_do_not_match() -
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -1628,6 +1666,7 @@ async fn add_node_should_return_an_error_if_multiple_services_are_specified_with
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -1752,6 +1791,7 @@ async fn add_node_should_set_random_ports_if_enable_metrics_server_is_true() ->
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -1964,6 +2004,7 @@ async fn add_node_should_use_a_custom_port_range_for_metrics_server() -> Result<
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -1991,6 +2032,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_is_use
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2005,6 +2047,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_is_use
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -2054,6 +2097,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_is_use
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -2083,6 +2127,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_in_ran
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2097,6 +2142,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_in_ran
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -2146,6 +2192,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_metrics_port_in_ran
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -2342,6 +2389,7 @@ async fn add_node_should_use_a_custom_port_range_for_the_rpc_server() -> Result<
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -2380,6 +2428,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_is_used()
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2394,6 +2443,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_is_used()
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -2443,6 +2493,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_is_used()
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -2472,6 +2523,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_in_range_i
         nat_status: None,
         nodes: vec![NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2486,6 +2538,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_in_range_i
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -2535,6 +2588,7 @@ async fn add_node_should_return_an_error_if_duplicate_custom_rpc_port_in_range_i
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &MockServiceControl::new(),
@@ -2587,6 +2641,7 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -2599,6 +2654,7 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -2641,6 +2697,7 @@ async fn add_node_should_disable_upnp_and_home_network_if_nat_status_is_public()
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -2690,6 +2747,7 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -2702,6 +2760,7 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -2744,6 +2803,7 @@ async fn add_node_should_enable_upnp_if_nat_status_is_upnp() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -2793,6 +2853,7 @@ async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Resul
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -2805,6 +2866,7 @@ async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Resul
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12001),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -2847,6 +2909,7 @@ async fn add_node_should_enable_home_network_if_nat_status_is_private() -> Resul
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -2921,6 +2984,7 @@ async fn add_node_should_return_an_error_if_nat_status_is_none_but_auto_set_nat_
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -3511,6 +3575,7 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -3523,6 +3588,7 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -3566,6 +3632,7 @@ async fn add_node_should_not_delete_the_source_binary_if_path_arg_is_used() -> R
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -3616,6 +3683,7 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -3628,6 +3696,7 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -3671,6 +3740,7 @@ async fn add_node_should_apply_the_home_network_flag_if_it_is_used() -> Result<(
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -3721,6 +3791,7 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -3733,6 +3804,7 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -3776,6 +3848,7 @@ async fn add_node_should_add_the_node_in_user_mode() -> Result<()> {
             user: Some(get_username()),
             user_mode: true,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -3823,6 +3896,7 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
 
     let install_ctx = InstallNodeServiceCtxBuilder {
         autostart: false,
+        bandwidth_limits: Default::default(),
         bootstrap_peers: vec![],
         data_dir_path: node_data_dir.to_path_buf().join("safenode1"),
         env_variables: None,
@@ -3835,6 +3909,7 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
         name: "safenode1".to_string(),
         node_port: None,
         owner: None,
+        resource_limits: Default::default(),
         rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
         safenode_path: node_data_dir
             .to_path_buf()
@@ -3878,6 +3953,7 @@ async fn add_node_should_add_the_node_with_upnp_enabled() -> Result<()> {
             user: Some(get_username()),
             user_mode: true,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -3993,6 +4069,7 @@ async fn add_node_should_assign_an_owner() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
@@ -4008,6 +4085,273 @@ async fn add_node_should_assign_an_owner() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn add_node_should_apply_the_bandwidth_limits() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nat_status: None,
+        nodes: vec![],
+        save_path: node_reg_path.to_path_buf(),
+    };
+
+    let bandwidth_limits = BandwidthLimits {
+        max_upload: Some(500),
+        max_download: Some(2000),
+        max_per_peer: None,
+    };
+
+    let mut mock_service_control = MockServiceControl::new();
+    let mut seq = Sequence::new();
+    mock_service_control
+        .expect_get_available_port()
+        .times(1)
+        .returning(|| Ok(8081))
+        .in_sequence(&mut seq);
+
+    mock_service_control
+        .expect_install()
+        .with(
+            eq(ServiceInstallCtx {
+                args: vec![
+                    OsString::from("--rpc"),
+                    OsString::from("127.0.0.1:8081"),
+                    OsString::from("--root-dir"),
+                    OsString::from(
+                        node_data_dir
+                            .to_path_buf()
+                            .join("safenode1")
+                            .to_string_lossy()
+                            .to_string(),
+                    ),
+                    OsString::from("--log-output-dest"),
+                    OsString::from(
+                        node_logs_dir
+                            .to_path_buf()
+                            .join("safenode1")
+                            .to_string_lossy()
+                            .to_string(),
+                    ),
+                    OsString::from("--max-upload-bandwidth"),
+                    OsString::from("500"),
+                    OsString::from("--max-download-bandwidth"),
+                    OsString::from("2000"),
+                ],
+                autostart: false,
+                contents: None,
+                environment: None,
+                label: "safenode1".parse()?,
+                program: node_data_dir
+                    .to_path_buf()
+                    .join("safenode1")
+                    .join(SAFENODE_FILE_NAME),
+                username: Some(get_username()),
+                working_directory: None,
+            }),
+            eq(false),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()))
+        .in_sequence(&mut seq);
+
+    add_node(
+        AddNodeServiceOptions {
+            bandwidth_limits,
+            delete_safenode_src: true,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            user: Some(get_username()),
+            version: latest_version.to_string(),
+            ..Default::default()
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await?;
+
+    assert_eq!(node_registry.nodes[0].bandwidth_limits, bandwidth_limits);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_node_should_apply_the_resource_limits() -> Result<()> {
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let node_reg_path = tmp_data_dir.child("node_reg.json");
+
+    let latest_version = "0.96.4";
+    let temp_dir = assert_fs::TempDir::new()?;
+    let node_data_dir = temp_dir.child("data");
+    node_data_dir.create_dir_all()?;
+    let node_logs_dir = temp_dir.child("logs");
+    node_logs_dir.create_dir_all()?;
+    let safenode_download_path = temp_dir.child(SAFENODE_FILE_NAME);
+    safenode_download_path.write_binary(b"fake safenode bin")?;
+
+    let mut node_registry = NodeRegistry {
+        auditor: None,
+        bootstrap_peers: vec![],
+        daemon: None,
+        environment_variables: None,
+        faucet: None,
+        nat_status: None,
+        nodes: vec![],
+        save_path: node_reg_path.to_path_buf(),
+    };
+
+    let resource_limits = ResourceLimits {
+        cpu_quota: Some(150),
+        max_memory_mb: Some(2048),
+        max_open_files: Some(4096),
+        io_weight: Some(50),
+    };
+
+    let mut mock_service_control = MockServiceControl::new();
+    let mut seq = Sequence::new();
+    mock_service_control
+        .expect_get_available_port()
+        .times(1)
+        .returning(|| Ok(8081))
+        .in_sequence(&mut seq);
+
+    // The limits can only be rendered into the service definition on a host running systemd,
+    // elsewhere the service must not be installed without them.
+    let supported = ResourceLimits::is_supported();
+    mock_service_control
+        .expect_install()
+        .withf(|install_ctx, user_mode| {
+            !user_mode
+                && install_ctx.contents.as_ref().is_some_and(|contents| {
+                    contents.contains("ExecStart=")
+                        && contents.contains("CPUQuota=150%")
+                        && contents.contains("MemoryMax=2048M")
+                        && contents.contains("LimitNOFILE=4096")
+                        && contents.contains("IOWeight=50")
+                })
+        })
+        .times(usize::from(supported))
+        .returning(|_, _| Ok(()))
+        .in_sequence(&mut seq);
+
+    let result = add_node(
+        AddNodeServiceOptions {
+            auto_restart: false,
+            auto_set_nat_flags: false,
+            bootstrap_peers: vec![],
+            count: None,
+            delete_safenode_src: true,
+            enable_metrics_server: false,
+            env_variables: None,
+            genesis: false,
+            home_network: false,
+            local: false,
+            log_format: None,
+            metrics_port: None,
+            owner: None,
+            node_port: None,
+            resource_limits,
+            rpc_address: None,
+            rpc_port: None,
+            safenode_dir_path: temp_dir.to_path_buf(),
+            safenode_src_path: safenode_download_path.to_path_buf(),
+            service_data_dir_path: node_data_dir.to_path_buf(),
+            service_log_dir_path: node_logs_dir.to_path_buf(),
+            upnp: false,
+            user: Some(get_username()),
+            user_mode: false,
+            version: latest_version.to_string(),
+            ..Default::default()
+        },
+        &mut node_registry,
+        &mock_service_control,
+        VerbosityLevel::Normal,
+    )
+    .await;
+
+    if supported {
+        assert!(result.is_ok());
+        assert_eq!(node_registry.nodes[0].resource_limits, resource_limits);
+    } else {
+        assert!(result.is_err());
+        assert!(node_registry.nodes.is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn resource_limits_should_render_the_systemd_directives() {
+    let resource_limits = ResourceLimits {
+        cpu_quota: Some(150),
+        max_memory_mb: Some(2048),
+        max_open_files: None,
+        io_weight: Some(50),
+    };
+    assert_eq!(
+        resource_limits.to_systemd_directives(),
+        vec!["CPUQuota=150%", "MemoryMax=2048M", "IOWeight=50"]
+    );
+    assert!(ResourceLimits::default().to_systemd_directives().is_empty());
+}
+
+#[test]
+fn resource_limits_unit_should_match_the_service_manager_definition() -> Result<()> {
+    let resource_limits = ResourceLimits {
+        max_memory_mb: Some(2048),
+        ..Default::default()
+    };
+    let mut install_ctx = ServiceInstallCtx {
+        args: vec![OsString::from("--rpc"), OsString::from("127.0.0.1:8081")],
+        autostart: false,
+        contents: None,
+        environment: Some(vec![("SN_LOG".to_string(), "all".to_string())]),
+        label: "safenode1".parse()?,
+        program: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
+        username: Some("safe".to_string()),
+        working_directory: None,
+    };
+
+    assert_eq!(
+        resource_limits.systemd_unit(&install_ctx, false),
+        "[Unit]\n\
+        Description=safenode1\n\
+        [Service]\n\
+        Environment=\"SN_LOG=all\"\n\
+        ExecStart=/var/safenode-manager/services/safenode1/safenode --rpc 127.0.0.1:8081\n\
+        Restart=on-failure\n\
+        User=safe\n\
+        MemoryMax=2048M"
+    );
+
+    install_ctx.autostart = true;
+    let system_unit = resource_limits.systemd_unit(&install_ctx, false);
+    assert!(system_unit.ends_with("MemoryMax=2048M\n[Install]\nWantedBy=multi-user.target"));
+
+    // A user-mode service runs as the current user, so it must not name one.
+    let user_unit = resource_limits.systemd_unit(&install_ctx, true);
+    assert!(!user_unit.contains("User="));
+    assert!(user_unit.ends_with("MemoryMax=2048M\n[Install]\nWantedBy=default.target"));
+    Ok(())
+}
+
 #[tokio::test]
 async fn add_node_should_auto_restart() -> Result<()> {
     let tmp_data_dir = assert_fs::TempDir::new()?;
@@ -4110,6 +4454,7 @@ async fn add_node_should_auto_restart() -> Result<()> {
             user: Some(get_username()),
             user_mode: false,
             version: latest_version.to_string(),
+            ..Default::default()
         },
         &mut node_registry,
         &mock_service_control,
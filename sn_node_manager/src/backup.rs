@@ -0,0 +1,336 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::relocate::SECRET_KEY_FILE_NAME;
+use color_eyre::{eyre::eyre, Help, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use libp2p::Multiaddr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sn_service_management::NodeServiceData;
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The version of the archive layout. Archives with a greater version cannot be restored.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const WALLET_DIR_NAME: &str = "wallet";
+const FORWARDED_BALANCE_FILE_NAME: &str = "forwarded_balance";
+const RECORD_STORE_DIR_NAME: &str = "record_store";
+
+/// Describes the content of a backup archive.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub created_at: String,
+    pub node_manager_version: String,
+    pub bootstrap_peers: Vec<Multiaddr>,
+    pub environment_variables: Option<Vec<(String, String)>>,
+    pub nodes: Vec<NodeBackup>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeBackup {
+    /// The service data of the node at the time of the backup.
+    pub service_data: NodeServiceData,
+    pub includes_records: bool,
+    /// The SHA-256 of each file, keyed by its path relative to the data directory.
+    pub checksums: BTreeMap<PathBuf, String>,
+}
+
+impl NodeBackup {
+    /// The directory of the node in the archive.
+    pub fn archive_dir(&self) -> PathBuf {
+        PathBuf::from(&self.service_data.service_name)
+    }
+}
+
+/// Write the identity, wallet and, optionally, the records of the nodes to a gzipped tar archive.
+pub fn create_archive(
+    archive_path: &Path,
+    nodes: &[&NodeServiceData],
+    include_records: bool,
+    bootstrap_peers: Vec<Multiaddr>,
+    environment_variables: Option<Vec<(String, String)>>,
+) -> Result<BackupManifest> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        // The archive holds the keypairs and wallets of the nodes.
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(archive_path)?;
+    // The mode only applies to a new file, an existing archive being overwritten keeps its own.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut node_backups = Vec::new();
+    for node in nodes {
+        let mut relative_paths = vec![PathBuf::from(SECRET_KEY_FILE_NAME)];
+        relative_paths.push(PathBuf::from(FORWARDED_BALANCE_FILE_NAME));
+        relative_paths.extend(files_under(&node.data_dir_path, WALLET_DIR_NAME)?);
+        if include_records {
+            relative_paths.extend(files_under(&node.data_dir_path, RECORD_STORE_DIR_NAME)?);
+        }
+
+        let mut checksums = BTreeMap::new();
+        for relative_path in relative_paths {
+            let path = node.data_dir_path.join(&relative_path);
+            if !path.is_file() {
+                if relative_path == Path::new(SECRET_KEY_FILE_NAME) {
+                    return Err(eyre!("{} has no keypair at {path:?}", node.service_name))
+                        .suggestion("A node only has a keypair once it has been started");
+                }
+                continue;
+            }
+            checksums.insert(relative_path.clone(), checksum(&path)?);
+            builder
+                .append_path_with_name(&path, Path::new(&node.service_name).join(&relative_path))?;
+        }
+        debug!(
+            "Added {} files of {} to the backup",
+            checksums.len(),
+            node.service_name
+        );
+        node_backups.push(NodeBackup {
+            service_data: (*node).clone(),
+            includes_records: include_records,
+            checksums,
+        });
+    }
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        node_manager_version: env!("CARGO_PKG_VERSION").to_string(),
+        bootstrap_peers,
+        environment_variables,
+        nodes: node_backups,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_FILE_NAME, manifest_json.as_slice())?;
+    builder.into_inner()?.finish()?;
+
+    Ok(manifest)
+}
+
+/// Extract an archive to the directory and verify the checksums of its files.
+pub fn extract_archive(archive_path: &Path, dest_dir: &Path) -> Result<BackupManifest> {
+    let file = File::open(archive_path)?;
+    tar::Archive::new(GzDecoder::new(file)).unpack(dest_dir)?;
+
+    let manifest_path = dest_dir.join(MANIFEST_FILE_NAME);
+    if !manifest_path.exists() {
+        return Err(eyre!(
+            "{archive_path:?} is not a node backup: it has no manifest"
+        ));
+    }
+    let manifest: BackupManifest = serde_json::from_slice(&std::fs::read(manifest_path)?)?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(eyre!(
+            "The backup format version {} is not supported",
+            manifest.format_version
+        ))
+        .suggestion("Upgrade safenode-manager to restore this backup");
+    }
+
+    for node in manifest.nodes.iter() {
+        for (relative_path, expected) in node.checksums.iter() {
+            let path = dest_dir.join(node.archive_dir()).join(relative_path);
+            if !path.is_file() || checksum(&path)? != *expected {
+                return Err(eyre!(
+                    "The checksum of {relative_path:?} of {} does not match",
+                    node.service_data.service_name
+                ))
+                .suggestion("The archive is corrupted");
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// The paths of the files under the directory, relative to `root`.
+fn files_under(root: &Path, dir_name: &str) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::from(dir_name)];
+    while let Some(relative_dir) = dirs.pop() {
+        let dir = root.join(&relative_dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative_path);
+            } else {
+                files.push(relative_path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn checksum(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0; 8192];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use sn_service_management::ServiceStatus;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    fn node(data_dir_path: PathBuf) -> NodeServiceData {
+        NodeServiceData {
+            auto_restart: false,
+            bandwidth_limits: Default::default(),
+            connected_peers: None,
+            safenode_path: data_dir_path.join("safenode"),
+            data_dir_path,
+            genesis: false,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from("/var/log/safenode/safenode1"),
+            log_format: None,
+            metrics_port: None,
+            node_port: Some(12000),
+            number: 1,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            resource_limits: Default::default(),
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 13000),
+            service_name: "safenode1".to_string(),
+            status: ServiceStatus::Stopped,
+            upnp: false,
+            user: None,
+            user_mode: true,
+            version: "0.98.1".to_string(),
+        }
+    }
+
+    #[test]
+    fn archive_should_round_trip_with_checksums() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let data_dir = tmp_dir.child("safenode1");
+        data_dir
+            .child(SECRET_KEY_FILE_NAME)
+            .write_binary(b"keypair")?;
+        data_dir
+            .child(FORWARDED_BALANCE_FILE_NAME)
+            .write_str("100")?;
+        data_dir
+            .child("wallet/main_secret_key")
+            .write_str("wallet key")?;
+        data_dir
+            .child("record_store/a1b2")
+            .write_binary(b"record")?;
+        data_dir
+            .child("safenode")
+            .write_binary(b"fake safenode bin")?;
+        let node = node(data_dir.to_path_buf());
+        let archive = tmp_dir.child("backup.tar.gz");
+
+        let manifest = create_archive(archive.path(), &[&node], false, vec![], None)?;
+        assert_eq!(
+            manifest.nodes[0].checksums.keys().collect::<Vec<_>>(),
+            vec![
+                Path::new(FORWARDED_BALANCE_FILE_NAME),
+                Path::new(SECRET_KEY_FILE_NAME),
+                Path::new("wallet/main_secret_key"),
+            ]
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(archive.path())?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let extract_dir = tmp_dir.child("extracted");
+        let manifest = extract_archive(archive.path(), extract_dir.path())?;
+        assert_eq!(manifest.format_version, BACKUP_FORMAT_VERSION);
+        assert_eq!(manifest.nodes[0].service_data.service_name, "safenode1");
+        extract_dir
+            .child("safenode1")
+            .child(SECRET_KEY_FILE_NAME)
+            .assert("keypair");
+        extract_dir
+            .child("safenode1/record_store")
+            .assert(predicates::path::missing());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn overwritten_archive_should_only_be_readable_by_its_owner() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let data_dir = tmp_dir.child("safenode1");
+        data_dir
+            .child(SECRET_KEY_FILE_NAME)
+            .write_binary(b"keypair")?;
+        let node = node(data_dir.to_path_buf());
+        let archive = tmp_dir.child("backup.tar.gz");
+        archive.write_binary(b"previous backup")?;
+        std::fs::set_permissions(archive.path(), std::fs::Permissions::from_mode(0o644))?;
+
+        let _ = create_archive(archive.path(), &[&node], false, vec![], None)?;
+
+        let mode = std::fs::metadata(archive.path())?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        Ok(())
+    }
+
+    #[test]
+    fn node_without_a_keypair_should_not_be_backed_up() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let data_dir = tmp_dir.child("safenode1");
+        data_dir.create_dir_all()?;
+        let node = node(data_dir.to_path_buf());
+
+        let result = create_archive(
+            tmp_dir.child("backup.tar.gz").path(),
+            &[&node],
+            true,
+            vec![],
+            None,
+        );
+        assert!(result.is_err());
+        Ok(())
+    }
+}
@@ -6,17 +6,24 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use color_eyre::{eyre::eyre, Result};
 use libp2p::Multiaddr;
 use sn_logging::{LogBuilder, LogFormat};
 use sn_node_manager::{
     add_services::config::{parse_port_range, PortRange},
     cmd::{self},
+    output::CommandOutput,
+    rolling_upgrade::{
+        RollingUpgradeOptions, DEFAULT_HEALTH_CHECK_TIMEOUT, DEFAULT_MIN_CONNECTED_PEERS,
+    },
     VerbosityLevel,
 };
 use sn_peers_acquisition::PeersArgs;
-use std::{net::Ipv4Addr, path::PathBuf};
+use sn_protocol::transport::TransportMode;
+use sn_releases::ReleaseType;
+use sn_service_management::{BandwidthLimits, ResourceLimits};
+use std::{net::Ipv4Addr, path::PathBuf, str::FromStr, time::Duration};
 use tracing::Level;
 
 const DEFAULT_NODE_COUNT: u16 = 25;
@@ -38,6 +45,15 @@ pub(crate) struct Cmd {
     /// Output trace-level logging to stderr.
     #[clap(long, conflicts_with = "debug")]
     trace: bool,
+
+    /// Output the result of the command as a single JSON document.
+    ///
+    /// The document has the outcome for each service the command operated on. The exit code is 1
+    /// if the command failed, or 2 if it failed for some of the services only.
+    ///
+    /// The status commands keep their own document, which is the status of each service.
+    #[clap(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -81,6 +97,13 @@ pub enum SubCmd {
         /// mutually exclusive.
         #[clap(long, conflicts_with = "first")]
         count: Option<u16>,
+        /// Limit the CPU time of each node, as a percentage of one core.
+        ///
+        /// For example, 150 allows a node to use one and a half cores.
+        ///
+        /// Resource limits are only applied on Linux hosts running systemd.
+        #[clap(long)]
+        cpu_quota: Option<u32>,
         /// Provide the path for the data directory for the installed node.
         ///
         /// This path is a prefix. Each installed node will have its own directory underneath it.
@@ -108,6 +131,13 @@ pub enum SubCmd {
         /// This enables the use of safenode services from a home network with a router.
         #[clap(long)]
         home_network: bool,
+        /// Set the relative IO weight of each node, from 1 to 10000.
+        ///
+        /// Services have a weight of 100 by default.
+        ///
+        /// Resource limits are only applied on Linux hosts running systemd.
+        #[clap(long, value_parser = clap::value_parser!(u16).range(1..=10000))]
+        io_weight: Option<u16>,
         /// Set this flag to launch safenode with the --local flag.
         ///
         /// This is useful for building a service-based local network.
@@ -130,6 +160,35 @@ pub enum SubCmd {
         /// If the argument is not used, the default format will be applied.
         #[clap(long, value_parser = LogFormat::parse_from_str, verbatim_doc_comment)]
         log_format: Option<LogFormat>,
+        /// Specify the maximum download bandwidth of the node(s), in kilobytes per second.
+        ///
+        /// When the limit is approached, the replication between nodes is slowed down to leave
+        /// room for the GETs of the clients.
+        ///
+        /// If not used, the download bandwidth is unlimited.
+        #[clap(long)]
+        max_download_bandwidth: Option<u64>,
+        /// Limit the memory of each node, in megabytes.
+        ///
+        /// Resource limits are only applied on Linux hosts running systemd.
+        #[clap(long)]
+        max_memory_mb: Option<u64>,
+        /// Limit the number of files each node can have open.
+        ///
+        /// Resource limits are only applied on Linux hosts running systemd.
+        #[clap(long)]
+        max_open_files: Option<u64>,
+        /// Specify the maximum upload and download bandwidth with each peer, in kilobytes per
+        /// second.
+        ///
+        /// If not used, the bandwidth with each peer is only limited by the other limits.
+        #[clap(long)]
+        max_peer_bandwidth: Option<u64>,
+        /// Specify the maximum upload bandwidth of the node(s), in kilobytes per second.
+        ///
+        /// If not used, the upload bandwidth is unlimited.
+        #[clap(long)]
+        max_upload_bandwidth: Option<u64>,
         /// Specify a port for the open metrics server.
         ///
         /// This argument should only be used with a safenode binary that has the open-metrics
@@ -213,8 +272,59 @@ pub enum SubCmd {
         #[clap(long)]
         version: Option<String>,
     },
+    /// Converge the safenode services to a declarative fleet config file.
+    ///
+    /// The TOML file describes the desired node set: the count, port ranges, data and log
+    /// directories, owner, NAT flags, log format, environment variables, bandwidth limits and a
+    /// version pin. Services are removed, reconfigured, upgraded, added and started to match it.
+    ///
+    /// The ports and directories of existing nodes are not changed; any drift is reported.
+    ///
+    /// This command must run as the root/administrative user.
+    #[clap(name = "apply")]
+    Apply {
+        /// The path of the fleet config file.
+        ///
+        /// It is read as YAML if its extension is '.yaml' or '.yml', as TOML otherwise.
+        #[clap(long, short)]
+        config: PathBuf,
+        /// Print the plan without changing any service.
+        #[clap(long)]
+        dry_run: bool,
+    },
     #[clap(subcommand)]
     Auditor(AuditorSubCmd),
+    /// Back up the identity of safenode service(s) to an archive.
+    ///
+    /// The archive holds the keypair, reward wallet and forwarded balance of each node, and
+    /// optionally its records, along with checksums of the files. It can be used with the restore
+    /// command to rebuild the nodes with the same peer IDs on another host.
+    ///
+    /// If no peer ID(s) or service name(s) are supplied, all services will be backed up.
+    #[clap(name = "backup")]
+    Backup {
+        /// Set to include the record store of each node.
+        ///
+        /// Records can be large, and the nodes should be stopped so they do not change during the
+        /// backup.
+        #[clap(long)]
+        include_records: bool,
+        /// The path of the archive.
+        ///
+        /// Defaults to a timestamped file in the current directory.
+        #[clap(long)]
+        path: Option<PathBuf>,
+        /// The peer ID of the service to back up.
+        ///
+        /// The argument can be used multiple times to back up many services.
+        #[clap(long)]
+        peer_id: Vec<String>,
+        /// The name of the service to back up.
+        ///
+        /// The argument can be used multiple times to back up many services.
+        #[clap(long, conflicts_with = "peer_id")]
+        service_name: Vec<String>,
+    },
     /// Get node reward balances.
     #[clap(name = "balance")]
     Balance {
@@ -231,10 +341,43 @@ pub enum SubCmd {
     },
     #[clap(subcommand)]
     Daemon(DaemonSubCmd),
+    /// Check the safenode services for problems.
+    ///
+    /// Reports ports assigned to several services or used by other processes, missing binaries,
+    /// stale PIDs, and services defined in the OS but missing from the node registry, or the
+    /// other way around.
+    ///
+    /// The command fails if any problem is found.
+    #[clap(name = "doctor")]
+    Doctor {},
     #[clap(subcommand)]
     Faucet(FaucetSubCmd),
     #[clap(subcommand)]
     Local(LocalSubCmd),
+    /// Move the data of a safenode service to another directory, e.g., on a bigger disk.
+    ///
+    /// The node is stopped, its data, including the record store and keypair, is copied and
+    /// verified, and the service is redefined and started again with the same peer ID. The old
+    /// data is deleted once the node is running from the new location.
+    ///
+    /// This command must run as the root/administrative user.
+    #[clap(name = "move")]
+    Move {
+        /// The name of the service to move.
+        #[clap(long)]
+        service_name: String,
+        /// The new data directory.
+        ///
+        /// This path is a prefix, like for the add command. The node will have its own directory
+        /// underneath it.
+        #[clap(long)]
+        data_dir_path: PathBuf,
+        /// A new log directory.
+        ///
+        /// This path is a prefix, like for the add command. The existing logs are not moved.
+        #[clap(long)]
+        log_dir_path: Option<PathBuf>,
+    },
     #[clap(subcommand)]
     NatDetection(NatDetectionSubCmd),
     /// Remove safenode service(s).
@@ -273,6 +416,40 @@ pub enum SubCmd {
         #[clap(long, short)]
         force: bool,
     },
+    /// Restore safenode service(s) from an archive created by the backup command.
+    ///
+    /// Each node is added as a new service with the settings it was backed up with, then its
+    /// keypair, wallet and records are copied to its data directory, so it keeps its peer ID and
+    /// unclaimed rewards. Nodes whose peer ID is already registered are not restored.
+    ///
+    /// The services are not started.
+    ///
+    /// This command must run as the root/administrative user.
+    #[clap(name = "restore")]
+    Restore {
+        /// Provide a path for the data directories of the restored services.
+        ///
+        /// This path is a prefix, like for the add command.
+        #[clap(long, verbatim_doc_comment)]
+        data_dir_path: Option<PathBuf>,
+        /// Provide a path for the log directories of the restored services.
+        ///
+        /// This path is a prefix, like for the add command.
+        #[clap(long, verbatim_doc_comment)]
+        log_dir_path: Option<PathBuf>,
+        /// The path of the archive.
+        #[clap(long)]
+        path: PathBuf,
+        /// Set to assign new ports rather than the ones the nodes were backed up with.
+        #[clap(long)]
+        reassign_ports: bool,
+        /// The name of a service in the backup to restore.
+        ///
+        /// The argument can be used multiple times to restore many services. All the services in
+        /// the backup are restored if it is not used.
+        #[clap(long)]
+        service_name: Vec<String>,
+    },
     /// Start safenode service(s).
     ///
     /// If no peer ID(s) or service name(s) are supplied, all services will be started.
@@ -301,14 +478,11 @@ pub enum SubCmd {
     #[clap(name = "status")]
     Status {
         /// Set this flag to display more details
-        #[clap(long)]
+        #[clap(long, conflicts_with = "json")]
         details: bool,
         /// Set this flag to return an error if any nodes are not running
         #[clap(long)]
         fail: bool,
-        /// Set this flag to output the status as a JSON document
-        #[clap(long, conflicts_with = "details")]
-        json: bool,
     },
     /// Stop safenode service(s).
     ///
@@ -389,6 +563,28 @@ pub enum SubCmd {
         /// The version number should be in the form X.Y.Z, with no 'v' prefix.
         #[clap(long)]
         version: Option<String>,
+        /// Upgrade the services in batches, waiting for the upgraded nodes to rejoin the network
+        /// before moving on to the next batch.
+        ///
+        /// The upgrade halts at the first batch that fails. The interval is applied between the
+        /// batches rather than between each service.
+        #[clap(long, conflicts_with = "do_not_start")]
+        rolling: bool,
+        /// The number of services upgraded together in a rolling upgrade.
+        #[clap(long, default_value_t = 1, requires = "rolling")]
+        batch_size: usize,
+        /// The minimum number of connected peers for an upgraded node to be considered back on the
+        /// network in a rolling upgrade.
+        #[clap(long, default_value_t = DEFAULT_MIN_CONNECTED_PEERS, requires = "rolling")]
+        min_connected_peers: usize,
+        /// How long to wait for an upgraded node to be back on the network in a rolling upgrade.
+        ///
+        /// Units are seconds.
+        #[clap(long, default_value_t = DEFAULT_HEALTH_CHECK_TIMEOUT.as_secs(), requires = "rolling")]
+        health_check_timeout: u64,
+        /// Put the previous binary back on the nodes of a batch that failed in a rolling upgrade.
+        #[clap(long, requires = "rolling")]
+        rollback: bool,
     },
 }
 
@@ -781,6 +977,15 @@ pub enum LocalSubCmd {
         /// Set to skip the network validation process
         #[clap(long)]
         skip_validation: bool,
+        /// Specify the transports the nodes listen on and dial with.
+        ///
+        /// Valid values are "quic", "tcp" or "quic-and-tcp". If not used, the nodes default to
+        /// "quic".
+        ///
+        /// The argument exists to support testing scenarios, e.g. using "tcp" to exercise networks
+        /// where UDP is blocked.
+        #[clap(long, value_parser = TransportMode::from_str)]
+        transport: Option<TransportMode>,
     },
     /// Run a local network.
     ///
@@ -855,27 +1060,40 @@ pub enum LocalSubCmd {
         /// Set to skip the network validation process
         #[clap(long)]
         skip_validation: bool,
+        /// Specify the transports the nodes listen on and dial with.
+        ///
+        /// Valid values are "quic", "tcp" or "quic-and-tcp". If not used, the nodes default to
+        /// "quic".
+        ///
+        /// The argument exists to support testing scenarios, e.g. using "tcp" to exercise networks
+        /// where UDP is blocked.
+        #[clap(long, value_parser = TransportMode::from_str)]
+        transport: Option<TransportMode>,
     },
     /// Get the status of the local nodes.
     #[clap(name = "status")]
     Status {
         /// Set this flag to display more details
-        #[clap(long)]
+        #[clap(long, conflicts_with = "json")]
         details: bool,
         /// Set this flag to return an error if any nodes are not running
         #[clap(long)]
         fail: bool,
-        /// Set this flag to output the status as a JSON document
-        #[clap(long, conflicts_with = "details")]
-        json: bool,
     },
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
     color_eyre::install()?;
-    let args = Cmd::parse();
-    let verbosity = VerbosityLevel::from(args.verbose);
+    let matches = Cmd::command().get_matches();
+    let command = command_name(&matches);
+    let args = Cmd::from_arg_matches(&matches)?;
+    // The human-readable output is suppressed so only the document is written to stdout.
+    let verbosity = if args.json {
+        VerbosityLevel::Minimal
+    } else {
+        VerbosityLevel::from(args.verbose)
+    };
 
     let _log_handle = if args.debug || args.trace {
         let level = if args.debug {
@@ -892,18 +1110,29 @@ async fn main() -> Result<()> {
 
     tracing::info!("Executing cmd: {:?}", args.cmd);
 
-    match args.cmd {
+    let has_own_json_output = matches!(
+        args.cmd,
+        SubCmd::Status { .. } | SubCmd::Local(LocalSubCmd::Status { .. })
+    );
+    let result = match args.cmd {
         SubCmd::Add {
             auto_restart,
             auto_set_nat_flags,
             count,
+            cpu_quota,
             data_dir_path,
             enable_metrics_server,
             env_variables,
             home_network,
+            io_weight,
             local,
             log_dir_path,
             log_format,
+            max_download_bandwidth,
+            max_memory_mb,
+            max_open_files,
+            max_peer_bandwidth,
+            max_upload_bandwidth,
             metrics_port,
             node_port,
             owner,
@@ -915,34 +1144,44 @@ async fn main() -> Result<()> {
             upnp,
             user,
             version,
-        } => {
-            let _ = cmd::node::add(
-                auto_restart,
-                auto_set_nat_flags,
-                count,
-                data_dir_path,
-                enable_metrics_server,
-                env_variables,
-                home_network,
-                local,
-                log_dir_path,
-                log_format,
-                metrics_port,
-                node_port,
-                owner,
-                peers,
-                rpc_address,
-                rpc_port,
-                path,
-                upnp,
-                url,
-                user,
-                version,
-                verbosity,
-            )
-            .await?;
-            Ok(())
-        }
+        } => cmd::node::add(
+            auto_restart,
+            auto_set_nat_flags,
+            BandwidthLimits {
+                max_upload: max_upload_bandwidth,
+                max_download: max_download_bandwidth,
+                max_per_peer: max_peer_bandwidth,
+            },
+            count,
+            data_dir_path,
+            enable_metrics_server,
+            env_variables,
+            home_network,
+            local,
+            log_dir_path,
+            log_format,
+            metrics_port,
+            node_port,
+            owner,
+            peers,
+            ResourceLimits {
+                cpu_quota,
+                max_memory_mb,
+                max_open_files,
+                io_weight,
+            },
+            rpc_address,
+            rpc_port,
+            path,
+            upnp,
+            url,
+            user,
+            version,
+            verbosity,
+        )
+        .await
+        .and_then(|added_service_names| cmd::node::added_service_outcomes(&added_service_names)),
+        SubCmd::Apply { config, dry_run } => cmd::node::apply(config, dry_run, verbosity).await,
         SubCmd::Auditor(AuditorSubCmd::Add {
             beta_encryption_key,
             env_variables,
@@ -951,30 +1190,39 @@ async fn main() -> Result<()> {
             peers,
             url,
             version,
-        }) => {
-            cmd::auditor::add(
-                beta_encryption_key,
-                env_variables,
-                log_dir_path,
-                *peers,
-                path,
-                url,
-                version,
-                verbosity,
-            )
+        }) => cmd::auditor::add(
+            beta_encryption_key,
+            env_variables,
+            log_dir_path,
+            *peers,
+            path,
+            url,
+            version,
+            verbosity,
+        )
+        .await
+        .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SnAuditor)),
+        SubCmd::Auditor(AuditorSubCmd::Start {}) => cmd::auditor::start(verbosity)
             .await
-        }
-        SubCmd::Auditor(AuditorSubCmd::Start {}) => cmd::auditor::start(verbosity).await,
-        SubCmd::Auditor(AuditorSubCmd::Stop {}) => cmd::auditor::stop(verbosity).await,
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SnAuditor)),
+        SubCmd::Auditor(AuditorSubCmd::Stop {}) => cmd::auditor::stop(verbosity)
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SnAuditor)),
         SubCmd::Auditor(AuditorSubCmd::Upgrade {
             do_not_start,
             force,
             env_variables,
             url,
             version,
-        }) => {
-            cmd::auditor::upgrade(do_not_start, force, env_variables, url, version, verbosity).await
-        }
+        }) => cmd::auditor::upgrade(do_not_start, force, env_variables, url, version, verbosity)
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SnAuditor)),
+        SubCmd::Backup {
+            include_records,
+            path,
+            peer_id: peer_ids,
+            service_name: service_names,
+        } => cmd::node::backup(include_records, path, peer_ids, service_names, verbosity).await,
         SubCmd::Balance {
             peer_id: peer_ids,
             service_name: service_names,
@@ -986,9 +1234,16 @@ async fn main() -> Result<()> {
             path,
             url,
             version,
-        }) => cmd::daemon::add(address, env_variables, port, path, url, version, verbosity).await,
-        SubCmd::Daemon(DaemonSubCmd::Start {}) => cmd::daemon::start(verbosity).await,
-        SubCmd::Daemon(DaemonSubCmd::Stop {}) => cmd::daemon::stop(verbosity).await,
+        }) => cmd::daemon::add(address, env_variables, port, path, url, version, verbosity)
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SafenodeManagerDaemon)),
+        SubCmd::Daemon(DaemonSubCmd::Start {}) => cmd::daemon::start(verbosity)
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SafenodeManagerDaemon)),
+        SubCmd::Daemon(DaemonSubCmd::Stop {}) => cmd::daemon::stop(verbosity)
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::SafenodeManagerDaemon)),
+        SubCmd::Doctor {} => cmd::node::doctor(verbosity).await,
         SubCmd::Faucet(faucet_command) => match faucet_command {
             FaucetSubCmd::Add {
                 env_variables,
@@ -997,37 +1252,39 @@ async fn main() -> Result<()> {
                 peers,
                 url,
                 version,
-            } => {
-                cmd::faucet::add(
-                    env_variables,
-                    log_dir_path,
-                    peers,
-                    path,
-                    url,
-                    version,
-                    verbosity,
-                )
+            } => cmd::faucet::add(
+                env_variables,
+                log_dir_path,
+                peers,
+                path,
+                url,
+                version,
+                verbosity,
+            )
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::Faucet)),
+            FaucetSubCmd::Start {} => cmd::faucet::start(verbosity)
                 .await
-            }
-            FaucetSubCmd::Start {} => cmd::faucet::start(verbosity).await,
-            FaucetSubCmd::Stop {} => cmd::faucet::stop(verbosity).await,
+                .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::Faucet)),
+            FaucetSubCmd::Stop {} => cmd::faucet::stop(verbosity)
+                .await
+                .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::Faucet)),
             FaucetSubCmd::Upgrade {
                 do_not_start,
                 force,
                 env_variables: provided_env_variable,
                 url,
                 version,
-            } => {
-                cmd::faucet::upgrade(
-                    do_not_start,
-                    force,
-                    provided_env_variable,
-                    url,
-                    version,
-                    verbosity,
-                )
-                .await
-            }
+            } => cmd::faucet::upgrade(
+                do_not_start,
+                force,
+                provided_env_variable,
+                url,
+                version,
+                verbosity,
+            )
+            .await
+            .and_then(|()| cmd::auxiliary_service_outcomes(ReleaseType::Faucet)),
         },
         SubCmd::Local(local_command) => match local_command {
             LocalSubCmd::Join {
@@ -1043,6 +1300,7 @@ async fn main() -> Result<()> {
                 owner_prefix,
                 peers,
                 skip_validation: _,
+                transport,
             } => {
                 cmd::local::join(
                     build,
@@ -1057,6 +1315,7 @@ async fn main() -> Result<()> {
                     owner_prefix,
                     peers,
                     true,
+                    transport,
                     verbosity,
                 )
                 .await
@@ -1075,6 +1334,7 @@ async fn main() -> Result<()> {
                 node_version,
                 log_format,
                 skip_validation: _,
+                transport,
             } => {
                 cmd::local::run(
                     build,
@@ -1089,41 +1349,67 @@ async fn main() -> Result<()> {
                     owner,
                     owner_prefix,
                     true,
+                    transport,
                     verbosity,
                 )
                 .await
             }
-            LocalSubCmd::Status {
-                details,
-                fail,
-                json,
-            } => cmd::local::status(details, fail, json).await,
+            LocalSubCmd::Status { details, fail } => cmd::local::status(details, fail, args.json)
+                .await
+                .map(|()| Vec::new()),
         },
+        SubCmd::Move {
+            service_name,
+            data_dir_path,
+            log_dir_path,
+        } => cmd::node::move_node(service_name, data_dir_path, log_dir_path, verbosity).await,
         SubCmd::NatDetection(NatDetectionSubCmd::Run {
             path,
             servers,
             url,
             version,
-        }) => {
-            cmd::nat_detection::run_nat_detection(servers, true, path, url, version, verbosity)
-                .await
-        }
+        }) => cmd::nat_detection::run_nat_detection(servers, true, path, url, version, verbosity)
+            .await
+            .map(|()| Vec::new()),
         SubCmd::Remove {
             keep_directories,
             peer_id: peer_ids,
             service_name: service_names,
         } => cmd::node::remove(keep_directories, peer_ids, service_names, verbosity).await,
-        SubCmd::Reset { force } => cmd::node::reset(force, verbosity).await,
+        SubCmd::Reset { force } => {
+            if args.json && !force {
+                Err(eyre!(
+                    "The reset must be confirmed with --force when the output is JSON"
+                ))
+            } else {
+                cmd::node::reset(force, verbosity).await
+            }
+        }
+        SubCmd::Restore {
+            data_dir_path,
+            log_dir_path,
+            path,
+            reassign_ports,
+            service_name: service_names,
+        } => {
+            cmd::node::restore(
+                path,
+                data_dir_path,
+                log_dir_path,
+                reassign_ports,
+                service_names,
+                verbosity,
+            )
+            .await
+        }
         SubCmd::Start {
             interval,
             peer_id: peer_ids,
             service_name: service_names,
         } => cmd::node::start(interval, peer_ids, service_names, verbosity).await,
-        SubCmd::Status {
-            details,
-            fail,
-            json,
-        } => cmd::node::status(details, fail, json).await,
+        SubCmd::Status { details, fail } => cmd::node::status(details, fail, args.json)
+            .await
+            .map(|()| Vec::new()),
         SubCmd::Stop {
             peer_id: peer_ids,
             service_name: service_names,
@@ -1138,7 +1424,18 @@ async fn main() -> Result<()> {
             env_variables: provided_env_variable,
             url,
             version,
+            rolling,
+            batch_size,
+            min_connected_peers,
+            health_check_timeout,
+            rollback,
         } => {
+            let rolling = rolling.then(|| RollingUpgradeOptions {
+                batch_size,
+                min_connected_peers,
+                health_check_timeout: Duration::from_secs(health_check_timeout),
+                rollback,
+            });
             cmd::node::upgrade(
                 do_not_start,
                 path,
@@ -1146,6 +1443,7 @@ async fn main() -> Result<()> {
                 interval,
                 peer_ids,
                 provided_env_variable,
+                rolling,
                 service_names,
                 url,
                 version,
@@ -1153,7 +1451,25 @@ async fn main() -> Result<()> {
             )
             .await
         }
+    };
+
+    if args.json && !has_own_json_output {
+        let output = CommandOutput::new(&command, &result);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        std::process::exit(output.exit_code());
+    }
+    result.map(|_| ())
+}
+
+/// The full name of the subcommand, e.g., "local run".
+fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut matches = matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        names.push(name);
+        matches = sub_matches;
     }
+    names.join(" ")
 }
 
 fn get_log_builder(level: Level) -> Result<LogBuilder> {
@@ -12,20 +12,46 @@ extern crate tracing;
 use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
 use libp2p_identity::PeerId;
-use sn_logging::LogBuilder;
-use sn_node_manager::{config::get_node_registry_path, rpc, DAEMON_DEFAULT_PORT};
+use sn_logging::{LogBuilder, LogFormat};
+use sn_node_manager::{
+    add_services::config::{parse_port_range, PortRange},
+    cmd,
+    config::{get_daemon_auth_token_path, get_node_registry_path},
+    daemon_auth,
+    output::{FailedOperations, ServiceOutcome},
+    rolling_upgrade::{
+        RollingUpgradeOptions, DEFAULT_HEALTH_CHECK_TIMEOUT, DEFAULT_MIN_CONNECTED_PEERS,
+    },
+    rpc, VerbosityLevel, DAEMON_DEFAULT_PORT,
+};
+use sn_peers_acquisition::{parse_peer_addr, PeersArgs};
 use sn_service_management::{
     safenode_manager_proto::{
         get_status_response::Node,
+        node_operation_progress::Stage,
         safe_node_manager_server::{SafeNodeManager, SafeNodeManagerServer},
-        GetStatusRequest, GetStatusResponse, NodeServiceRestartRequest, NodeServiceRestartResponse,
+        AddNodesRequest, EnvVariable, GetStatusRequest, GetStatusResponse,
+        MaintainRunningNodesRequest, NodeOperationProgress, NodeSelection,
+        NodeServiceRestartRequest, NodeServiceRestartResponse, RemoveNodesRequest,
+        ResetNodesRequest, StartNodesRequest, StopNodesRequest, UpgradeNodesRequest,
     },
-    NodeRegistry,
+    BandwidthLimits, NodeRegistry, ResourceLimits, ServiceStatus,
+};
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
 };
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{transport::Server, Code, Request, Response, Status};
 use tracing::Level;
 
+/// Number of progress updates buffered for a client reading them slowly
+const PROGRESS_CHANNEL_SIZE: usize = 64;
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
@@ -37,19 +63,39 @@ struct Args {
     /// If not set, the daemon listens locally for commands.
     #[clap(long, default_value_t = Ipv4Addr::new(127, 0, 0, 1))]
     address: Ipv4Addr,
+    /// Specify the file holding the token the RPC clients must present.
+    ///
+    /// The token is generated if the file does not exist. It defaults to the 'daemon_auth_token'
+    /// file in the node manager directory.
+    #[clap(long)]
+    token_path: Option<PathBuf>,
 }
 
-struct SafeNodeManagerDaemon {}
+type ProgressStream = ReceiverStream<Result<NodeOperationProgress, Status>>;
+
+struct SafeNodeManagerDaemon {
+    /// The node operations all update the node registry, so they are run one at a time.
+    operation_lock: Arc<Mutex<()>>,
+}
 
 // Implementing RPC interface for service defined in .proto
 #[tonic::async_trait]
 impl SafeNodeManager for SafeNodeManagerDaemon {
+    type AddNodesStream = ProgressStream;
+    type StartNodesStream = ProgressStream;
+    type StopNodesStream = ProgressStream;
+    type UpgradeNodesStream = ProgressStream;
+    type RemoveNodesStream = ProgressStream;
+    type ResetNodesStream = ProgressStream;
+    type MaintainRunningNodesStream = ProgressStream;
+
     async fn restart_node_service(
         &self,
         request: Request<NodeServiceRestartRequest>,
     ) -> Result<Response<NodeServiceRestartResponse>, Status> {
         println!("RPC request received {:?}", request.get_ref());
         info!("RPC request received {:?}", request.get_ref());
+        let _guard = self.operation_lock.lock().await;
         let node_registry = Self::load_node_registry().map_err(|err| {
             Status::new(
                 Code::Internal,
@@ -97,6 +143,176 @@ impl SafeNodeManager for SafeNodeManagerDaemon {
         info!("Node status retrieved, nod len: {:?}", nodes_info.len());
         Ok(Response::new(GetStatusResponse { nodes: nodes_info }))
     }
+
+    async fn add_nodes(
+        &self,
+        request: Request<AddNodesRequest>,
+    ) -> Result<Response<Self::AddNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let args = AddArgs::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(format!("Invalid add request: {err}")))?;
+
+        self.run_operation("add", move |progress| async move {
+            let added_services = args.add().await?;
+            for service_name in added_services {
+                progress
+                    .report(Stage::NodeSucceeded, Some(service_name), "Added")
+                    .await;
+            }
+            Ok(())
+        })
+    }
+
+    async fn start_nodes(
+        &self,
+        request: Request<StartNodesRequest>,
+    ) -> Result<Response<Self::StartNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let request = request.into_inner();
+
+        self.run_operation("start", move |progress| async move {
+            Self::for_each_node(
+                request.selection,
+                &progress,
+                "Started",
+                |peer_ids, names| {
+                    cmd::node::start(
+                        request.interval_millis,
+                        peer_ids,
+                        names,
+                        VerbosityLevel::Minimal,
+                    )
+                },
+            )
+            .await
+        })
+    }
+
+    async fn stop_nodes(
+        &self,
+        request: Request<StopNodesRequest>,
+    ) -> Result<Response<Self::StopNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let request = request.into_inner();
+
+        self.run_operation("stop", move |progress| async move {
+            Self::for_each_node(
+                request.selection,
+                &progress,
+                "Stopped",
+                |peer_ids, names| cmd::node::stop(peer_ids, names, VerbosityLevel::Minimal),
+            )
+            .await
+        })
+    }
+
+    async fn upgrade_nodes(
+        &self,
+        request: Request<UpgradeNodesRequest>,
+    ) -> Result<Response<Self::UpgradeNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let request = request.into_inner();
+        let selection = request.selection.unwrap_or_default();
+        let rolling = request.rolling.map(|rolling| RollingUpgradeOptions {
+            batch_size: rolling.batch_size as usize,
+            min_connected_peers: if rolling.min_connected_peers == 0 {
+                DEFAULT_MIN_CONNECTED_PEERS
+            } else {
+                rolling.min_connected_peers as usize
+            },
+            health_check_timeout: if rolling.health_check_timeout_secs == 0 {
+                DEFAULT_HEALTH_CHECK_TIMEOUT
+            } else {
+                Duration::from_secs(rolling.health_check_timeout_secs)
+            },
+            rollback: rolling.rollback,
+        });
+
+        // The nodes are upgraded in a single call, for the binary to be downloaded only once.
+        self.run_operation("upgrade", move |progress| async move {
+            let result = cmd::node::upgrade(
+                request.do_not_start,
+                request.custom_bin_path.map(PathBuf::from),
+                request.force,
+                request.interval_millis,
+                selection.peer_ids,
+                env_variables(request.env_variables),
+                rolling,
+                selection.service_names,
+                request.url,
+                request.version,
+                VerbosityLevel::Minimal,
+            )
+            .await;
+            Self::report_outcomes(&progress, result, |outcome| {
+                match (&outcome.previous_version, &outcome.version) {
+                    (Some(previous_version), Some(version)) => {
+                        format!("Upgraded from {previous_version} to {version}")
+                    }
+                    _ => "No upgrade required".to_string(),
+                }
+            })
+            .await
+        })
+    }
+
+    async fn remove_nodes(
+        &self,
+        request: Request<RemoveNodesRequest>,
+    ) -> Result<Response<Self::RemoveNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let request = request.into_inner();
+
+        self.run_operation("remove", move |progress| async move {
+            Self::for_each_node(
+                request.selection,
+                &progress,
+                "Removed",
+                |peer_ids, names| {
+                    cmd::node::remove(
+                        request.keep_directories,
+                        peer_ids,
+                        names,
+                        VerbosityLevel::Minimal,
+                    )
+                },
+            )
+            .await
+        })
+    }
+
+    async fn reset_nodes(
+        &self,
+        request: Request<ResetNodesRequest>,
+    ) -> Result<Response<Self::ResetNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        if !request.get_ref().confirm {
+            return Err(Status::failed_precondition(
+                "A reset removes all the nodes and their data, it must be confirmed",
+            ));
+        }
+
+        self.run_operation("reset", |progress| async move {
+            let result = cmd::node::reset(true, VerbosityLevel::Minimal).await;
+            Self::report_outcomes(&progress, result, |_| "Removed".to_string()).await
+        })
+    }
+
+    async fn maintain_running_nodes(
+        &self,
+        request: Request<MaintainRunningNodesRequest>,
+    ) -> Result<Response<Self::MaintainRunningNodesStream>, Status> {
+        info!("RPC request received {:?}", request.get_ref());
+        let request = request.into_inner();
+        let max_nodes_to_run = u16::try_from(request.max_nodes_to_run)
+            .map_err(|err| Status::invalid_argument(format!("Invalid node count: {err}")))?;
+        let args = AddArgs::try_from(request.add_options.unwrap_or_default())
+            .map_err(|err| Status::invalid_argument(format!("Invalid add options: {err}")))?;
+
+        self.run_operation("maintain running nodes", move |_progress| {
+            args.maintain(max_nodes_to_run, request.start_interval_millis)
+        })
+    }
 }
 
 impl SafeNodeManagerDaemon {
@@ -124,18 +340,398 @@ impl SafeNodeManagerDaemon {
 
 // The SafeNodeManager trait returns `Status` as its error. So the actual logic is here and we can easily map the errors
 // into Status inside the trait fns.
-impl SafeNodeManagerDaemon {}
+impl SafeNodeManagerDaemon {
+    /// Run a node operation on its own thread, streaming its progress back to the client.
+    ///
+    /// The operations of the node manager block while waiting between nodes, they would otherwise stall
+    /// the other requests. An operation keeps running if the client goes away.
+    fn run_operation<F, Fut, T>(
+        &self,
+        name: &'static str,
+        operation: F,
+    ) -> Result<Response<ProgressStream>, Status>
+    where
+        F: FnOnce(ProgressReporter) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>>,
+    {
+        let guard = Arc::clone(&self.operation_lock)
+            .try_lock_owned()
+            .map_err(|_| Status::unavailable("Another node operation is in progress"))?;
+        let (sender, receiver) = mpsc::channel(PROGRESS_CHANNEL_SIZE);
+        let progress = ProgressReporter { sender };
+
+        let _handle = std::thread::spawn(move || {
+            let _guard = guard;
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(err) => {
+                    error!("Failed to create the runtime of the {name} operation: {err:?}");
+                    let _ = progress.sender.blocking_send(Ok(NodeOperationProgress {
+                        stage: Stage::Failed as i32,
+                        target: None,
+                        message: format!("The {name} operation could not be started: {err}"),
+                    }));
+                    return;
+                }
+            };
+
+            runtime.block_on(async move {
+                info!("Running the {name} operation");
+                progress
+                    .report(
+                        Stage::Started,
+                        None,
+                        format!("The {name} operation has started"),
+                    )
+                    .await;
+                match operation(progress.clone()).await {
+                    Ok(_) => {
+                        info!("The {name} operation has completed");
+                        progress
+                            .report(
+                                Stage::Completed,
+                                None,
+                                format!("The {name} operation has completed"),
+                            )
+                            .await;
+                    }
+                    Err(err) => {
+                        error!("The {name} operation failed: {err:?}");
+                        progress
+                            .report(
+                                Stage::Failed,
+                                None,
+                                format!("The {name} operation failed: {err}"),
+                            )
+                            .await;
+                    }
+                }
+            });
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    /// Apply the operation to the selected nodes one by one, reporting the outcome for each of them.
+    async fn for_each_node<F, Fut, T>(
+        selection: Option<NodeSelection>,
+        progress: &ProgressReporter,
+        done: &str,
+        operation: F,
+    ) -> Result<()>
+    where
+        F: Fn(Vec<String>, Vec<String>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let selection = selection.unwrap_or_default();
+        let mut targets = selection
+            .peer_ids
+            .into_iter()
+            .map(|peer_id| (vec![peer_id.clone()], vec![], peer_id))
+            .chain(
+                selection
+                    .service_names
+                    .into_iter()
+                    .map(|name| (vec![], vec![name.clone()], name)),
+            )
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            targets = Self::load_node_registry()?
+                .nodes
+                .iter()
+                .filter(|node| node.status != ServiceStatus::Removed)
+                .map(|node| {
+                    (
+                        vec![],
+                        vec![node.service_name.clone()],
+                        node.service_name.clone(),
+                    )
+                })
+                .collect();
+        }
+
+        let mut failed_count = 0;
+        for (peer_ids, service_names, target) in targets {
+            match operation(peer_ids, service_names).await {
+                Ok(_) => {
+                    progress
+                        .report(Stage::NodeSucceeded, Some(target), done)
+                        .await
+                }
+                Err(err) => {
+                    error!("Node operation failed for {target}: {err:?}");
+                    failed_count += 1;
+                    progress
+                        .report(Stage::NodeFailed, Some(target), err.to_string())
+                        .await;
+                }
+            }
+        }
+
+        if failed_count > 0 {
+            return Err(eyre!("The operation failed for {failed_count} node(s)"));
+        }
+        Ok(())
+    }
+
+    /// Report the outcome of each node of an operation applied to all the nodes in a single call,
+    /// including the ones carried by the error if it failed for some of them.
+    async fn report_outcomes<F>(
+        progress: &ProgressReporter,
+        result: Result<Vec<ServiceOutcome>>,
+        done: F,
+    ) -> Result<()>
+    where
+        F: Fn(&ServiceOutcome) -> String,
+    {
+        let outcomes = match &result {
+            Ok(outcomes) => outcomes.clone(),
+            Err(err) => err
+                .downcast_ref::<FailedOperations>()
+                .map(|failed| failed.outcomes.clone())
+                .unwrap_or_default(),
+        };
+        for outcome in outcomes {
+            let target = Some(outcome.service_name.clone());
+            match &outcome.error {
+                None => {
+                    progress
+                        .report(Stage::NodeSucceeded, target, done(&outcome))
+                        .await
+                }
+                Some(err) => progress.report(Stage::NodeFailed, target, err).await,
+            }
+        }
+        result.map(|_| ())
+    }
+}
+
+#[derive(Clone)]
+struct ProgressReporter {
+    sender: mpsc::Sender<Result<NodeOperationProgress, Status>>,
+}
+
+impl ProgressReporter {
+    async fn report(&self, stage: Stage, target: Option<String>, message: impl Into<String>) {
+        let progress = NodeOperationProgress {
+            stage: stage as i32,
+            target,
+            message: message.into(),
+        };
+        if let Err(err) = self.sender.send(Ok(progress)).await {
+            debug!("The client is no longer listening to the progress: {err}");
+        }
+    }
+}
+
+/// The arguments of `cmd::node::add`, parsed from an RPC request
+struct AddArgs {
+    auto_restart: bool,
+    auto_set_nat_flags: bool,
+    bandwidth_limits: BandwidthLimits,
+    count: Option<u16>,
+    data_dir_path: Option<PathBuf>,
+    enable_metrics_server: bool,
+    env_variables: Option<Vec<(String, String)>>,
+    home_network: bool,
+    local: bool,
+    log_dir_path: Option<PathBuf>,
+    log_format: Option<LogFormat>,
+    metrics_port: Option<PortRange>,
+    node_port: Option<PortRange>,
+    owner: Option<String>,
+    peers_args: PeersArgs,
+    resource_limits: ResourceLimits,
+    rpc_address: Option<Ipv4Addr>,
+    rpc_port: Option<PortRange>,
+    src_path: Option<PathBuf>,
+    upnp: bool,
+    url: Option<String>,
+    user: Option<String>,
+    version: Option<String>,
+}
+
+impl TryFrom<AddNodesRequest> for AddArgs {
+    type Error = color_eyre::Report;
+
+    fn try_from(request: AddNodesRequest) -> Result<Self> {
+        let count = if request.count == 0 {
+            None
+        } else {
+            Some(u16::try_from(request.count)?)
+        };
+        let log_format = request
+            .log_format
+            .map(|format| {
+                LogFormat::parse_from_str(&format)
+                    .map_err(|err| eyre!("Invalid log format {format:?}: {err}"))
+            })
+            .transpose()?;
+        let peers = request
+            .peers
+            .iter()
+            .map(String::as_str)
+            .map(parse_peer_addr)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            auto_restart: request.auto_restart,
+            auto_set_nat_flags: request.auto_set_nat_flags,
+            bandwidth_limits: BandwidthLimits {
+                max_upload: request.max_upload_bandwidth,
+                max_download: request.max_download_bandwidth,
+                max_per_peer: request.max_peer_bandwidth,
+            },
+            count,
+            data_dir_path: request.data_dir_path.map(PathBuf::from),
+            enable_metrics_server: request.enable_metrics_server,
+            env_variables: env_variables(request.env_variables),
+            home_network: request.home_network,
+            local: request.local,
+            log_dir_path: request.log_dir_path.map(PathBuf::from),
+            log_format,
+            metrics_port: request
+                .metrics_port
+                .as_deref()
+                .map(parse_port_range)
+                .transpose()?,
+            node_port: request
+                .node_port
+                .as_deref()
+                .map(parse_port_range)
+                .transpose()?,
+            owner: request.owner,
+            peers_args: PeersArgs {
+                first: request.first,
+                peers,
+                ..Default::default()
+            },
+            resource_limits: ResourceLimits {
+                cpu_quota: request.cpu_quota,
+                max_memory_mb: request.max_memory_mb,
+                max_open_files: request.max_open_files,
+                io_weight: request.io_weight.map(u16::try_from).transpose()?,
+            },
+            rpc_address: request
+                .rpc_address
+                .map(|address| address.parse::<Ipv4Addr>())
+                .transpose()?,
+            rpc_port: request
+                .rpc_port
+                .as_deref()
+                .map(parse_port_range)
+                .transpose()?,
+            src_path: request.src_path.map(PathBuf::from),
+            upnp: request.upnp,
+            url: request.url,
+            user: request.user,
+            version: request.version,
+        })
+    }
+}
+
+impl AddArgs {
+    async fn add(self) -> Result<Vec<String>> {
+        cmd::node::add(
+            self.auto_restart,
+            self.auto_set_nat_flags,
+            self.bandwidth_limits,
+            self.count,
+            self.data_dir_path,
+            self.enable_metrics_server,
+            self.env_variables,
+            self.home_network,
+            self.local,
+            self.log_dir_path,
+            self.log_format,
+            self.metrics_port,
+            self.node_port,
+            self.owner,
+            self.peers_args,
+            self.resource_limits,
+            self.rpc_address,
+            self.rpc_port,
+            self.src_path,
+            self.upnp,
+            self.url,
+            self.user,
+            self.version,
+            VerbosityLevel::Minimal,
+        )
+        .await
+    }
+
+    async fn maintain(self, max_nodes_to_run: u16, start_interval_millis: u64) -> Result<()> {
+        cmd::node::maintain_n_running_nodes(
+            self.auto_restart,
+            self.auto_set_nat_flags,
+            self.bandwidth_limits,
+            max_nodes_to_run,
+            self.data_dir_path,
+            self.enable_metrics_server,
+            self.env_variables,
+            self.home_network,
+            self.local,
+            self.log_dir_path,
+            self.log_format,
+            self.metrics_port,
+            self.node_port,
+            self.owner,
+            self.peers_args,
+            self.resource_limits,
+            self.rpc_address,
+            self.rpc_port,
+            self.src_path,
+            self.url,
+            self.upnp,
+            self.user,
+            self.version,
+            VerbosityLevel::Minimal,
+            start_interval_millis,
+        )
+        .await
+    }
+}
+
+fn env_variables(variables: Vec<EnvVariable>) -> Option<Vec<(String, String)>> {
+    if variables.is_empty() {
+        return None;
+    }
+    Some(
+        variables
+            .into_iter()
+            .map(|variable| (variable.key, variable.value))
+            .collect(),
+    )
+}
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
     let _log_handles = get_log_builder()?.initialize()?;
     println!("Starting safenodemand");
     let args = Args::parse();
-    let service = SafeNodeManagerDaemon {};
+    let token_path = match args.token_path {
+        Some(path) => path,
+        None => get_daemon_auth_token_path()?,
+    };
+    let token = daemon_auth::load_or_create_token(&token_path)?;
+    println!("RPC clients must present the token stored at {token_path:?}");
+    let service = SafeNodeManagerDaemon {
+        operation_lock: Arc::new(Mutex::new(())),
+    };
 
     // adding our service to our server.
     if let Err(err) = Server::builder()
-        .add_service(SafeNodeManagerServer::new(service))
+        .add_service(SafeNodeManagerServer::with_interceptor(
+            service,
+            move |request: Request<()>| {
+                daemon_auth::check_request(&request, &token)?;
+                Ok(request)
+            },
+        ))
         .serve(SocketAddr::new(IpAddr::V4(args.address), args.port))
         .await
     {
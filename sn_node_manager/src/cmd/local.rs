@@ -11,14 +11,16 @@
 use super::get_bin_path;
 use crate::{
     local::{kill_network, run_network, LocalNetworkOptions},
+    output::ServiceOutcome,
     print_banner, status_report, VerbosityLevel,
 };
 use color_eyre::{eyre::eyre, Help, Report, Result};
 use sn_logging::LogFormat;
 use sn_peers_acquisition::PeersArgs;
+use sn_protocol::transport::TransportMode;
 use sn_releases::{ReleaseType, SafeReleaseRepoActions};
 use sn_service_management::{
-    control::ServiceController, get_local_node_registry_path, NodeRegistry,
+    control::ServiceController, get_local_node_registry_path, NodeRegistry, ServiceStatus,
 };
 use std::path::PathBuf;
 
@@ -35,8 +37,9 @@ pub async fn join(
     owner_prefix: Option<String>,
     peers_args: PeersArgs,
     skip_validation: bool,
+    transport: Option<TransportMode>,
     verbosity: VerbosityLevel,
-) -> Result<(), Report> {
+) -> Result<Vec<ServiceOutcome>, Report> {
     if verbosity != VerbosityLevel::Minimal {
         print_banner("Joining Local Network");
     }
@@ -91,26 +94,39 @@ pub async fn join(
         safenode_bin_path: node_path,
         skip_validation,
         log_format,
+        transport,
     };
     run_network(options, &mut local_node_registry, &ServiceController {}).await?;
-    Ok(())
+    Ok(local_node_registry
+        .nodes
+        .iter()
+        .map(ServiceOutcome::from_node)
+        .collect())
 }
 
-pub fn kill(keep_directories: bool, verbosity: VerbosityLevel) -> Result<()> {
+pub fn kill(keep_directories: bool, verbosity: VerbosityLevel) -> Result<Vec<ServiceOutcome>> {
     let local_reg_path = &get_local_node_registry_path()?;
     let local_node_registry = NodeRegistry::load(local_reg_path)?;
     if local_node_registry.nodes.is_empty() {
         info!("No local network is currently running, cannot kill it");
         println!("No local network is currently running");
-    } else {
-        if verbosity != VerbosityLevel::Minimal {
-            print_banner("Killing Local Network");
-        }
-        info!("Kill local network");
-        kill_network(&local_node_registry, keep_directories)?;
-        std::fs::remove_file(local_reg_path)?;
+        return Ok(Vec::new());
     }
-    Ok(())
+
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Killing Local Network");
+    }
+    info!("Kill local network");
+    kill_network(&local_node_registry, keep_directories)?;
+    std::fs::remove_file(local_reg_path)?;
+    Ok(local_node_registry
+        .nodes
+        .iter()
+        .map(|node| ServiceOutcome {
+            status: Some(ServiceStatus::Removed),
+            ..ServiceOutcome::from_node(node)
+        })
+        .collect())
 }
 
 pub async fn run(
@@ -126,8 +142,9 @@ pub async fn run(
     owner: Option<String>,
     owner_prefix: Option<String>,
     skip_validation: bool,
+    transport: Option<TransportMode>,
     verbosity: VerbosityLevel,
-) -> Result<(), Report> {
+) -> Result<Vec<ServiceOutcome>, Report> {
     // In the clean case, the node registry must be loaded *after* the existing network has
     // been killed, which clears it out.
     let local_node_reg_path = &get_local_node_registry_path()?;
@@ -188,11 +205,16 @@ pub async fn run(
         safenode_bin_path: node_path,
         skip_validation,
         log_format,
+        transport,
     };
     run_network(options, &mut local_node_registry, &ServiceController {}).await?;
 
     local_node_registry.save()?;
-    Ok(())
+    Ok(local_node_registry
+        .nodes
+        .iter()
+        .map(ServiceOutcome::from_node)
+        .collect())
 }
 
 pub async fn status(details: bool, fail: bool, json: bool) -> Result<()> {
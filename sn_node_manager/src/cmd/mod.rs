@@ -14,19 +14,39 @@ pub mod nat_detection;
 pub mod node;
 
 use crate::{
+    config,
     helpers::{download_and_extract_release, get_bin_version},
+    output::ServiceOutcome,
     print_banner, VerbosityLevel,
 };
 use color_eyre::{eyre::eyre, Result};
 use colored::Colorize;
 use semver::Version;
 use sn_releases::{ReleaseType, SafeReleaseRepoActions};
-use sn_service_management::UpgradeResult;
+use sn_service_management::{NodeRegistry, UpgradeResult};
 use std::{
     path::PathBuf,
     process::{Command, Stdio},
 };
 
+/// The outcome of a command on the auditor, faucet or daemon, read back from the node registry.
+pub fn auxiliary_service_outcomes(release_type: ReleaseType) -> Result<Vec<ServiceOutcome>> {
+    let node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    let outcome = match release_type {
+        ReleaseType::SnAuditor => node_registry.auditor.as_ref().map(|auditor| {
+            ServiceOutcome::from_service(&auditor.service_name, &auditor.status, &auditor.version)
+        }),
+        ReleaseType::Faucet => node_registry.faucet.as_ref().map(|faucet| {
+            ServiceOutcome::from_service(&faucet.service_name, &faucet.status, &faucet.version)
+        }),
+        ReleaseType::SafenodeManagerDaemon => node_registry.daemon.as_ref().map(|daemon| {
+            ServiceOutcome::from_service(&daemon.service_name, &daemon.status, &daemon.version)
+        }),
+        _ => None,
+    };
+    Ok(outcome.into_iter().collect())
+}
+
 pub async fn download_and_get_upgrade_bin_path(
     custom_bin_path: Option<PathBuf>,
     release_type: ReleaseType,
@@ -14,30 +14,51 @@ use crate::{
         add_node,
         config::{AddNodeServiceOptions, PortRange},
     },
+    backup,
     config::{self, is_running_as_root},
+    doctor::{self, Host},
+    fleet::{self, FleetAction, FleetConfig},
     helpers::{download_and_extract_release, get_bin_version},
-    print_banner, refresh_node_registry, status_report, ServiceManager, VerbosityLevel,
+    output::{merge_outcomes, FailedOperations, ServiceOutcome},
+    ports::{self, PortPool},
+    print_banner, refresh_node_registry, relocate,
+    rolling_upgrade::{backup_binary, wait_for_node_health, RollingUpgradeOptions},
+    status_report, ServiceManager, VerbosityLevel,
+};
+use color_eyre::{
+    eyre::{eyre, Report},
+    Help, Result,
 };
-use color_eyre::{eyre::eyre, Help, Result};
 use colored::Colorize;
+use libp2p::Multiaddr;
 use libp2p_identity::PeerId;
 use semver::Version;
+use service_manager::ServiceInstallCtx;
 use sn_logging::LogFormat;
 use sn_peers_acquisition::PeersArgs;
 use sn_releases::{ReleaseType, SafeReleaseRepoActions};
 use sn_service_management::{
     control::{ServiceControl, ServiceController},
     rpc::RpcClient,
-    NodeRegistry, NodeService, ServiceStateActions, ServiceStatus, UpgradeOptions, UpgradeResult,
+    BandwidthLimits, NodeRegistry, NodeService, NodeServiceData, ResourceLimits,
+    ServiceStateActions, ServiceStatus, UpgradeOptions, UpgradeResult,
 };
 use sn_transfers::HotWallet;
-use std::{cmp::Ordering, io::Write, net::Ipv4Addr, path::PathBuf, str::FromStr};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    io::Write,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 use tracing::debug;
 
 /// Returns the added service names
 pub async fn add(
     auto_restart: bool,
     auto_set_nat_flags: bool,
+    bandwidth_limits: BandwidthLimits,
     count: Option<u16>,
     data_dir_path: Option<PathBuf>,
     enable_metrics_server: bool,
@@ -50,6 +71,7 @@ pub async fn add(
     node_port: Option<PortRange>,
     owner: Option<String>,
     peers_args: PeersArgs,
+    resource_limits: ResourceLimits,
     rpc_address: Option<Ipv4Addr>,
     rpc_port: Option<PortRange>,
     src_path: Option<PathBuf>,
@@ -84,6 +106,29 @@ pub async fn add(
     )?;
 
     let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+
+    let port_pool = PortPool::load(&config::get_port_pool_path()?)?;
+    let service_count = count.unwrap_or(1);
+    let mut used_ports = ports::registry_ports(&node_registry.nodes);
+    let node_port = resolve_ports(
+        node_port,
+        port_pool.node_range()?,
+        service_count,
+        &mut used_ports,
+    )?;
+    let rpc_port = resolve_ports(
+        rpc_port,
+        port_pool.rpc_range()?,
+        service_count,
+        &mut used_ports,
+    )?;
+    let metrics_pool = if enable_metrics_server {
+        port_pool.metrics_range()?
+    } else {
+        None
+    };
+    let metrics_port = resolve_ports(metrics_port, metrics_pool, service_count, &mut used_ports)?;
+
     let release_repo = <dyn SafeReleaseRepoActions>::default_config();
 
     let (safenode_src_path, version) = if let Some(path) = src_path.clone() {
@@ -135,6 +180,7 @@ pub async fn add(
     let options = AddNodeServiceOptions {
         auto_restart,
         auto_set_nat_flags,
+        bandwidth_limits,
         bootstrap_peers,
         count,
         delete_safenode_src: src_path.is_none(),
@@ -147,6 +193,7 @@ pub async fn add(
         metrics_port,
         owner,
         node_port,
+        resource_limits,
         rpc_address,
         rpc_port,
         safenode_src_path,
@@ -168,11 +215,132 @@ pub async fn add(
     Ok(added_services_names)
 }
 
+/// The outcomes for services that were just added, read from the node registry.
+pub fn added_service_outcomes(service_names: &[String]) -> Result<Vec<ServiceOutcome>> {
+    let node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    Ok(node_registry
+        .nodes
+        .iter()
+        .filter(|node| service_names.contains(&node.service_name))
+        .map(ServiceOutcome::from_node)
+        .collect())
+}
+
+/// Use the requested ports, or allocate them from the reserved pool, checking no other process is
+/// listening on them. Conflicts with the registry are checked when the services are added.
+fn resolve_ports(
+    requested: Option<PortRange>,
+    pool: Option<PortRange>,
+    count: u16,
+    used_ports: &mut HashSet<u16>,
+) -> Result<Option<PortRange>> {
+    let range = match (requested, pool) {
+        (Some(range), _) => {
+            ports::check_ports_not_in_use(&range)?;
+            Some(range)
+        }
+        (None, Some(pool)) => {
+            let range = ports::allocate(&pool, count, used_ports, ports::is_port_in_use)?;
+            debug!("Allocated ports {range:?} from the pool {pool:?}");
+            Some(range)
+        }
+        (None, None) => None,
+    };
+    if let Some(range) = &range {
+        let (start, end) = ports::bounds(range);
+        used_ports.extend(start..=end);
+    }
+    Ok(range)
+}
+
+/// Archive the keypair, reward wallet and forwarded balance of the nodes, and optionally their
+/// records, with the checksums of the files.
+pub async fn backup(
+    include_records: bool,
+    output_path: Option<PathBuf>,
+    peer_ids: Vec<String>,
+    service_names: Vec<String>,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Backup Safenode Services");
+    }
+    info!("Backing up safenode services with include_records={include_records} for: {peer_ids:?}, {service_names:?}");
+
+    let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    refresh_node_registry(
+        &mut node_registry,
+        &ServiceController {},
+        verbosity != VerbosityLevel::Minimal,
+        false,
+    )
+    .await?;
+    node_registry.save()?;
+
+    let service_indices = get_services_for_ops(&node_registry, peer_ids, service_names)?;
+    if service_indices.is_empty() {
+        info!("No services to back up");
+        if verbosity != VerbosityLevel::Minimal {
+            println!("No services to back up");
+        }
+        return Ok(Vec::new());
+    }
+    let nodes = service_indices
+        .iter()
+        .map(|&index| &node_registry.nodes[index])
+        .collect::<Vec<_>>();
+    if include_records
+        && nodes
+            .iter()
+            .any(|node| node.status == ServiceStatus::Running)
+    {
+        warn!("Backing up the records of running nodes");
+        if verbosity != VerbosityLevel::Minimal {
+            println!("The records of running nodes can change while they are copied. Stop the nodes for a consistent backup.");
+        }
+    }
+
+    let output_path = output_path.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "safenode-backup-{}.tar.gz",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+        ))
+    });
+    let manifest = backup::create_archive(
+        &output_path,
+        &nodes,
+        include_records,
+        node_registry.bootstrap_peers.clone(),
+        node_registry.environment_variables.clone(),
+    )?;
+
+    info!(
+        "Backed up {} node(s) to {output_path:?}",
+        manifest.nodes.len()
+    );
+    if verbosity != VerbosityLevel::Minimal {
+        for node_backup in manifest.nodes.iter() {
+            println!(
+                "{} Backed up {} ({}) with {} file(s)",
+                "✓".green(),
+                node_backup.service_data.service_name,
+                node_backup
+                    .service_data
+                    .peer_id
+                    .map_or("-".to_string(), |peer_id| peer_id.to_string()),
+                node_backup.checksums.len()
+            );
+        }
+        println!("The backup was written to {output_path:?}");
+    }
+    Ok(nodes.into_iter().map(ServiceOutcome::from_node).collect())
+}
+
 pub async fn balance(
     peer_ids: Vec<String>,
     service_names: Vec<String>,
     verbosity: VerbosityLevel,
-) -> Result<()> {
+) -> Result<Vec<ServiceOutcome>> {
     if verbosity != VerbosityLevel::Minimal {
         print_banner("Reward Balances");
     }
@@ -190,24 +358,87 @@ pub async fn balance(
     if service_indices.is_empty() {
         info!("Service indices is empty, cannot obtain the balance");
         // This could be the case if all services are at `Removed` status.
-        println!("No balances to display");
-        return Ok(());
+        if verbosity != VerbosityLevel::Minimal {
+            println!("No balances to display");
+        }
+        return Ok(Vec::new());
     }
     debug!("Obtaining balances for {} services", service_indices.len());
 
+    let mut outcomes = Vec::new();
     for &index in &service_indices {
         let node = &mut node_registry.nodes[index];
         let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
         let service = NodeService::new(node, Box::new(rpc_client));
         let wallet = HotWallet::load_from(&service.service_data.data_dir_path)
             .inspect_err(|err| error!("Error while loading hot wallet: {err:?}"))?;
-        println!(
-            "{}: {}",
-            service.service_data.service_name,
-            wallet.balance()
-        );
+        if verbosity != VerbosityLevel::Minimal {
+            println!(
+                "{}: {}",
+                service.service_data.service_name,
+                wallet.balance()
+            );
+        }
+        let mut outcome = ServiceOutcome::from_node(service.service_data);
+        outcome.reward_balance = Some(wallet.balance().to_string());
+        outcomes.push(outcome);
     }
-    Ok(())
+    Ok(outcomes)
+}
+
+/// Report problems with the node services: port conflicts, missing binaries, stale PIDs and
+/// services missing from the registry or from the OS.
+pub async fn doctor(verbosity: VerbosityLevel) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Safenode Services Doctor");
+    }
+
+    let node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    let problems = doctor::diagnose(&node_registry.nodes, &Host {})?;
+    let mut outcomes = node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .map(ServiceOutcome::from_node)
+        .collect::<Vec<_>>();
+    if problems.is_empty() {
+        if verbosity != VerbosityLevel::Minimal {
+            println!("{} No problems found", "✓".green());
+        }
+        return Ok(outcomes);
+    }
+
+    for problem in problems.iter() {
+        warn!("Doctor found a problem: {problem:?}");
+        if verbosity != VerbosityLevel::Minimal {
+            println!("{} {problem}", "✕".red());
+        }
+        for service_name in problem.service_names() {
+            let index = match outcomes
+                .iter()
+                .position(|outcome| outcome.service_name == service_name)
+            {
+                Some(index) => index,
+                None => {
+                    outcomes.push(ServiceOutcome {
+                        service_name: service_name.to_string(),
+                        ..Default::default()
+                    });
+                    outcomes.len() - 1
+                }
+            };
+            let outcome = &mut outcomes[index];
+            outcome.error = Some(match outcome.error.take() {
+                Some(error) => format!("{error}; {problem}"),
+                None => problem.to_string(),
+            });
+        }
+    }
+    Err(Report::from(FailedOperations {
+        message: format!("Found {} problem(s) with the node services", problems.len()),
+        outcomes,
+    }))
+    .suggestion("Running 'status' refreshes the registry, which clears stale PIDs")
 }
 
 pub async fn remove(
@@ -215,7 +446,7 @@ pub async fn remove(
     peer_ids: Vec<String>,
     service_names: Vec<String>,
     verbosity: VerbosityLevel,
-) -> Result<()> {
+) -> Result<Vec<ServiceOutcome>> {
     if verbosity != VerbosityLevel::Minimal {
         print_banner("Remove Safenode Services");
     }
@@ -237,10 +468,10 @@ pub async fn remove(
         if verbosity != VerbosityLevel::Minimal {
             println!("No services were eligible for removal");
         }
-        return Ok(());
+        return Ok(Vec::new());
     }
 
-    let mut failed_services = Vec::new();
+    let mut outcomes = Vec::new();
     for &index in &service_indices {
         let node = &mut node_registry.nodes[index];
         let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
@@ -251,19 +482,22 @@ pub async fn remove(
             Ok(()) => {
                 debug!("Removed service {}", node.service_name);
                 node_registry.save()?;
+                outcomes.push(ServiceOutcome::from_node(&node_registry.nodes[index]));
             }
             Err(err) => {
                 error!("Failed to remove service {}: {err}", node.service_name);
-                failed_services.push((node.service_name.clone(), err.to_string()))
+                outcomes.push(ServiceOutcome::from_node(node).with_error(err.to_string()));
             }
         }
     }
 
-    summarise_any_failed_ops(failed_services, "remove", verbosity)
+    summarise_any_failed_ops(outcomes, "remove", verbosity)
 }
 
-pub async fn reset(force: bool, verbosity: VerbosityLevel) -> Result<()> {
-    print_banner("Reset Safenode Services");
+pub async fn reset(force: bool, verbosity: VerbosityLevel) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Reset Safenode Services");
+    }
     info!("Resetting all safenode services, with force={force}");
 
     if !force {
@@ -274,12 +508,12 @@ pub async fn reset(force: bool, verbosity: VerbosityLevel) -> Result<()> {
         std::io::stdin().read_line(&mut input)?;
         if input.trim().to_lowercase() != "y" {
             println!("Reset aborted");
-            return Ok(());
+            return Ok(Vec::new());
         }
     }
 
     stop(vec![], vec![], verbosity).await?;
-    remove(false, vec![], vec![], verbosity).await?;
+    let outcomes = remove(false, vec![], vec![], verbosity).await?;
 
     // Due the possibility of repeated runs of the `reset` command, we need to check for the
     // existence of this file before attempting to delete it, since `remove_file` will return an
@@ -290,7 +524,186 @@ pub async fn reset(force: bool, verbosity: VerbosityLevel) -> Result<()> {
         std::fs::remove_file(node_registry_path)?;
     }
 
-    Ok(())
+    Ok(outcomes)
+}
+
+/// Register the nodes of a backup as new services, with their keypair, wallet and records, so a
+/// host can be rebuilt with the same peer IDs and unclaimed rewards.
+///
+/// The restored services are numbered after the existing ones, so they can get new names. They
+/// are not started.
+pub async fn restore(
+    archive_path: PathBuf,
+    data_dir_path: Option<PathBuf>,
+    log_dir_path: Option<PathBuf>,
+    reassign_ports: bool,
+    service_names: Vec<String>,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Restore Safenode Services");
+    }
+    info!("Restoring safenode services from {archive_path:?} for: {service_names:?}");
+
+    let extract_dir = std::env::temp_dir().join(format!(
+        "safenode-restore-{}",
+        uuid::Uuid::new_v4().simple()
+    ));
+    let result = restore_from_dir(
+        &archive_path,
+        &extract_dir,
+        data_dir_path,
+        log_dir_path,
+        reassign_ports,
+        service_names,
+        verbosity,
+    )
+    .await;
+    if let Err(err) = std::fs::remove_dir_all(&extract_dir) {
+        warn!("Failed to remove the extracted backup at {extract_dir:?}: {err}");
+    }
+    result
+}
+
+async fn restore_from_dir(
+    archive_path: &Path,
+    extract_dir: &Path,
+    data_dir_path: Option<PathBuf>,
+    log_dir_path: Option<PathBuf>,
+    reassign_ports: bool,
+    service_names: Vec<String>,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    let manifest = backup::extract_archive(archive_path, extract_dir)?;
+    for service_name in service_names.iter() {
+        if !manifest
+            .nodes
+            .iter()
+            .any(|node| node.service_data.service_name == *service_name)
+        {
+            return Err(eyre!("The backup has no service named '{service_name}'"));
+        }
+    }
+
+    let node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    let registered_peer_ids = node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .filter_map(|node| node.peer_id)
+        .collect::<HashSet<_>>();
+
+    let mut outcomes = Vec::new();
+    for node_backup in manifest.nodes.iter().filter(|node| {
+        service_names.is_empty() || service_names.contains(&node.service_data.service_name)
+    }) {
+        let service_data = &node_backup.service_data;
+        let backup_name = service_data.service_name.clone();
+        if let Some(peer_id) = service_data
+            .peer_id
+            .filter(|peer_id| registered_peer_ids.contains(peer_id))
+        {
+            error!("Cannot restore {backup_name}: {peer_id} is already registered");
+            outcomes.push(ServiceOutcome::from_node(service_data).with_error(format!(
+                "A node with peer ID {peer_id} is already registered"
+            )));
+            continue;
+        }
+
+        let result: Result<ServiceOutcome> = async {
+            let (node_port, rpc_port, metrics_port) = if reassign_ports {
+                (None, None, None)
+            } else {
+                (
+                    service_data.node_port.map(PortRange::Single),
+                    Some(PortRange::Single(service_data.rpc_socket_addr.port())),
+                    service_data.metrics_port.map(PortRange::Single),
+                )
+            };
+            let rpc_address = match service_data.rpc_socket_addr.ip() {
+                IpAddr::V4(address) => Some(address),
+                IpAddr::V6(_) => None,
+            };
+            let added_names = add(
+                service_data.auto_restart,
+                false,
+                service_data.bandwidth_limits,
+                Some(1),
+                data_dir_path.clone(),
+                service_data.metrics_port.is_some(),
+                manifest.environment_variables.clone(),
+                service_data.home_network,
+                service_data.local,
+                log_dir_path.clone(),
+                service_data.log_format,
+                metrics_port,
+                node_port,
+                service_data.owner.clone(),
+                PeersArgs {
+                    peers: manifest.bootstrap_peers.clone(),
+                    ..Default::default()
+                },
+                service_data.resource_limits,
+                rpc_address,
+                rpc_port,
+                None,
+                service_data.upnp,
+                None,
+                service_data.user.clone(),
+                Some(service_data.version.clone()),
+                verbosity,
+            )
+            .await?;
+            let new_name = added_names
+                .first()
+                .ok_or_else(|| eyre!("The service was not added"))?
+                .clone();
+
+            let node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+            let node = node_registry
+                .nodes
+                .iter()
+                .find(|node| node.service_name == new_name)
+                .ok_or_else(|| eyre!("{new_name} is not in the node registry"))?;
+            relocate::copy_and_verify(
+                &extract_dir.join(node_backup.archive_dir()),
+                &node.data_dir_path,
+            )?;
+            if let Some(user) = &node.user {
+                config::set_owner_recursively(&node.data_dir_path, user)?;
+            }
+            // The registry only learns the peer ID once the node has started.
+            let mut outcome = ServiceOutcome::from_node(node);
+            outcome.peer_id = service_data.peer_id.map(|peer_id| peer_id.to_string());
+            Ok(outcome)
+        }
+        .await;
+
+        match result {
+            Ok(outcome) => {
+                info!("Restored {backup_name} as {}", outcome.service_name);
+                if verbosity != VerbosityLevel::Minimal {
+                    println!(
+                        "{} Restored {backup_name} ({}) as {}",
+                        "✓".green(),
+                        outcome.peer_id.as_deref().unwrap_or("-"),
+                        outcome.service_name
+                    );
+                }
+                outcomes.push(outcome);
+            }
+            Err(err) => {
+                error!("Failed to restore {backup_name}: {err}");
+                outcomes.push(ServiceOutcome::from_node(service_data).with_error(err.to_string()));
+            }
+        }
+    }
+
+    let outcomes = summarise_any_failed_ops(outcomes, "restore", verbosity)?;
+    if verbosity != VerbosityLevel::Minimal {
+        println!("Use the start command to run the restored services");
+    }
+    Ok(outcomes)
 }
 
 pub async fn start(
@@ -298,7 +711,7 @@ pub async fn start(
     peer_ids: Vec<String>,
     service_names: Vec<String>,
     verbosity: VerbosityLevel,
-) -> Result<()> {
+) -> Result<Vec<ServiceOutcome>> {
     if verbosity != VerbosityLevel::Minimal {
         print_banner("Start Safenode Services");
     }
@@ -322,10 +735,10 @@ pub async fn start(
         if verbosity != VerbosityLevel::Minimal {
             println!("No services were eligible to be started");
         }
-        return Ok(());
+        return Ok(Vec::new());
     }
 
-    let mut failed_services = Vec::new();
+    let mut outcomes = Vec::new();
     for &index in &service_indices {
         let node = &mut node_registry.nodes[index];
         let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
@@ -344,15 +757,16 @@ pub async fn start(
             Ok(()) => {
                 debug!("Started service {}", node.service_name);
                 node_registry.save()?;
+                outcomes.push(ServiceOutcome::from_node(&node_registry.nodes[index]));
             }
             Err(err) => {
                 error!("Failed to start service {}: {err}", node.service_name);
-                failed_services.push((node.service_name.clone(), err.to_string()))
+                outcomes.push(ServiceOutcome::from_node(node).with_error(err.to_string()));
             }
         }
     }
 
-    summarise_any_failed_ops(failed_services, "start", verbosity)
+    summarise_any_failed_ops(outcomes, "start", verbosity)
 }
 
 pub async fn status(details: bool, fail: bool, json: bool) -> Result<()> {
@@ -378,7 +792,7 @@ pub async fn stop(
     peer_ids: Vec<String>,
     service_names: Vec<String>,
     verbosity: VerbosityLevel,
-) -> Result<()> {
+) -> Result<Vec<ServiceOutcome>> {
     if verbosity != VerbosityLevel::Minimal {
         print_banner("Stop Safenode Services");
     }
@@ -400,10 +814,10 @@ pub async fn stop(
         if verbosity != VerbosityLevel::Minimal {
             println!("No services were eligible to be stopped");
         }
-        return Ok(());
+        return Ok(Vec::new());
     }
 
-    let mut failed_services = Vec::new();
+    let mut outcomes = Vec::new();
     for &index in &service_indices {
         let node = &mut node_registry.nodes[index];
         let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
@@ -414,15 +828,16 @@ pub async fn stop(
             Ok(()) => {
                 debug!("Stopped service {}", node.service_name);
                 node_registry.save()?;
+                outcomes.push(ServiceOutcome::from_node(&node_registry.nodes[index]));
             }
             Err(err) => {
                 error!("Failed to stop service {}: {err}", node.service_name);
-                failed_services.push((node.service_name.clone(), err.to_string()))
+                outcomes.push(ServiceOutcome::from_node(node).with_error(err.to_string()));
             }
         }
     }
 
-    summarise_any_failed_ops(failed_services, "stop", verbosity)
+    summarise_any_failed_ops(outcomes, "stop", verbosity)
 }
 
 pub async fn upgrade(
@@ -432,11 +847,18 @@ pub async fn upgrade(
     interval: u64,
     peer_ids: Vec<String>,
     provided_env_variables: Option<Vec<(String, String)>>,
+    rolling: Option<RollingUpgradeOptions>,
     service_names: Vec<String>,
     url: Option<String>,
     version: Option<String>,
     verbosity: VerbosityLevel,
-) -> Result<()> {
+) -> Result<Vec<ServiceOutcome>> {
+    if rolling.is_some() && do_not_start {
+        return Err(eyre!(
+            "A rolling upgrade has to start the nodes to check they rejoin the network"
+        ));
+    }
+
     // In the case of a custom binary, we want to force the use of it. Regardless of its version
     // number, the user has probably built it for some special case. They may have not used the
     // `--force` flag; if they didn't, we can just do that for them here.
@@ -485,30 +907,38 @@ pub async fn upgrade(
             if verbosity != VerbosityLevel::Minimal {
                 println!("{} All nodes are at the latest version", "✓".green());
             }
-            return Ok(());
+            return Ok(Vec::new());
         }
     }
 
     let service_indices = get_services_for_ops(&node_registry, peer_ids, service_names)?;
     trace!("service_indices len: {}", service_indices.len());
+    if let Some(rolling) = rolling {
+        return upgrade_in_batches(
+            &mut node_registry,
+            &service_indices,
+            provided_env_variables,
+            upgrade_bin_path,
+            target_version,
+            use_force,
+            interval,
+            rolling,
+            verbosity,
+        )
+        .await;
+    }
     let mut upgrade_summary = Vec::new();
 
     for &index in &service_indices {
+        let options = upgrade_options(
+            &node_registry,
+            &provided_env_variables,
+            upgrade_bin_path.clone(),
+            target_version.clone(),
+            use_force,
+            !do_not_start,
+        );
         let node = &mut node_registry.nodes[index];
-        let env_variables = if provided_env_variables.is_some() {
-            &provided_env_variables
-        } else {
-            &node_registry.environment_variables
-        };
-        let options = UpgradeOptions {
-            auto_restart: false,
-            bootstrap_peers: node_registry.bootstrap_peers.clone(),
-            env_variables: env_variables.clone(),
-            force: use_force,
-            start_service: !do_not_start,
-            target_bin_path: upgrade_bin_path.clone(),
-            target_version: target_version.clone(),
-        };
         let service_name = node.service_name.clone();
 
         let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
@@ -541,26 +971,735 @@ pub async fn upgrade(
     }
 
     node_registry.save()?;
-    print_upgrade_summary(upgrade_summary.clone());
+    if verbosity != VerbosityLevel::Minimal {
+        print_upgrade_summary(upgrade_summary.clone());
+    }
 
+    let outcomes = upgrade_outcomes(&node_registry, &upgrade_summary);
     if upgrade_summary.iter().any(|(_, r)| {
         matches!(r, UpgradeResult::Error(_))
             || matches!(r, UpgradeResult::UpgradedButNotStarted(_, _, _))
     }) {
-        return Err(eyre!("There was a problem upgrading one or more nodes").suggestion(
+        return Err(FailedOperations {
+            message: "There was a problem upgrading one or more nodes".to_string(),
+            outcomes,
+        })
+        .suggestion(
             "For any services that were upgraded but did not start, you can attempt to start them \
-                again using the 'start' command."));
+                again using the 'start' command.",
+        );
+    }
+
+    Ok(outcomes)
+}
+
+fn upgrade_outcomes(
+    node_registry: &NodeRegistry,
+    upgrade_summary: &[(String, UpgradeResult)],
+) -> Vec<ServiceOutcome> {
+    upgrade_summary
+        .iter()
+        .filter_map(|(service_name, upgrade_result)| {
+            node_registry
+                .nodes
+                .iter()
+                .find(|node| node.service_name == *service_name)
+                .map(|node| ServiceOutcome::from_upgrade(node, upgrade_result))
+        })
+        .collect()
+}
+
+/// Upgrade the services a batch at a time, waiting for the upgraded nodes to rejoin the network
+/// before moving on to the next batch.
+///
+/// The upgrade halts at the first batch with a node failing to upgrade or to become healthy. The
+/// nodes of that batch are put back on their previous binary if a rollback was requested.
+async fn upgrade_in_batches(
+    node_registry: &mut NodeRegistry,
+    service_indices: &[usize],
+    provided_env_variables: Option<Vec<(String, String)>>,
+    upgrade_bin_path: PathBuf,
+    target_version: Version,
+    use_force: bool,
+    interval: u64,
+    rolling: RollingUpgradeOptions,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    let batches = service_indices
+        .chunks(rolling.batch_size.max(1))
+        .collect::<Vec<_>>();
+    let batch_count = batches.len();
+    let mut upgrade_summary = Vec::new();
+
+    for (batch_number, batch) in batches.into_iter().enumerate() {
+        let batch_number = batch_number + 1;
+        info!(
+            "Upgrading batch {batch_number}/{batch_count} of {} services",
+            batch.len()
+        );
+        if verbosity != VerbosityLevel::Minimal {
+            println!("Upgrading batch {batch_number}/{batch_count}");
+        }
+
+        // The services whose binary was replaced, with their previous version and binary
+        let mut upgraded = Vec::new();
+        let mut batch_error = None;
+        for &index in batch {
+            let options = upgrade_options(
+                node_registry,
+                &provided_env_variables,
+                upgrade_bin_path.clone(),
+                target_version.clone(),
+                use_force,
+                true,
+            );
+            let node = &mut node_registry.nodes[index];
+            let service_name = node.service_name.clone();
+            let previous_version = node.version.clone();
+            let backup_path = match backup_binary(&node.safenode_path, &previous_version) {
+                Ok(path) => path,
+                Err(err) => {
+                    batch_error = Some(format!("{service_name} could not be backed up: {err}"));
+                    break;
+                }
+            };
+
+            let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+            let service = NodeService::new(node, Box::new(rpc_client));
+            let mut service_manager =
+                ServiceManager::new(service, Box::new(ServiceController {}), verbosity);
+            let upgrade_result = service_manager
+                .upgrade(options)
+                .await
+                .unwrap_or_else(|err| UpgradeResult::Error(format!("Error: {err}")));
+            info!("Service: {service_name} upgrade result: {upgrade_result:?}");
+
+            match &upgrade_result {
+                UpgradeResult::NotRequired => {
+                    let _ = std::fs::remove_file(&backup_path);
+                }
+                UpgradeResult::Upgraded(..) | UpgradeResult::Forced(..) => {
+                    upgraded.push((index, previous_version, backup_path));
+                }
+                UpgradeResult::UpgradedButNotStarted(..) | UpgradeResult::Error(_) => {
+                    upgraded.push((index, previous_version, backup_path));
+                    batch_error = Some(format!("{service_name} failed to upgrade"));
+                }
+            }
+            upgrade_summary.push((service_name, upgrade_result));
+            if batch_error.is_some() {
+                break;
+            }
+        }
+        node_registry.save()?;
+
+        if batch_error.is_none() {
+            for (index, _, _) in &upgraded {
+                let node = &node_registry.nodes[*index];
+                let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+                match wait_for_node_health(
+                    &rpc_client,
+                    &target_version,
+                    rolling.min_connected_peers,
+                    rolling.health_check_timeout,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        info!("Service {} rejoined the network", node.service_name);
+                        if verbosity != VerbosityLevel::Minimal {
+                            println!("{} {} rejoined the network", "✓".green(), node.service_name);
+                        }
+                    }
+                    Err(err) => {
+                        batch_error = Some(format!(
+                            "{} did not rejoin the network: {err}",
+                            node.service_name
+                        ));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(batch_error) = batch_error {
+            error!("Rolling upgrade halted at batch {batch_number}/{batch_count}: {batch_error}");
+            if rolling.rollback {
+                let rollback_summary =
+                    roll_back(node_registry, &upgraded, &provided_env_variables, verbosity).await;
+                upgrade_summary.extend(rollback_summary);
+                node_registry.save()?;
+            }
+            if verbosity != VerbosityLevel::Minimal {
+                print_upgrade_summary(upgrade_summary.clone());
+            }
+            return Err(FailedOperations {
+                message: format!(
+                    "The rolling upgrade halted at batch {batch_number}/{batch_count}: {batch_error}"
+                ),
+                outcomes: upgrade_outcomes(node_registry, &upgrade_summary),
+            })
+            .suggestion(if rolling.rollback {
+                "The nodes of the failed batch were rolled back. The nodes of the remaining \
+                    batches were not upgraded."
+            } else {
+                "The nodes of the remaining batches were not upgraded. The previous binaries of \
+                    the failed batch were kept next to the upgraded ones, with a '.bak' extension."
+            });
+        }
+
+        for (_, _, backup_path) in upgraded {
+            let _ = std::fs::remove_file(backup_path);
+        }
+        if batch_number < batch_count {
+            debug!("Sleeping for {} milliseconds", interval);
+            std::thread::sleep(std::time::Duration::from_millis(interval));
+        }
+    }
+
+    if verbosity != VerbosityLevel::Minimal {
+        print_upgrade_summary(upgrade_summary.clone());
+    }
+    Ok(upgrade_outcomes(node_registry, &upgrade_summary))
+}
+
+/// Put the previous binary back on the given services, returning the outcome for each of them.
+async fn roll_back(
+    node_registry: &mut NodeRegistry,
+    upgraded: &[(usize, String, PathBuf)],
+    provided_env_variables: &Option<Vec<(String, String)>>,
+    verbosity: VerbosityLevel,
+) -> Vec<(String, UpgradeResult)> {
+    let mut rollback_summary = Vec::new();
+    for (index, previous_version, backup_path) in upgraded {
+        let service_name = node_registry.nodes[*index].service_name.clone();
+        let previous_version = match Version::parse(previous_version) {
+            Ok(version) => version,
+            Err(err) => {
+                error!("Cannot roll back {service_name} to {previous_version}: {err}");
+                rollback_summary.push((
+                    service_name,
+                    UpgradeResult::Error(format!("Could not be rolled back: {err}")),
+                ));
+                continue;
+            }
+        };
+        info!("Rolling back {service_name} to {previous_version}");
+
+        let options = upgrade_options(
+            node_registry,
+            provided_env_variables,
+            backup_path.clone(),
+            previous_version,
+            true,
+            true,
+        );
+        let node = &mut node_registry.nodes[*index];
+        let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+        let service = NodeService::new(node, Box::new(rpc_client));
+        let mut service_manager =
+            ServiceManager::new(service, Box::new(ServiceController {}), verbosity);
+        match service_manager.upgrade(options).await {
+            Ok(result) => {
+                let _ = std::fs::remove_file(backup_path);
+                rollback_summary.push((service_name, result));
+            }
+            Err(err) => {
+                error!("Failed to roll back {service_name}: {err}");
+                rollback_summary.push((
+                    service_name,
+                    UpgradeResult::Error(format!("Could not be rolled back: {err}")),
+                ));
+            }
+        }
+    }
+    rollback_summary
+}
+
+fn upgrade_options(
+    node_registry: &NodeRegistry,
+    provided_env_variables: &Option<Vec<(String, String)>>,
+    target_bin_path: PathBuf,
+    target_version: Version,
+    force: bool,
+    start_service: bool,
+) -> UpgradeOptions {
+    let env_variables = if provided_env_variables.is_some() {
+        provided_env_variables
+    } else {
+        &node_registry.environment_variables
+    };
+    UpgradeOptions {
+        auto_restart: false,
+        bootstrap_peers: node_registry.bootstrap_peers.clone(),
+        env_variables: env_variables.clone(),
+        force,
+        start_service,
+        target_bin_path,
+        target_version,
+    }
+}
+
+/// Converge the nodes to the fleet config: remove, reconfigure, upgrade, add and start services.
+///
+/// With `dry_run`, only the plan is printed. The outcome of every service the plan operated on is
+/// returned, including when one of its steps failed.
+pub async fn apply(
+    config_path: PathBuf,
+    dry_run: bool,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Apply Fleet Config");
+    }
+    let fleet_config = FleetConfig::load(&config_path)?;
+    info!("Applying the fleet config at {config_path:?} with dry_run={dry_run}");
+
+    let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    refresh_node_registry(
+        &mut node_registry,
+        &ServiceController {},
+        verbosity != VerbosityLevel::Minimal,
+        false,
+    )
+    .await?;
+    node_registry.save()?;
+
+    let plan = fleet::plan(&fleet_config, &node_registry)?;
+    info!("Fleet plan: {plan:?}");
+    if verbosity != VerbosityLevel::Minimal {
+        println!("Plan:");
+        print!("{plan}");
+    }
+    if dry_run || plan.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut outcomes = Vec::new();
+
+    let remove_names = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            FleetAction::Remove { service_name } => Some(service_name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !remove_names.is_empty() {
+        merge_outcomes(
+            &mut outcomes,
+            remove(false, vec![], remove_names, verbosity).await,
+        )?;
+    }
+
+    let reconfigure_names = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            FleetAction::Reconfigure { service_name, .. } => Some(service_name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if plan.env_variables.is_some() || !reconfigure_names.is_empty() {
+        merge_outcomes(
+            &mut outcomes,
+            reconfigure(
+                &fleet_config,
+                plan.env_variables.clone(),
+                reconfigure_names,
+                verbosity,
+            )
+            .await,
+        )?;
+    }
+
+    let upgrade_names = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            FleetAction::Upgrade { service_name, .. } => Some(service_name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !upgrade_names.is_empty() {
+        // The pin can be a downgrade, which requires force.
+        let upgrade_result = upgrade(
+            false,
+            None,
+            true,
+            0,
+            vec![],
+            None,
+            None,
+            upgrade_names,
+            None,
+            fleet_config.version.clone(),
+            verbosity,
+        )
+        .await;
+        merge_outcomes(&mut outcomes, upgrade_result)?;
+    }
+
+    let mut start_names = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            FleetAction::Start { service_name } => Some(service_name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for action in plan.actions.iter() {
+        if let FleetAction::Add {
+            count,
+            node_port,
+            rpc_port,
+            metrics_port,
+        } = action
+        {
+            let add_result = add(
+                fleet_config.auto_restart,
+                false,
+                fleet_config.bandwidth,
+                Some(*count),
+                fleet_config.data_dir_path.clone(),
+                fleet_config.enable_metrics_server,
+                fleet_config.env_variables(),
+                fleet_config.home_network,
+                fleet_config.local,
+                fleet_config.log_dir_path.clone(),
+                fleet_config.log_format()?,
+                metrics_port.clone(),
+                node_port.clone(),
+                fleet_config.owner.clone(),
+                PeersArgs {
+                    peers: fleet_config.bootstrap_peers()?,
+                    ..Default::default()
+                },
+                fleet_config.resources,
+                fleet_config.rpc_address,
+                rpc_port.clone(),
+                None,
+                fleet_config.upnp,
+                None,
+                fleet_config.user.clone(),
+                fleet_config.version.clone(),
+                verbosity,
+            )
+            .await;
+            let added_names = match add_result {
+                Ok(added_names) => added_names,
+                // The error is returned along with the outcomes of the previous steps.
+                Err(err) => return merge_outcomes(&mut outcomes, Err(err)).map(|()| outcomes),
+            };
+            merge_outcomes(&mut outcomes, added_service_outcomes(&added_names))?;
+            start_names.extend(added_names);
+        }
+    }
+    if !start_names.is_empty() {
+        merge_outcomes(
+            &mut outcomes,
+            start(0, vec![], start_names, verbosity).await,
+        )?;
+    }
+
+    Ok(outcomes)
+}
+
+/// Apply the settings of the fleet config to existing nodes by reinstalling their services with
+/// the same binary.
+///
+/// The settings are only recorded for the nodes whose service could be reinstalled; the others are
+/// put back to their previous definition and restarted.
+async fn reconfigure(
+    fleet_config: &FleetConfig,
+    env_variables: Option<Option<Vec<(String, String)>>>,
+    service_names: Vec<String>,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    let log_format = fleet_config.log_format()?;
+    let service_indices = get_services_for_ops(&node_registry, vec![], service_names)?;
+    let bootstrap_peers = node_registry.bootstrap_peers.clone();
+    let previous_env_variables = node_registry.environment_variables.clone();
+    let new_env_variables = env_variables.unwrap_or_else(|| previous_env_variables.clone());
+
+    let mut outcomes = Vec::new();
+    for &index in &service_indices {
+        let node = &mut node_registry.nodes[index];
+        let service_name = node.service_name.clone();
+        let previous_node = node.clone();
+        let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+        let service = NodeService::new(node, Box::new(rpc_client));
+        let mut service_manager =
+            ServiceManager::new(service, Box::new(ServiceController {}), verbosity);
+
+        // The upgrade path copies the target binary over the current one, which cannot be used
+        // with the binary the node already runs, so the service is reinstalled directly.
+        let result: Result<()> = async {
+            let previous_install_ctx = install_ctx(
+                &previous_node,
+                reinstall_options(
+                    &previous_node,
+                    bootstrap_peers.clone(),
+                    previous_env_variables.clone(),
+                )?,
+            )?;
+            service_manager.stop().await?;
+
+            let node = &mut *service_manager.service.service_data;
+            node.auto_restart = fleet_config.auto_restart;
+            node.bandwidth_limits = fleet_config.bandwidth;
+            node.home_network = fleet_config.home_network;
+            node.log_format = log_format;
+            node.owner = fleet_config.owner.clone();
+            node.resource_limits = fleet_config.resources;
+            node.upnp = fleet_config.upnp;
+            let options =
+                reinstall_options(node, bootstrap_peers.clone(), new_env_variables.clone())?;
+
+            if let Err(err) = reinstall_service(&service_manager, options, previous_install_ctx) {
+                *service_manager.service.service_data = previous_node.clone();
+                if let Err(start_err) = service_manager.start().await {
+                    error!(
+                        "Failed to restart {service_name} after a failed reconfigure: {start_err}"
+                    );
+                }
+                return Err(err);
+            }
+            service_manager.start().await?;
+            Ok(())
+        }
+        .await;
+        let outcome = ServiceOutcome::from_node(service_manager.service.service_data);
+        match result {
+            Ok(()) => {
+                if verbosity != VerbosityLevel::Minimal {
+                    println!("{} Reconfigured {service_name}", "✓".green());
+                }
+                outcomes.push(outcome);
+            }
+            Err(err) => {
+                error!("Failed to reconfigure {service_name}: {err}");
+                outcomes.push(outcome.with_error(err.to_string()));
+            }
+        }
     }
 
+    // The environment variables are shared, so they are only recorded once every node uses them.
+    if outcomes.iter().all(|outcome| outcome.error.is_none()) {
+        node_registry.environment_variables = new_env_variables;
+    }
+    node_registry.save()?;
+    summarise_any_failed_ops(outcomes, "reconfigure", verbosity)
+}
+
+/// The options to reinstall the service of a node with the binary it already runs.
+fn reinstall_options(
+    node: &NodeServiceData,
+    bootstrap_peers: Vec<Multiaddr>,
+    env_variables: Option<Vec<(String, String)>>,
+) -> Result<UpgradeOptions> {
+    Ok(UpgradeOptions {
+        auto_restart: node.auto_restart,
+        bootstrap_peers,
+        env_variables,
+        force: true,
+        start_service: true,
+        target_bin_path: node.safenode_path.clone(),
+        target_version: Version::parse(&node.version)?,
+    })
+}
+
+/// The service definition of a node, e.g. to restore it after its replacement failed.
+fn install_ctx(node: &NodeServiceData, options: UpgradeOptions) -> Result<ServiceInstallCtx> {
+    let mut node = node.clone();
+    let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+    let service = NodeService::new(&mut node, Box::new(rpc_client));
+    Ok(service.build_upgrade_install_context(options)?)
+}
+
+/// Replace the definition of a stopped node service with one built from its current service data.
+///
+/// If the new definition cannot be installed, the previous one is installed again before the
+/// error is returned.
+fn reinstall_service(
+    service_manager: &ServiceManager<NodeService>,
+    options: UpgradeOptions,
+    previous_install_ctx: ServiceInstallCtx,
+) -> Result<()> {
+    let service_name = service_manager.service.name();
+    let user_mode = service_manager.service.is_user_mode();
+    let install_ctx = service_manager
+        .service
+        .build_upgrade_install_context(options)?;
+    service_manager
+        .service_control
+        .uninstall(&service_name, user_mode)?;
+    if let Err(err) = service_manager
+        .service_control
+        .install(install_ctx, user_mode)
+    {
+        error!("Failed to install the new definition of {service_name}: {err}");
+        service_manager
+            .service_control
+            .install(previous_install_ctx, user_mode)
+            .map_err(|restore_err| {
+                eyre!(
+                    "The service {service_name} could not be reinstalled ({err}) \
+                    and its previous definition could not be restored: {restore_err}"
+                )
+            })?;
+        return Err(eyre!(
+            "The service {service_name} could not be reinstalled, its previous definition was restored: {err}"
+        ));
+    }
     Ok(())
 }
 
+/// Move the data of a node, and optionally its logs, to new directories, keeping its peer ID.
+///
+/// The directories are prefixes, like for `add`. The data is copied and verified before the
+/// service is redefined and started; the old data is only deleted once the node has started with
+/// the same peer ID.
+pub async fn move_node(
+    service_name: String,
+    data_dir_path: PathBuf,
+    log_dir_path: Option<PathBuf>,
+    verbosity: VerbosityLevel,
+) -> Result<Vec<ServiceOutcome>> {
+    if verbosity != VerbosityLevel::Minimal {
+        print_banner("Move Safenode Service");
+    }
+    info!("Moving {service_name} to {data_dir_path:?}, with log dir {log_dir_path:?}");
+
+    let mut node_registry = NodeRegistry::load(&config::get_node_registry_path()?)?;
+    refresh_node_registry(
+        &mut node_registry,
+        &ServiceController {},
+        verbosity != VerbosityLevel::Minimal,
+        false,
+    )
+    .await?;
+    let index = get_services_for_ops(&node_registry, vec![], vec![service_name.clone()])?[0];
+
+    let old_data_dir_path = node_registry.nodes[index].data_dir_path.clone();
+    let new_data_dir_path = data_dir_path.join(&service_name);
+    if new_data_dir_path == old_data_dir_path {
+        return Err(eyre!(
+            "The data of {service_name} is already at {new_data_dir_path:?}"
+        ));
+    }
+    relocate::check_destination(&old_data_dir_path, &new_data_dir_path)?;
+    let new_log_dir_path = log_dir_path.map(|path| path.join(&service_name));
+    let previous_peer_id = node_registry.nodes[index].peer_id;
+    let user = node_registry.nodes[index].user.clone();
+
+    {
+        let node = &mut node_registry.nodes[index];
+        let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+        let service = NodeService::new(node, Box::new(rpc_client));
+        let mut service_manager =
+            ServiceManager::new(service, Box::new(ServiceController {}), verbosity);
+        service_manager.stop().await?;
+    }
+    node_registry.save()?;
+
+    if verbosity != VerbosityLevel::Minimal {
+        println!("Copying {old_data_dir_path:?} to {new_data_dir_path:?}...");
+    }
+    let copied_files = match relocate::copy_and_verify(&old_data_dir_path, &new_data_dir_path) {
+        Ok(copied_files) => copied_files,
+        Err(err) => {
+            error!("Failed to copy the data of {service_name}: {err}");
+            let _ = std::fs::remove_dir_all(&new_data_dir_path);
+            return Err(err).suggestion(
+                "The node was left at its original location and stopped; use 'start' to run it again",
+            );
+        }
+    };
+    if verbosity != VerbosityLevel::Minimal {
+        println!("{} Copied and verified {copied_files} file(s)", "✓".green());
+    }
+    if let Some(user) = &user {
+        config::set_owner_recursively(&new_data_dir_path, user)?;
+    }
+    if let Some(new_log_dir_path) = &new_log_dir_path {
+        match &user {
+            Some(user) => config::create_owned_dir(new_log_dir_path.clone(), user)?,
+            None => std::fs::create_dir_all(new_log_dir_path)?,
+        }
+    }
+
+    let bootstrap_peers = node_registry.bootstrap_peers.clone();
+    let env_variables = node_registry.environment_variables.clone();
+    let node = &mut node_registry.nodes[index];
+    let previous_node = node.clone();
+    let previous_install_ctx = install_ctx(
+        node,
+        reinstall_options(node, bootstrap_peers.clone(), env_variables.clone())?,
+    )?;
+    if let Ok(relative_bin_path) = node.safenode_path.strip_prefix(&old_data_dir_path) {
+        node.safenode_path = new_data_dir_path.join(relative_bin_path);
+    }
+    node.data_dir_path.clone_from(&new_data_dir_path);
+    if let Some(new_log_dir_path) = new_log_dir_path {
+        node.log_dir_path = new_log_dir_path;
+    }
+    let options = reinstall_options(node, bootstrap_peers, env_variables)?;
+    let start_result = {
+        let rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+        let service = NodeService::new(node, Box::new(rpc_client));
+        let mut service_manager =
+            ServiceManager::new(service, Box::new(ServiceController {}), verbosity);
+        if let Err(err) = reinstall_service(&service_manager, options, previous_install_ctx) {
+            // The previous definition is back in place, so the node keeps its original data.
+            *service_manager.service.service_data = previous_node;
+            Err(err)
+        } else {
+            service_manager.start().await.map_err(|err| eyre!(err))
+        }
+    };
+    node_registry.save()?;
+    if node_registry.nodes[index].data_dir_path == old_data_dir_path {
+        let _ = std::fs::remove_dir_all(&new_data_dir_path);
+        return start_result.map(|()| Vec::new()).suggestion(
+            "The node was left at its original location and stopped; use 'start' to run it again",
+        );
+    }
+    start_result?;
+
+    let node = &node_registry.nodes[index];
+    if previous_peer_id.is_some() && node.peer_id != previous_peer_id {
+        error!(
+            "{service_name} started with peer ID {:?} rather than {previous_peer_id:?}",
+            node.peer_id
+        );
+        return Err(eyre!(
+            "{service_name} did not keep its peer ID after the move"
+        ))
+        .suggestion(format!(
+            "The original data was kept at {old_data_dir_path:?}"
+        ));
+    }
+
+    std::fs::remove_dir_all(&old_data_dir_path)?;
+    info!("Moved {service_name} to {new_data_dir_path:?}");
+    if verbosity != VerbosityLevel::Minimal {
+        println!(
+            "{} Moved {service_name} to {new_data_dir_path:?}",
+            "✓".green()
+        );
+    }
+    Ok(vec![ServiceOutcome::from_node(&node_registry.nodes[index])])
+}
+
 /// Ensure n nodes are running by stopping nodes or by adding and starting nodes if required.
 ///
 /// The arguments here are mostly mirror those used in `add`.
 pub async fn maintain_n_running_nodes(
     auto_restart: bool,
     auto_set_nat_flags: bool,
+    bandwidth_limits: BandwidthLimits,
     max_nodes_to_run: u16,
     data_dir_path: Option<PathBuf>,
     enable_metrics_server: bool,
@@ -573,6 +1712,7 @@ pub async fn maintain_n_running_nodes(
     node_port: Option<PortRange>,
     owner: Option<String>,
     peers: PeersArgs,
+    resource_limits: ResourceLimits,
     rpc_address: Option<Ipv4Addr>,
     rpc_port: Option<PortRange>,
     src_path: Option<PathBuf>,
@@ -654,6 +1794,7 @@ pub async fn maintain_n_running_nodes(
                 let added_service_list = add(
                     auto_restart,
                     auto_set_nat_flags,
+                    bandwidth_limits,
                     Some(to_add_count as u16),
                     data_dir_path,
                     enable_metrics_server,
@@ -666,6 +1807,7 @@ pub async fn maintain_n_running_nodes(
                     node_port,
                     owner,
                     peers,
+                    resource_limits,
                     rpc_address,
                     rpc_port,
                     src_path,
@@ -742,21 +1884,36 @@ fn get_services_for_ops(
     Ok(service_indices)
 }
 
+/// Print the services the operation failed for, returning an error carrying all the outcomes if
+/// there are any.
 fn summarise_any_failed_ops(
-    failed_services: Vec<(String, String)>,
+    outcomes: Vec<ServiceOutcome>,
     verb: &str,
     verbosity: VerbosityLevel,
-) -> Result<()> {
-    if !failed_services.is_empty() {
+) -> Result<Vec<ServiceOutcome>> {
+    let failed_count = outcomes
+        .iter()
+        .filter(|outcome| !outcome.succeeded())
+        .count();
+    if failed_count > 0 {
         if verbosity != VerbosityLevel::Minimal {
-            println!("Failed to {verb} {} service(s):", failed_services.len());
-            for failed in failed_services.iter() {
-                println!("{} {}: {}", "✕".red(), failed.0, failed.1);
+            println!("Failed to {verb} {failed_count} service(s):");
+            for outcome in outcomes.iter().filter(|outcome| !outcome.succeeded()) {
+                println!(
+                    "{} {}: {}",
+                    "✕".red(),
+                    outcome.service_name,
+                    outcome.error.as_deref().unwrap_or_default()
+                );
             }
         }
 
         error!("Failed to {verb} one or more services");
-        return Err(eyre!("Failed to {verb} one or more services"));
+        return Err(FailedOperations {
+            message: format!("Failed to {verb} one or more services"),
+            outcomes,
+        }
+        .into());
     }
-    Ok(())
+    Ok(outcomes)
 }
@@ -8,7 +8,7 @@
 
 use color_eyre::{eyre::eyre, Result};
 use sn_releases::ReleaseType;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[cfg(unix)]
 pub fn get_daemon_install_path() -> PathBuf {
@@ -104,6 +104,20 @@ pub fn get_node_registry_path() -> Result<PathBuf> {
     Ok(path.join("node_registry.json"))
 }
 
+/// Get the path of the file holding the token the daemon's RPC clients must present.
+pub fn get_daemon_auth_token_path() -> Result<PathBuf> {
+    let path = get_node_manager_path()?.join("daemon_auth_token");
+    debug!("Daemon auth token path is: {path:?}");
+    Ok(path)
+}
+
+/// Get the path of the file defining the ports reserved for the node services.
+pub fn get_port_pool_path() -> Result<PathBuf> {
+    let path = get_node_manager_path()?.join("port_pool.toml");
+    debug!("Port pool path is: {path:?}");
+    Ok(path)
+}
+
 /// Get the data directory for the service.
 ///
 /// It's a little counter-intuitive, but the owner will be `None` in the case of a user-mode
@@ -242,6 +256,37 @@ pub fn create_owned_dir(path: PathBuf, _owner: &str) -> Result<()> {
     Ok(())
 }
 
+/// Give the user ownership of the directory and everything in it.
+#[cfg(unix)]
+pub fn set_owner_recursively(path: &Path, owner: &str) -> Result<()> {
+    use nix::unistd::{chown, Gid, Uid};
+    use users::get_user_by_name;
+
+    debug!("Setting the owner of {path:?} to {owner}");
+    let user = get_user_by_name(owner).ok_or_else(|| {
+        error!("User '{owner}' does not exist");
+        eyre!("User '{owner}' does not exist")
+    })?;
+    let uid = Uid::from_raw(user.uid());
+    let gid = Gid::from_raw(user.primary_group_id());
+
+    let mut paths = vec![path.to_path_buf()];
+    while let Some(path) = paths.pop() {
+        chown(&path, Some(uid), Some(gid))?;
+        if path.is_dir() {
+            for entry in std::fs::read_dir(&path)? {
+                paths.push(entry?.path());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn set_owner_recursively(_path: &Path, _owner: &str) -> Result<()> {
+    Ok(())
+}
+
 #[cfg(unix)]
 pub fn is_running_as_root() -> bool {
     use nix::unistd::geteuid;
@@ -0,0 +1,122 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use color_eyre::{eyre::eyre, Result};
+use std::{fs::OpenOptions, io::Write, path::Path};
+use tonic::{metadata::MetadataValue, Request, Status};
+use uuid::Uuid;
+
+/// The metadata key carrying the token of the daemon.
+pub const AUTH_METADATA_KEY: &str = "authorization";
+
+/// Read the token of the daemon, generating it on the first run.
+///
+/// The file is only readable by its owner, so only the users allowed to manage the nodes locally
+/// can hand the token over to a remote client.
+pub fn load_or_create_token(path: &Path) -> Result<String> {
+    if path.exists() {
+        return read_token(path);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(token.as_bytes())?;
+    info!("Generated a new daemon auth token at {path:?}");
+
+    Ok(token)
+}
+
+/// Read the token of the daemon.
+pub fn read_token(path: &Path) -> Result<String> {
+    let token = std::fs::read_to_string(path)
+        .map_err(|err| eyre!("Could not read the daemon auth token at {path:?}: {err}"))?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        return Err(eyre!("The daemon auth token at {path:?} is empty"));
+    }
+    Ok(token)
+}
+
+/// Attach the token to a request sent to the daemon.
+pub fn authorise_request<T>(request: &mut Request<T>, token: &str) -> Result<()> {
+    let value = MetadataValue::from_str(&format!("Bearer {token}"))
+        .map_err(|err| eyre!("Invalid daemon auth token: {err}"))?;
+    let _ = request.metadata_mut().insert(AUTH_METADATA_KEY, value);
+    Ok(())
+}
+
+/// Reject the requests that do not carry the token of the daemon.
+pub fn check_request<T>(request: &Request<T>, token: &str) -> Result<(), Status> {
+    let provided = request
+        .metadata()
+        .get(AUTH_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("The daemon auth token is missing"))?;
+
+    if !constant_time_eq(provided.as_bytes(), token.as_bytes()) {
+        warn!("Rejected an RPC request with an invalid auth token");
+        return Err(Status::unauthenticated("The daemon auth token is invalid"));
+    }
+    Ok(())
+}
+
+/// Compare without leaking through the timing how much of the token was guessed right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+
+    #[test]
+    fn token_is_created_once_and_then_reloaded() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("daemon_auth_token");
+
+        let token = load_or_create_token(&path)?;
+        assert_eq!(token.len(), 64);
+        assert_eq!(load_or_create_token(&path)?, token);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn only_requests_with_the_token_are_accepted() -> Result<()> {
+        let token = "secret-token";
+
+        let mut request = Request::new(());
+        assert!(check_request(&request, token).is_err());
+
+        authorise_request(&mut request, "wrong-token")?;
+        assert!(check_request(&request, token).is_err());
+
+        authorise_request(&mut request, token)?;
+        assert!(check_request(&request, token).is_ok());
+        Ok(())
+    }
+}
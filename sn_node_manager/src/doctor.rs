@@ -0,0 +1,393 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::ports;
+use color_eyre::Result;
+use serde::Serialize;
+use sn_service_management::{NodeServiceData, ServiceStatus};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+use sysinfo::{Pid, System};
+
+/// A problem with the node services found by `safenode-manager doctor`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum Problem {
+    /// Several services in the registry are assigned the same port.
+    PortConflict { port: u16, services: Vec<String> },
+    /// A port of a service that is not running is used by another process, so it cannot start.
+    PortInUse { port: u16, service_name: String },
+    /// The binary of a service does not exist.
+    MissingBinary { service_name: String, path: PathBuf },
+    /// The registry has a PID for a service whose process no longer exists.
+    StalePid { service_name: String, pid: u32 },
+    /// A service is defined in the OS but is not in the registry.
+    UnregisteredService { service_name: String },
+    /// A service in the registry is not defined in the OS.
+    MissingServiceDefinition { service_name: String },
+}
+
+impl Problem {
+    /// The services affected by the problem.
+    pub fn service_names(&self) -> Vec<&str> {
+        match self {
+            Problem::PortConflict { services, .. } => {
+                services.iter().map(|name| name.as_str()).collect()
+            }
+            Problem::PortInUse { service_name, .. }
+            | Problem::MissingBinary { service_name, .. }
+            | Problem::StalePid { service_name, .. }
+            | Problem::UnregisteredService { service_name }
+            | Problem::MissingServiceDefinition { service_name } => vec![service_name.as_str()],
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::PortConflict { port, services } => {
+                write!(f, "Port {port} is assigned to {}", services.join(", "))
+            }
+            Problem::PortInUse { port, service_name } => write!(
+                f,
+                "Port {port} of {service_name} is in use by another process"
+            ),
+            Problem::MissingBinary { service_name, path } => {
+                write!(f, "The binary of {service_name} is missing at {path:?}")
+            }
+            Problem::StalePid { service_name, pid } => write!(
+                f,
+                "{service_name} is registered as running with PID {pid}, but there is no such process"
+            ),
+            Problem::UnregisteredService { service_name } => write!(
+                f,
+                "The {service_name} service is defined in the OS but is not in the node registry"
+            ),
+            Problem::MissingServiceDefinition { service_name } => write!(
+                f,
+                "{service_name} is in the node registry but its service is not defined in the OS"
+            ),
+        }
+    }
+}
+
+/// What `diagnose` needs to know about the host.
+pub trait HostInspector {
+    /// The names of the safenode services defined in the OS.
+    fn installed_services(&self) -> Result<Vec<String>>;
+    fn is_process_running(&self, pid: u32) -> bool;
+    fn is_port_in_use(&self, port: u16) -> bool;
+    fn path_exists(&self, path: &Path) -> bool;
+}
+
+pub struct Host {}
+
+impl HostInspector for Host {
+    fn installed_services(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for (dir, extension) in service_definition_dirs() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let name = match extension {
+                    Some(extension) => file_name.strip_suffix(extension),
+                    None => Some(file_name.as_str()),
+                };
+                if let Some(name) = name.filter(|name| is_node_service_name(name)) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        #[cfg(windows)]
+        {
+            let output = std::process::Command::new("sc")
+                .args(["query", "state=", "all"])
+                .output()?;
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                if let Some(name) = line.trim().strip_prefix("SERVICE_NAME:") {
+                    let name = name.trim();
+                    if is_node_service_name(name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    fn is_process_running(&self, pid: u32) -> bool {
+        let mut system = System::new();
+        system.refresh_process(Pid::from_u32(pid))
+    }
+
+    fn is_port_in_use(&self, port: u16) -> bool {
+        ports::is_port_in_use(port)
+    }
+
+    fn path_exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// The directories holding service definitions, with the extension of the definition files.
+fn service_definition_dirs() -> Vec<(PathBuf, Option<&'static str>)> {
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "linux") {
+        dirs.push((PathBuf::from("/etc/systemd/system"), Some(".service")));
+        dirs.push((PathBuf::from("/etc/init.d"), None));
+        if let Some(config_dir) = dirs_next::config_dir() {
+            dirs.push((config_dir.join("systemd").join("user"), Some(".service")));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push((PathBuf::from("/Library/LaunchDaemons"), Some(".plist")));
+        if let Some(home_dir) = dirs_next::home_dir() {
+            dirs.push((
+                home_dir.join("Library").join("LaunchAgents"),
+                Some(".plist"),
+            ));
+        }
+    }
+    dirs
+}
+
+fn is_node_service_name(name: &str) -> bool {
+    name.strip_prefix("safenode")
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Check the nodes in the registry against the state of the host.
+///
+/// The registry should not be refreshed beforehand, because a refresh clears stale PIDs.
+pub fn diagnose(nodes: &[NodeServiceData], host: &dyn HostInspector) -> Result<Vec<Problem>> {
+    let nodes = nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .collect::<Vec<_>>();
+    let mut problems = Vec::new();
+
+    let mut port_assignments: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+    for node in nodes.iter() {
+        for port in node_ports(node) {
+            port_assignments
+                .entry(port)
+                .or_default()
+                .push(node.service_name.clone());
+        }
+    }
+    for (port, services) in port_assignments {
+        if services.len() > 1 {
+            problems.push(Problem::PortConflict { port, services });
+        }
+    }
+
+    for node in nodes.iter() {
+        if !host.path_exists(&node.safenode_path) {
+            problems.push(Problem::MissingBinary {
+                service_name: node.service_name.clone(),
+                path: node.safenode_path.clone(),
+            });
+        }
+        match (&node.status, node.pid) {
+            (ServiceStatus::Running, Some(pid)) if !host.is_process_running(pid) => {
+                problems.push(Problem::StalePid {
+                    service_name: node.service_name.clone(),
+                    pid,
+                });
+            }
+            (ServiceStatus::Running, _) => {}
+            _ => {
+                for port in node_ports(node) {
+                    if host.is_port_in_use(port) {
+                        problems.push(Problem::PortInUse {
+                            port,
+                            service_name: node.service_name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let installed = host
+        .installed_services()?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    let registered = nodes
+        .iter()
+        .map(|node| node.service_name.clone())
+        .collect::<BTreeSet<_>>();
+    for service_name in installed.difference(&registered) {
+        problems.push(Problem::UnregisteredService {
+            service_name: service_name.clone(),
+        });
+    }
+    // Without any definition found, the service manager of the host is not one we can inspect.
+    if !installed.is_empty() {
+        for service_name in registered.difference(&installed) {
+            problems.push(Problem::MissingServiceDefinition {
+                service_name: service_name.clone(),
+            });
+        }
+    }
+
+    Ok(problems)
+}
+
+fn node_ports(node: &NodeServiceData) -> Vec<u16> {
+    let mut ports = vec![node.rpc_socket_addr.port()];
+    ports.extend(node.node_port);
+    ports.extend(node.metrics_port);
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashSet,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
+    };
+
+    #[derive(Default)]
+    struct FakeHost {
+        installed_services: Vec<String>,
+        running_pids: HashSet<u32>,
+        ports_in_use: HashSet<u16>,
+        existing_paths: HashSet<PathBuf>,
+    }
+
+    impl HostInspector for FakeHost {
+        fn installed_services(&self) -> Result<Vec<String>> {
+            Ok(self.installed_services.clone())
+        }
+
+        fn is_process_running(&self, pid: u32) -> bool {
+            self.running_pids.contains(&pid)
+        }
+
+        fn is_port_in_use(&self, port: u16) -> bool {
+            self.ports_in_use.contains(&port)
+        }
+
+        fn path_exists(&self, path: &Path) -> bool {
+            self.existing_paths.contains(path)
+        }
+    }
+
+    fn node(number: u16, status: ServiceStatus, pid: Option<u32>) -> NodeServiceData {
+        NodeServiceData {
+            auto_restart: false,
+            bandwidth_limits: Default::default(),
+            connected_peers: None,
+            data_dir_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}"
+            )),
+            genesis: false,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+            log_format: None,
+            metrics_port: None,
+            node_port: Some(12000 + number),
+            number,
+            owner: None,
+            peer_id: None,
+            pid,
+            resource_limits: Default::default(),
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 13000 + number),
+            safenode_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}/safenode"
+            )),
+            service_name: format!("safenode{number}"),
+            status,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }
+    }
+
+    fn healthy_host(nodes: &[NodeServiceData]) -> FakeHost {
+        FakeHost {
+            installed_services: nodes.iter().map(|n| n.service_name.clone()).collect(),
+            running_pids: nodes.iter().filter_map(|n| n.pid).collect(),
+            ports_in_use: HashSet::new(),
+            existing_paths: nodes.iter().map(|n| n.safenode_path.clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn healthy_nodes_should_have_no_problems() -> Result<()> {
+        let nodes = vec![
+            node(1, ServiceStatus::Running, Some(1000)),
+            node(2, ServiceStatus::Stopped, None),
+        ];
+        let problems = diagnose(&nodes, &healthy_host(&nodes))?;
+        assert!(problems.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn problems_should_be_reported() -> Result<()> {
+        let mut clashing = node(2, ServiceStatus::Stopped, None);
+        clashing.node_port = Some(12001);
+        let nodes = vec![node(1, ServiceStatus::Running, Some(1000)), clashing];
+        let mut host = healthy_host(&nodes);
+        host.running_pids.clear();
+        host.ports_in_use.insert(13002);
+        host.existing_paths.remove(&PathBuf::from(
+            "/var/safenode-manager/services/safenode2/safenode",
+        ));
+        host.installed_services = vec!["safenode1".to_string(), "safenode7".to_string()];
+
+        let problems = diagnose(&nodes, &host)?;
+        assert_eq!(
+            problems,
+            vec![
+                Problem::PortConflict {
+                    port: 12001,
+                    services: vec!["safenode1".to_string(), "safenode2".to_string()],
+                },
+                Problem::StalePid {
+                    service_name: "safenode1".to_string(),
+                    pid: 1000,
+                },
+                Problem::MissingBinary {
+                    service_name: "safenode2".to_string(),
+                    path: PathBuf::from("/var/safenode-manager/services/safenode2/safenode"),
+                },
+                Problem::PortInUse {
+                    port: 13002,
+                    service_name: "safenode2".to_string(),
+                },
+                Problem::UnregisteredService {
+                    service_name: "safenode7".to_string(),
+                },
+                Problem::MissingServiceDefinition {
+                    service_name: "safenode2".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn only_node_services_should_be_recognised() {
+        assert!(is_node_service_name("safenode12"));
+        assert!(!is_node_service_name("safenode"));
+        assert!(!is_node_service_name("safenodemanagerd"));
+        assert!(!is_node_service_name("faucet"));
+    }
+}
@@ -0,0 +1,778 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{
+    add_services::config::{parse_port_range, PortRange},
+    ports::{self, bounds},
+};
+use color_eyre::{eyre::eyre, Help, Result};
+use libp2p::Multiaddr;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sn_logging::LogFormat;
+use sn_service_management::{
+    BandwidthLimits, NodeRegistry, NodeServiceData, ResourceLimits, ServiceStatus,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The desired set of nodes on this machine, which `safenode-manager apply` converges to.
+///
+/// It is read from a YAML file if its extension is `.yaml` or `.yml`, from a TOML file otherwise.
+///
+/// Example:
+///
+/// ```toml
+/// count = 20
+/// version = "0.108.0"
+/// node_port = "12000-12019"
+/// owner = "maidsafe"
+/// home_network = true
+/// log_format = "json"
+///
+/// [env]
+/// SN_LOG = "all"
+///
+/// [bandwidth]
+/// max_upload = 1024
+///
+/// [resources]
+/// max_memory_mb = 2048
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FleetConfig {
+    /// The number of nodes that should exist. It is required, for a missing count not to remove
+    /// every node.
+    pub count: u16,
+    /// Pin the nodes to this version of `safenode`. The latest version is used for new nodes when
+    /// there is no pin, and existing nodes are left on their version.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The port, or range of ports, the nodes listen on, e.g., "12000-12019".
+    #[serde(default)]
+    pub node_port: Option<String>,
+    #[serde(default)]
+    pub rpc_address: Option<Ipv4Addr>,
+    #[serde(default)]
+    pub rpc_port: Option<String>,
+    #[serde(default)]
+    pub enable_metrics_server: bool,
+    #[serde(default)]
+    pub metrics_port: Option<String>,
+    #[serde(default)]
+    pub data_dir_path: Option<PathBuf>,
+    #[serde(default)]
+    pub log_dir_path: Option<PathBuf>,
+    /// Either "default" or "json".
+    #[serde(default)]
+    pub log_format: Option<String>,
+    /// The owner, or reward address, the nodes are run for.
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub upnp: bool,
+    #[serde(default)]
+    pub home_network: bool,
+    #[serde(default)]
+    pub auto_restart: bool,
+    #[serde(default)]
+    pub local: bool,
+    #[serde(default)]
+    pub user: Option<String>,
+    /// The bootstrap peers of new nodes.
+    #[serde(default)]
+    pub peers: Vec<String>,
+    /// The environment variables of the node services.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub bandwidth: BandwidthLimits,
+    #[serde(default)]
+    pub resources: ResourceLimits,
+}
+
+impl FleetConfig {
+    /// Read the config from a YAML or TOML file, depending on its extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| eyre!("Could not read the fleet config at {path:?}: {err}"))?;
+        let config: FleetConfig = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)
+                .map_err(|err| eyre!("The fleet config at {path:?} is not valid: {err}"))?,
+            _ => toml::from_str(&contents)
+                .map_err(|err| eyre!("The fleet config at {path:?} is not valid: {err}"))?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, range) in [
+            ("node_port", self.node_port_range()?),
+            ("rpc_port", self.rpc_port_range()?),
+            ("metrics_port", self.metrics_port_range()?),
+        ] {
+            if let Some(range) = range {
+                let (start, end) = bounds(&range);
+                if usize::from(end - start) + 1 < usize::from(self.count) {
+                    return Err(eyre!(
+                        "The {name} range {start}-{end} cannot hold {} nodes",
+                        self.count
+                    ))
+                    .suggestion("Widen the port range or reduce the count");
+                }
+            }
+        }
+        if self.metrics_port.is_some() && !self.enable_metrics_server {
+            return Err(eyre!(
+                "The metrics_port setting requires enable_metrics_server = true"
+            ));
+        }
+        self.log_format()?;
+        self.bootstrap_peers()?;
+        Ok(())
+    }
+
+    pub fn node_port_range(&self) -> Result<Option<PortRange>> {
+        self.node_port.as_deref().map(parse_port_range).transpose()
+    }
+
+    pub fn rpc_port_range(&self) -> Result<Option<PortRange>> {
+        self.rpc_port.as_deref().map(parse_port_range).transpose()
+    }
+
+    pub fn metrics_port_range(&self) -> Result<Option<PortRange>> {
+        self.metrics_port
+            .as_deref()
+            .map(parse_port_range)
+            .transpose()
+    }
+
+    pub fn log_format(&self) -> Result<Option<LogFormat>> {
+        self.log_format
+            .as_deref()
+            .map(|format| LogFormat::parse_from_str(format).map_err(|err| eyre!("{err}")))
+            .transpose()
+    }
+
+    pub fn bootstrap_peers(&self) -> Result<Vec<Multiaddr>> {
+        self.peers
+            .iter()
+            .map(|peer| {
+                Multiaddr::from_str(peer)
+                    .map_err(|err| eyre!("The peer {peer} is not a valid multiaddr: {err}"))
+            })
+            .collect()
+    }
+
+    /// The environment variables in the form stored in the node registry.
+    pub fn env_variables(&self) -> Option<Vec<(String, String)>> {
+        if self.env.is_empty() {
+            None
+        } else {
+            Some(self.env.clone().into_iter().collect())
+        }
+    }
+}
+
+/// A step towards the desired node set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FleetAction {
+    /// Remove a node that is beyond the desired count.
+    Remove { service_name: String },
+    /// Reinstall the service of a node whose settings differ from the config.
+    Reconfigure {
+        service_name: String,
+        changes: Vec<String>,
+    },
+    /// Upgrade, or downgrade, a node to the pinned version.
+    Upgrade {
+        service_name: String,
+        from: String,
+        to: String,
+    },
+    /// Add and start the missing nodes.
+    Add {
+        count: u16,
+        node_port: Option<PortRange>,
+        rpc_port: Option<PortRange>,
+        metrics_port: Option<PortRange>,
+    },
+    /// Start a node that is not running.
+    Start { service_name: String },
+}
+
+impl fmt::Display for FleetAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FleetAction::Remove { service_name } => write!(f, "remove {service_name}"),
+            FleetAction::Reconfigure {
+                service_name,
+                changes,
+            } => write!(f, "reconfigure {service_name}: {}", changes.join(", ")),
+            FleetAction::Upgrade {
+                service_name,
+                from,
+                to,
+            } => write!(f, "upgrade {service_name} from {from} to {to}"),
+            FleetAction::Add {
+                count,
+                node_port,
+                rpc_port,
+                metrics_port,
+            } => {
+                write!(f, "add {count} node(s)")?;
+                for (name, range) in [
+                    ("node ports", node_port),
+                    ("RPC ports", rpc_port),
+                    ("metrics ports", metrics_port),
+                ] {
+                    if let Some(range) = range {
+                        let (start, end) = bounds(range);
+                        if start == end {
+                            write!(f, ", {name} {start}")?;
+                        } else {
+                            write!(f, ", {name} {start}-{end}")?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            FleetAction::Start { service_name } => write!(f, "start {service_name}"),
+        }
+    }
+}
+
+/// The actions converging the node registry to the config, in the order they should be applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FleetPlan {
+    pub actions: Vec<FleetAction>,
+    /// The node registry environment variables to set, if they differ from the config.
+    pub env_variables: Option<Option<Vec<(String, String)>>>,
+    /// Differences that `apply` does not reconcile, like the ports of existing nodes.
+    pub warnings: Vec<String>,
+}
+
+impl FleetPlan {
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty() && self.env_variables.is_none()
+    }
+}
+
+impl fmt::Display for FleetPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            writeln!(f, "Nothing to do: the nodes match the config")?;
+        } else {
+            if self.env_variables.is_some() {
+                writeln!(f, "  - set the environment variables of the node services")?;
+            }
+            for action in self.actions.iter() {
+                writeln!(f, "  - {action}")?;
+            }
+        }
+        for warning in self.warnings.iter() {
+            writeln!(f, "  ! {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Work out the actions that bring the nodes in the registry in line with the config.
+///
+/// Nodes are removed from the highest number down, so the remaining nodes keep their numbering.
+/// A node that needs to be both reconfigured and upgraded is reconfigured first, so the upgrade
+/// installs it with the new settings.
+pub fn plan(config: &FleetConfig, node_registry: &NodeRegistry) -> Result<FleetPlan> {
+    let mut nodes = node_registry
+        .nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|node| node.number);
+    let keep = nodes.len().min(usize::from(config.count));
+    let (kept, excess) = nodes.split_at(keep);
+
+    let mut plan = FleetPlan::default();
+    for node in excess.iter().rev() {
+        plan.actions.push(FleetAction::Remove {
+            service_name: node.service_name.clone(),
+        });
+    }
+
+    let env_variables = config.env_variables();
+    let env_changed = sorted(&node_registry.environment_variables) != sorted(&env_variables);
+    if env_changed {
+        plan.env_variables = Some(env_variables);
+    }
+
+    let log_format = config.log_format()?;
+    let pinned_version = config
+        .version
+        .as_deref()
+        .map(|version| {
+            Version::parse(version.trim_start_matches('v'))
+                .map_err(|err| eyre!("The version pin {version} is not valid: {err}"))
+        })
+        .transpose()?;
+    let node_port_range = config.node_port_range()?;
+    let rpc_port_range = config.rpc_port_range()?;
+    let metrics_port_range = config.metrics_port_range()?;
+
+    for node in kept {
+        let mut changes = node_changes(config, node, log_format);
+        if env_changed {
+            changes.push("environment variables".to_string());
+        }
+        let reconfigure = !changes.is_empty();
+        if reconfigure {
+            plan.actions.push(FleetAction::Reconfigure {
+                service_name: node.service_name.clone(),
+                changes,
+            });
+        }
+
+        let mut upgrade = false;
+        if let Some(pinned_version) = &pinned_version {
+            if Version::parse(&node.version).ok().as_ref() != Some(pinned_version) {
+                upgrade = true;
+                plan.actions.push(FleetAction::Upgrade {
+                    service_name: node.service_name.clone(),
+                    from: node.version.clone(),
+                    to: pinned_version.to_string(),
+                });
+            }
+        }
+
+        // Reconfiguring and upgrading both leave the node running.
+        if !reconfigure && !upgrade && node.status != ServiceStatus::Running {
+            plan.actions.push(FleetAction::Start {
+                service_name: node.service_name.clone(),
+            });
+        }
+
+        plan.warnings.extend(unmanaged_drift(
+            config,
+            node,
+            &node_port_range,
+            &rpc_port_range,
+        ));
+    }
+
+    let missing = config.count.saturating_sub(kept.len() as u16);
+    if missing > 0 {
+        let mut used_ports = HashSet::new();
+        for node in kept {
+            used_ports.extend(node.node_port);
+            used_ports.extend(node.metrics_port);
+            used_ports.insert(node.rpc_socket_addr.port());
+        }
+        let node_port = free_ports(&node_port_range, missing, &used_ports, "node_port")?;
+        let rpc_port = free_ports(&rpc_port_range, missing, &used_ports, "rpc_port")?;
+        let metrics_port = free_ports(&metrics_port_range, missing, &used_ports, "metrics_port")?;
+        plan.actions.push(FleetAction::Add {
+            count: missing,
+            node_port,
+            rpc_port,
+            metrics_port,
+        });
+    }
+
+    Ok(plan)
+}
+
+fn node_changes(
+    config: &FleetConfig,
+    node: &NodeServiceData,
+    log_format: Option<LogFormat>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    if node.owner != config.owner {
+        changes.push(format!("owner {:?} -> {:?}", node.owner, config.owner));
+    }
+    if node.upnp != config.upnp {
+        changes.push(format!("upnp {} -> {}", node.upnp, config.upnp));
+    }
+    if node.home_network != config.home_network {
+        changes.push(format!(
+            "home network {} -> {}",
+            node.home_network, config.home_network
+        ));
+    }
+    if node.log_format != log_format {
+        changes.push(format!(
+            "log format {} -> {}",
+            node.log_format.map_or("default", |format| format.as_str()),
+            log_format.map_or("default", |format| format.as_str())
+        ));
+    }
+    if node.bandwidth_limits != config.bandwidth {
+        changes.push("bandwidth limits".to_string());
+    }
+    if node.resource_limits != config.resources {
+        changes.push("resource limits".to_string());
+    }
+    if node.auto_restart != config.auto_restart {
+        changes.push(format!(
+            "auto restart {} -> {}",
+            node.auto_restart, config.auto_restart
+        ));
+    }
+    changes
+}
+
+/// Ports and directories are fixed when a node is added, so changing them means replacing the node.
+fn unmanaged_drift(
+    config: &FleetConfig,
+    node: &NodeServiceData,
+    node_port_range: &Option<PortRange>,
+    rpc_port_range: &Option<PortRange>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(range) = node_port_range {
+        if !node.node_port.is_some_and(|port| in_range(range, port)) {
+            warnings.push(format!(
+                "{} listens on port {} outside of the node_port range",
+                node.service_name,
+                node.node_port
+                    .map_or("(random)".to_string(), |port| port.to_string())
+            ));
+        }
+    }
+    if let Some(range) = rpc_port_range {
+        if !in_range(range, node.rpc_socket_addr.port()) {
+            warnings.push(format!(
+                "{} has RPC port {} outside of the rpc_port range",
+                node.service_name,
+                node.rpc_socket_addr.port()
+            ));
+        }
+    }
+    if let Some(data_dir_path) = &config.data_dir_path {
+        if !node.data_dir_path.starts_with(data_dir_path) {
+            warnings.push(format!(
+                "{} keeps its data in {:?}, outside of {data_dir_path:?}",
+                node.service_name, node.data_dir_path
+            ));
+        }
+    }
+    warnings
+}
+
+/// Find `count` consecutive unused ports within the range.
+fn free_ports(
+    range: &Option<PortRange>,
+    count: u16,
+    used_ports: &HashSet<u16>,
+    name: &str,
+) -> Result<Option<PortRange>> {
+    range
+        .as_ref()
+        .map(|range| {
+            ports::allocate(range, count, used_ports, |_| false)
+                .map_err(|err| eyre!("Cannot add nodes within the {name} range: {err}"))
+        })
+        .transpose()
+}
+
+fn in_range(range: &PortRange, port: u16) -> bool {
+    let (start, end) = bounds(range);
+    (start..=end).contains(&port)
+}
+
+fn sorted(env_variables: &Option<Vec<(String, String)>>) -> Vec<(String, String)> {
+    let mut env_variables = env_variables.clone().unwrap_or_default();
+    env_variables.sort();
+    env_variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, SocketAddr};
+
+    fn node(number: u16, status: ServiceStatus) -> NodeServiceData {
+        NodeServiceData {
+            auto_restart: false,
+            bandwidth_limits: BandwidthLimits::default(),
+            connected_peers: None,
+            data_dir_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}"
+            )),
+            genesis: false,
+            home_network: false,
+            listen_addr: None,
+            local: false,
+            log_dir_path: PathBuf::from(format!("/var/log/safenode/safenode{number}")),
+            log_format: None,
+            metrics_port: None,
+            node_port: Some(12000 + number - 1),
+            number,
+            owner: None,
+            peer_id: None,
+            pid: None,
+            resource_limits: ResourceLimits::default(),
+            reward_balance: None,
+            rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 13000 + number - 1),
+            safenode_path: PathBuf::from(format!(
+                "/var/safenode-manager/services/safenode{number}/safenode"
+            )),
+            service_name: format!("safenode{number}"),
+            status,
+            upnp: false,
+            user: Some("safe".to_string()),
+            user_mode: false,
+            version: "0.98.1".to_string(),
+        }
+    }
+
+    fn registry(nodes: Vec<NodeServiceData>) -> NodeRegistry {
+        NodeRegistry {
+            auditor: None,
+            bootstrap_peers: vec![],
+            daemon: None,
+            environment_variables: None,
+            faucet: None,
+            nat_status: None,
+            nodes,
+            save_path: PathBuf::from("/tmp/node_registry.json"),
+        }
+    }
+
+    fn config(count: u16) -> FleetConfig {
+        FleetConfig {
+            count,
+            node_port: Some("12000-12009".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn config_should_be_parsed_from_toml() -> Result<()> {
+        let config: FleetConfig = toml::from_str(
+            r#"
+            count = 3
+            version = "0.98.2"
+            node_port = "12000-12002"
+            owner = "maidsafe"
+            home_network = true
+            log_format = "json"
+
+            [env]
+            SN_LOG = "all"
+
+            [bandwidth]
+            max_upload = 1024
+            "#,
+        )?;
+        config.validate()?;
+        assert_eq!(config.count, 3);
+        assert_eq!(config.log_format()?, Some(LogFormat::Json));
+        assert_eq!(
+            config.env_variables(),
+            Some(vec![("SN_LOG".to_string(), "all".to_string())])
+        );
+        assert_eq!(config.bandwidth.max_upload, Some(1024));
+        Ok(())
+    }
+
+    #[test]
+    fn config_should_be_loaded_from_yaml() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let path = tmp_dir.path().join("fleet.yaml");
+        std::fs::write(
+            &path,
+            r#"
+count: 3
+version: "0.98.2"
+node_port: "12000-12002"
+home_network: true
+env:
+  SN_LOG: all
+bandwidth:
+  max_upload: 1024
+"#,
+        )?;
+
+        let config = FleetConfig::load(&path)?;
+        assert_eq!(config.count, 3);
+        assert_eq!(config.version, Some("0.98.2".to_string()));
+        assert!(config.home_network);
+        assert_eq!(
+            config.env_variables(),
+            Some(vec![("SN_LOG".to_string(), "all".to_string())])
+        );
+        assert_eq!(config.bandwidth.max_upload, Some(1024));
+
+        // the TOML parser is used for the other extensions
+        let path = tmp_dir.path().join("fleet.toml");
+        std::fs::write(&path, "count: 3")?;
+        assert!(FleetConfig::load(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config_should_require_the_count() {
+        assert!(toml::from_str::<FleetConfig>("version = \"0.108.0\"").is_err());
+    }
+
+    #[test]
+    fn config_should_reject_unknown_fields_and_small_port_ranges() {
+        assert!(toml::from_str::<FleetConfig>("count = 1\nnode_ports = \"12000\"").is_err());
+        let config = FleetConfig {
+            count: 3,
+            node_port: Some("12000-12001".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn converged_registry_should_have_an_empty_plan() -> Result<()> {
+        let registry = registry(vec![
+            node(1, ServiceStatus::Running),
+            node(2, ServiceStatus::Running),
+        ]);
+        let plan = plan(&config(2), &registry)?;
+        assert!(plan.is_empty());
+        assert!(plan.warnings.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn excess_nodes_should_be_removed_from_the_highest_number() -> Result<()> {
+        let registry = registry(vec![
+            node(1, ServiceStatus::Running),
+            node(2, ServiceStatus::Removed),
+            node(3, ServiceStatus::Running),
+            node(4, ServiceStatus::Stopped),
+        ]);
+        let plan = plan(&config(1), &registry)?;
+        assert_eq!(
+            plan.actions,
+            vec![
+                FleetAction::Remove {
+                    service_name: "safenode4".to_string()
+                },
+                FleetAction::Remove {
+                    service_name: "safenode3".to_string()
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_nodes_should_be_added_on_free_ports() -> Result<()> {
+        // safenode1 uses port 12000, safenode3 uses 12002
+        let registry = registry(vec![
+            node(1, ServiceStatus::Running),
+            node(3, ServiceStatus::Running),
+        ]);
+        let plan = plan(&config(4), &registry)?;
+        assert_eq!(
+            plan.actions,
+            vec![FleetAction::Add {
+                count: 2,
+                node_port: Some(PortRange::Range(12003, 12004)),
+                rpc_port: None,
+                metrics_port: None,
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn adding_should_fail_without_enough_free_ports() {
+        let registry = registry(vec![node(2, ServiceStatus::Running)]);
+        let config = FleetConfig {
+            count: 3,
+            node_port: Some("12000-12002".to_string()),
+            ..Default::default()
+        };
+        assert!(plan(&config, &registry).is_err());
+    }
+
+    #[test]
+    fn drifted_and_outdated_nodes_should_be_reconfigured_then_upgraded() -> Result<()> {
+        let mut stopped = node(2, ServiceStatus::Stopped);
+        stopped.owner = Some("maidsafe".to_string());
+        let registry = registry(vec![node(1, ServiceStatus::Running), stopped]);
+        let config = FleetConfig {
+            owner: Some("maidsafe".to_string()),
+            version: Some("0.98.2".to_string()),
+            ..config(2)
+        };
+
+        let plan = plan(&config, &registry)?;
+        assert_eq!(
+            plan.actions,
+            vec![
+                FleetAction::Reconfigure {
+                    service_name: "safenode1".to_string(),
+                    changes: vec!["owner None -> Some(\"maidsafe\")".to_string()],
+                },
+                FleetAction::Upgrade {
+                    service_name: "safenode1".to_string(),
+                    from: "0.98.1".to_string(),
+                    to: "0.98.2".to_string(),
+                },
+                FleetAction::Upgrade {
+                    service_name: "safenode2".to_string(),
+                    from: "0.98.1".to_string(),
+                    to: "0.98.2".to_string(),
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stopped_node_should_be_started_and_port_drift_reported() -> Result<()> {
+        let mut stopped = node(1, ServiceStatus::Stopped);
+        stopped.node_port = Some(15000);
+        let registry = registry(vec![stopped]);
+
+        let plan = plan(&config(1), &registry)?;
+        assert_eq!(
+            plan.actions,
+            vec![FleetAction::Start {
+                service_name: "safenode1".to_string()
+            }]
+        );
+        assert_eq!(plan.warnings.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn changed_env_variables_should_reconfigure_every_node() -> Result<()> {
+        let registry = registry(vec![node(1, ServiceStatus::Running)]);
+        let mut config = config(1);
+        config.env.insert("SN_LOG".to_string(), "all".to_string());
+
+        let plan = plan(&config, &registry)?;
+        assert_eq!(
+            plan.env_variables,
+            Some(Some(vec![("SN_LOG".to_string(), "all".to_string())]))
+        );
+        assert_eq!(
+            plan.actions,
+            vec![FleetAction::Reconfigure {
+                service_name: "safenode1".to_string(),
+                changes: vec!["environment variables".to_string()],
+            }]
+        );
+        Ok(())
+    }
+}
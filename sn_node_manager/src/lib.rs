@@ -10,11 +10,19 @@
 extern crate tracing;
 
 pub mod add_services;
+pub mod backup;
 pub mod cmd;
 pub mod config;
+pub mod daemon_auth;
+pub mod doctor;
 pub mod error;
+pub mod fleet;
 pub mod helpers;
 pub mod local;
+pub mod output;
+pub mod ports;
+pub mod relocate;
+pub mod rolling_upgrade;
 pub mod rpc;
 pub mod rpc_client;
 
@@ -40,17 +48,24 @@ use crate::error::{Error, Result};
 use colored::Colorize;
 use semver::Version;
 use sn_service_management::{
-    control::ServiceControl, error::Error as ServiceError, rpc::RpcClient, NodeRegistry,
-    NodeService, NodeServiceData, ServiceStateActions, ServiceStatus, UpgradeOptions,
-    UpgradeResult,
+    control::ServiceControl,
+    error::Error as ServiceError,
+    event_history::read_node_event_history,
+    rpc::{NodeEventEntry, RpcActions, RpcClient, NOTABLE_NODE_EVENT_KINDS},
+    NodeRegistry, NodeService, NodeServiceData, ResourceLimits, ServiceStateActions, ServiceStatus,
+    UpgradeOptions, UpgradeResult,
 };
 use sn_transfers::HotWallet;
+use std::time::{Duration, SystemTime};
 use tracing::debug;
 
 pub const DAEMON_DEFAULT_PORT: u16 = 12500;
 pub const DAEMON_SERVICE_NAME: &str = "safenodemand";
 
 const RPC_START_UP_DELAY_MS: u64 = 3000;
+/// The number of notable events shown per node in the detailed status.
+const RECENT_EVENTS_COUNT: usize = 5;
+const RECENT_EVENTS_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
 
 pub struct ServiceManager<T: ServiceStateActions + Send> {
     pub service: T,
@@ -416,6 +431,33 @@ pub async fn status_report(
                     .as_ref()
                     .map_or("-".to_string(), |o| o.to_string())
             );
+            println!(
+                "Resource limits: {}",
+                if node.resource_limits.is_unlimited() {
+                    "-".to_string()
+                } else if !ResourceLimits::is_supported() {
+                    format!(
+                        "{} (not applied without systemd)",
+                        node.resource_limits.to_systemd_directives().join(", ")
+                    )
+                } else {
+                    node.resource_limits.to_systemd_directives().join(", ")
+                }
+            );
+            let recent_events = get_recent_notable_events(node).await;
+            if recent_events.is_empty() {
+                println!("Recent events: -");
+            } else {
+                println!("Recent events:");
+                for event in recent_events {
+                    println!(
+                        "  {} {}",
+                        chrono::DateTime::<chrono::Local>::from(event.timestamp)
+                            .format("%Y-%m-%d %H:%M:%S"),
+                        event.description
+                    );
+                }
+            }
             println!();
         }
 
@@ -588,6 +630,55 @@ pub async fn refresh_node_registry(
     Ok(())
 }
 
+/// Returns the most recent notable events of a node, most recent first.
+///
+/// The events of a running node are obtained through RPC. They are read from the history the
+/// node persisted in its data dir when it is not running, or when it cannot be reached.
+///
+/// The events are only informational, so failing to obtain them, e.g. from a node that predates
+/// the event history, is not an error.
+pub async fn get_recent_notable_events(node: &NodeServiceData) -> Vec<NodeEventEntry> {
+    let since = SystemTime::now()
+        .checked_sub(RECENT_EVENTS_PERIOD)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    if node.status == ServiceStatus::Running {
+        let mut rpc_client = RpcClient::from_socket_addr(node.rpc_socket_addr);
+        rpc_client.set_max_attempts(1);
+        let kinds = NOTABLE_NODE_EVENT_KINDS
+            .iter()
+            .map(|kind| kind.to_string())
+            .collect();
+        match rpc_client.node_event_history(since, kinds).await {
+            Ok(events) => return events.into_iter().rev().take(RECENT_EVENTS_COUNT).collect(),
+            Err(err) => debug!(
+                "Could not obtain the event history of {} through RPC: {err:?}",
+                node.service_name
+            ),
+        }
+    }
+
+    match read_node_event_history(&node.data_dir_path) {
+        Ok(history) => history
+            .events
+            .into_iter()
+            .map(|persisted| persisted.entry)
+            .filter(|entry| {
+                entry.timestamp >= since && NOTABLE_NODE_EVENT_KINDS.contains(&entry.kind.as_str())
+            })
+            .rev()
+            .take(RECENT_EVENTS_COUNT)
+            .collect(),
+        Err(err) => {
+            debug!(
+                "Could not read the event history of {}: {err:?}",
+                node.service_name
+            );
+            Vec::new()
+        }
+    }
+}
+
 pub fn print_banner(text: &str) {
     let padding = 2;
     let text_width = text.len() + padding * 2;
@@ -633,7 +724,7 @@ mod tests {
     use sn_service_management::{
         error::{Error as ServiceControlError, Result as ServiceControlResult},
         node::{NodeService, NodeServiceData},
-        rpc::{NetworkInfo, NodeInfo, RecordAddress, RpcActions},
+        rpc::{NetworkInfo, NodeEventEntry, NodeInfo, RecordAddress, RpcActions},
         UpgradeOptions, UpgradeResult,
     };
     use sn_transfers::NanoTokens;
@@ -642,6 +733,7 @@ mod tests {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         str::FromStr,
+        time::SystemTime,
     };
 
     mock! {
@@ -650,6 +742,7 @@ mod tests {
         impl RpcActions for RpcClient {
             async fn node_info(&self) -> ServiceControlResult<NodeInfo>;
             async fn network_info(&self) -> ServiceControlResult<NetworkInfo>;
+            async fn node_event_history(&self, since: SystemTime, kinds: Vec<String>) -> ServiceControlResult<Vec<NodeEventEntry>>;
             async fn record_addresses(&self) -> ServiceControlResult<Vec<RecordAddress>>;
             async fn node_restart(&self, delay_millis: u64, retain_peer_id: bool) -> ServiceControlResult<()>;
             async fn node_stop(&self, delay_millis: u64) -> ServiceControlResult<()>;
@@ -719,6 +812,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -733,6 +827,7 @@ mod tests {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -818,6 +913,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -834,6 +930,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -883,6 +980,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -899,6 +997,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -990,6 +1089,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1006,6 +1106,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1068,6 +1169,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1082,6 +1184,7 @@ mod tests {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1156,6 +1259,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1170,6 +1274,7 @@ mod tests {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1211,6 +1316,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1227,6 +1333,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1259,6 +1366,7 @@ mod tests {
     async fn stop_should_not_return_error_for_attempt_to_stop_installed_service() -> Result<()> {
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1273,6 +1381,7 @@ mod tests {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1305,6 +1414,7 @@ mod tests {
     ) -> Result<()> {
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1321,6 +1431,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1354,6 +1465,7 @@ mod tests {
     async fn stop_should_return_ok_when_attempting_to_stop_a_removed_service() -> Result<()> {
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1368,6 +1480,7 @@ mod tests {
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1416,6 +1529,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1432,6 +1546,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -1540,6 +1655,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1556,6 +1672,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -1627,6 +1744,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1643,6 +1761,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -1757,6 +1876,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1773,6 +1893,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -1900,6 +2021,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -1916,6 +2038,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2038,6 +2161,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2054,6 +2178,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2177,6 +2302,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2193,6 +2319,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2345,6 +2472,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2361,6 +2489,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2496,6 +2625,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2512,6 +2642,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2650,6 +2781,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2666,6 +2798,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2801,6 +2934,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2817,6 +2951,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -2952,6 +3087,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -2968,6 +3104,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -3106,6 +3243,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -3122,6 +3260,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -3260,6 +3399,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -3276,6 +3416,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -3414,6 +3555,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: true,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -3430,6 +3572,7 @@ mod tests {
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
             pid: Some(1000),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: current_node_bin.to_path_buf(),
@@ -3483,6 +3626,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: data_dir.to_path_buf(),
             genesis: false,
@@ -3497,6 +3641,7 @@ mod tests {
             owner: None,
             pid: None,
             peer_id: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: safenode_bin.to_path_buf(),
@@ -3539,6 +3684,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -3555,6 +3701,7 @@ mod tests {
             peer_id: Some(PeerId::from_str(
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -3611,6 +3758,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
             genesis: false,
@@ -3627,6 +3775,7 @@ mod tests {
             peer_id: Some(PeerId::from_str(
                 "12D3KooWS2tpXGGTmg2AHFiDh57yPQnat49YHnyqoggzXZWpqkCR",
             )?),
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: PathBuf::from("/var/safenode-manager/services/safenode1/safenode"),
@@ -3675,6 +3824,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: data_dir.to_path_buf(),
             genesis: false,
@@ -3689,6 +3839,7 @@ mod tests {
             owner: None,
             pid: None,
             peer_id: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: safenode_bin.to_path_buf(),
@@ -3737,6 +3888,7 @@ mod tests {
 
         let mut service_data = NodeServiceData {
             auto_restart: false,
+            bandwidth_limits: Default::default(),
             connected_peers: None,
             data_dir_path: data_dir.to_path_buf(),
             genesis: false,
@@ -3751,6 +3903,7 @@ mod tests {
             owner: None,
             pid: None,
             peer_id: None,
+            resource_limits: Default::default(),
             reward_balance: Some(NanoTokens::zero()),
             rpc_socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8081),
             safenode_path: safenode_bin.to_path_buf(),
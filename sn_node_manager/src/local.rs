@@ -15,6 +15,7 @@ use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 use mockall::automock;
 
 use sn_logging::LogFormat;
+use sn_protocol::transport::TransportMode;
 use sn_service_management::{
     control::ServiceControl,
     rpc::{RpcActions, RpcClient},
@@ -47,6 +48,8 @@ pub trait Launcher {
 pub struct LocalSafeLauncher {
     pub faucet_bin_path: PathBuf,
     pub safenode_bin_path: PathBuf,
+    /// The `--transport` passed to the nodes, e.g. `tcp` to test networks where UDP is blocked
+    pub transport: Option<TransportMode>,
 }
 
 impl Launcher for LocalSafeLauncher {
@@ -97,6 +100,11 @@ impl Launcher for LocalSafeLauncher {
             args.push(log_format.as_str().to_string());
         }
 
+        if let Some(transport) = &self.transport {
+            args.push("--transport".to_string());
+            args.push(transport.to_string());
+        }
+
         args.push("--local".to_string());
         args.push("--rpc".to_string());
         args.push(rpc_socket_addr.to_string());
@@ -196,6 +204,7 @@ pub struct LocalNetworkOptions {
     pub safenode_bin_path: PathBuf,
     pub skip_validation: bool,
     pub log_format: Option<LogFormat>,
+    pub transport: Option<TransportMode>,
 }
 
 pub async fn run_network(
@@ -208,6 +217,7 @@ pub async fn run_network(
     let launcher = LocalSafeLauncher {
         safenode_bin_path: options.safenode_bin_path.to_path_buf(),
         faucet_bin_path: options.faucet_bin_path.to_path_buf(),
+        transport: options.transport,
     };
 
     let (bootstrap_peers, start) = if options.join {
@@ -347,6 +357,7 @@ pub async fn run_node(
 
     Ok(NodeServiceData {
         auto_restart: false,
+        bandwidth_limits: Default::default(),
         connected_peers,
         data_dir_path: node_info.data_path,
         genesis: run_options.genesis,
@@ -361,6 +372,7 @@ pub async fn run_node(
         owner: run_options.owner,
         peer_id: Some(peer_id),
         pid: Some(node_info.pid),
+        resource_limits: Default::default(),
         reward_balance: None,
         rpc_socket_addr: run_options.rpc_socket_addr,
         safenode_path: launcher.get_safenode_path(),
@@ -444,9 +456,9 @@ mod tests {
     use mockall::predicate::*;
     use sn_service_management::{
         error::Result as RpcResult,
-        rpc::{NetworkInfo, NodeInfo, RecordAddress, RpcActions},
+        rpc::{NetworkInfo, NodeEventEntry, NodeInfo, RecordAddress, RpcActions},
     };
-    use std::str::FromStr;
+    use std::{str::FromStr, time::SystemTime};
 
     mock! {
         pub RpcClient {}
@@ -454,6 +466,7 @@ mod tests {
         impl RpcActions for RpcClient {
             async fn node_info(&self) -> RpcResult<NodeInfo>;
             async fn network_info(&self) -> RpcResult<NetworkInfo>;
+            async fn node_event_history(&self, since: SystemTime, kinds: Vec<String>) -> RpcResult<Vec<NodeEventEntry>>;
             async fn record_addresses(&self) -> RpcResult<Vec<RecordAddress>>;
             async fn node_restart(&self, delay_millis: u64, retain_peer_id: bool) -> RpcResult<()>;
             async fn node_stop(&self, delay_millis: u64) -> RpcResult<()>;
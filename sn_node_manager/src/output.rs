@@ -0,0 +1,270 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use color_eyre::{eyre::Report, Result};
+use serde::{Deserialize, Serialize};
+use sn_service_management::{NodeServiceData, ServiceStatus, UpgradeResult};
+
+/// The version of the `--json` output. It changes when a field is removed or changes meaning;
+/// new fields can be added without changing it.
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// The exit code when some services succeeded and others failed.
+pub const EXIT_CODE_PARTIAL_FAILURE: i32 = 2;
+/// The exit code when the command failed, or failed for all services.
+pub const EXIT_CODE_FAILURE: i32 = 1;
+
+/// The result of a command, emitted as a single JSON document in `--json` mode.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CommandOutput {
+    pub schema_version: u32,
+    pub command: String,
+    pub result: CommandResult,
+    /// The outcome for each service the command operated on, in the order of the operations.
+    pub services: Vec<ServiceOutcome>,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandResult {
+    Success,
+    PartialFailure,
+    Failure,
+}
+
+/// The outcome of an operation on a single service.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ServiceOutcome {
+    pub service_name: String,
+    pub peer_id: Option<String>,
+    pub status: Option<ServiceStatus>,
+    pub version: Option<String>,
+    /// The version before an upgrade.
+    pub previous_version: Option<String>,
+    pub node_port: Option<u16>,
+    pub rpc_port: Option<u16>,
+    pub metrics_port: Option<u16>,
+    pub reward_balance: Option<String>,
+    /// Set when the operation failed for this service.
+    pub error: Option<String>,
+}
+
+impl ServiceOutcome {
+    pub fn from_node(node: &NodeServiceData) -> Self {
+        Self {
+            service_name: node.service_name.clone(),
+            peer_id: node.peer_id.map(|peer_id| peer_id.to_string()),
+            status: Some(node.status.clone()),
+            version: Some(node.version.clone()),
+            previous_version: None,
+            node_port: node.node_port,
+            rpc_port: Some(node.rpc_socket_addr.port()),
+            metrics_port: node.metrics_port,
+            reward_balance: node.reward_balance.map(|balance| balance.to_string()),
+            error: None,
+        }
+    }
+
+    /// The outcome for the auditor, faucet or daemon, which have no ports or peer ID to report.
+    pub fn from_service(service_name: &str, status: &ServiceStatus, version: &str) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            status: Some(status.clone()),
+            version: Some(version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn from_upgrade(node: &NodeServiceData, upgrade_result: &UpgradeResult) -> Self {
+        let mut outcome = Self::from_node(node);
+        match upgrade_result {
+            UpgradeResult::NotRequired => {}
+            UpgradeResult::Upgraded(previous_version, new_version)
+            | UpgradeResult::Forced(previous_version, new_version) => {
+                outcome.previous_version = Some(previous_version.clone());
+                outcome.version = Some(new_version.clone());
+            }
+            UpgradeResult::UpgradedButNotStarted(previous_version, new_version, err) => {
+                outcome.previous_version = Some(previous_version.clone());
+                outcome.version = Some(new_version.clone());
+                outcome.error = Some(format!("The service did not start: {err}"));
+            }
+            UpgradeResult::Error(err) => outcome.error = Some(err.clone()),
+        }
+        outcome
+    }
+
+    pub fn with_error(mut self, error: String) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// The error of a command that failed for some or all of its services, carrying the outcome for
+/// every service so it can be reported in `--json` mode.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct FailedOperations {
+    pub message: String,
+    pub outcomes: Vec<ServiceOutcome>,
+}
+
+impl CommandOutput {
+    pub fn new(command: &str, result: &Result<Vec<ServiceOutcome>>) -> Self {
+        let (services, error) = match result {
+            Ok(outcomes) => (outcomes.clone(), None),
+            Err(err) => (
+                failed_outcomes(err).unwrap_or_default(),
+                Some(err.to_string()),
+            ),
+        };
+        let succeeded = services
+            .iter()
+            .filter(|outcome| outcome.succeeded())
+            .count();
+        let result = match error {
+            None if succeeded == services.len() => CommandResult::Success,
+            _ if succeeded > 0 => CommandResult::PartialFailure,
+            _ => CommandResult::Failure,
+        };
+        Self {
+            schema_version: OUTPUT_SCHEMA_VERSION,
+            command: command.to_string(),
+            result,
+            services,
+            error,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self.result {
+            CommandResult::Success => 0,
+            CommandResult::PartialFailure => EXIT_CODE_PARTIAL_FAILURE,
+            CommandResult::Failure => EXIT_CODE_FAILURE,
+        }
+    }
+}
+
+/// Gather the outcomes of a step of a command into those of the previous steps, keeping the latest
+/// outcome of each service, so a failure reports every service the command operated on.
+pub fn merge_outcomes(
+    outcomes: &mut Vec<ServiceOutcome>,
+    step: Result<Vec<ServiceOutcome>>,
+) -> Result<()> {
+    let (step_outcomes, error) = match step {
+        Ok(step_outcomes) => (step_outcomes, None),
+        Err(err) => match err.downcast::<FailedOperations>() {
+            Ok(failed) => (failed.outcomes, Some(failed.message)),
+            Err(err) if outcomes.is_empty() => return Err(err),
+            Err(err) => (Vec::new(), Some(format!("{err:#}"))),
+        },
+    };
+    for outcome in step_outcomes {
+        match outcomes
+            .iter_mut()
+            .find(|existing| existing.service_name == outcome.service_name)
+        {
+            Some(existing) => *existing = outcome,
+            None => outcomes.push(outcome),
+        }
+    }
+    match error {
+        Some(message) => Err(FailedOperations {
+            message,
+            outcomes: outcomes.clone(),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
+fn failed_outcomes(err: &Report) -> Option<Vec<ServiceOutcome>> {
+    err.downcast_ref::<FailedOperations>()
+        .map(|failed| failed.outcomes.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color_eyre::eyre::eyre;
+
+    fn outcome(service_name: &str, error: Option<&str>) -> ServiceOutcome {
+        ServiceOutcome {
+            service_name: service_name.to_string(),
+            error: error.map(|error| error.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn failures_of_some_services_should_be_a_partial_failure() {
+        let outcomes = vec![
+            outcome("safenode1", None),
+            outcome("safenode2", Some("Failed to start")),
+        ];
+        let result: Result<Vec<ServiceOutcome>> = Err(FailedOperations {
+            message: "Failed to start one or more services".to_string(),
+            outcomes: outcomes.clone(),
+        }
+        .into());
+
+        let output = CommandOutput::new("start", &result);
+        assert_eq!(output.result, CommandResult::PartialFailure);
+        assert_eq!(output.services, outcomes);
+        assert_eq!(
+            output.error,
+            Some("Failed to start one or more services".to_string())
+        );
+        assert_eq!(output.exit_code(), EXIT_CODE_PARTIAL_FAILURE);
+    }
+
+    #[test]
+    fn merged_outcomes_should_keep_the_latest_outcome_of_each_service() {
+        let mut outcomes = Vec::new();
+        assert!(merge_outcomes(&mut outcomes, Ok(vec![outcome("safenode1", None)])).is_ok());
+
+        let result = merge_outcomes(
+            &mut outcomes,
+            Err(FailedOperations {
+                message: "Failed to start one or more services".to_string(),
+                outcomes: vec![
+                    outcome("safenode1", Some("Failed to start")),
+                    outcome("safenode2", None),
+                ],
+            }
+            .into()),
+        );
+
+        let expected = vec![
+            outcome("safenode1", Some("Failed to start")),
+            outcome("safenode2", None),
+        ];
+        assert_eq!(outcomes, expected);
+        let output = CommandOutput::new("apply", &result.map(|()| outcomes.clone()));
+        assert_eq!(output.result, CommandResult::PartialFailure);
+        assert_eq!(output.services, expected);
+    }
+
+    #[test]
+    fn errors_without_outcomes_should_be_a_failure() {
+        let result: Result<Vec<ServiceOutcome>> = Err(eyre!("No service named 'safenode9'"));
+        let output = CommandOutput::new("stop", &result);
+        assert_eq!(output.result, CommandResult::Failure);
+        assert!(output.services.is_empty());
+        assert_eq!(output.exit_code(), EXIT_CODE_FAILURE);
+
+        let output = CommandOutput::new("stop", &Ok(vec![outcome("safenode1", None)]));
+        assert_eq!(output.result, CommandResult::Success);
+        assert_eq!(output.exit_code(), 0);
+    }
+}
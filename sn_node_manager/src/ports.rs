@@ -0,0 +1,164 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::add_services::config::{parse_port_range, PortRange};
+use color_eyre::{eyre::eyre, Help, Result};
+use serde::{Deserialize, Serialize};
+use sn_service_management::{NodeServiceData, ServiceStatus};
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, TcpListener, UdpSocket},
+    path::Path,
+};
+
+/// The ports reserved for the node services, from which ports are allocated when none are
+/// specified.
+///
+/// It is read from a TOML file in the node manager directory, e.g.:
+///
+/// ```toml
+/// node = "12000-12999"
+/// rpc = "13000-13999"
+/// metrics = "14000-14999"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortPool {
+    pub node: Option<String>,
+    pub rpc: Option<String>,
+    pub metrics: Option<String>,
+}
+
+impl PortPool {
+    /// Read the pool, which is empty if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let pool: PortPool = toml::from_str(&contents)
+            .map_err(|err| eyre!("The port pool at {path:?} is not valid: {err}"))?;
+        pool.node_range()?;
+        pool.rpc_range()?;
+        pool.metrics_range()?;
+        Ok(pool)
+    }
+
+    pub fn node_range(&self) -> Result<Option<PortRange>> {
+        self.node.as_deref().map(parse_port_range).transpose()
+    }
+
+    pub fn rpc_range(&self) -> Result<Option<PortRange>> {
+        self.rpc.as_deref().map(parse_port_range).transpose()
+    }
+
+    pub fn metrics_range(&self) -> Result<Option<PortRange>> {
+        self.metrics.as_deref().map(parse_port_range).transpose()
+    }
+}
+
+/// The ports used by the nodes in the registry, other than removed ones.
+pub fn registry_ports(nodes: &[NodeServiceData]) -> HashSet<u16> {
+    let mut ports = HashSet::new();
+    for node in nodes
+        .iter()
+        .filter(|node| node.status != ServiceStatus::Removed)
+    {
+        ports.extend(node.node_port);
+        ports.extend(node.metrics_port);
+        ports.insert(node.rpc_socket_addr.port());
+    }
+    ports
+}
+
+/// Find `count` consecutive ports in the range that are neither used nor taken by `is_taken`.
+pub fn allocate(
+    range: &PortRange,
+    count: u16,
+    used_ports: &HashSet<u16>,
+    is_taken: impl Fn(u16) -> bool,
+) -> Result<PortRange> {
+    let (start, end) = bounds(range);
+    let mut first = start;
+    while count > 0 && u32::from(first) + u32::from(count) - 1 <= u32::from(end) {
+        let last = first + (count - 1);
+        match (first..=last).find(|port| used_ports.contains(port) || is_taken(*port)) {
+            Some(u16::MAX) => break,
+            Some(taken_port) => first = taken_port + 1,
+            None if count == 1 => return Ok(PortRange::Single(first)),
+            None => return Ok(PortRange::Range(first, last)),
+        }
+    }
+    Err(eyre!(
+        "There are not {count} consecutive free ports left in the range {start}-{end}"
+    ))
+    .suggestion("Widen the port range or free some of its ports")
+}
+
+/// Check none of the ports are bound by another process.
+pub fn check_ports_not_in_use(range: &PortRange) -> Result<()> {
+    let (start, end) = bounds(range);
+    let in_use = (start..=end)
+        .filter(|port| is_port_in_use(*port))
+        .collect::<Vec<_>>();
+    if !in_use.is_empty() {
+        error!("Ports {in_use:?} are already in use by other processes");
+        return Err(eyre!(
+            "Ports {in_use:?} are already in use by other processes"
+        ))
+        .suggestion("Stop the processes using the ports or choose other ports");
+    }
+    Ok(())
+}
+
+/// Whether a process is listening on the port. Nodes use UDP for QUIC and TCP for the RPC and
+/// metrics servers, so both are checked.
+pub fn is_port_in_use(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_err()
+        || UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).is_err()
+}
+
+pub fn bounds(range: &PortRange) -> (u16, u16) {
+    match range {
+        PortRange::Single(port) => (*port, *port),
+        PortRange::Range(start, end) => (*start, *end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocation_should_skip_used_and_taken_ports() -> Result<()> {
+        let used_ports = HashSet::from([12000, 12002]);
+        let range = allocate(&PortRange::Range(12000, 12010), 3, &used_ports, |port| {
+            port == 12004
+        })?;
+        assert_eq!(range, PortRange::Range(12005, 12007));
+
+        let range = allocate(&PortRange::Range(12000, 12010), 1, &used_ports, |_| false)?;
+        assert_eq!(range, PortRange::Single(12001));
+        Ok(())
+    }
+
+    #[test]
+    fn allocation_should_fail_when_the_range_is_exhausted() {
+        let used_ports = HashSet::from([12001]);
+        let result = allocate(&PortRange::Range(12000, 12002), 2, &used_ports, |_| false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pool_should_be_empty_without_a_file() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let pool = PortPool::load(&tmp_dir.path().join("port_pool.toml"))?;
+        assert_eq!(pool, PortPool::default());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use color_eyre::{eyre::eyre, Result};
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+/// The file holding the keypair of a node, which determines its peer ID.
+pub const SECRET_KEY_FILE_NAME: &str = "secret-key";
+
+/// Check a node can be moved from `src` to the directory, which must not exist or be empty.
+///
+/// Neither directory may contain the other: the copy would end up inside the data being copied,
+/// or be deleted along with the original data.
+pub fn check_destination(src: &Path, path: &Path) -> Result<()> {
+    let src = resolve(src)?;
+    let dst = resolve(path)?;
+    if dst.starts_with(&src) || src.starts_with(&dst) {
+        return Err(eyre!(
+            "The destination {path:?} cannot be inside the current directory {src:?}, or contain it"
+        ));
+    }
+    if path.exists() && std::fs::read_dir(path)?.next().is_some() {
+        return Err(eyre!("The destination {path:?} is not empty"));
+    }
+    Ok(())
+}
+
+/// Canonicalize a path which may not exist yet, through its closest existing ancestor.
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return Ok(path.to_path_buf());
+        };
+        missing.push(name);
+        existing = parent;
+    }
+    let mut resolved = existing.canonicalize()?;
+    resolved.extend(missing.into_iter().rev());
+    Ok(resolved)
+}
+
+/// Copy the directory tree, then verify every file of the copy matches the source byte for byte.
+///
+/// Returns the number of files copied.
+pub fn copy_and_verify(src: &Path, dst: &Path) -> Result<usize> {
+    let files = copy_dir(src, dst)?;
+    for relative_path in files.iter() {
+        if !files_match(&src.join(relative_path), &dst.join(relative_path))? {
+            return Err(eyre!(
+                "The copy of {relative_path:?} does not match the original"
+            ));
+        }
+    }
+    if src.join(SECRET_KEY_FILE_NAME).exists() && !dst.join(SECRET_KEY_FILE_NAME).exists() {
+        return Err(eyre!("The keypair of the node was not copied"));
+    }
+    debug!(
+        "Copied and verified {} files from {src:?} to {dst:?}",
+        files.len()
+    );
+    Ok(files.len())
+}
+
+/// Returns the paths of the copied files, relative to `src`.
+fn copy_dir(src: &Path, dst: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative_dir) = dirs.pop() {
+        std::fs::create_dir_all(dst.join(&relative_dir))?;
+        for entry in std::fs::read_dir(src.join(&relative_dir))? {
+            let entry = entry?;
+            let relative_path = relative_dir.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                dirs.push(relative_path);
+            } else {
+                std::fs::copy(entry.path(), dst.join(&relative_path))?;
+                files.push(relative_path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn files_match(a: &Path, b: &Path) -> Result<bool> {
+    if std::fs::metadata(a)?.len() != std::fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let mut a = BufReader::new(File::open(a)?);
+    let mut b = BufReader::new(File::open(b)?);
+    let mut a_buf = [0; 8192];
+    let mut b_buf = [0; 8192];
+    loop {
+        let read = a.read(&mut a_buf)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut b_buf[..read])?;
+        if a_buf[..read] != b_buf[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+
+    #[test]
+    fn data_dir_should_be_copied_and_verified() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let src = tmp_dir.child("old/safenode1");
+        src.child(SECRET_KEY_FILE_NAME).write_binary(b"keypair")?;
+        src.child("safenode").write_binary(b"fake safenode bin")?;
+        src.child("record_store/a1b2").write_binary(b"record")?;
+        src.child("record_store/nested/c3d4")
+            .write_binary(b"another record")?;
+        let dst = tmp_dir.child("new/safenode1");
+
+        check_destination(src.path(), dst.path())?;
+        let copied_files = copy_and_verify(src.path(), dst.path())?;
+
+        assert_eq!(copied_files, 4);
+        dst.child(SECRET_KEY_FILE_NAME).assert("keypair");
+        dst.child("record_store/nested/c3d4")
+            .assert("another record");
+        Ok(())
+    }
+
+    #[test]
+    fn non_empty_destination_should_be_rejected() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let dst = tmp_dir.child("safenode1");
+        dst.child("record_store/a1b2").write_binary(b"record")?;
+        assert!(check_destination(tmp_dir.child("old").path(), dst.path()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn nested_destination_should_be_rejected() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let src = tmp_dir.child("safenode1");
+        src.child("record_store/a1b2").write_binary(b"record")?;
+
+        assert!(check_destination(src.path(), src.child("moved/safenode1").path()).is_err());
+        assert!(check_destination(src.path(), tmp_dir.path()).is_err());
+        // The paths are compared once resolved
+        tmp_dir.child("other").create_dir_all()?;
+        let indirect = tmp_dir.path().join("other/../safenode1/moved");
+        assert!(check_destination(src.path(), &indirect).is_err());
+        assert!(check_destination(src.path(), tmp_dir.child("safenode2").path()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn differing_files_should_not_match() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let a = tmp_dir.child("a");
+        a.write_binary(b"record one")?;
+        let b = tmp_dir.child("b");
+        b.write_binary(b"record two")?;
+        assert!(!files_match(a.path(), b.path())?);
+        assert!(files_match(a.path(), a.path())?);
+        Ok(())
+    }
+}
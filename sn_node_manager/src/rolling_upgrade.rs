@@ -0,0 +1,225 @@
+// Copyright (C) 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use color_eyre::{eyre::eyre, Result};
+use semver::Version;
+use sn_service_management::rpc::RpcActions;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// A node emits `ConnectedToNetwork` once it is connected to its close group, which has this size.
+pub const DEFAULT_MIN_CONNECTED_PEERS: usize = 5;
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(300);
+const HEALTH_CHECK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Upgrade the nodes a batch at a time, only moving on to the next batch once the upgraded nodes
+/// are back on the network.
+#[derive(Clone, Debug)]
+pub struct RollingUpgradeOptions {
+    /// The number of nodes upgraded together.
+    pub batch_size: usize,
+    /// The minimum number of peers an upgraded node must be connected to for it to be healthy.
+    pub min_connected_peers: usize,
+    /// How long to wait for an upgraded node to be healthy before giving up.
+    pub health_check_timeout: Duration,
+    /// Put the previous binary back on the nodes of a failed batch.
+    pub rollback: bool,
+}
+
+impl Default for RollingUpgradeOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1,
+            min_connected_peers: DEFAULT_MIN_CONNECTED_PEERS,
+            health_check_timeout: DEFAULT_HEALTH_CHECK_TIMEOUT,
+            rollback: false,
+        }
+    }
+}
+
+/// Wait until the node runs the target version and is connected to at least `min_connected_peers`.
+///
+/// The `ConnectedToNetwork` event is emitted only once, possibly before we could subscribe to the
+/// events of the node, so the connected peers are polled instead.
+pub async fn wait_for_node_health(
+    rpc_client: &dyn RpcActions,
+    target_version: &Version,
+    min_connected_peers: usize,
+    timeout: Duration,
+) -> Result<()> {
+    wait_for_node_health_with_interval(
+        rpc_client,
+        target_version,
+        min_connected_peers,
+        timeout,
+        HEALTH_CHECK_POLL_INTERVAL,
+    )
+    .await
+}
+
+async fn wait_for_node_health_with_interval(
+    rpc_client: &dyn RpcActions,
+    target_version: &Version,
+    min_connected_peers: usize,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let last_state = match rpc_client.node_info().await {
+            Ok(node_info)
+                if Version::parse(&node_info.version).ok().as_ref() != Some(target_version) =>
+            {
+                format!("the node is running version {}", node_info.version)
+            }
+            Ok(_) => match rpc_client.network_info().await {
+                Ok(network_info) if network_info.connected_peers.len() >= min_connected_peers => {
+                    debug!(
+                        "The node is healthy with {} connected peers",
+                        network_info.connected_peers.len()
+                    );
+                    return Ok(());
+                }
+                Ok(network_info) => format!(
+                    "the node is connected to {} peers",
+                    network_info.connected_peers.len()
+                ),
+                Err(err) => format!("the network info could not be retrieved: {err}"),
+            },
+            Err(err) => format!("the node info could not be retrieved: {err}"),
+        };
+        trace!("Waiting for the node to be healthy: {last_state}");
+
+        if Instant::now() + poll_interval > deadline {
+            return Err(eyre!(
+                "The node was not healthy after {}s: {last_state}",
+                timeout.as_secs()
+            ));
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Keep a copy of the binary of a node, so that it can be put back if the upgrade fails.
+pub fn backup_binary(bin_path: &Path, version: &str) -> Result<PathBuf> {
+    let file_name = bin_path
+        .file_name()
+        .ok_or_else(|| eyre!("Could not get the file name of {bin_path:?}"))?
+        .to_string_lossy();
+    let backup_path = bin_path.with_file_name(format!("{file_name}.{version}.bak"));
+    std::fs::copy(bin_path, &backup_path)?;
+    debug!("Backed up {bin_path:?} to {backup_path:?}");
+    Ok(backup_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::prelude::*;
+    use async_trait::async_trait;
+    use libp2p_identity::PeerId;
+    use mockall::mock;
+    use sn_service_management::{
+        error::Result as ServiceControlResult,
+        rpc::{NetworkInfo, NodeEventEntry, NodeInfo, RecordAddress},
+    };
+    use std::time::SystemTime;
+
+    mock! {
+        pub RpcClient {}
+        #[async_trait]
+        impl RpcActions for RpcClient {
+            async fn node_info(&self) -> ServiceControlResult<NodeInfo>;
+            async fn network_info(&self) -> ServiceControlResult<NetworkInfo>;
+            async fn node_event_history(&self, since: SystemTime, kinds: Vec<String>) -> ServiceControlResult<Vec<NodeEventEntry>>;
+            async fn record_addresses(&self) -> ServiceControlResult<Vec<RecordAddress>>;
+            async fn node_restart(&self, delay_millis: u64, retain_peer_id: bool) -> ServiceControlResult<()>;
+            async fn node_stop(&self, delay_millis: u64) -> ServiceControlResult<()>;
+            async fn node_update(&self, delay_millis: u64) -> ServiceControlResult<()>;
+            async fn update_log_level(&self, log_levels: String) -> ServiceControlResult<()>;
+        }
+    }
+
+    fn node_info(version: &str) -> NodeInfo {
+        NodeInfo {
+            pid: 1000,
+            peer_id: PeerId::random(),
+            data_path: PathBuf::from("/var/safenode-manager/services/safenode1"),
+            log_path: PathBuf::from("/var/log/safenode/safenode1"),
+            version: version.to_string(),
+            uptime: Duration::from_secs(1),
+            wallet_balance: 0,
+        }
+    }
+
+    fn network_info(connected_peers: usize) -> NetworkInfo {
+        NetworkInfo {
+            connected_peers: (0..connected_peers).map(|_| PeerId::random()).collect(),
+            listeners: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn node_should_be_healthy_once_connected_to_enough_peers() -> Result<()> {
+        let mut mock_rpc_client = MockRpcClient::new();
+        mock_rpc_client
+            .expect_node_info()
+            .times(2)
+            .returning(|| Ok(node_info("0.98.2")));
+        let mut connected_peers = vec![5, 2].into_iter();
+        mock_rpc_client
+            .expect_network_info()
+            .times(2)
+            .returning(move || Ok(network_info(connected_peers.next_back().unwrap_or(0))));
+
+        wait_for_node_health_with_interval(
+            &mock_rpc_client,
+            &Version::parse("0.98.2")?,
+            5,
+            Duration::from_secs(1),
+            Duration::from_millis(10),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn node_running_the_previous_version_should_not_be_healthy() -> Result<()> {
+        let mut mock_rpc_client = MockRpcClient::new();
+        mock_rpc_client
+            .expect_node_info()
+            .returning(|| Ok(node_info("0.98.1")));
+        mock_rpc_client.expect_network_info().never();
+
+        let result = wait_for_node_health_with_interval(
+            &mock_rpc_client,
+            &Version::parse("0.98.2")?,
+            5,
+            Duration::from_millis(50),
+            Duration::from_millis(10),
+        )
+        .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn backup_should_be_kept_next_to_the_binary() -> Result<()> {
+        let tmp_data_dir = assert_fs::TempDir::new()?;
+        let bin = tmp_data_dir.child("safenode");
+        bin.write_binary(b"fake safenode bin")?;
+
+        let backup_path = backup_binary(bin.path(), "0.98.1")?;
+        assert_eq!(backup_path, tmp_data_dir.path().join("safenode.0.98.1.bak"));
+        assert_eq!(std::fs::read(backup_path)?, b"fake safenode bin");
+        Ok(())
+    }
+}
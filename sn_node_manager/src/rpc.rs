@@ -63,6 +63,7 @@ pub async fn restart_node_service(
             })?;
         let install_ctx = InstallNodeServiceCtxBuilder {
             autostart: current_node_clone.auto_restart,
+            bandwidth_limits: current_node_clone.bandwidth_limits,
             bootstrap_peers: node_registry.bootstrap_peers.clone(),
             data_dir_path: current_node_clone.data_dir_path.clone(),
             env_variables: node_registry.environment_variables.clone(),
@@ -75,6 +76,7 @@ pub async fn restart_node_service(
             owner: current_node_clone.owner.clone(),
             name: current_node_clone.service_name.clone(),
             node_port: current_node_clone.get_safenode_port(),
+            resource_limits: current_node_clone.resource_limits,
             rpc_socket_addr: current_node_clone.rpc_socket_addr,
             safenode_path: current_node_clone.safenode_path.clone(),
             service_user: current_node_clone.user.clone(),
@@ -178,6 +180,7 @@ pub async fn restart_node_service(
 
         let install_ctx = InstallNodeServiceCtxBuilder {
             autostart: current_node_clone.auto_restart,
+            bandwidth_limits: current_node_clone.bandwidth_limits,
             bootstrap_peers: node_registry.bootstrap_peers.clone(),
             data_dir_path: data_dir_path.clone(),
             env_variables: node_registry.environment_variables.clone(),
@@ -190,6 +193,7 @@ pub async fn restart_node_service(
             metrics_port: None,
             node_port: None,
             owner: None,
+            resource_limits: current_node_clone.resource_limits,
             rpc_socket_addr: current_node_clone.rpc_socket_addr,
             safenode_path: safenode_path.clone(),
             service_user: current_node_clone.user.clone(),
@@ -202,6 +206,7 @@ pub async fn restart_node_service(
 
         let mut node = NodeServiceData {
             auto_restart: current_node_clone.auto_restart,
+            bandwidth_limits: current_node_clone.bandwidth_limits,
             connected_peers: None,
             data_dir_path,
             genesis: current_node_clone.genesis,
@@ -216,6 +221,7 @@ pub async fn restart_node_service(
             owner: None,
             peer_id: None,
             pid: None,
+            resource_limits: current_node_clone.resource_limits,
             reward_balance: current_node_clone.reward_balance,
             rpc_socket_addr: current_node_clone.rpc_socket_addr,
             safenode_path,
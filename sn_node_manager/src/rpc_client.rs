@@ -1,3 +1,4 @@
+use crate::{config::get_daemon_auth_token_path, daemon_auth};
 use color_eyre::eyre::bail;
 use color_eyre::{eyre::eyre, Result};
 use libp2p_identity::PeerId;
@@ -19,19 +20,22 @@ pub async fn restart_node(
     rpc_server_address: SocketAddr,
     retain_peer_id: bool,
 ) -> Result<()> {
+    let token = daemon_auth::read_token(&get_daemon_auth_token_path()?)?;
     for peer_id in peer_ids {
         debug!("Sending NodeServiceRestartRequest to {peer_id:?} at {rpc_server_address:?}");
         let str_bytes = PeerId::from_str(&peer_id)?.to_bytes();
 
         let mut daemon_client = get_rpc_client(rpc_server_address).await?;
 
+        let mut request = Request::new(NodeServiceRestartRequest {
+            peer_id: str_bytes,
+            delay_millis: 0,
+            retain_peer_id,
+        });
+        daemon_auth::authorise_request(&mut request, &token)?;
         let _response = daemon_client
             .rpc
-            .restart_node_service(Request::new(NodeServiceRestartRequest {
-                peer_id: str_bytes,
-                delay_millis: 0,
-                retain_peer_id,
-            }))
+            .restart_node_service(request)
             .await
             .map_err(|err| {
                 error!("Failed to restart node service with {peer_id:?} at {rpc_server_address:?} with err: {err:?}");
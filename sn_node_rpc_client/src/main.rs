@@ -8,16 +8,23 @@
 //
 
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 
 use sn_logging::{Level, LogBuilder};
 use sn_node::NodeEvent;
 
-use sn_protocol::safenode_proto::{safe_node_client::SafeNodeClient, NodeEventsRequest};
+use libp2p::PeerId;
+use sn_protocol::safenode_proto::{
+    safe_node_client::SafeNodeClient, NodeEventHistoryRequest, NodeEventsRequest,
+    PeerReputationsRequest,
+};
 
 use sn_service_management::rpc::{RpcActions, RpcClient};
 
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    net::SocketAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tokio_stream::StreamExt;
 use tonic::Request;
 
@@ -39,10 +46,22 @@ enum Cmd {
     /// Retrieve information about the node's connections to the network
     #[clap(name = "netinfo")]
     Netinfo,
+    /// Retrieve the reputation of the peers the node recorded issues against
+    #[clap(name = "reputation")]
+    Reputation,
     /// Start listening for node events.
     /// Note this blocks the app and it will print events as they are broadcasted by the node
     #[clap(name = "events")]
-    Events,
+    Events {
+        /// Print the events from the node's history within this period before listening, e.g.
+        /// "30m", "12h" or "2d". The history is retained across restarts of the node.
+        #[clap(long, value_parser = parse_period)]
+        since: Option<Duration>,
+        /// Only print the events of this kind, e.g. "TerminateNode" or "RewardReceived".
+        /// This argument can be used multiple times.
+        #[clap(long = "kind")]
+        kinds: Vec<String>,
+    },
     /// Restart the node after the specified delay
     #[clap(name = "restart")]
     Restart {
@@ -95,7 +114,8 @@ async fn main() -> Result<()> {
     match opt.cmd {
         Cmd::Info => node_info(addr).await,
         Cmd::Netinfo => network_info(addr).await,
-        Cmd::Events => node_events(addr).await,
+        Cmd::Reputation => peer_reputations(addr).await,
+        Cmd::Events { since, kinds } => node_events(addr, since, kinds).await,
         Cmd::Restart {
             delay_millis,
             retain_peer_id,
@@ -144,9 +164,63 @@ pub async fn network_info(addr: SocketAddr) -> Result<()> {
     Ok(())
 }
 
-pub async fn node_events(addr: SocketAddr) -> Result<()> {
+pub async fn peer_reputations(addr: SocketAddr) -> Result<()> {
     let endpoint = format!("https://{addr}");
     let mut client = SafeNodeClient::connect(endpoint).await?;
+    let response = client
+        .peer_reputations(Request::new(PeerReputationsRequest {}))
+        .await?;
+
+    println!("Reputation of the peers the node recorded issues against:");
+    for reputation in response.into_inner().reputations {
+        let peer_id = PeerId::from_bytes(&reputation.peer_id)?;
+        let status = if reputation.is_bad { "BAD" } else { "ok" };
+        println!();
+        println!("Peer: {peer_id} ({status})");
+        for (issue, score) in reputation.scores.iter() {
+            println!("  {issue}: {score:.2}");
+        }
+        for evidence in reputation.evidences.iter() {
+            println!(
+                "  {}s ago, {}: {}",
+                evidence.age_secs, evidence.issue, evidence.details
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn node_events(
+    addr: SocketAddr,
+    since: Option<Duration>,
+    kinds: Vec<String>,
+) -> Result<()> {
+    let endpoint = format!("https://{addr}");
+    let mut client = SafeNodeClient::connect(endpoint).await?;
+
+    if let Some(since) = since {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let since_secs = now.saturating_sub(since).as_secs();
+        let response = client
+            .node_event_history(Request::new(NodeEventHistoryRequest {
+                since_secs,
+                kinds: kinds.clone(),
+            }))
+            .await?;
+
+        println!("Node events in the last {since:?}:");
+        for entry in response.into_inner().events {
+            let age_secs = now.as_secs().saturating_sub(entry.timestamp_secs);
+            match NodeEvent::from_bytes(&entry.event) {
+                Ok(event) => println!("{age_secs}s ago: {event:?}"),
+                // the event could be from a newer version of the node
+                Err(_) => println!("{age_secs}s ago: {}", entry.description),
+            }
+        }
+        println!();
+    }
+
     let response = client
         .node_events(Request::new(NodeEventsRequest {}))
         .await?;
@@ -156,6 +230,7 @@ pub async fn node_events(addr: SocketAddr) -> Result<()> {
     let mut stream = response.into_inner();
     while let Some(Ok(e)) = stream.next().await {
         match NodeEvent::from_bytes(&e.event) {
+            Ok(event) if !kinds.is_empty() && !kinds.iter().any(|k| k == event.kind()) => {}
             Ok(event) => println!("New event received: {event:?}"),
             Err(_) => {
                 println!("Error while parsing received NodeEvent");
@@ -220,3 +295,23 @@ pub async fn update_log_level(addr: SocketAddr, log_levels: String) -> Result<()
     println!("Node successfully received the request to update the log level to {log_levels:?}",);
     Ok(())
 }
+
+// Parse a period such as "90s", "30m", "12h" or "2d"
+fn parse_period(period: &str) -> Result<Duration> {
+    let (value, unit) = period.split_at(period.len().saturating_sub(1));
+    let value: u64 = value
+        .parse()
+        .map_err(|_| eyre!("Invalid period '{period}', expected e.g. 30m, 12h or 2d"))?;
+    let secs = match unit {
+        "s" => value,
+        "m" => value * 60,
+        "h" => value * 60 * 60,
+        "d" => value * 24 * 60 * 60,
+        _ => {
+            return Err(eyre!(
+                "Invalid unit in period '{period}', expected s, m, h or d"
+            ))
+        }
+    };
+    Ok(Duration::from_secs(secs))
+}
@@ -16,6 +16,7 @@ use libp2p::{multiaddr::Protocol, Multiaddr};
 use rand::{seq::SliceRandom, thread_rng};
 #[cfg(feature = "network-contacts")]
 use sn_networking::version::get_network_version;
+use sn_networking::TransportMode;
 use tracing::*;
 #[cfg(feature = "network-contacts")]
 use url::Url;
@@ -159,18 +160,36 @@ impl PeersArgs {
 }
 
 /// Parse strings like `1.2.3.4:1234` and `/ip4/1.2.3.4/tcp/1234` into a multiaddr.
+///
+/// The socket addresses are turned into QUIC addresses, see `parse_peer_addr_for_transport` to
+/// use another transport.
 pub fn parse_peer_addr(addr: &str) -> Result<Multiaddr> {
+    parse_peer_addr_for_transport(addr, TransportMode::Quic)
+}
+
+/// Parse strings like `1.2.3.4:1234` and `/ip4/1.2.3.4/tcp/1234` into a multiaddr.
+///
+/// The socket addresses are turned into an address of the given transport, i.e. TCP for
+/// `TransportMode::Tcp` and QUIC otherwise.
+pub fn parse_peer_addr_for_transport(addr: &str, transport: TransportMode) -> Result<Multiaddr> {
     // Parse valid IPv4 socket address, e.g. `1.2.3.4:1234`.
     if let Ok(addr) = addr.parse::<std::net::SocketAddrV4>() {
         let start_addr = Multiaddr::from(*addr.ip());
 
-        // Turn the address into a `/ip4/<ip>/udp/<port>/quic-v1` multiaddr.
+        // Turn the address into a `/ip4/<ip>/udp/<port>/quic-v1` or `/ip4/<ip>/tcp/<port>` multiaddr.
         #[cfg(not(feature = "websockets"))]
-        let multiaddr = start_addr
-            .with(Protocol::Udp(addr.port()))
-            .with(Protocol::QuicV1);
+        let multiaddr = if transport == TransportMode::Tcp {
+            start_addr.with(Protocol::Tcp(addr.port()))
+        } else {
+            start_addr
+                .with(Protocol::Udp(addr.port()))
+                .with(Protocol::QuicV1)
+        };
 
-        // Turn the address into a `/ip4/<ip>/udp/<port>/websocket-websys-v1` multiaddr.
+        // Turn the address into a `/ip4/<ip>/udp/<port>/websocket-websys-v1` multiaddr,
+        // WebSockets being the only transport then.
+        #[cfg(feature = "websockets")]
+        let _ = transport;
         #[cfg(feature = "websockets")]
         let multiaddr = start_addr
             .with(Protocol::Tcp(addr.port()))
@@ -24,6 +24,10 @@ pub enum Error {
     CouldNotObtainPortFromMultiAddr,
     #[error("Could not parse RetryStrategy")]
     ParseRetryStrategyError,
+    #[error("Could not parse wire protocol version range {0:?}")]
+    ParseWireVersionRangeError(String),
+    #[error("Invalid transport mode {0:?}, expected one of quic, tcp or quic-and-tcp")]
+    ParseTransportModeError(String),
     #[error("Could not obtain data dir")]
     CouldNotObtainDataDir,
 
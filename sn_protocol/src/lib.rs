@@ -19,6 +19,10 @@ pub mod node;
 pub mod node_rpc;
 /// Storage types for spends, chunks and registers.
 pub mod storage;
+/// The transports used to connect to the other peers
+pub mod transport;
+/// Versioning of the wire protocol of the messages
+pub mod version;
 
 // this includes code generated from .proto files
 #[allow(clippy::unwrap_used, clippy::clone_on_ref_ptr)]
@@ -42,11 +46,12 @@ use std::{
 };
 use xor_name::XorName;
 
-/// Returns the UDP port from the provided MultiAddr.
+/// Returns the port from the provided MultiAddr, whichever the transport.
 pub fn get_port_from_multiaddr(multi_addr: &Multiaddr) -> Option<u16> {
-    // assuming the listening addr contains /ip4/127.0.0.1/udp/56215/quic-v1/p2p/<peer_id>
+    // assuming the listening addr contains either /ip4/127.0.0.1/udp/56215/quic-v1/p2p/<peer_id>
+    // or /ip4/127.0.0.1/tcp/56215/p2p/<peer_id>
     for protocol in multi_addr.iter() {
-        if let Protocol::Udp(port) = protocol {
+        if let Protocol::Udp(port) | Protocol::Tcp(port) = protocol {
             return Some(port);
         }
     }
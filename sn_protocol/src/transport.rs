@@ -0,0 +1,138 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::error::{Error, Result};
+use libp2p::{multiaddr::Protocol, Multiaddr};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// The transports used to connect to the other peers.
+/// Ignored on wasm32, where only WebSockets are available.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransportMode {
+    /// QUIC only
+    #[default]
+    Quic,
+    /// TCP with noise and yamux only, for networks blocking UDP
+    Tcp,
+    /// Both QUIC and TCP, listening on the same port number for both.
+    /// All the known addresses of a peer are dialed concurrently and the first connection established is kept,
+    /// hence TCP gets used whenever UDP is blocked.
+    QuicAndTcp,
+}
+
+impl TransportMode {
+    pub fn uses_quic(&self) -> bool {
+        matches!(self, TransportMode::Quic | TransportMode::QuicAndTcp)
+    }
+
+    pub fn uses_tcp(&self) -> bool {
+        matches!(self, TransportMode::Tcp | TransportMode::QuicAndTcp)
+    }
+
+    /// Returns the address rewritten to use our transport, for the peers known through an address of the
+    /// other transport, e.g. from a network contacts file. As the nodes listen on the same port number with
+    /// both transports, only the protocols following the IP are changed.
+    /// The addresses already usable by us, and the WebSocket ones, are returned as is.
+    pub fn adapt_addr(&self, addr: Multiaddr) -> Multiaddr {
+        let mut protocols = addr.iter();
+        let Some(ip) = protocols.next() else {
+            return addr;
+        };
+        let rest: Vec<_> = protocols.collect();
+        let adapted: Vec<Protocol> = match (self, rest.as_slice()) {
+            (TransportMode::Tcp, [Protocol::Udp(port), Protocol::QuicV1, tail @ ..]) => {
+                [Protocol::Tcp(*port)]
+                    .into_iter()
+                    .chain(tail.iter().cloned())
+                    .collect()
+            }
+            (TransportMode::Quic, [Protocol::Tcp(port), tail @ ..])
+                if !matches!(tail.first(), Some(Protocol::Ws(_)) | Some(Protocol::Wss(_))) =>
+            {
+                [Protocol::Udp(*port), Protocol::QuicV1]
+                    .into_iter()
+                    .chain(tail.iter().cloned())
+                    .collect()
+            }
+            _ => return addr,
+        };
+        std::iter::once(ip).chain(adapted).collect()
+    }
+
+    /// Returns the addresses to dial a peer known through the given address, in order of preference.
+    /// With both transports, the QUIC address comes first and the TCP one on the same port number is the
+    /// fallback for when UDP is blocked.
+    pub fn dial_addrs(&self, addr: Multiaddr) -> Vec<Multiaddr> {
+        match self {
+            TransportMode::Quic | TransportMode::Tcp => vec![self.adapt_addr(addr)],
+            TransportMode::QuicAndTcp => {
+                let quic = TransportMode::Quic.adapt_addr(addr.clone());
+                let tcp = TransportMode::Tcp.adapt_addr(addr);
+                if quic == tcp {
+                    vec![quic]
+                } else {
+                    vec![quic, tcp]
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for TransportMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "quic" => Ok(TransportMode::Quic),
+            "tcp" => Ok(TransportMode::Tcp),
+            "quic-and-tcp" => Ok(TransportMode::QuicAndTcp),
+            _ => Err(Error::ParseTransportModeError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportMode::Quic => write!(f, "quic"),
+            TransportMode::Tcp => write!(f, "tcp"),
+            TransportMode::QuicAndTcp => write!(f, "quic-and-tcp"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_adapted_to_the_transport() -> color_eyre::Result<()> {
+        let peer = "12D3KooWRi6wF7yxWLuPSNskXc6kQ5cJ6eaymeMbCRdTnMesPgFx";
+        let quic: Multiaddr = format!("/ip4/1.2.3.4/udp/1200/quic-v1/p2p/{peer}").parse()?;
+        let tcp: Multiaddr = format!("/ip4/1.2.3.4/tcp/1200/p2p/{peer}").parse()?;
+        let ws: Multiaddr = format!("/ip4/1.2.3.4/tcp/1200/ws/p2p/{peer}").parse()?;
+
+        assert_eq!(TransportMode::Tcp.adapt_addr(quic.clone()), tcp);
+        assert_eq!(TransportMode::Quic.adapt_addr(tcp.clone()), quic);
+        assert_eq!(TransportMode::QuicAndTcp.adapt_addr(quic.clone()), quic);
+        assert_eq!(TransportMode::Quic.adapt_addr(ws.clone()), ws);
+        assert_eq!(
+            crate::get_port_from_multiaddr(&TransportMode::Tcp.adapt_addr(quic.clone())),
+            Some(1200)
+        );
+
+        assert_eq!(
+            TransportMode::QuicAndTcp.dial_addrs(tcp.clone()),
+            vec![quic.clone(), tcp.clone()]
+        );
+        assert_eq!(TransportMode::Tcp.dial_addrs(quic), vec![tcp]);
+        assert_eq!(TransportMode::QuicAndTcp.dial_addrs(ws.clone()), vec![ws]);
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::error::{Error, Result};
+use std::{fmt, str::FromStr};
+
+/// Version of the layout of the `Request`/`Response` messages on the wire.
+///
+/// It is bumped only when the layout of the messages changes, independently of the crate versions,
+/// so releases that do not touch the messages keep talking to each other.
+pub const WIRE_PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest version of the wire protocol we still understand.
+///
+/// Version 0 is the layout used before the versions got negotiated,
+/// when the protocol was identified by the truncated crate version.
+pub const MIN_WIRE_PROTOCOL_VERSION: u32 = 0;
+
+/// An inclusive range of wire protocol versions, as advertised by a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireVersionRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl WireVersionRange {
+    /// The range of versions supported by this release
+    pub const fn supported() -> Self {
+        Self {
+            min: MIN_WIRE_PROTOCOL_VERSION,
+            max: WIRE_PROTOCOL_VERSION,
+        }
+    }
+
+    pub fn contains(&self, version: u32) -> bool {
+        self.min <= version && version <= self.max
+    }
+
+    /// The highest version supported by both ranges, if any
+    pub fn highest_common(&self, other: &Self) -> Option<u32> {
+        let highest = self.max.min(other.max);
+        let lowest = self.min.max(other.min);
+        (lowest <= highest).then_some(highest)
+    }
+
+    /// The versions of the range, the highest first, i.e. in order of preference
+    pub fn versions(&self) -> impl Iterator<Item = u32> {
+        (self.min..=self.max).rev()
+    }
+}
+
+impl fmt::Display for WireVersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.min, self.max)
+    }
+}
+
+impl FromStr for WireVersionRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (min, max) = s
+            .split_once('-')
+            .ok_or_else(|| Error::ParseWireVersionRangeError(s.to_string()))?;
+        let min = min
+            .parse()
+            .map_err(|_| Error::ParseWireVersionRangeError(s.to_string()))?;
+        let max = max
+            .parse()
+            .map_err(|_| Error::ParseWireVersionRangeError(s.to_string()))?;
+        if min > max {
+            return Err(Error::ParseWireVersionRangeError(s.to_string()));
+        }
+        Ok(Self { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highest_common_version_is_negotiated() -> Result<()> {
+        let ours = WireVersionRange { min: 1, max: 3 };
+        let older: WireVersionRange = "0-2".parse()?;
+        let newer: WireVersionRange = "3-5".parse()?;
+        let too_new = WireVersionRange { min: 4, max: 6 };
+
+        assert_eq!(ours.highest_common(&older), Some(2));
+        assert_eq!(older.highest_common(&ours), Some(2));
+        assert_eq!(ours.highest_common(&newer), Some(3));
+        assert_eq!(ours.highest_common(&too_new), None);
+        assert_eq!(ours.versions().collect::<Vec<_>>(), vec![3, 2, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!("2".parse::<WireVersionRange>().is_err());
+        assert!("3-1".parse::<WireVersionRange>().is_err());
+        assert!("a-b".parse::<WireVersionRange>().is_err());
+        assert_eq!(
+            WireVersionRange::supported().to_string(),
+            format!("{MIN_WIRE_PROTOCOL_VERSION}-{WIRE_PROTOCOL_VERSION}")
+        );
+    }
+}
@@ -29,12 +29,16 @@ pub enum Error {
     ParseIntError(#[from] std::num::ParseIntError),
     #[error(transparent)]
     PeerIdParseError(#[from] libp2p_identity::ParseError),
+    #[error("The resource limits of '{0}' can only be applied on a host running systemd")]
+    ResourceLimitsRequireSystemd(String),
     #[error("Could not connect to RPC endpoint '{0}'")]
     RpcConnectionError(String),
     #[error("Could not obtain node info through RPC: {0}")]
     RpcNodeInfoError(String),
     #[error("Could not obtain network info through RPC: {0}")]
     RpcNetworkInfoError(String),
+    #[error("Could not obtain the node event history through RPC: {0}")]
+    RpcNodeEventHistoryError(String),
     #[error("Could not restart node through RPC: {0}")]
     RpcNodeRestartError(String),
     #[error("Could not stop node through RPC: {0}")]
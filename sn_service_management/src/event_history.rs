@@ -0,0 +1,83 @@
+// Copyright 2024 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{error::Result, rpc::NodeEventEntry};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+const NODE_EVENT_HISTORY_FILENAME: &str = "node_event_history";
+
+/// An event as persisted by the node in its data dir, one JSON document per line.
+///
+/// The entry can be read without `sn_node`, so the history of a node that is not running can
+/// still be shown.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PersistedNodeEvent {
+    pub entry: NodeEventEntry,
+    /// The event as encoded by `NodeEvent::to_bytes`
+    pub event: Vec<u8>,
+}
+
+impl PersistedNodeEvent {
+    /// Encode the event as a line of the history file.
+    pub fn to_line(&self) -> Result<Vec<u8>> {
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+/// The events read from the history file of a node, oldest first.
+#[derive(Debug, Default)]
+pub struct NodeEventHistoryFile {
+    pub events: Vec<PersistedNodeEvent>,
+    /// The length of the file up to the end of the last complete event. Anything beyond it was
+    /// partially written when the node got terminated.
+    pub valid_len: u64,
+}
+
+pub fn node_event_history_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(NODE_EVENT_HISTORY_FILENAME)
+}
+
+/// Read the history persisted in the data dir of a node. A missing file is an empty history.
+pub fn read_node_event_history(data_dir: &Path) -> Result<NodeEventHistoryFile> {
+    let file_path = node_event_history_path(data_dir);
+    let file = match File::open(&file_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(NodeEventHistoryFile::default()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut history = NodeEventHistoryFile::default();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 || line.last() != Some(&b'\n') {
+            break;
+        }
+        match serde_json::from_slice(&line) {
+            Ok(event) => history.events.push(event),
+            Err(err) => {
+                warn!(
+                    "Ignoring the node events after offset {} of {file_path:?}: {err:?}",
+                    history.valid_len
+                );
+                break;
+            }
+        }
+        history.valid_len += read as u64;
+    }
+    Ok(history)
+}
@@ -10,6 +10,7 @@ pub mod auditor;
 pub mod control;
 pub mod daemon;
 pub mod error;
+pub mod event_history;
 pub mod faucet;
 pub mod node;
 pub mod rpc;
@@ -35,7 +36,7 @@ use std::{
 pub use daemon::{DaemonService, DaemonServiceData};
 pub use error::{Error, Result};
 pub use faucet::{FaucetService, FaucetServiceData};
-pub use node::{NodeService, NodeServiceData};
+pub use node::{BandwidthLimits, NodeService, NodeServiceData, ResourceLimits};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ServiceStatus {
@@ -6,7 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{error::Result, rpc::RpcActions, ServiceStateActions, ServiceStatus, UpgradeOptions};
+use crate::{
+    error::{Error, Result},
+    rpc::RpcActions,
+    ServiceStateActions, ServiceStatus, UpgradeOptions,
+};
 use async_trait::async_trait;
 use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
@@ -85,6 +89,7 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             args.push(OsString::from("--owner"));
             args.push(OsString::from(owner));
         }
+        args.extend(self.service_data.bandwidth_limits.to_args());
 
         if !options.bootstrap_peers.is_empty() {
             let peers_str = options
@@ -97,7 +102,7 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             args.push(OsString::from(peers_str));
         }
 
-        Ok(ServiceInstallCtx {
+        let mut install_ctx = ServiceInstallCtx {
             args,
             autostart: options.auto_restart,
             contents: None,
@@ -106,7 +111,12 @@ impl<'a> ServiceStateActions for NodeService<'a> {
             program: self.service_data.safenode_path.to_path_buf(),
             username: self.service_data.user.clone(),
             working_directory: None,
-        })
+        };
+        install_ctx.contents = self
+            .service_data
+            .resource_limits
+            .service_definition(&install_ctx, self.service_data.user_mode)?;
+        Ok(install_ctx)
     }
 
     fn data_dir_path(&self) -> PathBuf {
@@ -220,10 +230,151 @@ impl<'a> ServiceStateActions for NodeService<'a> {
     }
 }
 
+/// The bandwidth limits of a node, in kilobytes per second. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BandwidthLimits {
+    pub max_upload: Option<u64>,
+    pub max_download: Option<u64>,
+    pub max_per_peer: Option<u64>,
+}
+
+impl BandwidthLimits {
+    /// The `safenode` arguments applying the limits
+    pub fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(max_upload) = self.max_upload {
+            args.push(OsString::from("--max-upload-bandwidth"));
+            args.push(OsString::from(max_upload.to_string()));
+        }
+        if let Some(max_download) = self.max_download {
+            args.push(OsString::from("--max-download-bandwidth"));
+            args.push(OsString::from(max_download.to_string()));
+        }
+        if let Some(max_per_peer) = self.max_per_peer {
+            args.push(OsString::from("--max-peer-bandwidth"));
+            args.push(OsString::from(max_per_peer.to_string()));
+        }
+        args
+    }
+}
+
+/// The resources a node service may use. `None` means unconstrained.
+///
+/// The limits are enforced by the service manager, which is only possible with systemd. Adding or
+/// reinstalling a service with limits fails on other hosts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// The CPU time as a percentage of one core, e.g., 150 for one and a half cores.
+    pub cpu_quota: Option<u32>,
+    /// The maximum memory, in megabytes.
+    pub max_memory_mb: Option<u64>,
+    /// The maximum number of open file descriptors.
+    pub max_open_files: Option<u64>,
+    /// The relative IO weight, from 1 to 10000. The default weight of a service is 100.
+    pub io_weight: Option<u16>,
+}
+
+impl ResourceLimits {
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The directives of the `[Service]` section of a systemd unit applying the limits
+    pub fn to_systemd_directives(&self) -> Vec<String> {
+        let mut directives = Vec::new();
+        if let Some(cpu_quota) = self.cpu_quota {
+            directives.push(format!("CPUQuota={cpu_quota}%"));
+        }
+        if let Some(max_memory_mb) = self.max_memory_mb {
+            directives.push(format!("MemoryMax={max_memory_mb}M"));
+        }
+        if let Some(max_open_files) = self.max_open_files {
+            directives.push(format!("LimitNOFILE={max_open_files}"));
+        }
+        if let Some(io_weight) = self.io_weight {
+            directives.push(format!("IOWeight={io_weight}"));
+        }
+        directives
+    }
+
+    /// Whether the limits can be applied on this host, which requires systemd.
+    pub fn is_supported() -> bool {
+        cfg!(target_os = "linux") && std::path::Path::new("/run/systemd/system").exists()
+    }
+
+    /// The service definition applying the limits, used in place of the one generated by the
+    /// service manager.
+    ///
+    /// Returns `None` when there are no limits, and an error when the host does not run systemd,
+    /// rather than installing a service without the limits.
+    pub fn service_definition(
+        &self,
+        install_ctx: &ServiceInstallCtx,
+        user_mode: bool,
+    ) -> Result<Option<String>> {
+        if self.is_unlimited() {
+            return Ok(None);
+        }
+        if !Self::is_supported() {
+            return Err(Error::ResourceLimitsRequireSystemd(
+                install_ctx.label.to_script_name(),
+            ));
+        }
+        Ok(Some(self.systemd_unit(install_ctx, user_mode)))
+    }
+
+    /// Render the unit the same way the service manager does, with the limits added.
+    pub fn systemd_unit(&self, install_ctx: &ServiceInstallCtx, user_mode: bool) -> String {
+        let mut lines = vec![
+            "[Unit]".to_string(),
+            format!("Description={}", install_ctx.label.to_script_name()),
+            "[Service]".to_string(),
+        ];
+        if let Some(working_directory) = &install_ctx.working_directory {
+            lines.push(format!(
+                "WorkingDirectory={}",
+                working_directory.to_string_lossy()
+            ));
+        }
+        for (var, val) in install_ctx.environment.iter().flatten() {
+            lines.push(format!("Environment=\"{var}={val}\""));
+        }
+        let args = install_ctx
+            .args
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(format!(
+            "ExecStart={} {args}",
+            install_ctx.program.to_string_lossy()
+        ));
+        lines.push("Restart=on-failure".to_string());
+        // A user-mode service runs as the current user and fails to start if it names one.
+        if !user_mode {
+            if let Some(username) = &install_ctx.username {
+                lines.push(format!("User={username}"));
+            }
+        }
+        lines.extend(self.to_systemd_directives());
+        if install_ctx.autostart {
+            lines.push("[Install]".to_string());
+            if user_mode {
+                lines.push("WantedBy=default.target".to_string());
+            } else {
+                lines.push("WantedBy=multi-user.target".to_string());
+            }
+        }
+        lines.join("\n")
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeServiceData {
     #[serde(default)]
     pub auto_restart: bool,
+    #[serde(default)]
+    pub bandwidth_limits: BandwidthLimits,
     #[serde(
         serialize_with = "serialize_connected_peers",
         deserialize_with = "deserialize_connected_peers"
@@ -249,6 +400,8 @@ pub struct NodeServiceData {
     )]
     pub peer_id: Option<PeerId>,
     pub pid: Option<u32>,
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
     pub reward_balance: Option<NanoTokens>,
     pub rpc_socket_addr: SocketAddr,
     pub safenode_path: PathBuf,
@@ -323,9 +476,10 @@ where
 }
 
 impl NodeServiceData {
-    /// Returns the UDP port from our node's listen address.
+    /// Returns the port from our node's listen address, UDP or TCP depending on its transport.
     pub fn get_safenode_port(&self) -> Option<u16> {
         // assuming the listening addr contains /ip4/127.0.0.1/udp/56215/quic-v1/p2p/<peer_id>
+        // or /ip4/127.0.0.1/tcp/56215/p2p/<peer_id>
         if let Some(multi_addrs) = &self.listen_addr {
             println!("Listening addresses are defined");
             for addr in multi_addrs {
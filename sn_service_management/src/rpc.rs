@@ -9,11 +9,17 @@
 use crate::error::{Error, Result};
 use async_trait::async_trait;
 use libp2p::{kad::RecordKey, Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
 use sn_protocol::safenode_proto::{
-    safe_node_client::SafeNodeClient, NetworkInfoRequest, NodeInfoRequest, RecordAddressesRequest,
-    RestartRequest, StopRequest, UpdateLogLevelRequest, UpdateRequest,
+    safe_node_client::SafeNodeClient, NetworkInfoRequest, NodeEventHistoryRequest, NodeInfoRequest,
+    RecordAddressesRequest, RestartRequest, StopRequest, UpdateLogLevelRequest, UpdateRequest,
+};
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
-use std::{net::SocketAddr, path::PathBuf, str::FromStr};
 use tokio::time::Duration;
 use tonic::Request;
 use tracing::error;
@@ -40,10 +46,32 @@ pub struct RecordAddress {
     pub key: RecordKey,
 }
 
+/// The kinds of node events worth surfacing to the users of a node.
+pub const NOTABLE_NODE_EVENT_KINDS: &[&str] = &[
+    "TerminateNode",
+    "ChannelClosed",
+    "PeerConsideredAsBad",
+    "RewardReceived",
+];
+
+/// An event from the node's history. The event itself is only provided as a description, since
+/// decoding it requires `sn_node`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeEventEntry {
+    pub timestamp: SystemTime,
+    pub kind: String,
+    pub description: String,
+}
+
 #[async_trait]
 pub trait RpcActions: Sync {
     async fn node_info(&self) -> Result<NodeInfo>;
     async fn network_info(&self) -> Result<NetworkInfo>;
+    async fn node_event_history(
+        &self,
+        since: SystemTime,
+        kinds: Vec<String>,
+    ) -> Result<Vec<NodeEventEntry>>;
     async fn record_addresses(&self) -> Result<Vec<RecordAddress>>;
     async fn node_restart(&self, delay_millis: u64, retain_peer_id: bool) -> Result<()>;
     async fn node_stop(&self, delay_millis: u64) -> Result<()>;
@@ -166,6 +194,36 @@ impl RpcActions for RpcClient {
         })
     }
 
+    async fn node_event_history(
+        &self,
+        since: SystemTime,
+        kinds: Vec<String>,
+    ) -> Result<Vec<NodeEventEntry>> {
+        let mut client = self.connect_with_retry().await?;
+        let since_secs = since
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let response = client
+            .node_event_history(Request::new(NodeEventHistoryRequest { since_secs, kinds }))
+            .await
+            .map_err(|e| {
+                error!("Could not obtain the node event history through RPC: {e:?}");
+                Error::RpcNodeEventHistoryError(e.to_string())
+            })?;
+        let events = response
+            .into_inner()
+            .events
+            .into_iter()
+            .map(|entry| NodeEventEntry {
+                timestamp: UNIX_EPOCH + Duration::from_secs(entry.timestamp_secs),
+                kind: entry.kind,
+                description: entry.description,
+            })
+            .collect();
+        Ok(events)
+    }
+
     async fn record_addresses(&self) -> Result<Vec<RecordAddress>> {
         let mut client = self.connect_with_retry().await?;
         let response = client
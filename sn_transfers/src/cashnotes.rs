@@ -25,7 +25,7 @@ pub use cashnote::CashNote;
 pub use hash::Hash;
 pub use nano::NanoTokens;
 pub use signed_spend::{SignedSpend, Spend};
-pub use spend_reason::SpendReason;
+pub use spend_reason::{DiscordNameCipher, SpendReason};
 pub use transaction::Transaction;
 pub use unique_keys::{DerivationIndex, DerivedSecretKey, MainPubkey, MainSecretKey, UniquePubkey};
 
@@ -19,8 +19,9 @@ pub(crate) use cashnotes::{Input, Output, TransactionBuilder};
 
 /// Types used in the public API
 pub use cashnotes::{
-    CashNote, DerivationIndex, DerivedSecretKey, Hash, MainPubkey, MainSecretKey, NanoTokens,
-    SignedSpend, Spend, SpendAddress, SpendReason, Transaction, UniquePubkey, UnsignedTransfer,
+    CashNote, DerivationIndex, DerivedSecretKey, DiscordNameCipher, Hash, MainPubkey,
+    MainSecretKey, NanoTokens, SignedSpend, Spend, SpendAddress, SpendReason, Transaction,
+    UniquePubkey, UnsignedTransfer,
 };
 pub use error::{Result, TransferError};
 /// Utilities exposed
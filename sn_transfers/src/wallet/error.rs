@@ -79,6 +79,9 @@ pub enum Error {
     /// The payment Quote has expired.
     #[error("The payment quote made for {0:?} has expired")]
     QuoteExpired(XorName),
+    /// The payment was refused by the policy of the payer
+    #[error("Payment refused by the payment policy: {0}")]
+    PaymentRefused(String),
 
     /// DAG error
     #[error("DAG error: {0}")]